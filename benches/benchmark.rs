@@ -2,8 +2,8 @@
 //! 
 //! 使用criterion进行性能基准测试
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use csv_tool::csv::CsvReader;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use csv_tool::csv::{sort_csv_data, sort_to_file, CsvReader, ExportFormat, ExportOptions, Exporter, ReaderOptions, SearchOptions, SearchPattern, SortKey, SortOptions, WriteOptions};
 use csv_tool::error::Result;
 use std::fs::File;
 use std::io::Write;
@@ -140,7 +140,7 @@ fn bench_read_first_page(c: &mut Criterion) {
     create_large_csv(&test_file, 10000).unwrap();
     
     c.bench_function("read_first_page", |b| {
-        let mut reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+        let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
         b.iter(|| {
             let records = reader.read_page(black_box(0), black_box(20)).unwrap();
             black_box(records.len())
@@ -157,7 +157,7 @@ fn bench_read_middle_page(c: &mut Criterion) {
     create_large_csv(&test_file, 10000).unwrap();
     
     c.bench_function("read_middle_page", |b| {
-        let mut reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+        let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
         b.iter(|| {
             let records = reader.read_page(black_box(250), black_box(20)).unwrap();
             black_box(records.len())
@@ -174,7 +174,7 @@ fn bench_read_last_page(c: &mut Criterion) {
     create_large_csv(&test_file, 10000).unwrap();
     
     c.bench_function("read_last_page", |b| {
-        let mut reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+        let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
         let total_pages = reader.total_pages(20);
         b.iter(|| {
             let records = reader.read_page(black_box(total_pages - 1), black_box(20)).unwrap();
@@ -187,6 +187,211 @@ fn bench_read_last_page(c: &mut Criterion) {
     remove_index_file(&test_file);
 }
 
+/// 对比单列排序有无列索引的性能差异
+fn bench_single_column_sort(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_column_index.csv");
+    create_large_csv(&test_file, 100_000).unwrap();
+    
+    let mut group = c.benchmark_group("single_column_sort");
+    // 按 salary 列降序取前100行
+    let sort_options = SortOptions::new().add_key(SortKey::descending(4));
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+    group.bench_function("full_scan", |b| {
+        b.iter(|| sort_csv_data(black_box(&reader), &sort_options, Some(100)).unwrap())
+    });
+    
+    let options = ReaderOptions::new().with_column_index(Some(4));
+    let reader = CsvReader::open_with_options(&test_file, &options).unwrap();
+    group.bench_function("column_index", |b| {
+        b.iter(|| sort_csv_data(black_box(&reader), &sort_options, Some(100)).unwrap())
+    });
+    
+    group.finish();
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    remove_index_file(&test_file);
+}
+
+/// 对比单列搜索有无列索引的性能差异（100万行）
+fn bench_column_search(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_column_search.csv");
+    create_large_csv(&test_file, 1_000_000).unwrap();
+    
+    let mut group = c.benchmark_group("column_search");
+    group.sample_size(10);
+    // 在 name 列中搜索只出现在一行中的值
+    let search_options = SearchOptions::new(SearchPattern::text("Name 876543", true))
+        .with_columns(vec![1]);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+    group.bench_function("full_scan", |b| {
+        b.iter(|| reader.search(black_box(&search_options)).unwrap())
+    });
+    
+    let options = ReaderOptions::new().with_column_index(Some(1));
+    let reader = CsvReader::open_with_options(&test_file, &options).unwrap();
+    group.bench_function("column_index", |b| {
+        b.iter(|| reader.search(black_box(&search_options)).unwrap())
+    });
+    
+    group.finish();
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    remove_index_file(&test_file);
+}
+
+/// 对比纯文本搜索逐行解析与原始字节预筛选的性能差异（100万行）
+fn bench_text_search(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_text_search.csv");
+    create_large_csv(&test_file, 1_000_000).unwrap();
+    
+    let mut group = c.benchmark_group("text_search");
+    group.sample_size(10);
+    let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+    
+    // 指定全部列时逐行解析每一行
+    let search_options = SearchOptions::new(SearchPattern::text("Name 876543", true))
+        .with_columns((0..6).collect());
+    group.bench_function("line_scan", |b| {
+        b.iter(|| reader.search(black_box(&search_options)).unwrap())
+    });
+    
+    // 不限制列时只解析包含搜索词的行
+    let search_options = SearchOptions::new(SearchPattern::text("Name 876543", true));
+    group.bench_function("prefilter", |b| {
+        b.iter(|| reader.search(black_box(&search_options)).unwrap())
+    });
+    
+    group.finish();
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    remove_index_file(&test_file);
+}
+
+/// 对比顺序扫描（逐行计数匹配，100万行）有无访问模式提示的吞吐量
+/// 
+/// 文件已在页缓存中时差别很小；冷缓存或文件大于内存时，顺序预读的效果才明显
+fn bench_sequential_scan_hints(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_scan_hints.csv");
+    create_large_csv(&test_file, 1_000_000).unwrap();
+    
+    let mut group = c.benchmark_group("sequential_scan");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(std::fs::metadata(&test_file).unwrap().len()));
+    let search_options = SearchOptions::new(SearchPattern::regex("^Name 9+$", true).unwrap())
+        .with_columns(vec![1]);
+    
+    for hints in [false, true] {
+        let options = ReaderOptions::new()
+            .with_persist_index(false)
+            .with_access_hints(hints);
+        let reader = CsvReader::open_with_options(&test_file, &options).unwrap();
+        let label = if hints { "advised" } else { "no_hints" };
+        group.bench_function(label, |b| {
+            b.iter(|| reader.count_matches(black_box(&search_options)).unwrap())
+        });
+    }
+    
+    group.finish();
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    remove_index_file(&test_file);
+}
+
+/// 对比一次扫描查找100个纯文本模式与逐个模式扫描100次（100万行）
+fn bench_any_of_search(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_any_of_search.csv");
+    create_large_csv(&test_file, 1_000_000).unwrap();
+    
+    let mut group = c.benchmark_group("any_of_search");
+    group.sample_size(10);
+    let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+    let needles: Vec<String> = (0..100).map(|i| format!("Name {}", 9_000 + i * 9_871)).collect();
+    
+    let combined = SearchPattern::any_of(needles.iter().map(|n| SearchPattern::text(n, true)).collect()).unwrap();
+    let search_options = SearchOptions::new(combined);
+    group.bench_function("any_of_100", |b| {
+        b.iter(|| reader.count_matches(black_box(&search_options)).unwrap())
+    });
+    
+    let sequential: Vec<SearchOptions> = needles.iter()
+        .map(|n| SearchOptions::new(SearchPattern::text(n, true)))
+        .collect();
+    group.bench_function("sequential_100", |b| {
+        b.iter(|| {
+            sequential.iter()
+                .map(|options| reader.count_matches(black_box(options)).unwrap())
+                .sum::<usize>()
+        })
+    });
+    
+    group.finish();
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    remove_index_file(&test_file);
+}
+
+/// 导出文件中间的一段行（100万行中的100行）
+fn bench_export_row_range(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_export_range.csv");
+    create_large_csv(&test_file, 1_000_000).unwrap();
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+    c.bench_function("export_middle_row_range", |b| {
+        b.iter(|| {
+            let options = ExportOptions::new(ExportFormat::JsonLines)
+                .with_row_range(black_box(500_000), black_box(500_100));
+            let mut buffer = Vec::new();
+            Exporter::new(&reader, options).export_to_writer(&mut buffer).unwrap();
+            black_box(buffer.len())
+        })
+    });
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    remove_index_file(&test_file);
+}
+
+/// 排序100万行并写入文件：先取得完整排序结果再写出，与只对行号排序后逐行复制对比
+fn bench_sort_to_file(c: &mut Criterion) {
+    let test_file = std::env::temp_dir().join("bench_sort_to_file.csv");
+    let output = std::env::temp_dir().join("bench_sort_to_file_out.csv");
+    create_large_csv(&test_file, 1_000_000).unwrap();
+    
+    let mut group = c.benchmark_group("sort_to_file");
+    group.sample_size(10);
+    let sort_options = SortOptions::new().add_key(SortKey::descending(4));
+    let reader = CsvReader::open(&test_file, true, b',', 1000).unwrap();
+    
+    group.bench_function("collect_then_write", |b| {
+        b.iter(|| {
+            let sorted = sort_csv_data(black_box(&reader), &sort_options, None).unwrap();
+            let mut writer = std::io::BufWriter::new(File::create(&output).unwrap());
+            for record in &sorted {
+                let fields: Vec<&str> = record.record.fields.iter().map(|f| f.as_ref()).collect();
+                writeln!(writer, "{}", fields.join(",")).unwrap();
+            }
+            writer.flush().unwrap();
+        })
+    });
+    group.bench_function("permutation_streaming", |b| {
+        b.iter(|| sort_to_file(black_box(&reader), &sort_options, None, &output, &WriteOptions::new()).unwrap())
+    });
+    
+    group.finish();
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&output).ok();
+    remove_index_file(&test_file);
+}
+
 criterion_group!(
     benches,
     bench_open_file,
@@ -194,7 +399,14 @@ criterion_group!(
     bench_open_with_cached_index,
     bench_read_first_page,
     bench_read_middle_page,
-    bench_read_last_page
+    bench_read_last_page,
+    bench_single_column_sort,
+    bench_column_search,
+    bench_text_search,
+    bench_sequential_scan_hints,
+    bench_any_of_search,
+    bench_export_row_range,
+    bench_sort_to_file
 );
 criterion_main!(benches);
 
@@ -10,7 +10,7 @@ fn main() -> Result<()> {
 
     // 示例1: 打开CSV文件
     println!("示例1: 打开CSV文件");
-    let mut reader = CsvReader::open(
+    let reader = CsvReader::open(
         "examples/sample.csv",
         true,   // 有表头
         b',',   // 逗号分隔符
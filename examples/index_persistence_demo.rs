@@ -45,7 +45,7 @@ fn main() -> Result<()> {
     // 第二次打开：加载索引
     println!("🔄 第二次打开文件（加载索引）...");
     let start2 = Instant::now();
-    let mut reader2 = CsvReader::open(csv_file, true, b',', 100)?;
+    let reader2 = CsvReader::open(csv_file, true, b',', 100)?;
     let duration2 = start2.elapsed();
     let info2 = reader2.info();
     
@@ -14,6 +14,9 @@ interface CsvFileInfo {
   total_rows: number;
   total_cols: number;
   headers: string[];
+  delimiter: string;
+  line_ending: string;
+  encoding: string;
 }
 
 interface CsvRow {
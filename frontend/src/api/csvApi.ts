@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type { CsvFileInfo } from '@/stores/csvStore';
 
 // 重新导出类型以便在其他地方使用
@@ -13,6 +14,7 @@ export interface PageData {
   page: number;
   total_pages: number;
   page_size: number;
+  is_exact: boolean;
 }
 
 export interface SearchOptions {
@@ -40,6 +42,74 @@ export interface QuickPreview {
   is_complete: boolean;
 }
 
+export type ExportFormat = 'json' | 'jsonl' | 'csv' | 'tsv' | 'sqlite';
+
+export interface ExportStats {
+  rows_exported: number;
+  cols_exported: number;
+  file_size: number;
+}
+
+/**
+ * Payload of the `export-progress` event
+ */
+export interface ExportProgress {
+  export_id: string;
+  percent: number;
+  rows_written: number;
+  total_rows: number;
+}
+
+export interface SortInfo {
+  total_rows: number;
+  /** Memory held by the cached sort order on the backend */
+  permutation_bytes: number;
+}
+
+export interface SortedPageData {
+  rows: Array<{ row_number: number; fields: string[] }>;
+  page: number;
+  total_pages: number;
+  page_size: number;
+}
+
+export interface ChangeStats {
+  cells_edited: number;
+  rows_deleted: number;
+  rows_inserted: number;
+  rows_appended: number;
+  effective_rows: number;
+  has_changes: boolean;
+}
+
+export interface SaveResult {
+  rows_written: number;
+  bytes_written: number;
+  file_path: string;
+  /** Present when the file was saved in place and reopened */
+  file_info: CsvFileInfo | null;
+}
+
+export interface ValueCount {
+  value: string;
+  count: number;
+}
+
+export interface ColumnStatsData {
+  column: number;
+  rows: number;
+  null_count: number;
+  distinct_count: number;
+  numeric: { min: number; max: number; mean: number } | null;
+  top_values: ValueCount[];
+}
+
+export interface UniqueValues {
+  values: ValueCount[];
+  total_distinct: number;
+  truncated: boolean;
+}
+
 export const csvApi = {
   /**
    * 打开CSV文件
@@ -88,6 +158,178 @@ export const csvApi = {
     });
   },
 
+  /**
+   * 按列排序并在后端缓存排序结果
+   */
+  sortAndCache: async (
+    filePath: string,
+    column: number,
+    order: 'asc' | 'desc',
+    dataType?: 'string' | 'number' | 'auto'
+  ): Promise<SortInfo> => {
+    return invoke<SortInfo>('sort_and_cache', {
+      filePath,
+      column,
+      order,
+      dataType: dataType ?? null,
+    });
+  },
+
+  /**
+   * 读取排序后视图的指定页
+   */
+  readSortedPage: async (
+    filePath: string,
+    page: number,
+    pageSize: number
+  ): Promise<SortedPageData> => {
+    return invoke<SortedPageData>('read_sorted_page', {
+      filePath,
+      page,
+      pageSize,
+    });
+  },
+
+  /**
+   * 取消排序，恢复文件原始顺序
+   */
+  clearSort: async (filePath: string): Promise<void> => {
+    return invoke('clear_sort', { filePath });
+  },
+
+  /**
+   * 开始编辑已打开的文件
+   */
+  beginEdit: async (filePath: string): Promise<ChangeStats> => {
+    return invoke<ChangeStats>('begin_edit', { filePath });
+  },
+
+  /**
+   * 修改单元格
+   */
+  editCell: async (
+    filePath: string,
+    row: number,
+    col: number,
+    value: string
+  ): Promise<ChangeStats> => {
+    return invoke<ChangeStats>('edit_cell', { filePath, row, col, value });
+  },
+
+  /**
+   * 删除行
+   */
+  deleteRows: async (filePath: string, rows: number[]): Promise<ChangeStats> => {
+    return invoke<ChangeStats>('delete_rows', { filePath, rows });
+  },
+
+  /**
+   * 插入行（不指定位置时追加到末尾）
+   */
+  addRow: async (
+    filePath: string,
+    fields: string[],
+    position?: number
+  ): Promise<ChangeStats> => {
+    return invoke<ChangeStats>('add_row', {
+      filePath,
+      fields,
+      position: position ?? null,
+    });
+  },
+
+  /**
+   * 获取未保存修改的统计
+   */
+  getChangeStats: async (filePath: string): Promise<ChangeStats> => {
+    return invoke<ChangeStats>('get_change_stats', { filePath });
+  },
+
+  /**
+   * 保存修改（不指定输出路径时覆盖原文件）
+   */
+  saveEdits: async (filePath: string, outputPath?: string): Promise<SaveResult> => {
+    return invoke<SaveResult>('save_edits', {
+      filePath,
+      outputPath: outputPath ?? null,
+    });
+  },
+
+  /**
+   * 放弃所有未保存的修改
+   */
+  discardEdits: async (filePath: string): Promise<void> => {
+    return invoke('discard_edits', { filePath });
+  },
+
+  /**
+   * 获取列统计（结果在后端按文件和列缓存）
+   */
+  columnStats: async (filePath: string, column: number): Promise<ColumnStatsData> => {
+    return invoke<ColumnStatsData>('column_stats', { filePath, column });
+  },
+
+  /**
+   * 获取列中最常见的若干个不同值，用于筛选下拉框
+   */
+  columnUniqueValues: async (
+    filePath: string,
+    column: number,
+    limit: number
+  ): Promise<UniqueValues> => {
+    return invoke<UniqueValues>('column_unique_values', { filePath, column, limit });
+  },
+
+  /**
+   * 取消正在进行的列统计扫描
+   */
+  cancelColumnScan: async (filePath: string, column: number): Promise<void> => {
+    return invoke('cancel_column_scan', { filePath, column });
+  },
+
+  /**
+   * 导出整个文件（或指定列、行范围），进度通过 onExportProgress 订阅
+   */
+  exportFile: async (
+    exportId: string,
+    filePath: string,
+    outputPath: string,
+    format: ExportFormat,
+    options?: {
+      columns?: number[];
+      fromRow?: number;
+      toRow?: number;
+      pretty?: boolean;
+    }
+  ): Promise<ExportStats> => {
+    return invoke<ExportStats>('export_csv', {
+      exportId,
+      filePath,
+      outputPath,
+      format,
+      columns: options?.columns ?? null,
+      fromRow: options?.fromRow ?? null,
+      toRow: options?.toRow ?? null,
+      pretty: options?.pretty ?? false,
+    });
+  },
+
+  /**
+   * 取消正在进行的导出
+   */
+  cancelExport: async (exportId: string): Promise<void> => {
+    return invoke('cancel_export', { exportId });
+  },
+
+  /**
+   * 订阅导出进度事件
+   */
+  onExportProgress: (
+    handler: (progress: ExportProgress) => void
+  ): Promise<UnlistenFn> => {
+    return listen<ExportProgress>('export-progress', (event) => handler(event.payload));
+  },
+
   /**
    * 关闭文件
    */
@@ -95,6 +337,27 @@ export const csvApi = {
     return invoke('close_file', { filePath });
   },
 
+  /**
+   * 关闭所有文件（未保存的编辑会被丢弃）
+   */
+  closeAllFiles: async (): Promise<void> => {
+    return invoke('close_all_files');
+  },
+
+  /**
+   * 列出已打开的文件，最近使用的在前
+   */
+  listOpenFiles: async (): Promise<CsvFileInfo[]> => {
+    return invoke<CsvFileInfo[]>('list_open_files');
+  },
+
+  /**
+   * 设置最多同时打开的文件数，返回因此被关闭的文件路径
+   */
+  setMaxOpenFiles: async (maxOpenFiles: number): Promise<string[]> => {
+    return invoke<string[]>('set_max_open_files', { maxOpenFiles });
+  },
+
   /**
    * 获取文件信息
    */
@@ -102,6 +365,13 @@ export const csvApi = {
     return invoke<CsvFileInfo>('get_file_info', { filePath });
   },
 
+  /**
+   * 刷新文件信息，后台索引完成后返回精确行数
+   */
+  refreshFileInfo: async (filePath: string): Promise<CsvFileInfo> => {
+    return invoke<CsvFileInfo>('refresh_file_info', { filePath });
+  },
+
   /**
    * Quick preview - instantly shows first N rows without waiting for index
    * Use this for large files to provide immediate feedback
@@ -1,4 +1,4 @@
-import { FileText, Database, Columns, File } from "lucide-react";
+import { FileText, Database, Columns, File, Settings2 } from "lucide-react";
 
 interface FileInfoProps {
   fileInfo: {
@@ -7,6 +7,9 @@ interface FileInfoProps {
     total_rows: number;
     total_cols: number;
     headers: string[];
+    delimiter: string;
+    line_ending: string;
+    encoding: string;
   };
 }
 
@@ -23,6 +26,12 @@ function formatFileSize(bytes: number): string {
   return `${size.toFixed(2)} ${units[unitIndex]}`;
 }
 
+function formatDelimiter(delimiter: string): string {
+  if (delimiter === "\t") return "Tab";
+  if (delimiter === " ") return "空格";
+  return delimiter;
+}
+
 export default function FileInfo({ fileInfo }: FileInfoProps) {
   return (
     <div className="bg-gray-800 border-b border-gray-700 px-4 py-3">
@@ -44,6 +53,13 @@ export default function FileInfo({ fileInfo }: FileInfoProps) {
           <span className="text-gray-400">总列数:</span>
           <span className="text-gray-200 font-medium">{fileInfo.total_cols}</span>
         </div>
+        <div className="flex items-center gap-2 text-sm">
+          <Settings2 className="w-4 h-4 text-gray-400" />
+          <span className="text-gray-400">格式:</span>
+          <span className="text-gray-200 font-medium">
+            {formatDelimiter(fileInfo.delimiter)} · {fileInfo.line_ending} · {fileInfo.encoding}
+          </span>
+        </div>
         <div className="flex items-center gap-2 text-sm">
           <FileText className="w-4 h-4 text-gray-400" />
           <span className="text-gray-400">列名:</span>
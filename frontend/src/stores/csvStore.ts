@@ -7,6 +7,13 @@ export interface CsvFileInfo {
   total_rows: number;
   total_cols: number;
   headers: string[];
+  delimiter: string;
+  line_ending: string;
+  encoding: string;
+  is_exact: boolean;
+  rows_lower_bound: number;
+  rows_upper_bound: number;
+  index_progress: number;
 }
 
 interface CsvState {
@@ -0,0 +1,151 @@
+//! 内存映射的访问模式提示
+//!
+//! 构建索引、搜索和导出从头到尾顺序读取文件，提示内核加大预读；
+//! 界面翻页按行号随机跳转，提示内核不做多余的预读，只提前读入目标页所在的数据。
+//! 提示只影响性能不影响结果，不支持 `madvise` 的平台上为空操作
+
+use memmap2::Mmap;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// 文件的访问模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessPattern {
+    /// 内核默认的预读策略
+    #[default]
+    Normal,
+    /// 从头到尾顺序读取（加大预读）
+    Sequential,
+    /// 随机跳转读取（不预读）
+    Random,
+}
+
+impl AccessPattern {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Sequential,
+            2 => Self::Random,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// 为整个映射设置访问模式（失败时忽略）
+pub fn advise(mmap: &Mmap, pattern: AccessPattern) {
+    #[cfg(unix)]
+    {
+        use memmap2::Advice;
+        let advice = match pattern {
+            AccessPattern::Normal => Advice::Normal,
+            AccessPattern::Sequential => Advice::Sequential,
+            AccessPattern::Random => Advice::Random,
+        };
+        let _ = mmap.advise(advice);
+    }
+    #[cfg(not(unix))]
+    let _ = (mmap, pattern);
+}
+
+/// 提示内核即将读取 `[start, end)`，提前读入页缓存（失败时忽略）
+pub fn will_need(mmap: &Mmap, start: usize, end: usize) {
+    let end = end.min(mmap.len());
+    if start >= end {
+        return;
+    }
+    #[cfg(unix)]
+    {
+        let _ = mmap.advise_range(memmap2::Advice::WillNeed, start, end - start);
+    }
+    #[cfg(not(unix))]
+    let _ = (mmap, start, end);
+}
+
+/// 读取器当前的访问模式提示
+///
+/// 平时使用调用者设置的模式（默认 [`AccessPattern::Normal`]），
+/// 顺序扫描期间临时切换为 [`AccessPattern::Sequential`]；
+/// 多个线程同时扫描时，最后一个扫描结束后才恢复原来的模式
+#[derive(Debug)]
+pub(crate) struct AccessHints {
+    enabled: bool,
+    resting: AtomicU8,
+    scans: AtomicUsize,
+}
+
+impl AccessHints {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            resting: AtomicU8::new(AccessPattern::Normal as u8),
+            scans: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 设置平时的访问模式，没有进行中的扫描时立即生效
+    pub(crate) fn set_pattern(&self, mmap: &Mmap, pattern: AccessPattern) {
+        self.resting.store(pattern as u8, Ordering::Relaxed);
+        if self.enabled && self.scans.load(Ordering::Acquire) == 0 {
+            advise(mmap, pattern);
+        }
+    }
+
+    /// 开始一次顺序扫描，返回的守卫释放时结束扫描
+    pub(crate) fn sequential<'a>(&'a self, mmap: &'a Mmap) -> SequentialScan<'a> {
+        if self.enabled && self.scans.fetch_add(1, Ordering::AcqRel) == 0 {
+            advise(mmap, AccessPattern::Sequential);
+        }
+        SequentialScan { hints: self, mmap }
+    }
+}
+
+/// 顺序扫描守卫，释放时恢复平时的访问模式
+pub(crate) struct SequentialScan<'a> {
+    hints: &'a AccessHints,
+    mmap: &'a Mmap,
+}
+
+impl Drop for SequentialScan<'_> {
+    fn drop(&mut self) {
+        let hints = self.hints;
+        if hints.enabled && hints.scans.fetch_sub(1, Ordering::AcqRel) == 1 {
+            advise(self.mmap, AccessPattern::from_u8(hints.resting.load(Ordering::Relaxed)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memmap2::MmapOptions;
+    use std::io::Write;
+
+    #[test]
+    fn test_nested_scans_restore_pattern() {
+        let path = std::env::temp_dir().join("test_advise_nested.csv");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "a,b\n1,2").unwrap();
+        drop(file);
+        let file = std::fs::File::open(&path).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file) }.unwrap();
+
+        let hints = AccessHints::new(true);
+        hints.set_pattern(&mmap, AccessPattern::Random);
+        {
+            let _outer = hints.sequential(&mmap);
+            let inner = hints.sequential(&mmap);
+            drop(inner);
+            assert_eq!(hints.scans.load(Ordering::Relaxed), 1);
+        }
+        assert_eq!(hints.scans.load(Ordering::Relaxed), 0);
+        assert_eq!(AccessPattern::from_u8(hints.resting.load(Ordering::Relaxed)), AccessPattern::Random);
+
+        // 超出映射范围的预读请求被截断，不会出错
+        will_need(&mmap, 0, usize::MAX);
+        will_need(&mmap, 10, 5);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
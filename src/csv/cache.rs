@@ -1,64 +1,208 @@
 use lru::LruCache;
+use std::borrow::Cow;
+use std::mem::size_of;
 use std::num::NonZeroUsize;
 use crate::csv::CsvRecord;
 
+/// 默认缓存的页面数
+pub const DEFAULT_CACHE_PAGES: usize = 10;
+
+/// 默认的缓存内存上限（字节）
+pub const DEFAULT_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
 /// 页面缓存
-/// 使用LRU（最近最少使用）策略缓存最近访问的页面
+/// 使用LRU（最近最少使用）策略缓存最近访问的页面，
+/// 同时限制页面数和缓存占用的内存（按字段长度估算）
 pub struct PageCache {
-    cache: LruCache<usize, Vec<CsvRecord<'static>>>,
+    /// 容量为0时不缓存
+    cache: Option<LruCache<usize, Vec<CsvRecord<'static>>>>,
+    /// 缓存内存上限（字节）
+    byte_budget: usize,
+    /// 当前缓存页面占用的内存（估算，字节）
+    bytes: usize,
 }
 
 impl PageCache {
-    /// 创建新的页面缓存
-    /// 
+    /// 创建新的页面缓存（内存上限为 [`DEFAULT_CACHE_BYTES`]）
+    ///
     /// # 参数
-    /// - `capacity`: 缓存容量（最多缓存多少个页面）
+    /// - `capacity`: 缓存容量（最多缓存多少个页面，0表示不缓存）
     pub fn new(capacity: usize) -> Self {
-        let capacity = NonZeroUsize::new(capacity.max(1)).unwrap();
         Self {
-            cache: LruCache::new(capacity),
+            cache: NonZeroUsize::new(capacity).map(LruCache::new),
+            byte_budget: DEFAULT_CACHE_BYTES,
+            bytes: 0,
+        }
+    }
+
+    /// 创建只按内存上限淘汰的页面缓存（不限制页面数）
+    ///
+    /// # 参数
+    /// - `bytes`: 缓存内存上限（字节，0表示不缓存）
+    pub fn new_with_budget(bytes: usize) -> Self {
+        Self {
+            cache: (bytes > 0).then(LruCache::unbounded),
+            byte_budget: bytes,
+            bytes: 0,
         }
     }
 
+    /// 是否启用缓存（容量不为0）
+    pub fn is_enabled(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    /// 获取缓存容量
+    pub fn capacity(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| cache.cap().get())
+    }
+
+    /// 获取缓存内存上限（字节）
+    pub fn byte_budget(&self) -> usize {
+        self.byte_budget
+    }
+
+    /// 获取当前缓存页面占用的内存（估算，字节）
+    pub fn memory_usage(&self) -> usize {
+        self.bytes
+    }
+
     /// 获取缓存的页面
-    /// 
+    ///
     /// # 参数
     /// - `page`: 页码
-    /// 
+    ///
     /// # 返回
     /// 如果缓存中存在该页面，返回Some，否则返回None
     pub fn get(&mut self, page: &usize) -> Option<&Vec<CsvRecord<'static>>> {
-        self.cache.get(page)
+        self.cache.as_mut()?.get(page)
     }
 
     /// 将页面放入缓存
-    /// 
+    ///
+    /// 超过页面数或内存上限时淘汰最久未访问的页面；单页超过内存上限时不缓存
+    ///
     /// # 参数
     /// - `page`: 页码
     /// - `records`: 该页的记录数据
     pub fn put(&mut self, page: usize, records: Vec<CsvRecord<'static>>) {
-        self.cache.put(page, records);
+        let Some(cache) = &mut self.cache else {
+            return;
+        };
+
+        let size = page_size(&records);
+        if size > self.byte_budget {
+            if let Some(old) = cache.pop(&page) {
+                self.bytes -= page_size(&old);
+            }
+            return;
+        }
+
+        // 替换同一页或按页面数淘汰时返回被移除的页面
+        if let Some((_, old)) = cache.push(page, records) {
+            self.bytes -= page_size(&old);
+        }
+        self.bytes += size;
+
+        while self.bytes > self.byte_budget {
+            match cache.pop_lru() {
+                Some((_, old)) => self.bytes -= page_size(&old),
+                None => break,
+            }
+        }
     }
 
     /// 清空缓存
     pub fn clear(&mut self) {
-        self.cache.clear();
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+        self.bytes = 0;
     }
 
     /// 获取当前缓存大小
     pub fn len(&self) -> usize {
-        self.cache.len()
+        self.cache.as_ref().map_or(0, |cache| cache.len())
     }
 
     /// 检查缓存是否为空
     pub fn is_empty(&self) -> bool {
-        self.cache.len() == 0
+        self.len() == 0
     }
 }
 
 impl Default for PageCache {
     fn default() -> Self {
-        Self::new(10) // 默认缓存10页
+        Self::new(DEFAULT_CACHE_PAGES)
     }
 }
 
+/// 估算一页记录占用的内存：字段内容长度加上记录和字段的结构开销
+fn page_size(records: &[CsvRecord<'static>]) -> usize {
+    records.iter()
+        .map(|record| {
+            size_of::<CsvRecord<'static>>()
+                + record.fields.capacity() * size_of::<Cow<'static, str>>()
+                + record.fields.iter().map(|field| field.len()).sum::<usize>()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每页一条记录，字段内容为 `width` 字节
+    fn page(width: usize) -> Vec<CsvRecord<'static>> {
+        vec![CsvRecord { fields: vec![Cow::Owned("x".repeat(width))], quoted: None }]
+    }
+
+    #[test]
+    fn test_evict_by_bytes_in_lru_order() {
+        let page_bytes = page_size(&page(1000));
+        let mut cache = PageCache::new_with_budget(page_bytes * 3);
+
+        for i in 0..3 {
+            cache.put(i, page(1000));
+        }
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.memory_usage(), page_bytes * 3);
+
+        // 访问第0页后，再放入新页淘汰的是最久未访问的第1页
+        assert!(cache.get(&0).is_some());
+        cache.put(3, page(1000));
+        assert_eq!(cache.len(), 3);
+        assert!(cache.get(&1).is_none());
+
+        // 两倍大的页面需要淘汰两页（第2页、第0页）
+        cache.put(4, page(2000));
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&0).is_none());
+        assert!(cache.get(&3).is_some());
+        assert!(cache.get(&4).is_some());
+        assert!(cache.memory_usage() <= cache.byte_budget());
+
+        // 超过内存上限的页面不缓存，也不淘汰已有页面
+        cache.put(5, page(page_bytes * 4));
+        assert!(cache.get(&5).is_none());
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert_eq!(cache.memory_usage(), 0);
+    }
+
+    #[test]
+    fn test_replace_page_updates_usage() {
+        let mut cache = PageCache::new(2);
+        cache.put(0, page(100));
+        cache.put(0, page(500));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.memory_usage(), page_size(&page(500)));
+
+        // 按页面数淘汰时同样扣除内存
+        cache.put(1, page(100));
+        cache.put(2, page(100));
+        assert!(cache.get(&0).is_none());
+        assert_eq!(cache.memory_usage(), page_size(&page(100)) * 2);
+    }
+}
@@ -0,0 +1,278 @@
+//! 列索引模块
+//!
+//! 记录某一列在每一行中的字节范围，按单列统计或排序时只需读取该列的字节，
+//! 不必解析整行；并为每个行块记录该列值的三元组布隆过滤器，
+//! 按单列搜索纯文本时可以跳过不可能包含搜索词的行块
+
+use crate::csv::CsvRecord;
+use crate::csv::encoding::skip_bom;
+use crate::csv::lines::{data_start, DataLines, LineFormat};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// 每个布隆过滤器覆盖的行数
+const FILTER_BLOCK_ROWS: usize = 1024;
+
+/// 每个三元组占用的位数（3个哈希函数下误判率约3%）
+const FILTER_BITS_PER_TRIGRAM: usize = 8;
+
+/// 每个三元组设置的位数
+const FILTER_HASHES: u64 = 3;
+
+/// 单列字节范围索引
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnIndex {
+    /// 列索引（从0开始）
+    column: usize,
+    /// 构建时使用的分隔符
+    delimiter: u8,
+    /// 每行该字段的起始偏移量（缺少该列的行记为行尾位置）
+    starts: Vec<u64>,
+    /// 每行该字段的字节长度（含引号，缺少该列的行为0）
+    lengths: Vec<u32>,
+    /// 每 [`FILTER_BLOCK_ROWS`] 行一个布隆过滤器
+    filters: Vec<BlockFilter>,
+}
+
+impl ColumnIndex {
+    /// 扫描文件构建列索引
+    ///
+    /// # 参数
+    /// - `data`: 文件内容
+    /// - `has_headers`: 是否有表头
+    /// - `format`: 分行方式
+    /// - `column`: 列索引（从0开始）
+    /// - `delimiter`: 分隔符
+    pub fn build(data: &[u8], has_headers: bool, format: LineFormat, column: usize, delimiter: u8) -> Self {
+        // 跳过BOM和表头
+        let data_start = data_start(data, has_headers, format);
+
+        let mut index = Self {
+            column,
+            delimiter,
+            starts: Vec::new(),
+            lengths: Vec::new(),
+            filters: Vec::new(),
+        };
+
+        for (line_start, line_end) in DataLines::new(data, data_start, format) {
+            index.push_line(data, line_start, line_end);
+        }
+
+        index.filters = (0..index.len())
+            .step_by(FILTER_BLOCK_ROWS)
+            .map(|start| index.build_filter(data, start..(start + FILTER_BLOCK_ROWS).min(index.len())))
+            .collect();
+
+        index
+    }
+
+    /// 为一个行块构建布隆过滤器
+    /// 
+    /// 同时记录原值和小写值的三元组，区分和不区分大小写的搜索都可以使用
+    fn build_filter(&self, data: &[u8], rows: std::ops::Range<usize>) -> BlockFilter {
+        let mut trigrams = HashSet::new();
+        for row in rows {
+            let Some(value) = self.field(data, row) else {
+                continue;
+            };
+            trigrams.extend(value.as_bytes().windows(3).map(xxh3_64));
+            let lowercase = value.to_lowercase();
+            if lowercase != value {
+                trigrams.extend(lowercase.as_bytes().windows(3).map(xxh3_64));
+            }
+        }
+
+        let mut filter = BlockFilter::with_capacity(trigrams.len());
+        for hash in trigrams {
+            filter.insert(hash);
+        }
+        filter
+    }
+
+    /// 记录一行中目标字段的字节范围
+    fn push_line(&mut self, data: &[u8], line_start: usize, line_end: usize) {
+        let mut line = &data[line_start..line_end];
+        // 去除行尾的 \r（处理 Windows 换行符 CRLF）
+        if line.last() == Some(&b'\r') {
+            line = &line[..line.len() - 1];
+        }
+
+        let mut field = 0;
+        let mut field_start = 0;
+        let mut in_quotes = false;
+        let mut range = None;
+
+        for (i, &byte) in line.iter().enumerate() {
+            if byte == b'"' {
+                in_quotes = !in_quotes;
+            } else if byte == self.delimiter && !in_quotes {
+                if field == self.column {
+                    range = Some((field_start, i));
+                    break;
+                }
+                field += 1;
+                field_start = i + 1;
+            }
+        }
+
+        let (start, end) = match range {
+            Some(range) => range,
+            None if field == self.column => (field_start, line.len()),
+            None => (line.len(), line.len()),
+        };
+
+        self.starts.push((line_start + start) as u64);
+        self.lengths.push((end - start) as u32);
+    }
+
+    /// 获取列索引（从0开始）
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// 获取构建时使用的分隔符
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// 已索引的行数
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// 是否没有任何行
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// 读取指定行的字段值（处理引号和转义）
+    ///
+    /// # 参数
+    /// - `data`: 构建索引时的文件内容
+    /// - `row`: 行号（不包括表头，从0开始）
+    pub fn field<'a>(&self, data: &'a [u8], row: usize) -> Option<Cow<'a, str>> {
+        let start = *self.starts.get(row)? as usize;
+        let end = start + self.lengths[row] as usize;
+        Some(CsvRecord::parse_field(&data[start..end]))
+    }
+
+    /// 读取该列的所有值
+    pub fn values<'a>(&self, data: &'a [u8]) -> Vec<Cow<'a, str>> {
+        (0..self.len())
+            .filter_map(|row| self.field(data, row))
+            .collect()
+    }
+
+    /// 可能包含指定文本的行（按行号升序）
+    /// 
+    /// 跳过布隆过滤器判定不含该文本任一三元组的行块；返回的行仍需逐一比较。
+    /// 不区分大小写时应传入小写的文本
+    pub fn candidate_rows<'s>(&'s self, needle: &'s str) -> impl Iterator<Item = usize> + 's {
+        self.filters
+            .iter()
+            .enumerate()
+            .filter(move |(_, filter)| filter.may_contain(needle.as_bytes()))
+            .flat_map(move |(block, _)| {
+                let start = block * FILTER_BLOCK_ROWS;
+                start..(start + FILTER_BLOCK_ROWS).min(self.len())
+            })
+    }
+
+    /// 由字段位置定位并解析整行
+    ///
+    /// # 参数
+    /// - `data`: 构建索引时的文件内容
+    /// - `format`: 构建索引时的分行方式
+    /// - `row`: 行号（不包括表头，从0开始）
+    pub fn record<'a>(&self, data: &'a [u8], format: LineFormat, row: usize) -> Option<CsvRecord<'a>> {
+        let field_start = *self.starts.get(row)? as usize;
+        // 第一行没有表头且以BOM开头时跳过BOM
+        let line_start = format.rfind_end(data, 0, field_start).map_or(skip_bom(data), |pos| pos + 1);
+        let line_end = format.find_end(data, field_start).unwrap_or(data.len());
+        Some(CsvRecord::parse_line(&data[line_start..line_end], self.delimiter))
+    }
+}
+
+/// 行块的三元组布隆过滤器
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct BlockFilter {
+    bits: Vec<u64>,
+}
+
+impl BlockFilter {
+    /// 按三元组数量分配位数组
+    fn with_capacity(trigrams: usize) -> Self {
+        let words = (trigrams * FILTER_BITS_PER_TRIGRAM).div_ceil(64).max(1);
+        Self { bits: vec![0; words] }
+    }
+
+    /// 三元组哈希对应的位（双重哈希）
+    fn positions(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let bit_count = self.bits.len() as u64 * 64;
+        let (h1, h2) = (hash & 0xffff_ffff, hash >> 32);
+        (0..FILTER_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize)
+    }
+
+    fn insert(&mut self, hash: u64) {
+        for pos in self.positions(hash) {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+    }
+
+    /// 是否可能包含该文本（不足3字节的文本无法判断，总是返回true）
+    fn may_contain(&self, needle: &[u8]) -> bool {
+        needle.windows(3).all(|trigram| {
+            self.positions(xxh3_64(trigram))
+                .all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_column_index() {
+        let data = b"id,name,city\r\n1,\"Smith, John\",Beijing\r\n2,Bob\r\n3,\"Say \"\"hi\"\"\",Shenzhen";
+        let index = ColumnIndex::build(data, true, LineFormat::default(), 1, b',');
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.values(data), vec!["Smith, John", "Bob", "Say \"hi\""]);
+
+        // 缺少该列的行读取为空值
+        let city = ColumnIndex::build(data, true, LineFormat::default(), 2, b',');
+        assert_eq!(city.values(data), vec!["Beijing", "", "Shenzhen"]);
+
+        let record = city.record(data, LineFormat::default(), 2).unwrap();
+        assert_eq!(record.fields, vec!["3", "Say \"hi\"", "Shenzhen"]);
+        assert!(city.field(data, 3).is_none());
+    }
+
+    #[test]
+    fn test_candidate_rows_skip_blocks() {
+        let mut data = String::from("id,email\n");
+        for i in 0..3 * FILTER_BLOCK_ROWS {
+            data.push_str(&format!("{},user{}@example.com\n", i, i % 100));
+        }
+        // 只有第二个行块包含该值
+        let target = FILTER_BLOCK_ROWS + 5;
+        data = data.replace(
+            &format!("\n{},user{}@example.com\n", target, target % 100),
+            &format!("\n{},Zed.Quux@Mail.org\n", target),
+        );
+
+        let index = ColumnIndex::build(data.as_bytes(), true, LineFormat::default(), 1, b',');
+        let block = FILTER_BLOCK_ROWS..2 * FILTER_BLOCK_ROWS;
+        let rows: Vec<usize> = index.candidate_rows("Zed.Quux").collect();
+        assert_eq!(rows, block.clone().collect::<Vec<_>>());
+        // 小写值同样被记录
+        let rows: Vec<usize> = index.candidate_rows("zed.quux@mail").collect();
+        assert_eq!(rows, block.collect::<Vec<_>>());
+
+        // 过短的文本无法过滤
+        assert_eq!(index.candidate_rows("ze").count(), index.len());
+    }
+}
@@ -0,0 +1,123 @@
+//! 压缩文件支持
+//!
+//! gzip压缩的CSV在打开时解压到临时文件，之后按普通文件进行内存映射
+
+use crate::error::{CsvError, Result};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// gzip魔数
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// 同一进程内临时文件计数，避免重名
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// 检查文件是否为gzip压缩（扩展名为 .gz 或魔数匹配）
+pub fn is_gzip_file<P: AsRef<Path>>(path: P) -> Result<bool> {
+    let path = path.as_ref();
+    let has_gz_ext = path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"));
+    if has_gz_ext {
+        return Ok(true);
+    }
+
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 2];
+    let n = file.read(&mut magic)?;
+    Ok(n == 2 && magic == GZIP_MAGIC)
+}
+
+/// 将gzip文件解压到临时目录（遵循 `$TMPDIR`）
+///
+/// # 返回
+/// 解压后的临时文件路径，由调用者负责清理
+pub fn decompress_to_temp<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let stem = path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("data");
+    let stem = stem.strip_suffix(".csv").unwrap_or(stem);
+    let temp_path = std::env::temp_dir().join(format!(
+        "csv_tool_{}_{}_{}.csv",
+        stem,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let input = File::open(path)?;
+    let mut decoder = MultiGzDecoder::new(BufReader::new(input));
+    let mut output = BufWriter::new(File::create(&temp_path)?);
+
+    let result = std::io::copy(&mut decoder, &mut output).and_then(|_| output.flush());
+    if let Err(e) = result {
+        drop(output);
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(CsvError::Format(format!("gzip解压失败: {}", e)));
+    }
+
+    Ok(temp_path)
+}
+
+/// 解压得到的临时文件
+///
+/// 释放时自动删除，除非调用了 [`DecompressedFile::keep`]
+#[derive(Debug)]
+pub struct DecompressedFile {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl DecompressedFile {
+    /// 将gzip文件解压到临时目录
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self {
+            path: decompress_to_temp(path)?,
+            keep: false,
+        })
+    }
+
+    /// 获取临时文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 设置释放时是否保留临时文件
+    pub fn keep(&mut self, keep: bool) {
+        self.keep = keep;
+    }
+}
+
+impl Drop for DecompressedFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn test_decompress_to_temp() {
+        let gz_path = std::env::temp_dir().join("test_compression_unit.csv.gz");
+        let mut encoder = GzEncoder::new(File::create(&gz_path).unwrap(), Compression::default());
+        encoder.write_all(b"a,b\n1,2\n").unwrap();
+        encoder.finish().unwrap();
+
+        assert!(is_gzip_file(&gz_path).unwrap());
+
+        let temp = decompress_to_temp(&gz_path).unwrap();
+        assert_eq!(std::fs::read(&temp).unwrap(), b"a,b\n1,2\n");
+
+        let _ = std::fs::remove_file(&temp);
+        let _ = std::fs::remove_file(&gz_path);
+    }
+}
@@ -0,0 +1,162 @@
+//! 字段分隔符
+//!
+//! 除单字节分隔符外，也支持 `||`、`::` 这样由多个字节组成的分隔符
+
+use crate::error::{CsvError, Result};
+use std::fmt;
+
+/// 字段分隔符
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// 单字节分隔符（如 `,`、`;`、制表符）
+    Byte(u8),
+    /// 多字节分隔符（如 `||`）
+    Bytes(Vec<u8>),
+}
+
+impl Delimiter {
+    /// 由字节序列创建分隔符，只有一个字节时为 [`Delimiter::Byte`]
+    ///
+    /// # Panics
+    /// 字节序列为空时panic
+    pub fn new(bytes: &[u8]) -> Self {
+        match bytes {
+            [] => panic!("分隔符不能为空"),
+            [byte] => Delimiter::Byte(*byte),
+            _ => Delimiter::Bytes(bytes.to_vec()),
+        }
+    }
+
+    /// 解析用户输入的分隔符
+    ///
+    /// 支持单个ASCII字符（`\t` 和 `tab` 表示制表符）和 `||`、`::` 这样的多字符ASCII分隔符；
+    /// 全角逗号等非ASCII字符会被拒绝，而不是截断成错误的字节
+    pub fn parse(value: &str) -> Result<Self> {
+        if value == "\\t" || value == "tab" {
+            return Ok(Delimiter::Byte(b'\t'));
+        }
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii() => Ok(Delimiter::Byte(c as u8)),
+            (Some(_), Some(_)) if value.is_ascii() => Ok(Delimiter::new(value.as_bytes())),
+            (Some(c), None) => Err(CsvError::Format(format!(
+                "分隔符必须由ASCII字符组成，{:?} 占 {} 个字节（全角标点请改用对应的半角字符）",
+                c,
+                c.len_utf8()
+            ))),
+            _ => Err(CsvError::Format(format!(
+                "分隔符必须由ASCII字符组成（制表符可写作 \\t 或 tab），收到 {:?}",
+                value
+            ))),
+        }
+    }
+
+    /// 分隔符的字节序列
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Delimiter::Byte(byte) => std::slice::from_ref(byte),
+            Delimiter::Bytes(bytes) => bytes,
+        }
+    }
+
+    /// 单字节分隔符的字节，多字节分隔符返回None
+    pub fn as_byte(&self) -> Option<u8> {
+        match self {
+            Delimiter::Byte(byte) => Some(*byte),
+            Delimiter::Bytes(_) => None,
+        }
+    }
+
+    /// 是否为多字节分隔符
+    pub fn is_multi_byte(&self) -> bool {
+        matches!(self, Delimiter::Bytes(_))
+    }
+
+    /// `line[pos..]` 是否以分隔符开头
+    pub(crate) fn matches_at(&self, line: &[u8], pos: usize) -> bool {
+        match self {
+            Delimiter::Byte(byte) => line.get(pos) == Some(byte),
+            Delimiter::Bytes(bytes) => line[pos..].starts_with(bytes),
+        }
+    }
+
+    /// 文本中是否包含完整的分隔符
+    pub(crate) fn occurs_in(&self, text: &str) -> bool {
+        match self {
+            Delimiter::Byte(byte) => text.as_bytes().contains(byte),
+            Delimiter::Bytes(bytes) => text
+                .as_bytes()
+                .windows(bytes.len())
+                .any(|window| window == bytes.as_slice()),
+        }
+    }
+}
+
+impl Default for Delimiter {
+    fn default() -> Self {
+        Delimiter::Byte(b',')
+    }
+}
+
+impl From<u8> for Delimiter {
+    fn from(byte: u8) -> Self {
+        Delimiter::Byte(byte)
+    }
+}
+
+impl From<&Delimiter> for Delimiter {
+    fn from(delimiter: &Delimiter) -> Self {
+        delimiter.clone()
+    }
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(self.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv::CsvRecord;
+
+    #[test]
+    fn test_delimiter_kinds() {
+        assert_eq!(Delimiter::new(b","), Delimiter::Byte(b','));
+        assert_eq!(Delimiter::new(b"||"), Delimiter::Bytes(b"||".to_vec()));
+        assert_eq!(Delimiter::new(b"::").as_bytes(), b"::");
+        assert_eq!(Delimiter::from(b'\t').as_byte(), Some(b'\t'));
+        assert!(Delimiter::new(b"||").is_multi_byte());
+
+        let pipes = Delimiter::new(b"||");
+        assert!(pipes.occurs_in("a||b"));
+        assert!(!pipes.occurs_in("a|b"));
+        assert!(pipes.matches_at(b"a||b", 1));
+        assert!(!pipes.matches_at(b"a|b", 1));
+    }
+
+    #[test]
+    fn test_parse_user_input() {
+        assert_eq!(Delimiter::parse(";").unwrap(), Delimiter::Byte(b';'));
+        assert_eq!(Delimiter::parse("\\t").unwrap(), Delimiter::Byte(b'\t'));
+        assert_eq!(Delimiter::parse("tab").unwrap(), Delimiter::Byte(b'\t'));
+        assert_eq!(Delimiter::parse("||").unwrap(), Delimiter::Bytes(b"||".to_vec()));
+
+        // 非ASCII字符和空输入被拒绝，不会截断成第一个字节
+        assert!(Delimiter::parse("，").is_err());
+        assert!(Delimiter::parse("|，").is_err());
+        assert!(Delimiter::parse("").is_err());
+    }
+
+    #[test]
+    fn test_parse_multi_byte_delimiter() {
+        let pipes = Delimiter::new(b"||");
+        let record = CsvRecord::parse_line_with(b"a||b||c", &pipes);
+        assert_eq!(record.fields, vec!["a", "b", "c"]);
+
+        // 引号内的分隔符和单个 | 不拆分，行尾的 \r 被去除
+        let record = CsvRecord::parse_line_with(b"\"x||y\"||a|b||\r", &pipes);
+        assert_eq!(record.fields, vec!["x||y", "a|b", ""]);
+    }
+}
@@ -0,0 +1,125 @@
+//! 文件编码检测与转换
+//!
+//! 内部处理统一基于UTF-8字节，UTF-16文件在打开时一次性转码
+
+use crate::error::{CsvError, Result};
+use memmap2::{Mmap, MmapOptions};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// UTF-8 BOM
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 数据开头UTF-8 BOM的字节数（没有BOM时为0）
+///
+/// 所有跳过BOM的地方都使用此函数，保证表头、第一行数据和索引的起始位置一致
+pub fn skip_bom(data: &[u8]) -> usize {
+    if data.starts_with(UTF8_BOM) {
+        UTF8_BOM.len()
+    } else {
+        0
+    }
+}
+
+/// 文本编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// UTF-8（含或不含BOM）
+    Utf8,
+    /// UTF-16 小端（BOM: FF FE）
+    Utf16Le,
+    /// UTF-16 大端（BOM: FE FF）
+    Utf16Be,
+}
+
+impl TextEncoding {
+    /// 根据文件开头的BOM检测编码
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] == 0xFE {
+            TextEncoding::Utf16Le
+        } else if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
+            TextEncoding::Utf16Be
+        } else {
+            TextEncoding::Utf8
+        }
+    }
+
+    /// 读取文件开头并检测编码
+    pub fn detect_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut bom = [0u8; 2];
+        let n = file.read(&mut bom)?;
+        Ok(Self::detect(&bom[..n]))
+    }
+
+    /// 是否为UTF-16编码
+    pub fn is_utf16(&self) -> bool {
+        matches!(self, TextEncoding::Utf16Le | TextEncoding::Utf16Be)
+    }
+
+    /// 获取编码名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+/// 将UTF-16数据（不含BOM）解码为UTF-8字节
+///
+/// 无效的代理对会被替换为 U+FFFD，末尾的奇数字节会被忽略
+pub fn transcode_utf16(data: &[u8], encoding: TextEncoding) -> Vec<u8> {
+    let units = data.chunks_exact(2).map(|pair| match encoding {
+        TextEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+        _ => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+
+    let mut utf8 = String::with_capacity(data.len() / 2 * 3 / 2);
+    for ch in char::decode_utf16(units) {
+        utf8.push(ch.unwrap_or(char::REPLACEMENT_CHARACTER));
+    }
+    utf8.into_bytes()
+}
+
+/// 将UTF-16内存映射转码到匿名内存映射中
+///
+/// 返回的映射与文件映射类型相同，后续的索引和解析逻辑无需区分编码
+pub fn transcode_to_mmap(mmap: &Mmap, encoding: TextEncoding) -> Result<Mmap> {
+    // 跳过2字节BOM
+    let utf8 = transcode_utf16(&mmap[2.min(mmap.len())..], encoding);
+    if utf8.is_empty() {
+        return Err(CsvError::Format("文件为空或格式错误".to_string()));
+    }
+
+    let mut anon = MmapOptions::new()
+        .len(utf8.len())
+        .map_anon()
+        .map_err(|e| CsvError::Mmap(e.to_string()))?;
+    anon.copy_from_slice(&utf8);
+    anon.make_read_only().map_err(|e| CsvError::Mmap(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(TextEncoding::detect(b"\xFF\xFEa\0"), TextEncoding::Utf16Le);
+        assert_eq!(TextEncoding::detect(b"\xFE\xFF\0a"), TextEncoding::Utf16Be);
+        assert_eq!(TextEncoding::detect(b"\xEF\xBB\xBFa"), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::detect(b"a,b"), TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_transcode_utf16() {
+        let le: Vec<u8> = "名,b\n".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        assert_eq!(transcode_utf16(&le, TextEncoding::Utf16Le), "名,b\n".as_bytes());
+
+        let be: Vec<u8> = "x,y".encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+        assert_eq!(transcode_utf16(&be, TextEncoding::Utf16Be), b"x,y");
+    }
+}
@@ -2,11 +2,33 @@
 //! 
 //! 支持将CSV数据导出为多种格式
 
-use crate::csv::{CsvReader, CsvRecord, SearchOptions};
+use crate::csv::{CsvReader, CsvRecord, SearchOptions, Searcher, ColumnSchema, ColumnType, FloatFormat, ProgressCallback};
+use crate::csv::lines::{count_lines, DataLines, LineFormat};
+use crate::csv::schema::{is_null_value, DEFAULT_SAMPLE_ROWS};
 use crate::error::{CsvError, Result};
+use rusqlite::types::ValueRef;
+use rayon::prelude::*;
+use rusqlite::Connection;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 每写入多少行汇报一次进度并检查取消标志
+const PROGRESS_INTERVAL_ROWS: usize = 10_000;
+
+/// 默认启用并行CSV/TSV导出的最小数据大小（字节）
+pub const PARALLEL_EXPORT_MIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 并行导出时每块的大致字节数（在行结束符之后切分）
+const EXPORT_CHUNK_BYTES: usize = 2 * 1024 * 1024;
+
+/// 并行导出时每批处理的块数（每个线程），处理完一批即按顺序写出，限制占用的内存
+const PARALLEL_EXPORT_BATCH_CHUNKS: usize = 4;
 
 /// 导出格式
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -19,6 +41,8 @@ pub enum ExportFormat {
     Csv,
     /// 制表符分隔值
     Tsv,
+    /// SQLite数据库文件
+    Sqlite,
 }
 
 impl ExportFormat {
@@ -30,6 +54,7 @@ impl ExportFormat {
             "jsonl" | "ndjson" => Some(ExportFormat::JsonLines),
             "csv" => Some(ExportFormat::Csv),
             "tsv" => Some(ExportFormat::Tsv),
+            "db" | "sqlite" => Some(ExportFormat::Sqlite),
             _ => None,
         }
     }
@@ -41,6 +66,7 @@ impl ExportFormat {
             ExportFormat::JsonLines => "jsonl",
             ExportFormat::Csv => "csv",
             ExportFormat::Tsv => "tsv",
+            ExportFormat::Sqlite => "db",
         }
     }
 
@@ -51,12 +77,40 @@ impl ExportFormat {
             ExportFormat::JsonLines => "JSON Lines",
             ExportFormat::Csv => "CSV",
             ExportFormat::Tsv => "TSV",
+            ExportFormat::Sqlite => "SQLite",
+        }
+    }
+}
+
+/// 字段数与表头列数不一致的行的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RaggedPolicy {
+    /// 原样导出（JSON中缺少的字段为null，多余的字段没有对应的键）
+    #[default]
+    Keep,
+    /// 跳过该行，跳过的行数记录在 [`ExportStats::rows_skipped`]
+    Skip,
+    /// 缺少的字段补为空字符串，多余的字段截断，使每行都与表头等宽
+    Pad,
+    /// 返回 [`CsvError::ParseAt`] 并停止导出
+    Error,
+}
+
+impl RaggedPolicy {
+    /// 从字符串解析处理方式
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "keep" => Some(RaggedPolicy::Keep),
+            "skip" => Some(RaggedPolicy::Skip),
+            "pad" => Some(RaggedPolicy::Pad),
+            "error" => Some(RaggedPolicy::Error),
+            _ => None,
         }
     }
 }
 
 /// 导出选项
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ExportOptions {
     /// 导出格式
     pub format: ExportFormat,
@@ -72,6 +126,50 @@ pub struct ExportOptions {
     pub delimiter: u8,
     /// 是否包含表头
     pub include_headers: bool,
+    /// JSON导出时按列统一类型（而不是逐个单元格猜测）
+    pub typed: bool,
+    /// 显式指定的列类型（为None时自动推断）
+    pub schema: Option<Vec<ColumnSchema>>,
+    /// JSON导出时所有字段都输出为字符串
+    pub keep_strings: bool,
+    /// 为重复的列名添加 `_2`、`_3` 后缀，避免JSON键重复
+    pub dedupe_headers: bool,
+    /// JSON导出时按列名中的 `.` 生成嵌套对象（如 `address.city`）
+    pub nested: bool,
+    /// 按列类型导出时浮点数列的输出格式
+    pub float_format: FloatFormat,
+    /// 导出进度回调，参数为（已写入行数，总行数）
+    pub progress: Option<ProgressCallback>,
+    /// 取消标志，置为true后导出返回错误
+    pub cancel_flag: Option<Arc<AtomicBool>>,
+    /// 数据不小于该大小（字节）时并行导出CSV/TSV，`u64::MAX` 表示始终按顺序导出
+    pub parallel_min_bytes: u64,
+    /// 字段数与表头列数不一致的行的处理方式
+    pub on_ragged: RaggedPolicy,
+}
+
+impl fmt::Debug for ExportOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportOptions")
+            .field("format", &self.format)
+            .field("columns", &self.columns)
+            .field("row_range", &self.row_range)
+            .field("search_filter", &self.search_filter)
+            .field("pretty", &self.pretty)
+            .field("delimiter", &self.delimiter)
+            .field("include_headers", &self.include_headers)
+            .field("typed", &self.typed)
+            .field("schema", &self.schema)
+            .field("keep_strings", &self.keep_strings)
+            .field("dedupe_headers", &self.dedupe_headers)
+            .field("nested", &self.nested)
+            .field("float_format", &self.float_format)
+            .field("progress", &self.progress.is_some())
+            .field("cancel_flag", &self.cancel_flag)
+            .field("parallel_min_bytes", &self.parallel_min_bytes)
+            .field("on_ragged", &self.on_ragged)
+            .finish()
+    }
 }
 
 impl Default for ExportOptions {
@@ -84,6 +182,16 @@ impl Default for ExportOptions {
             pretty: false,
             delimiter: b',',
             include_headers: true,
+            typed: false,
+            schema: None,
+            keep_strings: false,
+            dedupe_headers: false,
+            nested: false,
+            float_format: FloatFormat::default(),
+            progress: None,
+            cancel_flag: None,
+            parallel_min_bytes: PARALLEL_EXPORT_MIN_BYTES,
+            on_ragged: RaggedPolicy::default(),
         }
     }
 }
@@ -132,6 +240,67 @@ impl ExportOptions {
         self.include_headers = include;
         self
     }
+
+    /// 设置JSON导出是否按列统一类型
+    pub fn with_typed(mut self, typed: bool) -> Self {
+        self.typed = typed;
+        self
+    }
+
+    /// 显式指定列类型（同时启用按列类型导出）
+    pub fn with_schema(mut self, schema: Vec<ColumnSchema>) -> Self {
+        self.schema = Some(schema);
+        self.typed = true;
+        self
+    }
+
+    /// 设置JSON导出是否所有字段都输出为字符串
+    pub fn with_keep_strings(mut self, keep_strings: bool) -> Self {
+        self.keep_strings = keep_strings;
+        self
+    }
+
+    /// 设置是否为重复的列名添加后缀
+    pub fn with_dedupe_headers(mut self, dedupe_headers: bool) -> Self {
+        self.dedupe_headers = dedupe_headers;
+        self
+    }
+
+    /// 设置JSON导出时是否按列名中的 `.` 生成嵌套对象
+    pub fn with_nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    /// 设置浮点数列的输出格式（仅在按列类型导出JSON或SQLite时生效）
+    pub fn with_float_format(mut self, float_format: FloatFormat) -> Self {
+        self.float_format = float_format;
+        self
+    }
+
+    /// 设置导出进度回调，参数为（已写入行数，总行数）
+    pub fn with_progress(mut self, progress: ProgressCallback) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// 设置取消标志，其他线程将其置为true后导出提前结束并返回错误
+    pub fn with_cancel_flag(mut self, cancel_flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(cancel_flag);
+        self
+    }
+
+    /// 设置并行导出CSV/TSV的最小数据大小（0表示总是并行，`u64::MAX` 表示不并行）
+    pub fn with_parallel_min_bytes(mut self, min_bytes: u64) -> Self {
+        self.parallel_min_bytes = min_bytes;
+        self
+    }
+
+    /// 设置字段数与表头列数不一致的行的处理方式
+    pub fn with_on_ragged(mut self, policy: RaggedPolicy) -> Self {
+        self.on_ragged = policy;
+        self
+    }
 }
 
 /// 导出统计信息
@@ -143,54 +312,279 @@ pub struct ExportStats {
     pub cols_exported: usize,
     /// 输出文件大小（字节）
     pub file_size: u64,
+    /// 因字段数与表头不一致而跳过的行数（[`RaggedPolicy::Skip`]）
+    pub rows_skipped: usize,
+}
+
+/// 统计写入字节数的包装器
+struct CountingWriter<W> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bytes_written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 导出过程中的进度汇报和取消检查
+struct ExportMonitor<'a> {
+    progress: Option<&'a ProgressCallback>,
+    cancel_flag: Option<&'a AtomicBool>,
+    total: usize,
+}
+
+impl<'a> ExportMonitor<'a> {
+    fn new(options: &'a ExportOptions, total: usize) -> Self {
+        Self {
+            progress: options.progress.as_ref(),
+            cancel_flag: options.cancel_flag.as_deref(),
+            total,
+        }
+    }
+
+    /// 已写入 `rows` 行，每 [`PROGRESS_INTERVAL_ROWS`] 行汇报进度，被取消时返回错误
+    fn row_written(&self, rows: usize) -> Result<()> {
+        if !rows.is_multiple_of(PROGRESS_INTERVAL_ROWS) {
+            return Ok(());
+        }
+        self.report(rows)
+    }
+
+    /// 汇报已处理 `rows` 行并检查取消标志，被取消时返回错误
+    fn report(&self, rows: usize) -> Result<()> {
+        if self.cancel_flag.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            return Err(CsvError::Format("导出已取消".to_string()));
+        }
+        if let Some(progress) = self.progress {
+            progress(rows as u64, self.total as u64);
+        }
+        Ok(())
+    }
+
+    /// 全部写入后汇报100%进度
+    fn finish(&self) {
+        if let Some(progress) = self.progress {
+            progress(self.total as u64, self.total as u64);
+        }
+    }
+}
+
+/// SQLite导出使用的表名
+pub const SQLITE_TABLE_NAME: &str = "data";
+
+/// SQL查询结果
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    /// 结果列名
+    pub columns: Vec<String>,
+    /// 结果行（所有值已转换为字符串，NULL为空字符串）
+    pub rows: Vec<Vec<String>>,
 }
 
 /// 导出器
 pub struct Exporter<'a> {
     reader: &'a CsvReader,
     options: ExportOptions,
+    /// 本次导出跳过的行数（并行导出时在多个线程中累加）
+    rows_skipped: AtomicUsize,
 }
 
 impl<'a> Exporter<'a> {
     /// 创建新的导出器
     pub fn new(reader: &'a CsvReader, options: ExportOptions) -> Self {
-        Self { reader, options }
+        Self { reader, options, rows_skipped: AtomicUsize::new(0) }
     }
 
     /// 导出到文件
     pub fn export_to_file<P: AsRef<Path>>(&self, path: P) -> Result<ExportStats> {
         let path = path.as_ref();
+        if self.options.format == ExportFormat::Sqlite {
+            return self.export_sqlite(path);
+        }
+
         let file = File::create(path)
             .map_err(|e| CsvError::Io(e))?;
         let mut writer = BufWriter::new(file);
 
+        self.export_to_writer(&mut writer)
+    }
+
+    /// 导出到任意写入目标（如标准输出或内存缓冲区）
+    /// 
+    /// SQLite格式需要数据库文件，不支持写入流
+    pub fn export_to_writer(&self, writer: &mut impl Write) -> Result<ExportStats> {
+        let mut writer = CountingWriter { inner: writer, bytes_written: 0 };
+        self.rows_skipped.store(0, Ordering::Relaxed);
+        let _scan = self.reader.sequential_scan();
+
         let stats = match self.options.format {
             ExportFormat::Json => self.export_json(&mut writer)?,
             ExportFormat::JsonLines => self.export_jsonl(&mut writer)?,
             ExportFormat::Csv | ExportFormat::Tsv => self.export_csv(&mut writer)?,
+            ExportFormat::Sqlite => {
+                return Err(CsvError::Format("SQLite格式只能导出到文件".to_string()));
+            }
         };
 
         writer.flush().map_err(|e| CsvError::Io(e))?;
 
-        // 获取文件大小
+        Ok(ExportStats {
+            rows_exported: stats.0,
+            cols_exported: stats.1,
+            file_size: writer.bytes_written,
+            rows_skipped: self.rows_skipped.load(Ordering::Relaxed),
+        })
+    }
+
+    /// 导出为SQLite数据库文件
+    ///
+    /// 创建名为 `data` 的表（列名取自表头，按推断的列类型声明为INTEGER、REAL或TEXT，
+    /// 启用 `keep_strings` 时全部为TEXT），并在单个事务中批量插入所有行。
+    /// 数值列中的空值存为NULL。已存在的输出文件会被覆盖。
+    pub fn export_sqlite<P: AsRef<Path>>(&self, path: P) -> Result<ExportStats> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        let mut conn = Connection::open(path)?;
+        self.rows_skipped.store(0, Ordering::Relaxed);
+        let _scan = self.reader.sequential_scan();
+        let (rows, cols) = self.load_into_sqlite(&mut conn)?;
+        conn.close().map_err(|(_, e)| CsvError::Sqlite(e))?;
+
         let file_size = std::fs::metadata(path)
             .map(|m| m.len())
             .unwrap_or(0);
 
         Ok(ExportStats {
-            rows_exported: stats.0,
-            cols_exported: stats.1,
+            rows_exported: rows,
+            cols_exported: cols,
             file_size,
+            rows_skipped: self.rows_skipped.load(Ordering::Relaxed),
         })
     }
 
+    /// 将数据载入内存SQLite数据库并执行SQL查询
+    ///
+    /// 表名为 `data`，同样遵循列选择、行范围和搜索筛选设置。
+    pub fn query_sql(&self, sql: &str) -> Result<QueryResult> {
+        let mut conn = Connection::open_in_memory()?;
+        self.load_into_sqlite(&mut conn)?;
+
+        let mut stmt = conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names()
+            .into_iter()
+            .map(|c| c.to_string())
+            .collect();
+        let col_count = columns.len();
+
+        let mut rows = Vec::new();
+        let mut query_rows = stmt.query([])?;
+        while let Some(row) = query_rows.next()? {
+            let mut values = Vec::with_capacity(col_count);
+            for i in 0..col_count {
+                values.push(sqlite_value_to_string(row.get_ref(i)?));
+            }
+            rows.push(values);
+        }
+
+        Ok(QueryResult { columns, rows })
+    }
+
+    /// 建表并在事务中批量插入记录
+    /// 
+    /// 列数取自第一条导出的记录（没有记录时取表头）。记录经 [`Self::for_each_export_record`]
+    /// 逐行读取并立即插入，不在内存中收集，占用的内存与行数无关
+    fn load_into_sqlite(&self, conn: &mut Connection) -> Result<(usize, usize)> {
+        let headers = self.get_export_headers();
+        let column_types = self.get_export_column_types()?;
+        let sql_types = if self.options.keep_strings {
+            Vec::new()
+        } else {
+            self.infer_export_column_types()?
+        };
+        let sql_type_of = |i: usize| sql_types.get(i).copied().unwrap_or(ColumnType::String);
+
+        let tx = conn.transaction()?;
+        let create_table = |field_count: usize| -> Result<(rusqlite::Statement, usize)> {
+            let columns = sqlite_column_names(&headers, field_count);
+            if columns.is_empty() {
+                return Err(CsvError::Format("没有可导出的列，无法创建SQLite表".to_string()));
+            }
+            let column_defs: Vec<String> = columns.iter()
+                .enumerate()
+                .map(|(i, c)| format!("{} {}", quote_sql_identifier(c), sqlite_type_name(sql_type_of(i))))
+                .collect();
+            tx.execute(
+                &format!("CREATE TABLE {} ({})", SQLITE_TABLE_NAME, column_defs.join(", ")),
+                [],
+            )?;
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            let insert_sql = format!("INSERT INTO {} VALUES ({})", SQLITE_TABLE_NAME, placeholders);
+            Ok((tx.prepare(&insert_sql)?, columns.len()))
+        };
+
+        let mut insert = None;
+        let rows = self.for_each_export_record(|_, record| {
+            let mut fields = self.get_record_fields(record);
+            if insert.is_none() {
+                insert = Some(create_table(fields.len())?);
+            }
+            if let Some((stmt, cols)) = &mut insert {
+                // 列数不一致的行：补空或截断
+                fields.resize(*cols, String::new());
+                if let Some(types) = &column_types {
+                    self.format_float_fields(&mut fields, types);
+                }
+                // 数值列中的空值存为NULL，避免与数值比较时按文本处理
+                let values = fields.iter().enumerate().map(|(i, field)| {
+                    let numeric = matches!(sql_type_of(i), ColumnType::Integer | ColumnType::Float);
+                    (!(numeric && field.trim().is_empty())).then_some(field.as_str())
+                });
+                stmt.execute(rusqlite::params_from_iter(values))?;
+            }
+            Ok(())
+        })?;
+        let cols = match &insert {
+            Some((_, cols)) => *cols,
+            None => create_table(0)?.1,
+        };
+        drop(insert);
+        tx.commit()?;
+
+        Ok((rows, cols))
+    }
+
+    /// 按 `float_format` 格式化浮点数列中的数值，其他值不变
+    fn format_float_fields(&self, fields: &mut [String], column_types: &[ColumnType]) {
+        for (field, column_type) in fields.iter_mut().zip(column_types) {
+            if *column_type != ColumnType::Float {
+                continue;
+            }
+            if let Ok(value) = field.trim().parse::<f64>() {
+                if value.is_finite() {
+                    *field = self.options.float_format.format_text(field, value);
+                }
+            }
+        }
+    }
+
     /// 导出为JSON格式
     fn export_json<W: Write>(&self, writer: &mut W) -> Result<(usize, usize)> {
         let headers = self.get_export_headers();
-        let records = self.get_export_records()?;
+        let column_types = self.get_export_column_types()?;
         
+        let layout = self.json_layout(&headers);
         let cols = headers.len();
-        let rows = records.len();
 
         if self.options.pretty {
             writeln!(writer, "[").map_err(|e| CsvError::Io(e))?;
@@ -198,8 +592,8 @@ impl<'a> Exporter<'a> {
             write!(writer, "[").map_err(|e| CsvError::Io(e))?;
         }
 
-        for (i, record) in records.iter().enumerate() {
-            let json_obj = self.record_to_json(&headers, record);
+        let rows = self.for_each_export_record(|i, record| {
+            let json_obj = self.record_to_json(&layout, record, column_types.as_deref());
             
             if self.options.pretty {
                 if i > 0 {
@@ -212,7 +606,8 @@ impl<'a> Exporter<'a> {
                 }
                 write!(writer, "{}", json_obj).map_err(|e| CsvError::Io(e))?;
             }
-        }
+            Ok(())
+        })?;
 
         if self.options.pretty {
             writeln!(writer).map_err(|e| CsvError::Io(e))?;
@@ -227,23 +622,24 @@ impl<'a> Exporter<'a> {
     /// 导出为JSON Lines格式
     fn export_jsonl<W: Write>(&self, writer: &mut W) -> Result<(usize, usize)> {
         let headers = self.get_export_headers();
-        let records = self.get_export_records()?;
+        let column_types = self.get_export_column_types()?;
         
+        let layout = self.json_layout(&headers);
         let cols = headers.len();
-        let rows = records.len();
 
-        for record in &records {
-            let json_obj = self.record_to_json(&headers, record);
-            writeln!(writer, "{}", json_obj).map_err(|e| CsvError::Io(e))?;
-        }
+        let rows = self.for_each_export_record(|_, record| {
+            let json_obj = self.record_to_json(&layout, record, column_types.as_deref());
+            writeln!(writer, "{}", json_obj).map_err(CsvError::Io)
+        })?;
 
         Ok((rows, cols))
     }
 
     /// 导出为CSV/TSV格式
+    /// 
+    /// 数据较大时各块行并行解析、筛选和格式化，再按原有顺序写出，输出与顺序导出完全相同
     fn export_csv<W: Write>(&self, writer: &mut W) -> Result<(usize, usize)> {
         let headers = self.get_export_headers();
-        let records = self.get_export_records()?;
         
         let delimiter = if self.options.format == ExportFormat::Tsv {
             b'\t'
@@ -253,7 +649,6 @@ impl<'a> Exporter<'a> {
         let delimiter_char = delimiter as char;
 
         let cols = headers.len();
-        let mut rows = 0;
 
         // 写入表头
         if self.options.include_headers && !headers.is_empty() {
@@ -264,110 +659,321 @@ impl<'a> Exporter<'a> {
                 .map_err(|e| CsvError::Io(e))?;
         }
 
+        if self.use_parallel_export() {
+            return Ok((self.write_csv_rows_parallel(writer, delimiter)?, cols));
+        }
+
         // 写入数据行
-        for record in &records {
-            let fields = self.get_record_fields(record);
-            let line: Vec<String> = fields.iter()
-                .map(|f| escape_csv_field(f, delimiter))
+        let rows = self.for_each_export_record(|_, record| {
+            writeln!(writer, "{}", self.csv_line(record, delimiter))
+                .map_err(CsvError::Io)
+        })?;
+
+        Ok((rows, cols))
+    }
+
+    /// 按顺序把要导出的每条记录交给 `write`（参数为已写出的行数和记录），返回写出的行数
+    /// 
+    /// 已应用搜索筛选、行范围和 `on_ragged`，不在内存中保留记录：
+    /// 有搜索筛选时边搜索边写出（行范围按匹配的顺序计算），
+    /// 否则借助行索引定位到范围起点，逐行读取到范围终点或文件末尾
+    fn for_each_export_record(&self, mut write: impl FnMut(usize, &CsvRecord) -> Result<()>) -> Result<usize> {
+        let total_rows = self.reader.info().total_rows;
+        let mut rows = 0;
+        // 写出一条记录，跳过的行只计数
+        let mut emit = |row: usize, record: CsvRecord<'_>| -> Result<()> {
+            match self.check_ragged(row, record)? {
+                Some(record) => {
+                    write(rows, &record)?;
+                    rows += 1;
+                }
+                None => {
+                    self.rows_skipped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Ok(())
+        };
+
+        if let Some(filter) = &self.options.search_filter {
+            // 按扫描到的行号汇报进度
+            let monitor = ExportMonitor::new(&self.options, total_rows);
+            let (start, end) = self.options.row_range.unwrap_or((0, usize::MAX));
+            let mut matched = 0;
+            let mut next_report = 0;
+            self.reader.for_each_match(filter, |row, _, record| {
+                if row >= next_report {
+                    monitor.report(row)?;
+                    next_report = row + PROGRESS_INTERVAL_ROWS;
+                }
+                if matched >= end {
+                    return Ok(false);
+                }
+                if matched >= start {
+                    emit(row, record)?;
+                }
+                matched += 1;
+                Ok(matched < end)
+            })?;
+            monitor.finish();
+        } else {
+            // 快速打开时总行数只是估算值，只用于汇报进度
+            let (start, end) = self.options.row_range.unwrap_or((0, usize::MAX));
+            let monitor = ExportMonitor::new(&self.options, end.min(total_rows).saturating_sub(start));
+            if let Some(lines) = self.reader.line_spans_from(start)? {
+                let _scan = self.reader.sequential_scan();
+                let data = self.reader.data();
+                for (i, (line_start, line_end)) in lines.take(end.saturating_sub(start)).enumerate() {
+                    monitor.row_written(i)?;
+                    emit(start + i, self.reader.parse_line(&data[line_start..line_end]))?;
+                }
+            }
+            monitor.finish();
+        }
+
+        Ok(rows)
+    }
+
+    /// CSV/TSV导出是否并行处理（按数据大小决定）
+    fn use_parallel_export(&self) -> bool {
+        let info = self.reader.info();
+        let data_size = info.decompressed_size.unwrap_or(info.file_size);
+        data_size >= self.options.parallel_min_bytes
+    }
+
+    /// 并行导出数据行：按字节位置在行结束符处分块，块内解析、筛选并格式化，再按块的顺序写出
+    /// 
+    /// 每次并行处理一批块，写出后再处理下一批，内存占用与文件大小无关；
+    /// 块按字节切分，快速打开时总行数只是估算值也总是处理到文件末尾。
+    /// 有搜索筛选时行范围和最大结果数按匹配的顺序计算，与顺序导出一致
+    fn write_csv_rows_parallel<W: Write>(&self, writer: &mut W, delimiter: u8) -> Result<usize> {
+        let data = self.reader.data();
+        let format = self.reader.line_format();
+        let searcher = self.options.search_filter.clone().map(Searcher::new);
+        // 没有筛选时行范围直接限定扫描的行，否则需要扫描所有行，按匹配的序号筛选
+        let (scan_start, scan_end) = match (&searcher, self.options.row_range) {
+            (None, Some((start, end))) => (start, end),
+            _ => (0, usize::MAX),
+        };
+        let (match_start, match_end) = match &self.options.search_filter {
+            Some(filter) => {
+                let (start, end) = self.options.row_range.unwrap_or((0, usize::MAX));
+                (start, end.min(filter.max_results.unwrap_or(usize::MAX)))
+            }
+            None => (0, usize::MAX),
+        };
+        let batch_size = rayon::current_num_threads() * PARALLEL_EXPORT_BATCH_CHUNKS;
+
+        let total_rows = self.reader.info().total_rows;
+        let monitor = ExportMonitor::new(&self.options, scan_end.min(total_rows).saturating_sub(scan_start));
+        let mut pos = match self.reader.line_spans_from(scan_start)? {
+            Some(lines) => lines.position(),
+            None => data.len(),
+        };
+        let _scan = self.reader.sequential_scan();
+        let scanned = AtomicUsize::new(0);
+        let mut row = scan_start;
+        let mut matched = 0;
+        let mut rows = 0;
+        while pos < data.len() && row < scan_end && matched < match_end {
+            // 先并行统计各块的行数，得到每块第一行的行号，报错的行号与顺序导出一致
+            let spans = export_chunks(data, format, pos, batch_size);
+            pos = spans.last().map_or(data.len(), |&(_, end)| end);
+            let counts: Vec<usize> = spans
+                .par_iter()
+                .map(|&(start, end)| count_lines(&data[..end], start, format))
                 .collect();
-            writeln!(writer, "{}", line.join(&delimiter_char.to_string()))
-                .map_err(|e| CsvError::Io(e))?;
-            rows += 1;
+            let chunks: Vec<(usize, usize, usize)> = spans
+                .into_iter()
+                .zip(counts)
+                .map(|((start, end), count)| {
+                    let first_row = row;
+                    row = row.saturating_add(count);
+                    (start, end, first_row)
+                })
+                .collect();
+
+            // 每个匹配的行先按 `on_ragged` 处理：None表示跳过，错误留到按顺序写出时返回，
+            // 这样行范围、跳过的行数和报告的错误行都与顺序导出一致
+            let chunk_lines: Vec<Vec<Result<Option<String>>>> = chunks
+                .par_iter()
+                .map(|&(start, end, first_row)| -> Result<Vec<Result<Option<String>>>> {
+                    let mut lines = Vec::new();
+                    let mut scanned_rows = 0;
+                    for ((line_start, line_end), row) in DataLines::new(&data[..end], start, format).zip(first_row..scan_end) {
+                        scanned_rows += 1;
+                        let record = self.reader.parse_line(&data[line_start..line_end]);
+                        if searcher.as_ref().is_none_or(|searcher| searcher.is_match(&record)) {
+                            lines.push(self.check_ragged(row, record)
+                                .map(|record| record.map(|record| self.csv_line(&record, delimiter))));
+                        }
+                    }
+                    monitor.report(scanned.fetch_add(scanned_rows, Ordering::Relaxed) + scanned_rows)?;
+                    Ok(lines)
+                })
+                .collect::<Result<_>>()?;
+
+            // 各块按行号顺序返回，依次写出即为原有顺序
+            for line in chunk_lines.into_iter().flatten() {
+                if matched >= match_end {
+                    break;
+                }
+                matched += 1;
+                if matched <= match_start {
+                    continue;
+                }
+                let Some(line) = line? else {
+                    self.rows_skipped.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                };
+                writeln!(writer, "{}", line).map_err(CsvError::Io)?;
+                rows += 1;
+            }
         }
+        monitor.finish();
+        Ok(rows)
+    }
 
-        Ok((rows, cols))
+    /// 一条记录的CSV/TSV行（不含行结束符）
+    fn csv_line(&self, record: &CsvRecord, delimiter: u8) -> String {
+        let line: Vec<String> = self.get_record_fields(record)
+            .iter()
+            .map(|f| escape_csv_field(f, delimiter))
+            .collect();
+        line.join(&(delimiter as char).to_string())
     }
 
     /// 获取要导出的表头
     fn get_export_headers(&self) -> Vec<String> {
         let all_headers = self.reader.headers();
         
-        match &self.options.columns {
+        // 表头之外的列按位置命名，保证表头与所选列一一对应
+        let headers = match &self.options.columns {
             Some(cols) => cols.iter()
-                .filter_map(|&i| all_headers.get(i).cloned())
+                .map(|&i| all_headers.get(i).cloned().unwrap_or_else(|| format!("col{}", i + 1)))
                 .collect(),
             None => all_headers.to_vec(),
+        };
+        
+        if self.options.dedupe_headers {
+            dedupe_headers(headers)
+        } else {
+            headers
         }
     }
 
-    /// 获取要导出的记录
-    fn get_export_records(&self) -> Result<Vec<CsvRecord<'static>>> {
-        // 如果有搜索筛选，使用搜索结果
-        if let Some(ref search_opts) = self.options.search_filter {
-            let results = self.reader.search(search_opts)?;
-            let records: Vec<CsvRecord<'static>> = results.into_iter()
-                .map(|r| r.record)
-                .collect();
-            return self.apply_row_range(records);
+    /// 获取导出列的统一JSON类型（未启用按列类型时返回None）
+    fn get_export_column_types(&self) -> Result<Option<Vec<ColumnType>>> {
+        if self.options.keep_strings || !self.options.typed {
+            return Ok(None);
         }
+        self.infer_export_column_types().map(Some)
+    }
 
-        // 否则读取所有行（或指定范围）
-        let info = self.reader.info();
-        let (start, end) = self.options.row_range
-            .unwrap_or((0, info.total_rows));
-        
-        let end = end.min(info.total_rows);
-        
-        // 直接扫描文件获取记录
-        self.scan_records(start, end)
+    /// 推断导出列的统一类型（不考虑是否启用按列类型）
+    fn infer_export_column_types(&self) -> Result<Vec<ColumnType>> {
+        // 未指定列结构时使用读取器在整个文件中分散采样推断的类型
+        let schema: Vec<ColumnType> = match &self.options.schema {
+            Some(schema) => schema.iter().map(|c| c.inferred_type).collect(),
+            None => self.reader
+                .infer_column_types(DEFAULT_SAMPLE_ROWS)?
+                .iter()
+                .map(|t| t.column_type)
+                .collect(),
+        };
+        let type_of = |i: usize| schema.get(i).copied().unwrap_or(ColumnType::String);
+
+        let types = match &self.options.columns {
+            Some(cols) => cols.iter().map(|&i| type_of(i)).collect(),
+            None => (0..schema.len()).map(type_of).collect(),
+        };
+        Ok(types)
     }
 
-    /// 扫描指定范围的记录
-    fn scan_records(&self, start: usize, end: usize) -> Result<Vec<CsvRecord<'static>>> {
-        let info = self.reader.info();
-        let end = end.min(info.total_rows);
-        
-        if start >= end {
-            return Ok(Vec::new());
+    /// 按 `on_ragged` 处理字段数与表头列数不一致的一行，返回None表示应跳过该行
+    /// 
+    /// `row` 为该行在文件中的行号（从0开始，不含表头），用于报错
+    fn check_ragged<'r>(&self, row: usize, mut record: CsvRecord<'r>) -> Result<Option<CsvRecord<'r>>> {
+        let width = self.reader.info().total_cols;
+        if record.fields.len() == width {
+            return Ok(Some(record));
         }
-
-        // 使用搜索功能获取所有记录（匹配所有行的正则表达式）
-        let all_pattern = crate::csv::SearchPattern::regex(".*", true)?;
-        let all_opts = SearchOptions::new(all_pattern)
-            .with_max_results(end);
-        
-        let results = self.reader.search(&all_opts)?;
-        
-        let records: Vec<CsvRecord<'static>> = results.into_iter()
-            .skip(start)
-            .take(end - start)
-            .map(|r| r.record)
-            .collect();
-        
-        Ok(records)
-    }
-
-    /// 应用行范围筛选
-    fn apply_row_range(&self, records: Vec<CsvRecord<'static>>) -> Result<Vec<CsvRecord<'static>>> {
-        match self.options.row_range {
-            Some((start, end)) => {
-                let end = end.min(records.len());
-                Ok(records.into_iter()
-                    .skip(start)
-                    .take(end.saturating_sub(start))
-                    .collect())
+        match self.options.on_ragged {
+            RaggedPolicy::Keep => Ok(Some(record)),
+            RaggedPolicy::Skip => Ok(None),
+            RaggedPolicy::Pad => {
+                record.fields.resize(width, Cow::Borrowed(""));
+                if let Some(quoted) = &mut record.quoted {
+                    quoted.resize(width, false);
+                }
+                Ok(Some(record))
             }
-            None => Ok(records),
+            RaggedPolicy::Error => Err(CsvError::ParseAt {
+                row: row + 1,
+                message: format!("有 {} 个字段，应为 {} 个", record.fields.len(), width),
+            }),
+        }
+    }
+
+    /// JSON对象的键结构：默认每个表头一个键，启用嵌套时按 `.` 拆分
+    fn json_layout(&self, headers: &[String]) -> Vec<(String, JsonNode)> {
+        if self.options.nested {
+            nested_layout(headers)
+        } else {
+            headers.iter()
+                .enumerate()
+                .map(|(i, h)| (h.clone(), JsonNode::Field(i)))
+                .collect()
         }
     }
 
     /// 将记录转换为JSON对象字符串
-    fn record_to_json(&self, headers: &[String], record: &CsvRecord) -> String {
-        let fields = self.get_record_fields(record);
-        
-        let pairs: Vec<String> = headers.iter()
-            .zip(fields.iter())
-            .map(|(h, v)| format!("\"{}\":{}", escape_json_string(h), json_value(v)))
+    /// 
+    /// 每个表头对应一个所选列，行中缺少的字段输出为null
+    fn record_to_json(&self, layout: &[(String, JsonNode)], record: &CsvRecord, column_types: Option<&[ColumnType]>) -> String {
+        let pairs: Vec<String> = layout.iter()
+            .map(|(key, node)| {
+                let value = match node {
+                    JsonNode::Field(i) => self.field_to_json(*i, record, column_types),
+                    JsonNode::Object(children) => self.record_to_json(children, record, column_types),
+                };
+                format!("\"{}\":{}", escape_json_string(key), value)
+            })
             .collect();
         
         format!("{{{}}}", pairs.join(","))
     }
 
+    /// 第 `i` 个导出列的JSON值
+    fn field_to_json(&self, i: usize, record: &CsvRecord, column_types: Option<&[ColumnType]>) -> String {
+        match record.fields.get(self.source_column(i)) {
+            None => "null".to_string(),
+            Some(v) if self.options.keep_strings => format!("\"{}\"", escape_json_string(v)),
+            Some(v) => match column_types {
+                Some(types) => json_typed_value(
+                    v,
+                    types.get(i).copied().unwrap_or(ColumnType::String),
+                    self.options.float_format,
+                ),
+                None => json_value(v),
+            },
+        }
+    }
+
+    /// 第 `i` 个导出列在源文件中的列索引
+    fn source_column(&self, i: usize) -> usize {
+        match &self.options.columns {
+            Some(cols) => cols[i],
+            None => i,
+        }
+    }
+
     /// 获取记录的字段（根据列筛选）
+    /// 
+    /// 指定了列时按所选列逐一取值，行中缺少的字段为空字符串
     fn get_record_fields(&self, record: &CsvRecord) -> Vec<String> {
         match &self.options.columns {
             Some(cols) => cols.iter()
-                .filter_map(|&i| record.fields.get(i).map(|f| f.to_string()))
+                .map(|&i| record.fields.get(i).map_or_else(String::new, |f| f.to_string()))
                 .collect(),
             None => record.fields.iter()
                 .map(|f| f.to_string())
@@ -376,6 +982,92 @@ impl<'a> Exporter<'a> {
     }
 }
 
+/// 从 `start`（位于行首）开始切分出最多 `count` 个块，返回各块的 `(起始, 结束)` 字节位置
+/// 
+/// 每块约 [`EXPORT_CHUNK_BYTES`] 字节，结束位置总在行结束符之后或数据末尾，不会把一行分到两块
+fn export_chunks(data: &[u8], format: LineFormat, start: usize, count: usize) -> Vec<(usize, usize)> {
+    let mut chunks = Vec::new();
+    let mut pos = start;
+    while pos < data.len() && chunks.len() < count {
+        let target = pos + EXPORT_CHUNK_BYTES;
+        let end = if target >= data.len() {
+            data.len()
+        } else {
+            format.find_end(data, target).map_or(data.len(), |end| end + 1)
+        };
+        chunks.push((pos, end));
+        pos = end;
+    }
+    chunks
+}
+
+/// JSON对象中一个键对应的值
+#[derive(Debug, Clone, PartialEq)]
+enum JsonNode {
+    /// 第N个导出列的值
+    Field(usize),
+    /// 嵌套对象
+    Object(Vec<(String, JsonNode)>),
+}
+
+/// 按列名中的 `.` 把列组织为嵌套对象，键的顺序与列的顺序一致
+/// 
+/// 路径与其他列冲突（如同时有 `a` 和 `a.b`）或含空段（如 `a..b`）的列保留原列名作为顶层键
+fn nested_layout(headers: &[String]) -> Vec<(String, JsonNode)> {
+    let mut root: Vec<(String, JsonNode)> = Vec::new();
+    for (i, header) in headers.iter().enumerate() {
+        let path: Vec<&str> = header.split('.').collect();
+        if path.iter().any(|segment| segment.is_empty()) || !insert_path(&mut root, &path, i) {
+            root.push((header.clone(), JsonNode::Field(i)));
+        }
+    }
+    root
+}
+
+/// 在对象中按路径插入字段，路径冲突时不修改并返回false
+fn insert_path(object: &mut Vec<(String, JsonNode)>, path: &[&str], field: usize) -> bool {
+    let (key, rest) = match path {
+        [key, rest @ ..] => (*key, rest),
+        [] => return false,
+    };
+    match object.iter_mut().find(|(k, _)| k == key) {
+        None if rest.is_empty() => {
+            object.push((key.to_string(), JsonNode::Field(field)));
+            true
+        }
+        None => {
+            let mut children = Vec::new();
+            insert_path(&mut children, rest, field);
+            object.push((key.to_string(), JsonNode::Object(children)));
+            true
+        }
+        Some((_, JsonNode::Object(children))) if !rest.is_empty() => insert_path(children, rest, field),
+        Some(_) => false,
+    }
+}
+
+/// 为重复的列名依次添加 `_2`、`_3` 等后缀（跳过已被占用的名称）
+fn dedupe_headers(headers: Vec<String>) -> Vec<String> {
+    let mut used: HashSet<String> = headers.iter().cloned().collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    
+    headers.into_iter()
+        .map(|name| {
+            if seen.insert(name.clone()) {
+                return name;
+            }
+            let mut n = 2;
+            loop {
+                let candidate = format!("{}_{}", name, n);
+                if used.insert(candidate.clone()) {
+                    return candidate;
+                }
+                n += 1;
+            }
+        })
+        .collect()
+}
+
 /// 转义JSON字符串
 fn escape_json_string(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -415,6 +1107,129 @@ fn json_value(s: &str) -> String {
     format!("\"{}\"", escape_json_string(s))
 }
 
+/// 按列类型将值转换为JSON格式
+/// 
+/// 无法按列类型解析的值退回为字符串
+fn json_typed_value(s: &str, column_type: ColumnType, float_format: FloatFormat) -> String {
+    if is_null_value(s) {
+        return "null".to_string();
+    }
+    match column_type {
+        ColumnType::Integer | ColumnType::Float => {
+            if let Some(number) = json_number(s, column_type, float_format) {
+                return number;
+            }
+        }
+        ColumnType::Bool => {
+            if s.eq_ignore_ascii_case("true") || s.eq_ignore_ascii_case("false") {
+                return s.to_lowercase();
+            }
+        }
+        ColumnType::Date | ColumnType::String => {}
+    }
+    format!("\"{}\"", escape_json_string(s))
+}
+
+/// 将数值列的值转换为JSON数字，无法解析时返回None
+///
+/// 整数列的整数原样输出；浮点数列按 `float_format` 格式化，
+/// `AsWritten` 时保留原始写法（原始写法不是合法的JSON数字时使用最短写法）
+fn json_number(s: &str, column_type: ColumnType, float_format: FloatFormat) -> Option<String> {
+    if let Ok(i) = s.parse::<i64>() {
+        if column_type == ColumnType::Integer || float_format == FloatFormat::Shortest {
+            return Some(i.to_string());
+        }
+    }
+    let f = s.parse::<f64>().ok().filter(|f| f.is_finite())?;
+    if float_format == FloatFormat::AsWritten && is_json_number(s) {
+        return Some(s.to_string());
+    }
+    Some(float_format.format(f))
+}
+
+/// 文本是否为合法的JSON数字（如 `+1`、`.5`、`1.` 都不合法）
+fn is_json_number(s: &str) -> bool {
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = s.strip_prefix('-').unwrap_or(s);
+    let int_len = digits(rest);
+    if int_len == 0 || (int_len > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[int_len..];
+    if let Some(frac) = rest.strip_prefix('.') {
+        let frac_len = digits(frac);
+        if frac_len == 0 {
+            return false;
+        }
+        rest = &frac[frac_len..];
+    }
+    if let Some(exp) = rest.strip_prefix(['e', 'E']) {
+        let exp = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+        let exp_len = digits(exp);
+        if exp_len == 0 {
+            return false;
+        }
+        rest = &exp[exp_len..];
+    }
+    rest.is_empty()
+}
+
+/// 生成SQLite列名（空表头或无表头时使用 colN）
+/// 
+/// SQLite的列名不区分大小写，重名（忽略大小写）的列依次加上 `_2`、`_3` 等后缀
+fn sqlite_column_names(headers: &[String], field_count: usize) -> Vec<String> {
+    let count = headers.len().max(field_count);
+    let names: Vec<String> = (0..count)
+        .map(|i| match headers.get(i) {
+            Some(h) if !h.trim().is_empty() => h.clone(),
+            _ => format!("col{}", i + 1),
+        })
+        .collect();
+
+    let mut used: HashSet<String> = names.iter().map(|name| name.to_lowercase()).collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    names.into_iter()
+        .map(|name| {
+            if seen.insert(name.to_lowercase()) {
+                return name;
+            }
+            let mut n = 2;
+            loop {
+                let candidate = format!("{}_{}", name, n);
+                if used.insert(candidate.to_lowercase()) {
+                    return candidate;
+                }
+                n += 1;
+            }
+        })
+        .collect()
+}
+
+/// 列类型对应的SQLite列类型（整数和浮点数以外的类型按文本存储）
+fn sqlite_type_name(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Integer => "INTEGER",
+        ColumnType::Float => "REAL",
+        ColumnType::Bool | ColumnType::Date | ColumnType::String => "TEXT",
+    }
+}
+
+/// 引用SQL标识符
+fn quote_sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// 将SQLite值转换为显示字符串
+fn sqlite_value_to_string(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).to_string(),
+        ValueRef::Blob(b) => format!("<BLOB {} 字节>", b.len()),
+    }
+}
+
 /// 转义CSV字段
 fn escape_csv_field(s: &str, delimiter: u8) -> String {
     let delimiter_char = delimiter as char;
@@ -452,6 +1267,61 @@ mod tests {
         assert_eq!(json_value(""), "null");
     }
 
+    #[test]
+    fn test_json_typed_value() {
+        let shortest = FloatFormat::Shortest;
+        assert_eq!(json_typed_value("007", ColumnType::String, shortest), "\"007\"");
+        assert_eq!(json_typed_value("42", ColumnType::Integer, shortest), "42");
+        assert_eq!(json_typed_value("+1.50", ColumnType::Float, shortest), "1.5");
+        assert_eq!(json_typed_value("n/a", ColumnType::Float, shortest), "\"n/a\"");
+        assert_eq!(json_typed_value("TRUE", ColumnType::Bool, shortest), "true");
+        assert_eq!(json_typed_value("", ColumnType::String, shortest), "null");
+
+        // 浮点数列按指定格式输出，整数列不受影响
+        assert_eq!(json_typed_value("3", ColumnType::Float, FloatFormat::Fixed(2)), "3.00");
+        assert_eq!(json_typed_value("1.2000000001", ColumnType::Float, FloatFormat::Fixed(2)), "1.20");
+        assert_eq!(json_typed_value("42", ColumnType::Integer, FloatFormat::Fixed(2)), "42");
+        assert_eq!(json_typed_value("3.0", ColumnType::Float, FloatFormat::AsWritten), "3.0");
+        assert_eq!(json_typed_value("+1.50", ColumnType::Float, FloatFormat::AsWritten), "1.5");
+    }
+
+    #[test]
+    fn test_is_json_number() {
+        assert!(is_json_number("0"));
+        assert!(is_json_number("-1.50e+3"));
+        assert!(!is_json_number("+1"));
+        assert!(!is_json_number(".5"));
+        assert!(!is_json_number("1."));
+        assert!(!is_json_number("007"));
+    }
+
+    #[test]
+    fn test_dedupe_headers() {
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(dedupe_headers(names(&["id", "name", "name"])), names(&["id", "name", "name_2"]));
+        // 已被占用的后缀跳过
+        assert_eq!(
+            dedupe_headers(names(&["a", "a", "a_2", "a"])),
+            names(&["a", "a_3", "a_2", "a_4"])
+        );
+    }
+
+    #[test]
+    fn test_nested_layout_conflicts() {
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let layout = nested_layout(&names(&["a.b", "x", "a.c.d", "a.b.e", "p..q"]));
+        assert_eq!(layout, vec![
+            ("a".to_string(), JsonNode::Object(vec![
+                ("b".to_string(), JsonNode::Field(0)),
+                ("c".to_string(), JsonNode::Object(vec![("d".to_string(), JsonNode::Field(2))])),
+            ])),
+            ("x".to_string(), JsonNode::Field(1)),
+            // 与 a.b 冲突、含空段的列保留原列名
+            ("a.b.e".to_string(), JsonNode::Field(3)),
+            ("p..q".to_string(), JsonNode::Field(4)),
+        ]);
+    }
+
     #[test]
     fn test_escape_csv_field() {
         assert_eq!(escape_csv_field("hello", b','), "hello");
@@ -473,6 +1343,49 @@ mod tests {
             ExportFormat::from_extension(Path::new("test.tsv")),
             Some(ExportFormat::Tsv)
         );
+        assert_eq!(
+            ExportFormat::from_extension(Path::new("test.db")),
+            Some(ExportFormat::Sqlite)
+        );
+        assert_eq!(
+            ExportFormat::from_extension(Path::new("test.sqlite")),
+            Some(ExportFormat::Sqlite)
+        );
+    }
+
+    #[test]
+    fn test_sqlite_column_names() {
+        let headers = vec!["id".to_string(), "".to_string()];
+        assert_eq!(sqlite_column_names(&headers, 3), vec!["id", "col2", "col3"]);
+        // 忽略大小写去重
+        let headers: Vec<String> = ["id", "name", "Name", "name_2"].iter().map(|h| h.to_string()).collect();
+        assert_eq!(sqlite_column_names(&headers, 4), vec!["id", "name", "Name_3", "name_2"]);
+        assert_eq!(quote_sql_identifier("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_export_chunks_split_at_line_ends() {
+        let mut data = Vec::new();
+        for i in 0..400_000 {
+            data.extend_from_slice(format!("{},row {}\r\n", i, i).as_bytes());
+        }
+        data.extend_from_slice(b"last");
+        let format = LineFormat::detect(&data);
+
+        let chunks = export_chunks(&data, format, 0, usize::MAX);
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks[0].0, 0);
+        assert_eq!(chunks.last().unwrap().1, data.len());
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+            assert_eq!(data[pair[0].1 - 1], b'\n');
+        }
+        let rows: usize = chunks.iter().map(|&(start, end)| count_lines(&data[..end], start, format)).sum();
+        assert_eq!(rows, 400_001);
+
+        // 最多切分出指定数量的块
+        assert_eq!(export_chunks(&data, format, 0, 1).len(), 1);
+        assert!(export_chunks(&data, format, data.len(), 4).is_empty());
     }
 }
 
@@ -1,14 +1,108 @@
+use crate::csv::{ColumnIndex, Delimiter};
+use crate::csv::encoding::skip_bom;
+use crate::csv::lines::{count_lines, data_start, DataLines, LineFormat};
+use crate::csv::utils::count_fields_quoted;
 use crate::error::{CsvError, Result};
 use memmap2::Mmap;
-use memchr::memchr_iter;  // SIMD加速的换行符查找
 use rayon::prelude::*;  // 并行处理
 use serde::{Deserialize, Serialize};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+/// 当前索引文件格式版本
+/// 
+/// - v1: 初始格式
+/// - v2: 元数据记录索引模式和表头设置，支持稠密索引
+/// - v3: 行索引可附带列索引
+/// - v4: 索引数据可压缩存储
+/// - v5: 元数据记录文件内容校验和
+/// - v6: 列索引附带按行块的布隆过滤器
+/// - v7: 偏移量和行号总是按差值+变长整数编码，压缩只表示是否再经过zlib
+/// - v8: 元数据记录整个文件的哈希，用于完整校验
+/// - v9: 空行不计入行数，元数据记录索引是否跳过空行
+pub const CURRENT_INDEX_VERSION: u32 = 9;
+
+/// 索引点数量达到此值时自动压缩索引数据（约512KB）
+const COMPRESS_MIN_POINTS: usize = 64 * 1024;
+
+/// 计算校验和时读取文件开头和末尾各多少字节
+const CHECKSUM_SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// 文件超过此大小时使用并行构建索引（100MB）
+pub const PARALLEL_BUILD_THRESHOLD: usize = 100 * 1024 * 1024;
+
+/// 并行构建时每个线程每扫描多少字节更新一次进度计数
+const PARALLEL_PROGRESS_STEP: usize = 1024 * 1024;
+
+/// 并行构建时调用线程读取进度计数的间隔
+const PARALLEL_PROGRESS_POLL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// 索引模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IndexMode {
+    /// 稀疏索引：每N行记录一次偏移量，定位后最多需要扫描N行
+    #[default]
+    Sparse,
+    /// 稠密索引：记录每一行的偏移量，定位为O(1)，
+    /// 但索引文件约为每行8字节（1亿行约800MB）
+    Dense,
+}
+
+impl IndexMode {
+    /// 构建索引时实际使用的粒度（稠密索引每行记录一次）
+    pub(crate) fn build_granularity(self, granularity: usize) -> usize {
+        match self {
+            IndexMode::Sparse => granularity,
+            IndexMode::Dense => 1,
+        }
+    }
+}
+
+/// 索引文件的存放位置
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum IndexLocation {
+    /// CSV文件同目录下的 `<文件名>.idx`（无权写入时改存到缓存目录）
+    #[default]
+    Sidecar,
+    /// 用户缓存目录（见 [`IndexLocation::cache_dir`]）
+    CacheDir,
+    /// 指定目录
+    Custom(PathBuf),
+}
+
+/// 指定默认索引目录的环境变量
+pub const INDEX_DIR_ENV: &str = "CSV_TOOL_INDEX_DIR";
+
+impl IndexLocation {
+    /// 按环境变量 `CSV_TOOL_INDEX_DIR` 确定的默认位置，未设置时为 [`IndexLocation::Sidecar`]
+    pub fn from_env() -> Self {
+        match std::env::var_os(INDEX_DIR_ENV).filter(|v| !v.is_empty()) {
+            Some(dir) => IndexLocation::Custom(PathBuf::from(dir)),
+            None => IndexLocation::Sidecar,
+        }
+    }
+
+    /// 用户缓存目录下存放索引的目录
+    /// 
+    /// 依次使用 `XDG_CACHE_HOME`、`LOCALAPPDATA`、`$HOME/.cache`，都没有时使用系统临时目录
+    pub fn cache_dir() -> PathBuf {
+        let env_dir = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+        env_dir("XDG_CACHE_HOME")
+            .or_else(|| env_dir("LOCALAPPDATA"))
+            .or_else(|| env_dir("HOME").map(|home| home.join(".cache")))
+            .unwrap_or_else(std::env::temp_dir)
+            .join("csv-tool")
+    }
+}
+
 /// 索引元数据
 /// 用于验证索引的有效性
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +119,21 @@ pub struct IndexMetadata {
     pub build_time: SystemTime,
     /// 索引粒度
     pub granularity: usize,
+    /// 索引模式
+    pub mode: IndexMode,
+    /// 构建时是否将第一行视为表头
+    pub has_headers: bool,
+    /// 索引数据是否经过zlib压缩
+    pub compressed: bool,
+    /// 文件内容校验和（文件大小及开头、末尾各64KB的xxh3哈希），
+    /// 由旧版本升级的索引没有校验和
+    pub checksum: Option<u64>,
+    /// 整个文件的xxh3哈希（见 [`RowIndex::verify_full_checksum`]），
+    /// 只在需要完整校验时记录，由旧版本升级的索引没有
+    pub full_checksum: Option<u64>,
+    /// 构建时是否跳过空行（见 [`LineFormat::with_skip_empty`]），
+    /// 由旧版本升级的索引为false，文件中有空行时行号不一致，需要重建
+    pub skips_empty_lines: bool,
 }
 
 impl IndexMetadata {
@@ -34,11 +143,63 @@ impl IndexMetadata {
             csv_path,
             csv_size,
             csv_mtime,
-            index_version: 1, // 当前索引格式版本
+            index_version: CURRENT_INDEX_VERSION,
             build_time: SystemTime::now(),
             granularity,
+            mode: IndexMode::Sparse,
+            has_headers: true,
+            compressed: false,
+            checksum: None,
+            full_checksum: None,
+            skips_empty_lines: true,
         }
     }
+
+    /// 记录磁盘上文件内容的校验和（按元数据中的文件大小计算，读取失败时不记录）
+    /// 
+    /// # 参数
+    /// - `csv_path`: CSV文件路径
+    pub fn with_checksum(mut self, csv_path: &Path) -> Self {
+        self.checksum = file_checksum(csv_path, self.csv_size).ok();
+        self
+    }
+
+    /// 记录整个文件的哈希，供 [`RowIndex::verify_full_checksum`] 使用（读取失败时不记录）
+    /// 
+    /// 需要读取整个文件，只应在要求完整校验时调用
+    pub fn with_full_checksum(mut self, csv_path: &Path) -> Self {
+        self.full_checksum = full_file_checksum(csv_path).ok();
+        self
+    }
+
+    /// 设置是否压缩索引数据
+    /// 
+    /// 索引点较多时即使未设置也会自动压缩
+    pub fn with_compression(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    /// 设置构建时是否有表头
+    pub fn with_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// 设置索引模式
+    pub fn with_mode(mut self, mode: IndexMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 索引是否按指定的表头设置、粒度和模式构建
+    /// 
+    /// 稠密索引与粒度无关
+    pub fn matches(&self, has_headers: bool, granularity: usize, mode: IndexMode) -> bool {
+        self.has_headers == has_headers
+            && self.mode == mode
+            && (mode == IndexMode::Dense || self.granularity == granularity)
+    }
 }
 
 /// 行数估算结果
@@ -52,15 +213,100 @@ pub struct RowEstimate {
     pub sampled_bytes: usize,
     /// 文件总字节数
     pub total_bytes: usize,
+    /// 估算区间下限（约95%置信度，精确值时与估算值相同）
+    pub lower_bound: usize,
+    /// 估算区间上限
+    pub upper_bound: usize,
+}
+
+impl RowEstimate {
+    /// 精确的行数
+    fn exact(rows: usize, total_bytes: usize) -> Self {
+        Self {
+            estimated_rows: rows,
+            is_exact: true,
+            sampled_bytes: total_bytes,
+            total_bytes,
+            lower_bound: rows,
+            upper_bound: rows,
+        }
+    }
+}
+
+/// 估算行数时采样的区域数（在文件中均匀分布，包括开头和末尾）
+const ESTIMATE_SAMPLE_REGIONS: usize = 8;
+
+/// 列数校验配置
+#[derive(Debug, Clone)]
+pub struct ColumnValidation {
+    /// 分隔符
+    pub delimiter: Delimiter,
+    /// 期望的列数
+    pub expected_cols: usize,
+    /// 最多记录多少条不一致的行
+    pub max_violations: usize,
+    /// 发现第一条不一致的行时立即报错
+    pub fail_fast: bool,
+}
+
+/// 列数不一致的行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnCountViolation {
+    /// 行号（不包括表头，从0开始）
+    pub row: usize,
+    /// 实际字段数
+    pub field_count: usize,
+}
+
+/// 列数校验报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// 期望的列数
+    pub expected_cols: usize,
+    /// 列数不一致的总行数
+    pub violation_count: usize,
+    /// 前N条列数不一致的行
+    pub violations: Vec<ColumnCountViolation>,
 }
 
-/// 稀疏行索引结构
-/// 每N行记录一次字节偏移，用于快速定位到目标行附近
+impl ValidationReport {
+    /// 所有行的列数是否一致
+    pub fn is_valid(&self) -> bool {
+        self.violation_count == 0
+    }
+
+    /// 校验一行的字段数
+    fn check_line(&mut self, row: usize, line: &[u8], validation: &ColumnValidation) -> Result<()> {
+        let field_count = count_fields_quoted(line, &validation.delimiter);
+        if field_count == self.expected_cols {
+            return Ok(());
+        }
+
+        if validation.fail_fast {
+            return Err(CsvError::Format(format!(
+                "第 {} 行有 {} 个字段，应为 {} 个",
+                row + 1,
+                field_count,
+                self.expected_cols
+            )));
+        }
+
+        self.violation_count += 1;
+        if self.violations.len() < validation.max_violations {
+            self.violations.push(ColumnCountViolation { row, field_count });
+        }
+        Ok(())
+    }
+}
+
+/// 行索引结构
+/// 稀疏模式下每N行记录一次字节偏移，用于快速定位到目标行附近；
+/// 稠密模式下记录每一行的字节偏移
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RowIndex {
     /// 字节偏移量列表
     offsets: Vec<u64>,
-    /// 对应的行号列表（不包括表头）
+    /// 对应的行号列表（不包括表头，稠密模式下为空：`offsets[i]` 即第 i 行）
     row_numbers: Vec<usize>,
     /// 索引粒度（每N行记录一次）
     granularity: usize,
@@ -72,6 +318,12 @@ pub struct RowIndex {
     /// 已索引的字节偏移量（用于增量构建）
     #[serde(default)]
     indexed_bytes: u64,
+    /// 索引模式
+    #[serde(default)]
+    mode: IndexMode,
+    /// 可选的单列字节范围索引
+    #[serde(default)]
+    column_index: Option<ColumnIndex>,
 }
 
 fn default_true() -> bool {
@@ -88,113 +340,136 @@ impl RowIndex {
             total_rows: 0,
             is_complete: false,
             indexed_bytes: 0,
+            mode: IndexMode::Sparse,
+            column_index: None,
+        }
+    }
+
+    /// 构建稠密索引（记录每一行的偏移量）
+    pub fn build_dense(mmap: &Mmap, has_headers: bool, format: LineFormat) -> Result<Self> {
+        Ok(Self::build(mmap, has_headers, format, 1)?.into_mode(IndexMode::Dense))
+    }
+
+    /// 将按 `mode.build_granularity()` 构建的索引转换为指定模式
+    /// 
+    /// 粒度为1时 `row_numbers[i] == i + 1`，稠密模式下无需保存，可使索引文件减半
+    pub(crate) fn into_mode(mut self, mode: IndexMode) -> Self {
+        if mode == IndexMode::Dense {
+            debug_assert_eq!(self.granularity, 1);
+            self.row_numbers = Vec::new();
+            self.mode = IndexMode::Dense;
+        }
+        self
+    }
+
+    /// 记录一个索引点（已扫描 `row` 行，`offset` 为最后一行的起始位置）
+    fn push_point(&mut self, offset: u64, row: usize) {
+        match self.mode {
+            IndexMode::Dense => self.offsets.push(offset),
+            IndexMode::Sparse => {
+                if row.is_multiple_of(self.granularity) {
+                    self.offsets.push(offset);
+                    self.row_numbers.push(row);
+                }
+            }
         }
     }
 
     /// 快速采样估算行数（不扫描整个文件）
     /// 
+    /// 在文件开头、末尾及中间均匀分布的多个区域采样，按各区域的行密度取平均，
+    /// 行宽在文件中前后变化较大时也能得到较准确的估算；
+    /// 各区域密度的差异用于给出估算区间
+    /// 
     /// # 参数
     /// - `mmap`: 内存映射的文件
     /// - `has_headers`: 是否有表头
-    /// - `sample_size`: 采样大小（字节），默认采样前 1MB
+    /// - `format`: 分行方式
+    /// - `sample_size`: 采样大小（字节，所有区域合计）
     /// 
     /// # 性能
     /// 对于任意大小的文件，都能在毫秒级完成估算
-    pub fn estimate_rows(mmap: &Mmap, has_headers: bool, sample_size: usize) -> RowEstimate {
+    pub fn estimate_rows(mmap: &Mmap, has_headers: bool, format: LineFormat, sample_size: usize) -> RowEstimate {
         let total_bytes = mmap.len();
         
         // 如果文件很小，直接精确计数
         if total_bytes <= sample_size {
-            let exact_count = Self::count_rows_exact(mmap, has_headers);
-            return RowEstimate {
-                estimated_rows: exact_count,
-                is_exact: true,
-                sampled_bytes: total_bytes,
-                total_bytes,
-            };
+            let exact_count = Self::count_rows_exact(mmap, has_headers, format);
+            return RowEstimate::exact(exact_count, total_bytes);
         }
 
         // 计算数据起始位置（跳过BOM和表头）
-        let start_offset = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3usize
-        } else {
-            0usize
-        };
+        let data_start = data_start(mmap, has_headers, format);
+        if data_start >= total_bytes {
+            return RowEstimate::exact(0, total_bytes);
+        }
 
-        let data_start = if has_headers {
-            let header_slice = &mmap[start_offset..];
-            if let Some(pos) = memchr::memchr(b'\n', header_slice) {
-                start_offset + pos + 1
+        let data_bytes = total_bytes - data_start;
+        let region_size = (sample_size / ESTIMATE_SAMPLE_REGIONS).max(1).min(data_bytes);
+        let step = (data_bytes - region_size) / (ESTIMATE_SAMPLE_REGIONS - 1);
+
+        // 每个区域的行密度（行/字节），只统计区域内完整的行
+        let mut densities = Vec::with_capacity(ESTIMATE_SAMPLE_REGIONS);
+        let mut sampled_bytes = 0;
+        for i in 0..ESTIMATE_SAMPLE_REGIONS {
+            let region_start = data_start + i * step;
+            let region_end = region_start + region_size;
+            // 只在区域内查找行结束符（区域末尾的 \r 按行结束符计算，对估算没有影响）
+            let region = &mmap[..region_end];
+            sampled_bytes += region_size;
+
+            // 不在数据开头的区域从第一个完整行开始
+            let first = if region_start == data_start {
+                region_start
             } else {
-                start_offset
-            }
-        } else {
-            start_offset
-        };
-
-        // 采样区域
-        let sample_end = (data_start + sample_size).min(total_bytes);
-        let sample_slice = &mmap[data_start..sample_end];
-        
-        // 计算采样区域的行数
-        let sample_rows = memchr_iter(b'\n', sample_slice).count();
-        let sampled_bytes = sample_end - data_start;
+                match format.find_end(region, region_start) {
+                    Some(pos) => pos + 1,
+                    None => continue,
+                }
+            };
+            let Some(last) = format.rfind_end(region, first, region_end) else {
+                continue;
+            };
+            let rows = format.count_ends(region, first, region_end);
+            densities.push(rows as f64 / (last + 1 - first) as f64);
+        }
 
-        // 如果采样区域没有换行符，假设整个文件就是一行
-        if sample_rows == 0 {
+        // 如果采样区域都没有完整的行，假设整个文件就是一行
+        if densities.is_empty() {
             return RowEstimate {
                 estimated_rows: 1,
                 is_exact: false,
                 sampled_bytes,
                 total_bytes,
+                lower_bound: 1,
+                upper_bound: 1,
             };
         }
 
-        // 计算平均每行字节数，然后估算总行数
-        let bytes_per_row = sampled_bytes as f64 / sample_rows as f64;
-        let data_bytes = total_bytes - data_start;
-        let estimated_rows = (data_bytes as f64 / bytes_per_row).ceil() as usize;
+        // 按平均行密度估算总行数，标准误差的2倍作为区间
+        let count = densities.len() as f64;
+        let mean = densities.iter().sum::<f64>() / count;
+        let variance = if densities.len() > 1 {
+            densities.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (count - 1.0)
+        } else {
+            0.0
+        };
+        let margin = 2.0 * (variance / count).sqrt();
+        let rows_at = |density: f64| (data_bytes as f64 * density.max(0.0)).ceil() as usize;
 
         RowEstimate {
-            estimated_rows,
+            estimated_rows: rows_at(mean).max(1),
             is_exact: false,
             sampled_bytes,
             total_bytes,
+            lower_bound: rows_at(mean - margin).max(1),
+            upper_bound: rows_at(mean + margin).max(1),
         }
     }
 
     /// 精确计算行数（扫描整个文件）
-    fn count_rows_exact(mmap: &Mmap, has_headers: bool) -> usize {
-        let start_offset = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3usize
-        } else {
-            0usize
-        };
-
-        let data_start = if has_headers {
-            let header_slice = &mmap[start_offset..];
-            if let Some(pos) = memchr::memchr(b'\n', header_slice) {
-                start_offset + pos + 1
-            } else {
-                return 0;
-            }
-        } else {
-            start_offset
-        };
-
-        if data_start >= mmap.len() {
-            return 0;
-        }
-
-        let data_slice = &mmap[data_start..];
-        let newline_count = memchr_iter(b'\n', data_slice).count();
-
-        // 如果文件最后没有换行符但有内容，加1
-        if !data_slice.is_empty() && data_slice[data_slice.len() - 1] != b'\n' {
-            newline_count + 1
-        } else {
-            newline_count
-        }
+    fn count_rows_exact(mmap: &Mmap, has_headers: bool, format: LineFormat) -> usize {
+        count_lines(mmap, data_start(mmap, has_headers, format), format)
     }
 
     /// 构建部分索引（只索引前N行）
@@ -202,6 +477,7 @@ impl RowIndex {
     /// # 参数
     /// - `mmap`: 内存映射的文件
     /// - `has_headers`: 是否有表头
+    /// - `format`: 分行方式
     /// - `granularity`: 索引粒度
     /// - `max_rows`: 最多索引多少行（None表示全部）
     /// 
@@ -210,75 +486,41 @@ impl RowIndex {
     pub fn build_partial(
         mmap: &Mmap,
         has_headers: bool,
+        format: LineFormat,
         granularity: usize,
         max_rows: Option<usize>,
     ) -> Result<(Self, bool)> {
         let total_bytes = mmap.len();
-        
-        // 跳过BOM
-        let start_offset = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3u64
-        } else {
-            0u64
-        };
-
-        // 跳过表头
-        let data_start = if has_headers {
-            let header_slice = &mmap[start_offset as usize..];
-            if let Some(pos) = memchr::memchr(b'\n', header_slice) {
-                start_offset + pos as u64 + 1
-            } else {
-                start_offset
-            }
-        } else {
-            start_offset
-        };
-
         let mut offsets = Vec::new();
         let mut row_numbers = Vec::new();
         let mut current_row = 0;
-        let mut line_start = data_start;
         let max_rows = max_rows.unwrap_or(usize::MAX);
 
-        let data_slice = &mmap[data_start as usize..];
-        let mut last_newline_pos = None;
-
-        for newline_pos in memchr_iter(b'\n', data_slice) {
-            let absolute_pos = data_start as usize + newline_pos;
-            let absolute_pos_u64 = absolute_pos as u64;
-            
-            last_newline_pos = Some(absolute_pos_u64);
+        let mut lines = DataLines::new(mmap, data_start(mmap, has_headers, format), format);
+        while let Some((line_start, line_end)) = lines.next() {
             current_row += 1;
             
-            if current_row % granularity == 0 {
-                offsets.push(line_start);
+            // 末尾没有换行符的最后一行不记录索引点
+            if line_end < total_bytes && current_row % granularity == 0 {
+                offsets.push(line_start as u64);
                 row_numbers.push(current_row);
             }
-            
-            line_start = absolute_pos_u64 + 1;
 
-            // 达到最大行数限制
-            if current_row >= max_rows {
+            // 达到最大行数限制且还有未扫描的数据
+            if current_row >= max_rows && lines.position() < total_bytes {
                 return Ok((Self {
                     offsets,
                     row_numbers,
                     granularity,
                     total_rows: current_row,
                     is_complete: false,
-                    indexed_bytes: line_start,
+                    indexed_bytes: lines.position() as u64,
+                    mode: IndexMode::Sparse,
+                    column_index: None,
                 }, false));
             }
         }
 
-        // 处理最后一行
-        if let Some(last_nl) = last_newline_pos {
-            if ((last_nl + 1) as usize) < total_bytes {
-                current_row += 1;
-            }
-        } else if (data_start as usize) < total_bytes {
-            current_row = 1;
-        }
-
         Ok((Self {
             offsets,
             row_numbers,
@@ -286,6 +528,8 @@ impl RowIndex {
             total_rows: current_row,
             is_complete: true,
             indexed_bytes: total_bytes as u64,
+            mode: IndexMode::Sparse,
+            column_index: None,
         }, true))
     }
 
@@ -293,11 +537,31 @@ impl RowIndex {
     /// 
     /// # 参数
     /// - `mmap`: 内存映射的文件
+    /// - `format`: 分行方式（与构建时相同）
     /// - `cancel_flag`: 取消标志，设为true时停止构建
     /// - `progress`: 进度报告（已处理字节数）
     pub fn continue_build(
         &mut self,
         mmap: &Mmap,
+        format: LineFormat,
+        cancel_flag: Option<&AtomicBool>,
+        progress: Option<&AtomicUsize>,
+    ) -> Result<bool> {
+        self.continue_build_until(mmap, format, usize::MAX, cancel_flag, progress)
+    }
+
+    /// 继续构建索引，直到已索引 `max_rows` 行或到达文件末尾
+    /// 
+    /// 用于按需读取超出已索引区域的行：只扫描到目标行为止，
+    /// 之后再读取同一区域时直接按索引定位
+    /// 
+    /// # 返回
+    /// 是否已完成（到达文件末尾）
+    pub fn continue_build_until(
+        &mut self,
+        mmap: &Mmap,
+        format: LineFormat,
+        max_rows: usize,
         cancel_flag: Option<&AtomicBool>,
         progress: Option<&AtomicUsize>,
     ) -> Result<bool> {
@@ -313,41 +577,42 @@ impl RowIndex {
             return Ok(true);
         }
 
-        let data_slice = &mmap[start_offset..];
-        let mut line_start = self.indexed_bytes;
+        // 列索引只覆盖已索引的行，继续构建后失效
+        self.column_index = None;
+
+        let mut lines = DataLines::new(mmap, start_offset, format);
         let mut current_row = self.total_rows;
 
-        for newline_pos in memchr_iter(b'\n', data_slice) {
+        loop {
             // 检查是否取消
             if let Some(flag) = cancel_flag {
                 if flag.load(Ordering::Relaxed) {
-                    self.indexed_bytes = line_start;
+                    self.indexed_bytes = lines.position() as u64;
                     self.total_rows = current_row;
                     return Ok(false);
                 }
             }
 
-            let absolute_pos = start_offset + newline_pos;
-            let absolute_pos_u64 = absolute_pos as u64;
-            
+            let Some((line_start, line_end)) = lines.next() else {
+                break;
+            };
             current_row += 1;
-            
-            if current_row % self.granularity == 0 {
-                self.offsets.push(line_start);
-                self.row_numbers.push(current_row);
+            // 末尾没有换行符的最后一行不记录索引点
+            if line_end < total_bytes {
+                self.push_point(line_start as u64, current_row);
             }
-            
-            line_start = absolute_pos_u64 + 1;
 
             // 更新进度
             if let Some(prog) = progress {
-                prog.store(absolute_pos, Ordering::Relaxed);
+                prog.store(line_end, Ordering::Relaxed);
             }
-        }
 
-        // 处理最后一行
-        if line_start < total_bytes as u64 {
-            current_row += 1;
+            // 达到目标行数且还有未扫描的数据
+            if current_row >= max_rows && lines.position() < total_bytes {
+                self.indexed_bytes = lines.position() as u64;
+                self.total_rows = current_row;
+                return Ok(false);
+            }
         }
 
         self.total_rows = current_row;
@@ -361,6 +626,138 @@ impl RowIndex {
         Ok(true)
     }
 
+    /// 用追加到文件末尾的数据扩展索引
+    /// 
+    /// `data` 为追加后的完整文件内容，只扫描 `indexed_bytes()` 之后的部分，
+    /// 按与构建时相同的 `format` 分行，结果与重新构建整个索引一致
+    /// 
+    /// # 返回
+    /// 新增的行数
+    pub fn extend_to(&mut self, data: &[u8], format: LineFormat) -> Result<usize> {
+        let indexed = self.indexed_bytes as usize;
+        if data.len() < indexed {
+            return Err(CsvError::IndexFile(format!(
+                "文件已被截断（{} < {} 字节），需要重建索引",
+                data.len(),
+                indexed
+            )));
+        }
+
+        // 列索引只覆盖已索引的行，扩展后失效
+        self.column_index = None;
+
+        let old_rows = self.total_rows;
+        let mut current_row = self.total_rows;
+        let mut resume = indexed;
+
+        // 上次扫描时末尾没有换行符的半行已计入总行数，需要回退后重新计算；
+        // 末尾的 \r 与追加的 \n 组成一个行结束符时同样重新计算，并去掉该行已记录的索引点。
+        // 上次作为空行跳过的半行没有计入，只需重新扫描
+        if indexed > 0 && current_row > 0 && !format.is_end(data, indexed - 1) {
+            resume = format.rfind_end(data, 0, indexed - 1).map_or(skip_bom(data), |pos| pos + 1);
+            let old_end = if format.is_end(&data[..indexed], indexed - 1) { indexed - 1 } else { indexed };
+            if !format.is_skipped(&data[resume..old_end]) {
+                current_row -= 1;
+                if self.offsets.last() == Some(&(resume as u64)) {
+                    self.offsets.pop();
+                    if self.mode == IndexMode::Sparse {
+                        self.row_numbers.pop();
+                    }
+                }
+            }
+        }
+
+        for (line_start, line_end) in DataLines::new(data, resume, format) {
+            current_row += 1;
+            // 末尾没有换行符的最后一行不记录索引点
+            if line_end < data.len() {
+                self.push_point(line_start as u64, current_row);
+            }
+        }
+
+        self.total_rows = current_row;
+        self.indexed_bytes = data.len() as u64;
+        self.is_complete = true;
+
+        Ok(current_row.saturating_sub(old_rows))
+    }
+
+    /// 文件只在末尾追加了数据时，增量扩展已加载的索引
+    /// 
+    /// 通过旧文件大小范围内的校验和确认原有内容未变，再只扫描新增的部分；
+    /// 文件被修改、截断或旧索引没有校验和时返回false，需要完整重建
+    /// 
+    /// # 参数
+    /// - `csv_path`: CSV文件路径
+    /// - `data`: 当前的文件内容
+    /// - `format`: 分行方式（与构建时相同）
+    /// - `old_metadata`: 加载索引时读取的元数据
+    pub fn refresh(
+        &mut self,
+        csv_path: &Path,
+        data: &[u8],
+        format: LineFormat,
+        old_metadata: &IndexMetadata,
+    ) -> Result<bool> {
+        let Some(checksum) = old_metadata.checksum else {
+            return Ok(false);
+        };
+        if !self.is_complete
+            || old_metadata.index_version != CURRENT_INDEX_VERSION
+            || !Self::same_csv_path(csv_path, old_metadata)
+        {
+            return Ok(false);
+        }
+
+        // 只处理文件变大的情况
+        let current_size = std::fs::metadata(csv_path)?.len();
+        if current_size <= old_metadata.csv_size || data.len() < self.indexed_bytes as usize {
+            return Ok(false);
+        }
+
+        // 旧文件范围内的内容必须与建索引时一致
+        if file_checksum(csv_path, old_metadata.csv_size)? != checksum {
+            return Ok(false);
+        }
+
+        self.extend_to(data, format)?;
+        Ok(true)
+    }
+
+    /// 用CSV文件新追加的数据扩展索引并保存索引文件
+    /// 
+    /// 仅适用于未压缩的UTF-8文件；只用 `\r` 换行的文件和空行按与 [`CsvReader`](crate::csv::CsvReader) 默认相同的方式处理
+    /// 
+    /// # 参数
+    /// - `csv_path`: CSV文件路径
+    /// - `has_headers`: 构建索引时是否有表头（记录到索引元数据）
+    /// - `location`: 索引文件存放位置
+    /// 
+    /// # 返回
+    /// 新增的行数
+    pub fn extend_and_save(&mut self, csv_path: &Path, has_headers: bool, location: &IndexLocation) -> Result<usize> {
+        let file = File::open(csv_path)?;
+        let file_metadata = file.metadata()?;
+        let mmap = unsafe { Mmap::map(&file) }
+            .map_err(|e| CsvError::Mmap(e.to_string()))?;
+        let format = LineFormat::detect(&mmap).with_skip_empty(true);
+
+        let added = self.extend_to(&mmap, format)?;
+
+        let metadata = IndexMetadata::new(
+            csv_path.to_path_buf(),
+            file_metadata.len(),
+            file_metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+            self.granularity,
+        )
+        .with_headers(has_headers)
+        .with_mode(self.mode)
+        .with_checksum(csv_path);
+        self.save_to_location(csv_path, &metadata, location)?;
+
+        Ok(added)
+    }
+
     /// 检查索引是否完成
     pub fn is_complete(&self) -> bool {
         self.is_complete
@@ -376,6 +773,7 @@ impl RowIndex {
     /// # 参数
     /// - `mmap`: 内存映射的文件
     /// - `has_headers`: 是否有表头
+    /// - `format`: 分行方式
     /// - `granularity`: 索引粒度（每N行记录一次）
     /// 
     /// # 注意
@@ -383,60 +781,98 @@ impl RowIndex {
     pub fn build(
         mmap: &Mmap, 
         has_headers: bool, 
+        format: LineFormat,
         granularity: usize,
     ) -> Result<Self> {
-        // 对于大文件（>100MB），使用并行构建
-        const PARALLEL_THRESHOLD: usize = 100 * 1024 * 1024; // 100MB
-        if mmap.len() > PARALLEL_THRESHOLD {
-            Self::build_parallel::<fn(f64, usize, usize)>(mmap, has_headers, granularity, None)
+        Self::build_auto::<fn(f64, usize, usize)>(mmap, has_headers, format, granularity, None)
+    }
+
+    /// 按文件大小选择并行或单线程构建索引，并汇报进度
+    /// 
+    /// # 参数
+    /// - `progress_callback`: 可选的进度回调函数 (进度百分比, 已处理字节数, 总字节数)
+    pub fn build_auto<F>(
+        mmap: &Mmap,
+        has_headers: bool,
+        format: LineFormat,
+        granularity: usize,
+        progress_callback: Option<F>,
+    ) -> Result<Self>
+    where
+        F: FnMut(f64, usize, usize),
+    {
+        if mmap.len() > PARALLEL_BUILD_THRESHOLD {
+            Self::build_parallel(mmap, has_headers, format, granularity, progress_callback)
         } else {
-            Self::build_with_progress::<fn(f64, usize, usize)>(mmap, has_headers, granularity, None)
+            Self::build_with_progress(mmap, has_headers, format, granularity, progress_callback)
         }
     }
 
     /// 并行构建索引（多线程）
     /// 
+    /// 各线程按已扫描的字节数累加共享计数器，调用线程定期读取计数器汇报进度
+    /// 
     /// # 参数
     /// - `mmap`: 内存映射的文件
     /// - `has_headers`: 是否有表头
+    /// - `format`: 分行方式
     /// - `granularity`: 索引粒度（每N行记录一次）
-    /// - `_progress_callback`: 可选的进度回调函数（当前未实现，保留用于未来扩展）
+    /// - `progress_callback`: 可选的进度回调函数 (进度百分比, 已处理字节数, 总字节数)，
+    ///   在调用线程中执行
     /// 
     /// # 性能
     /// 对于大文件（>100MB），使用多线程可以提升 2-4倍速度（取决于CPU核心数）
     pub fn build_parallel<F>(
         mmap: &Mmap,
         has_headers: bool,
+        format: LineFormat,
         granularity: usize,
-        _progress_callback: Option<F>,
+        progress_callback: Option<F>,
     ) -> Result<Self>
     where
-        F: FnMut(f64, usize, usize) + Send + Sync,
+        F: FnMut(f64, usize, usize),
     {
-        let total_bytes = mmap.len();
-        
-        // 如果有多余的字节，跳过BOM标记
-        let start_offset = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3u64
-        } else {
-            0u64
+        let processed = AtomicUsize::new(0);
+        let Some(mut callback) = progress_callback else {
+            return Self::build_parallel_counted(mmap, has_headers, format, granularity, &processed);
         };
 
-        // 计算数据起始位置（跳过表头）
-        let data_start_offset = if has_headers {
-            let header_slice = &mmap[start_offset as usize..];
-            if let Some(pos) = memchr::memchr(b'\n', header_slice) {
-                start_offset + pos as u64 + 1
-            } else {
-                start_offset
+        let total_bytes = mmap.len();
+        let result = std::thread::scope(|scope| {
+            let handle = scope.spawn(|| Self::build_parallel_counted(mmap, has_headers, format, granularity, &processed));
+            let mut last_reported = None;
+            while !handle.is_finished() {
+                let done = processed.load(Ordering::Relaxed);
+                if last_reported != Some(done) {
+                    callback(done as f64 / total_bytes.max(1) as f64 * 100.0, done, total_bytes);
+                    last_reported = Some(done);
+                }
+                std::thread::sleep(PARALLEL_PROGRESS_POLL);
             }
-        } else {
-            start_offset
-        };
+            handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })?;
+
+        callback(100.0, total_bytes, total_bytes);
+        Ok(result)
+    }
+
+    /// 并行构建索引，扫描过程中把已处理的字节数（含表头）累加到 `processed`
+    pub(crate) fn build_parallel_counted(
+        mmap: &[u8],
+        has_headers: bool,
+        format: LineFormat,
+        granularity: usize,
+        processed: &AtomicUsize,
+    ) -> Result<Self> {
+        let total_bytes = mmap.len();
+        
+        // 数据起始位置（跳过BOM和表头）
+        let data_start_offset = data_start(mmap, has_headers, format) as u64;
+        processed.fetch_add(data_start_offset as usize, Ordering::Relaxed);
 
         // 确定线程数和块大小
         let num_threads = rayon::current_num_threads();
-        let chunk_size = (total_bytes as usize - data_start_offset as usize) / num_threads;
+        let chunk_size = (total_bytes - data_start_offset as usize) / num_threads;
         // 确保块大小至少为1MB，避免过多线程
         let min_chunk_size = 1024 * 1024;
         let effective_chunk_size = chunk_size.max(min_chunk_size);
@@ -450,37 +886,26 @@ impl RowIndex {
             current_pos = chunk_end;
         }
 
-        // 并行处理每个块，查找所有换行符位置
-        let chunk_results: Vec<_> = chunks
+        // 并行处理每个块，查找所有行结束符位置；块之间不重叠，每个行结束符只记录一次
+        // （是否为行结束符按整个文件判断，跨块的 \r\n 只在 \n 处记录）。
+        // 每扫描 PARALLEL_PROGRESS_STEP 字节累加一次进度
+        let chunk_results: Vec<Vec<usize>> = chunks
             .into_par_iter()
             .map(|(chunk_start, chunk_end)| {
-                // 处理块边界：如果不是第一个块，需要找到第一个完整的行
-                let mut actual_start = chunk_start;
-                if chunk_start > data_start_offset as usize {
-                    // 向前查找换行符（最多向前查找1KB）
-                    let search_start = chunk_start.saturating_sub(1024);
-                    if let Some(pos) = memchr::memchr(b'\n', &mmap[search_start..chunk_start]) {
-                        actual_start = search_start + pos + 1;
-                    }
-                }
-
-                // 查找该块内的所有换行符位置
                 let mut newline_positions = Vec::new();
-                let chunk_data = &mmap[actual_start..chunk_end];
-                for pos in memchr_iter(b'\n', chunk_data) {
-                    newline_positions.push(actual_start + pos);
+                let mut step_start = chunk_start;
+                while step_start < chunk_end {
+                    let step_end = (step_start + PARALLEL_PROGRESS_STEP).min(chunk_end);
+                    format.for_each_end(mmap, step_start, step_end, |pos| newline_positions.push(pos));
+                    processed.fetch_add(step_end - step_start, Ordering::Relaxed);
+                    step_start = step_end;
                 }
-
-                (actual_start, newline_positions)
+                newline_positions
             })
             .collect();
 
-        // 合并所有块的结果，按位置排序
-        let mut all_newlines: Vec<usize> = chunk_results
-            .into_iter()
-            .flat_map(|(_, newlines)| newlines)
-            .collect();
-        all_newlines.sort_unstable();
+        // 各块按位置顺序返回，依次拼接即为有序的换行符位置
+        let all_newlines: Vec<usize> = chunk_results.into_iter().flatten().collect();
 
         // 计算索引点和行号
         let mut offsets = Vec::new();
@@ -489,38 +914,29 @@ impl RowIndex {
         let mut line_start = data_start_offset;
 
         for &nl_pos in &all_newlines {
-            current_row += 1;
-            let nl_pos_u64 = nl_pos as u64;
-            
-            // 每N行记录一次索引点
-            if current_row % granularity == 0 {
-                offsets.push(line_start);
-                row_numbers.push(current_row);
+            // 跳过的空行不计入行数
+            if !format.is_skipped(&mmap[line_start as usize..nl_pos]) {
+                current_row += 1;
+
+                // 每N行记录一次索引点
+                if current_row % granularity == 0 {
+                    offsets.push(line_start);
+                    row_numbers.push(current_row);
+                }
             }
             
             // 更新下一行的起始位置
-            line_start = nl_pos_u64 + 1;
+            line_start = nl_pos as u64 + 1;
         }
 
-        // 处理最后一行（如果文件末尾没有换行符，但还有内容）
-        let total_rows = if let Some(&last_nl) = all_newlines.last() {
-            if (last_nl + 1) < total_bytes {
-                all_newlines.len() + 1
-            } else {
-                all_newlines.len()
-            }
+        // 末尾没有换行符但还有内容时，最后一行也计入
+        let tail = &mmap[(line_start as usize).min(total_bytes)..];
+        let total_rows = if !tail.is_empty() && !format.is_skipped(tail) {
+            current_row + 1
         } else {
-            // 如果没有找到任何换行符，但文件不为空，说明只有一行
-            if (data_start_offset as usize) < total_bytes {
-                1
-            } else {
-                0
-            }
+            current_row
         };
 
-        // 注意：进度回调在多线程环境下难以实现，这里暂时忽略
-        // 如果需要进度显示，可以使用单线程版本
-
         Ok(Self {
             offsets,
             row_numbers,
@@ -528,6 +944,8 @@ impl RowIndex {
             total_rows,
             is_complete: true,
             indexed_bytes: total_bytes as u64,
+            mode: IndexMode::Sparse,
+            column_index: None,
         })
     }
 
@@ -536,11 +954,13 @@ impl RowIndex {
     /// # 参数
     /// - `mmap`: 内存映射的文件
     /// - `has_headers`: 是否有表头
+    /// - `format`: 分行方式
     /// - `granularity`: 索引粒度（每N行记录一次）
     /// - `progress_callback`: 可选的进度回调函数 (进度百分比, 已处理字节数, 总字节数)
     pub fn build_with_progress<F>(
         mmap: &Mmap, 
         has_headers: bool, 
+        format: LineFormat,
         granularity: usize,
         mut progress_callback: Option<F>,
     ) -> Result<Self>
@@ -550,65 +970,29 @@ impl RowIndex {
         let mut offsets = Vec::new();
         let mut row_numbers = Vec::new();
         let mut current_row = 0;
-        let current_offset: u64;
-        let mut line_start: u64;
 
         let total_bytes = mmap.len();
         let progress_interval = (total_bytes / 100).max(1024 * 1024); // 每1%或每1MB更新一次进度
         let mut last_progress_update = 0usize;
 
-        // 如果有多余的字节，跳过BOM标记
-        let start_offset = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3u64
-        } else {
-            0u64
-        };
-
-        // 跳过表头（如果存在）- 使用memchr加速
-        if has_headers {
-            let header_slice = &mmap[start_offset as usize..];
-            if let Some(pos) = memchr::memchr(b'\n', header_slice) {
-                line_start = start_offset + pos as u64 + 1;
-                current_offset = line_start;
-            } else {
-                // 如果没有找到换行符，整个文件就是一行
-                current_offset = start_offset;
-                line_start = start_offset;
-            }
-        } else {
-            current_offset = start_offset;
-            line_start = start_offset;
-        }
-
-        // 扫描文件，记录索引点 - 使用memchr批量查找换行符
-        let data_slice = &mmap[current_offset as usize..];
-        let mut last_newline_pos = None;
-        
-        // 批量处理换行符，减少循环开销
-        for newline_pos in memchr_iter(b'\n', data_slice) {
-            let absolute_pos = current_offset as usize + newline_pos;
-            let absolute_pos_u64 = absolute_pos as u64;
-            
+        // 扫描数据区（跳过BOM和表头），记录索引点 - 使用memchr批量查找换行符
+        for (line_start, line_end) in DataLines::new(mmap, data_start(mmap, has_headers, format), format) {
             // 更新进度（每1MB或1%更新一次）
             if let Some(ref mut callback) = progress_callback {
-                if absolute_pos - last_progress_update >= progress_interval {
-                    let progress = (absolute_pos as f64 / total_bytes as f64) * 100.0;
-                    callback(progress, absolute_pos, total_bytes);
-                    last_progress_update = absolute_pos;
+                if line_end - last_progress_update >= progress_interval {
+                    let progress = (line_end as f64 / total_bytes as f64) * 100.0;
+                    callback(progress, line_end, total_bytes);
+                    last_progress_update = line_end;
                 }
             }
             
-            last_newline_pos = Some(absolute_pos_u64);
             current_row += 1;
             
-            // 每N行记录一次索引点
-            if current_row % granularity == 0 {
-                offsets.push(line_start);
+            // 每N行记录一次索引点（末尾没有换行符的最后一行除外）
+            if line_end < total_bytes && current_row % granularity == 0 {
+                offsets.push(line_start as u64);
                 row_numbers.push(current_row);
             }
-            
-            // 更新下一行的起始位置
-            line_start = absolute_pos_u64 + 1;
         }
         
         // 最终进度更新
@@ -616,19 +1000,6 @@ impl RowIndex {
             callback(100.0, total_bytes, total_bytes);
         }
 
-        // 处理最后一行（如果文件末尾没有换行符，但还有内容）
-        if let Some(last_nl) = last_newline_pos {
-            // 如果最后一个换行符之后还有内容，说明还有一行
-            if ((last_nl + 1) as usize) < mmap.len() {
-                current_row += 1;
-            }
-        } else {
-            // 如果没有找到任何换行符，但文件不为空，说明只有一行
-            if (current_offset as usize) < mmap.len() {
-                current_row = 1;
-            }
-        }
-
         Ok(Self {
             offsets,
             row_numbers,
@@ -636,18 +1007,74 @@ impl RowIndex {
             total_rows: current_row,
             is_complete: true,
             indexed_bytes: total_bytes as u64,
+            mode: IndexMode::Sparse,
+            column_index: None,
         })
     }
 
-    /// 查找目标行对应的字节偏移量
+    /// 构建索引并校验每行的列数（单线程）
+    /// 
+    /// 除换行符外还需逐字节统计引号外的分隔符，比 [`RowIndex::build`] 慢，
+    /// 因此仅在需要校验时使用
     /// 
     /// # 参数
-    /// - `target_row`: 目标行号（不包括表头，从0开始）
+    /// - `mmap`: 内存映射的文件
+    /// - `has_headers`: 是否有表头
+    /// - `format`: 分行方式
+    /// - `granularity`: 索引粒度（每N行记录一次）
+    /// - `validation`: 列数校验配置
     /// 
     /// # 返回
-    /// 字节偏移量，用于定位到目标行附近
-    pub fn seek_to_row(&self, target_row: usize) -> Result<u64> {
-        let (offset, _) = self.seek_to_row_with_info(target_row)?;
+    /// (索引, 校验报告)；`fail_fast` 时遇到第一条不一致的行即返回 `CsvError::Format`
+    pub fn build_validated(
+        mmap: &Mmap,
+        has_headers: bool,
+        format: LineFormat,
+        granularity: usize,
+        validation: &ColumnValidation,
+    ) -> Result<(Self, ValidationReport)> {
+        let total_bytes = mmap.len();
+
+        let mut offsets = Vec::new();
+        let mut row_numbers = Vec::new();
+        let mut current_row = 0;
+        let mut report = ValidationReport {
+            expected_cols: validation.expected_cols,
+            ..ValidationReport::default()
+        };
+
+        for (line_start, line_end) in DataLines::new(mmap, data_start(mmap, has_headers, format), format) {
+            report.check_line(current_row, &mmap[line_start..line_end], validation)?;
+
+            current_row += 1;
+            // 末尾没有换行符的最后一行不记录索引点
+            if line_end < total_bytes && current_row % granularity == 0 {
+                offsets.push(line_start as u64);
+                row_numbers.push(current_row);
+            }
+        }
+
+        Ok((Self {
+            offsets,
+            row_numbers,
+            granularity,
+            total_rows: current_row,
+            is_complete: true,
+            indexed_bytes: total_bytes as u64,
+            mode: IndexMode::Sparse,
+            column_index: None,
+        }, report))
+    }
+
+    /// 查找目标行对应的字节偏移量
+    /// 
+    /// # 参数
+    /// - `target_row`: 目标行号（不包括表头，从0开始）
+    /// 
+    /// # 返回
+    /// 字节偏移量，用于定位到目标行附近
+    pub fn seek_to_row(&self, target_row: usize) -> Result<u64> {
+        let (offset, _) = self.seek_to_row_with_info(target_row)?;
         Ok(offset)
     }
 
@@ -672,22 +1099,32 @@ impl RowIndex {
             return Ok((0, 0));
         }
 
+        // 稠密索引直接定位；末尾没有换行符的最后一行没有索引点，从前一行开始扫描
+        if self.mode == IndexMode::Dense {
+            let row = target_row.min(self.offsets.len() - 1);
+            return Ok((self.offsets[row], row));
+        }
+
+        // 索引点记录的是已扫描行数 N 及此时的行首偏移量，
+        // 即 offsets[i] 是第 row_numbers[i] - 1 行（从0开始）的起始位置
+        let key = target_row + 1;
+
         // 如果目标行在第一个索引点之前，从头开始
-        if target_row < self.row_numbers[0] {
+        if key < self.row_numbers[0] {
             return Ok((0, 0));
         }
 
         // 二分查找找到最近的索引点
-        let idx = match self.row_numbers.binary_search(&target_row) {
+        let idx = match self.row_numbers.binary_search(&key) {
             Ok(i) => i, // 精确匹配
             Err(i) => {
                 // 找到插入位置，使用前一个索引点
-                // 这里 i > 0 因为我们已经处理了 target_row < row_numbers[0] 的情况
+                // 这里 i > 0 因为我们已经处理了 key < row_numbers[0] 的情况
                 i - 1
             }
         };
 
-        Ok((self.offsets[idx], self.row_numbers[idx]))
+        Ok((self.offsets[idx], self.row_numbers[idx] - 1))
     }
 
     /// 获取总行数
@@ -700,6 +1137,25 @@ impl RowIndex {
         self.granularity
     }
 
+    /// 获取索引模式
+    pub fn mode(&self) -> IndexMode {
+        self.mode
+    }
+
+    /// 获取列索引
+    /// 
+    /// 行数与索引不一致（如索引尚未构建完成）时返回 None
+    pub fn column_index(&self) -> Option<&ColumnIndex> {
+        self.column_index
+            .as_ref()
+            .filter(|column_index| column_index.len() == self.total_rows)
+    }
+
+    /// 设置列索引（随索引文件一起保存）
+    pub fn set_column_index(&mut self, column_index: Option<ColumnIndex>) {
+        self.column_index = column_index;
+    }
+
     /// 获取索引点数量
     pub fn index_count(&self) -> usize {
         self.offsets.len()
@@ -722,6 +1178,43 @@ impl RowIndex {
         path
     }
 
+    /// 按存放位置生成索引文件路径
+    /// 
+    /// 缓存目录和指定目录下的文件名带有CSV文件规范化路径的哈希，
+    /// 不同目录下的同名文件不会冲突
+    /// 
+    /// # 参数
+    /// - `csv_path`: CSV文件路径
+    /// - `location`: 索引文件存放位置
+    pub fn index_file_path_in(csv_path: &Path, location: &IndexLocation) -> PathBuf {
+        let dir = match location {
+            IndexLocation::Sidecar => return Self::index_file_path(csv_path),
+            IndexLocation::CacheDir => IndexLocation::cache_dir(),
+            IndexLocation::Custom(dir) => dir.clone(),
+        };
+        let canonical = csv_path.canonicalize().unwrap_or_else(|_| csv_path.to_path_buf());
+        let hash = xxh3_64(canonical.to_string_lossy().as_bytes());
+        let name = csv_path.file_name().map_or("index".into(), |name| name.to_string_lossy());
+        dir.join(format!("{}-{:016x}.idx", name, hash))
+    }
+
+    /// 查找已有的索引文件
+    /// 
+    /// 默认位置下同目录没有索引文件时，还会查找因无权写入而改存到缓存目录的索引
+    pub fn find_index_file(csv_path: &Path, location: &IndexLocation) -> Option<PathBuf> {
+        let index_path = Self::index_file_path_in(csv_path, location);
+        if index_path.exists() {
+            return Some(index_path);
+        }
+        if *location == IndexLocation::Sidecar {
+            let fallback = Self::index_file_path_in(csv_path, &IndexLocation::CacheDir);
+            if fallback.exists() {
+                return Some(fallback);
+            }
+        }
+        None
+    }
+
     /// 保存索引到文件
     /// 
     /// # 参数
@@ -732,17 +1225,50 @@ impl RowIndex {
     /// 成功时返回索引文件路径
     pub fn save_to_file(&self, csv_path: &Path, metadata: &IndexMetadata) -> Result<PathBuf> {
         let index_path = Self::index_file_path(csv_path);
-        
-        let mut file = File::create(&index_path)
-            .map_err(|e| CsvError::IndexFile(format!("无法创建索引文件: {}", e)))?;
+        self.save_to_path(&index_path, metadata)?;
+        Ok(index_path)
+    }
+
+    /// 按存放位置保存索引
+    /// 
+    /// 默认位置无权写入（只读目录、只读挂载）时改存到缓存目录
+    /// 
+    /// # 返回
+    /// 成功时返回实际写入的索引文件路径
+    pub fn save_to_location(&self, csv_path: &Path, metadata: &IndexMetadata, location: &IndexLocation) -> Result<PathBuf> {
+        let index_path = Self::index_file_path_in(csv_path, location);
+        if *location != IndexLocation::Sidecar {
+            if let Some(dir) = index_path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+
+        match self.save_to_path(&index_path, metadata) {
+            Err(CsvError::Io(e)) if *location == IndexLocation::Sidecar && is_unwritable(&e) => {
+                self.save_to_location(csv_path, metadata, &IndexLocation::CacheDir)
+            }
+            result => result.map(|_| index_path),
+        }
+    }
+
+    /// 保存索引到指定路径（索引点较多时自动压缩）
+    fn save_to_path(&self, index_path: &Path, metadata: &IndexMetadata) -> Result<()> {
+        let mut metadata = metadata.clone();
+        metadata.compressed |= self.offsets.len() >= COMPRESS_MIN_POINTS;
+        self.write_index_file(index_path, &metadata)
+    }
+
+    /// 写入索引文件
+    fn write_index_file(&self, index_path: &Path, metadata: &IndexMetadata) -> Result<()> {
+        // 保留IO错误类型，调用者据此判断是否改用其他位置
+        let mut file = File::create(index_path)?;
 
         // 序列化元数据
         let metadata_bytes = bincode::serialize(metadata)
             .map_err(|e| CsvError::IndexFile(format!("序列化元数据失败: {}", e)))?;
         
         // 序列化索引
-        let index_bytes = bincode::serialize(self)
-            .map_err(|e| CsvError::IndexFile(format!("序列化索引失败: {}", e)))?;
+        let index_bytes = self.encode_payload(metadata.compressed)?;
 
         // 写入文件格式：
         // [元数据长度: u64][元数据][索引数据]
@@ -754,11 +1280,13 @@ impl RowIndex {
         file.write_all(&index_bytes)
             .map_err(|e| CsvError::IndexFile(format!("写入索引数据失败: {}", e)))?;
 
-        Ok(index_path)
+        Ok(())
     }
 
     /// 从文件加载索引
     /// 
+    /// 旧版本格式的索引会升级到当前版本并写回文件，而不是重建
+    /// 
     /// # 参数
     /// - `index_path`: 索引文件路径
     /// 
@@ -778,21 +1306,221 @@ impl RowIndex {
         let mut metadata_bytes = vec![0u8; metadata_len];
         file.read_exact(&mut metadata_bytes)
             .map_err(|e| CsvError::IndexFile(format!("读取元数据失败: {}", e)))?;
-        
-        let metadata: IndexMetadata = bincode::deserialize(&metadata_bytes)
-            .map_err(|e| CsvError::IndexFile(format!("反序列化元数据失败: {}", e)))?;
 
         // 读取索引数据（剩余所有数据）
         let mut index_bytes = Vec::new();
         file.read_to_end(&mut index_bytes)
             .map_err(|e| CsvError::IndexFile(format!("读取索引数据失败: {}", e)))?;
 
-        let index: RowIndex = bincode::deserialize(&index_bytes)
+        // 各版本元数据的前几个字段相同，先读出版本号
+        let header: IndexVersionHeader = bincode::deserialize(&metadata_bytes)
+            .map_err(|e| CsvError::IndexFile(format!("反序列化元数据失败: {}", e)))?;
+
+        if header.index_version != CURRENT_INDEX_VERSION {
+            let (index, metadata) = Self::migrate(header.index_version, &metadata_bytes, &index_bytes)?;
+            // 写回升级后的索引，下次加载无需再迁移（写入失败不影响使用）
+            let _ = index.write_index_file(index_path, &metadata);
+            return Ok((index, metadata));
+        }
+
+        let metadata: IndexMetadata = bincode::deserialize(&metadata_bytes)
+            .map_err(|e| CsvError::IndexFile(format!("反序列化元数据失败: {}", e)))?;
+
+        let index = Self::decode_payload(&index_bytes, metadata.compressed)?;
+
+        Ok((index, metadata))
+    }
+
+    /// 序列化索引数据
+    /// 
+    /// 偏移量和行号按差值+变长整数编码（单调递增，差值通常只需1-2字节），
+    /// 压缩时再整体用zlib压缩
+    fn encode_payload(&self, compressed: bool) -> Result<Vec<u8>> {
+        let packed = PackedRowIndex {
+            offsets: encode_deltas(self.offsets.iter().copied()),
+            row_numbers: encode_deltas(self.row_numbers.iter().map(|&n| n as u64)),
+            granularity: self.granularity,
+            total_rows: self.total_rows,
+            is_complete: self.is_complete,
+            indexed_bytes: self.indexed_bytes,
+            mode: self.mode,
+            column_index: self.column_index.clone(),
+        };
+        let bytes = bincode::serialize(&packed)
+            .map_err(|e| CsvError::IndexFile(format!("序列化索引失败: {}", e)))?;
+        if !compressed {
+            return Ok(bytes);
+        }
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes)
+            .and_then(|_| encoder.finish())
+            .map_err(|e| CsvError::IndexFile(format!("压缩索引失败: {}", e)))
+    }
+
+    /// 反序列化索引数据
+    fn decode_payload(bytes: &[u8], compressed: bool) -> Result<Self> {
+        let mut decompressed = Vec::new();
+        let bytes = if compressed {
+            ZlibDecoder::new(bytes)
+                .read_to_end(&mut decompressed)
+                .map_err(|e| CsvError::IndexFile(format!("解压索引失败: {}", e)))?;
+            &decompressed
+        } else {
+            bytes
+        };
+        let packed: PackedRowIndex = bincode::deserialize(bytes)
             .map_err(|e| CsvError::IndexFile(format!("反序列化索引失败: {}", e)))?;
 
+        Ok(Self {
+            offsets: decode_deltas(&packed.offsets)?,
+            row_numbers: decode_deltas(&packed.row_numbers)?
+                .into_iter()
+                .map(|n| n as usize)
+                .collect(),
+            granularity: packed.granularity,
+            total_rows: packed.total_rows,
+            is_complete: packed.is_complete,
+            indexed_bytes: packed.indexed_bytes,
+            mode: packed.mode,
+            column_index: packed.column_index,
+        })
+    }
+
+    /// 将旧版本格式的索引升级到当前版本
+    /// 
+    /// # 参数
+    /// - `from_version`: 索引文件的格式版本
+    /// - `metadata_bytes`: 序列化的元数据
+    /// - `index_bytes`: 序列化的索引数据
+    /// 
+    /// # 返回
+    /// 升级后的(索引, 元数据)，版本号为 [`CURRENT_INDEX_VERSION`]
+    pub fn migrate(from_version: u32, metadata_bytes: &[u8], index_bytes: &[u8]) -> Result<(Self, IndexMetadata)> {
+        let decode_error = |e: bincode::Error| {
+            CsvError::IndexFile(format!("升级 v{} 索引失败: {}", from_version, e))
+        };
+
+        let (index, mut metadata) = match from_version {
+            1 => {
+                let v1: IndexMetadataV1 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                // 增量构建之前的 v1 索引没有 is_complete / indexed_bytes 字段
+                let index = match bincode::deserialize::<RowIndexV1>(index_bytes) {
+                    Ok(index) => index,
+                    Err(_) => {
+                        let legacy: RowIndexV1Legacy = bincode::deserialize(index_bytes).map_err(decode_error)?;
+                        RowIndexV1 {
+                            offsets: legacy.offsets,
+                            row_numbers: legacy.row_numbers,
+                            granularity: legacy.granularity,
+                            total_rows: legacy.total_rows,
+                            is_complete: true,
+                            indexed_bytes: v1.csv_size,
+                        }
+                    }
+                };
+                let index = RowIndexV2 {
+                    offsets: index.offsets,
+                    row_numbers: index.row_numbers,
+                    granularity: index.granularity,
+                    total_rows: index.total_rows,
+                    is_complete: index.is_complete,
+                    indexed_bytes: index.indexed_bytes,
+                    mode: IndexMode::Sparse,
+                };
+                // v1 未记录表头设置，按当时的默认值（有表头）处理
+                let metadata = IndexMetadataV2 {
+                    csv_path: v1.csv_path,
+                    csv_size: v1.csv_size,
+                    csv_mtime: v1.csv_mtime,
+                    index_version: v1.index_version,
+                    build_time: v1.build_time,
+                    granularity: v1.granularity,
+                    mode: IndexMode::Sparse,
+                    has_headers: true,
+                };
+                (index.into_current(), metadata.into_current())
+            }
+            2 => {
+                let metadata: IndexMetadataV2 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index: RowIndexV2 = bincode::deserialize(index_bytes).map_err(decode_error)?;
+                (index.into_current(), metadata.into_current())
+            }
+            // v3-v5 的列索引没有布隆过滤器，升级时丢弃（需要时重新构建）
+            3 => {
+                let metadata: IndexMetadataV2 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index: RowIndexV2 = bincode::deserialize(index_bytes).map_err(decode_error)?;
+                (index.into_current(), metadata.into_current())
+            }
+            4 => {
+                let metadata: IndexMetadataV4 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index = decode_payload_v5(index_bytes, metadata.compressed).map_err(decode_error)?;
+                (index.into_current(), metadata.into_current())
+            }
+            5 => {
+                let metadata: IndexMetadataV5 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index = decode_payload_v5(index_bytes, metadata.compressed).map_err(decode_error)?;
+                (index.into_current(), metadata.into_current())
+            }
+            // v6 未压缩时直接序列化行索引；压缩格式与当前相同
+            6 => {
+                let metadata: IndexMetadataV5 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index = if metadata.compressed {
+                    Self::decode_payload(index_bytes, true)?
+                } else {
+                    bincode::deserialize(index_bytes).map_err(decode_error)?
+                };
+                (index, metadata.into_current())
+            }
+            // v7 的索引数据与当前相同，元数据没有整个文件的哈希
+            7 => {
+                let metadata: IndexMetadataV5 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index = Self::decode_payload(index_bytes, metadata.compressed)?;
+                (index, metadata.into_current())
+            }
+            // v8 的索引数据与当前相同，构建时空行计入行数
+            8 => {
+                let metadata: IndexMetadataV8 = bincode::deserialize(metadata_bytes).map_err(decode_error)?;
+                let index = Self::decode_payload(index_bytes, metadata.compressed)?;
+                (index, metadata.into_current())
+            }
+            v if v > CURRENT_INDEX_VERSION => {
+                return Err(CsvError::IndexFile(format!(
+                    "索引文件由更新版本的程序创建: v{}（当前版本 v{}）",
+                    from_version, CURRENT_INDEX_VERSION
+                )));
+            }
+            _ => {
+                return Err(CsvError::IndexFile(format!(
+                    "不支持的索引版本: v{}（当前版本 v{}）",
+                    from_version, CURRENT_INDEX_VERSION
+                )));
+            }
+        };
+
+        metadata.index_version = CURRENT_INDEX_VERSION;
         Ok((index, metadata))
     }
 
+    /// 索引元数据记录的路径是否指向该CSV文件（规范化路径比较）
+    fn same_csv_path(csv_path: &Path, metadata: &IndexMetadata) -> bool {
+        match (csv_path.canonicalize(), metadata.csv_path.canonicalize()) {
+            (Ok(csv), Ok(meta)) => csv == meta,
+            _ => true,
+        }
+    }
+
+    /// 用整个文件的哈希校验索引
+    /// 
+    /// [`is_index_valid`](Self::is_index_valid) 只比较文件开头和末尾，
+    /// 中间部分大小不变的修改需要用此方法发现；没有记录哈希的索引视为无效
+    pub fn verify_full_checksum(csv_path: &Path, metadata: &IndexMetadata) -> bool {
+        match (metadata.full_checksum, full_file_checksum(csv_path)) {
+            (Some(expected), Ok(actual)) => expected == actual,
+            _ => false,
+        }
+    }
+
     /// 验证索引是否有效
     /// 
     /// # 参数
@@ -807,14 +1535,9 @@ impl RowIndex {
             return false;
         }
 
-        // 检查路径是否匹配（规范化路径比较）
-        let csv_path_normalized = csv_path.canonicalize().ok();
-        let metadata_path_normalized = metadata.csv_path.canonicalize().ok();
-        
-        if let (Some(csv), Some(meta)) = (csv_path_normalized, metadata_path_normalized) {
-            if csv != meta {
-                return false;
-            }
+        // 检查路径是否匹配
+        if !Self::same_csv_path(csv_path, metadata) {
+            return false;
         }
 
         // 检查文件大小
@@ -845,14 +1568,359 @@ impl RowIndex {
         }
 
         // 检查索引版本兼容性
-        if metadata.index_version != 1 {
+        if metadata.index_version != CURRENT_INDEX_VERSION {
             return false;
         }
 
+        // 大小和修改时间都一致时再比较内容校验和，
+        // 发现大小不变的原地修改（以及修改时间被还原的情况）
+        if let Some(checksum) = metadata.checksum {
+            match file_checksum(csv_path, metadata.csv_size) {
+                Ok(actual) if actual == checksum => {}
+                _ => return false,
+            }
+        }
+
         true
     }
 }
 
+/// 各版本元数据共有的前缀字段（用于读取版本号）
+#[derive(Deserialize)]
+struct IndexVersionHeader {
+    #[allow(dead_code)]
+    csv_path: PathBuf,
+    #[allow(dead_code)]
+    csv_size: u64,
+    #[allow(dead_code)]
+    csv_mtime: SystemTime,
+    index_version: u32,
+}
+
+/// v1 索引元数据
+#[derive(Deserialize)]
+struct IndexMetadataV1 {
+    csv_path: PathBuf,
+    csv_size: u64,
+    csv_mtime: SystemTime,
+    index_version: u32,
+    build_time: SystemTime,
+    granularity: usize,
+}
+
+/// v2/v3 索引元数据（没有压缩标志和校验和）
+#[derive(Deserialize)]
+struct IndexMetadataV2 {
+    csv_path: PathBuf,
+    csv_size: u64,
+    csv_mtime: SystemTime,
+    index_version: u32,
+    build_time: SystemTime,
+    granularity: usize,
+    mode: IndexMode,
+    has_headers: bool,
+}
+
+impl IndexMetadataV2 {
+    fn into_current(self) -> IndexMetadata {
+        IndexMetadataV4 {
+            csv_path: self.csv_path,
+            csv_size: self.csv_size,
+            csv_mtime: self.csv_mtime,
+            index_version: self.index_version,
+            build_time: self.build_time,
+            granularity: self.granularity,
+            mode: self.mode,
+            has_headers: self.has_headers,
+            compressed: false,
+        }
+        .into_current()
+    }
+}
+
+/// v4 索引元数据（没有校验和）
+#[derive(Deserialize)]
+struct IndexMetadataV4 {
+    csv_path: PathBuf,
+    csv_size: u64,
+    csv_mtime: SystemTime,
+    index_version: u32,
+    build_time: SystemTime,
+    granularity: usize,
+    mode: IndexMode,
+    has_headers: bool,
+    compressed: bool,
+}
+
+impl IndexMetadataV4 {
+    fn into_current(self) -> IndexMetadata {
+        IndexMetadataV5 {
+            csv_path: self.csv_path,
+            csv_size: self.csv_size,
+            csv_mtime: self.csv_mtime,
+            index_version: self.index_version,
+            build_time: self.build_time,
+            granularity: self.granularity,
+            mode: self.mode,
+            has_headers: self.has_headers,
+            compressed: self.compressed,
+            checksum: None,
+        }
+        .into_current()
+    }
+}
+
+/// v5-v7 索引元数据（没有整个文件的哈希）
+#[derive(Deserialize)]
+struct IndexMetadataV5 {
+    csv_path: PathBuf,
+    csv_size: u64,
+    csv_mtime: SystemTime,
+    index_version: u32,
+    build_time: SystemTime,
+    granularity: usize,
+    mode: IndexMode,
+    has_headers: bool,
+    compressed: bool,
+    checksum: Option<u64>,
+}
+
+impl IndexMetadataV5 {
+    fn into_current(self) -> IndexMetadata {
+        IndexMetadataV8 {
+            csv_path: self.csv_path,
+            csv_size: self.csv_size,
+            csv_mtime: self.csv_mtime,
+            index_version: self.index_version,
+            build_time: self.build_time,
+            granularity: self.granularity,
+            mode: self.mode,
+            has_headers: self.has_headers,
+            compressed: self.compressed,
+            checksum: self.checksum,
+            full_checksum: None,
+        }
+        .into_current()
+    }
+}
+
+/// v8 索引元数据（构建时空行计入行数）
+#[derive(Deserialize)]
+struct IndexMetadataV8 {
+    csv_path: PathBuf,
+    csv_size: u64,
+    csv_mtime: SystemTime,
+    index_version: u32,
+    build_time: SystemTime,
+    granularity: usize,
+    mode: IndexMode,
+    has_headers: bool,
+    compressed: bool,
+    checksum: Option<u64>,
+    full_checksum: Option<u64>,
+}
+
+impl IndexMetadataV8 {
+    fn into_current(self) -> IndexMetadata {
+        IndexMetadata {
+            csv_path: self.csv_path,
+            csv_size: self.csv_size,
+            csv_mtime: self.csv_mtime,
+            index_version: self.index_version,
+            build_time: self.build_time,
+            granularity: self.granularity,
+            mode: self.mode,
+            has_headers: self.has_headers,
+            compressed: self.compressed,
+            checksum: self.checksum,
+            full_checksum: self.full_checksum,
+            skips_empty_lines: false,
+        }
+    }
+}
+
+/// v1 行索引
+#[derive(Deserialize)]
+struct RowIndexV1 {
+    offsets: Vec<u64>,
+    row_numbers: Vec<usize>,
+    granularity: usize,
+    total_rows: usize,
+    is_complete: bool,
+    indexed_bytes: u64,
+}
+
+/// 增量构建之前的 v1 行索引
+#[derive(Deserialize)]
+struct RowIndexV1Legacy {
+    offsets: Vec<u64>,
+    row_numbers: Vec<usize>,
+    granularity: usize,
+    total_rows: usize,
+}
+
+/// v2 行索引（没有列索引；v3-v5 的行索引去掉末尾的列索引后与之相同）
+#[derive(Deserialize)]
+struct RowIndexV2 {
+    offsets: Vec<u64>,
+    row_numbers: Vec<usize>,
+    granularity: usize,
+    total_rows: usize,
+    is_complete: bool,
+    indexed_bytes: u64,
+    mode: IndexMode,
+}
+
+impl RowIndexV2 {
+    fn into_current(self) -> RowIndex {
+        RowIndex {
+            offsets: self.offsets,
+            row_numbers: self.row_numbers,
+            granularity: self.granularity,
+            total_rows: self.total_rows,
+            is_complete: self.is_complete,
+            indexed_bytes: self.indexed_bytes,
+            mode: self.mode,
+            column_index: None,
+        }
+    }
+}
+
+/// 是否为无权写入或只读文件系统导致的错误
+fn is_unwritable(e: &std::io::Error) -> bool {
+    // 只读文件系统（Unix 的 EROFS）
+    const READ_ONLY_FS: i32 = 30;
+    e.kind() == std::io::ErrorKind::PermissionDenied
+        || (cfg!(unix) && e.raw_os_error() == Some(READ_ONLY_FS))
+}
+
+/// 参与校验和计算的开头和末尾字节范围（小文件两者不重叠）
+fn checksum_ranges(size: u64) -> (Range<u64>, Range<u64>) {
+    let head_end = size.min(CHECKSUM_SAMPLE_BYTES);
+    let tail_start = size.saturating_sub(CHECKSUM_SAMPLE_BYTES).max(head_end);
+    (0..head_end, tail_start..size)
+}
+
+/// 计算文件前 `size` 字节的校验和：大小及开头、末尾各64KB的xxh3哈希
+fn file_checksum(path: &Path, size: u64) -> std::io::Result<u64> {
+    let mut file = File::open(path)?;
+    let (head, tail) = checksum_ranges(size);
+    let mut hasher = Xxh3::new();
+    hasher.update(&size.to_le_bytes());
+    for range in [head, tail] {
+        let mut buf = vec![0u8; (range.end - range.start) as usize];
+        file.seek(SeekFrom::Start(range.start))?;
+        file.read_exact(&mut buf)?;
+        hasher.update(&buf);
+    }
+    Ok(hasher.digest())
+}
+
+/// 整个文件的xxh3哈希
+fn full_file_checksum(path: &Path) -> std::io::Result<u64> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(xxh3_64(&[]));
+    }
+    let mmap = unsafe { Mmap::map(&file)? };
+    Ok(xxh3_64(&mmap))
+}
+
+/// v4/v5 压缩存储的行索引（只读取列索引之前的字段）
+#[derive(Deserialize)]
+struct PackedRowIndexV5 {
+    offsets: Vec<u8>,
+    row_numbers: Vec<u8>,
+    granularity: usize,
+    total_rows: usize,
+    is_complete: bool,
+    indexed_bytes: u64,
+    mode: IndexMode,
+}
+
+/// 解码 v4/v5 的索引数据，不读取末尾旧格式的列索引
+fn decode_payload_v5(bytes: &[u8], compressed: bool) -> std::result::Result<RowIndexV2, bincode::Error> {
+    if !compressed {
+        return bincode::deserialize(bytes);
+    }
+
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    let packed: PackedRowIndexV5 = bincode::deserialize(&decompressed)?;
+    let decode = |bytes: &[u8]| {
+        decode_deltas(bytes).map_err(|e| bincode::Error::from(bincode::ErrorKind::Custom(e.to_string())))
+    };
+
+    Ok(RowIndexV2 {
+        offsets: decode(&packed.offsets)?,
+        row_numbers: decode(&packed.row_numbers)?.into_iter().map(|n| n as usize).collect(),
+        granularity: packed.granularity,
+        total_rows: packed.total_rows,
+        is_complete: packed.is_complete,
+        indexed_bytes: packed.indexed_bytes,
+        mode: packed.mode,
+    })
+}
+
+/// 存储的行索引（偏移量和行号为差值+变长整数编码）
+#[derive(Serialize, Deserialize)]
+struct PackedRowIndex {
+    offsets: Vec<u8>,
+    row_numbers: Vec<u8>,
+    granularity: usize,
+    total_rows: usize,
+    is_complete: bool,
+    indexed_bytes: u64,
+    mode: IndexMode,
+    column_index: Option<ColumnIndex>,
+}
+
+/// 按差值+变长整数（LEB128）编码递增序列
+fn encode_deltas(values: impl Iterator<Item = u64>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut prev = 0u64;
+    for value in values {
+        let mut delta = value.wrapping_sub(prev);
+        prev = value;
+        loop {
+            let byte = (delta & 0x7f) as u8;
+            delta >>= 7;
+            if delta == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+    bytes
+}
+
+/// 解码 [`encode_deltas`] 编码的序列
+fn decode_deltas(bytes: &[u8]) -> Result<Vec<u64>> {
+    let mut values = Vec::new();
+    let mut prev = 0u64;
+    let mut delta = 0u64;
+    let mut shift = 0;
+    for &byte in bytes {
+        if shift >= 64 {
+            return Err(CsvError::IndexFile("索引数据损坏: 变长整数过长".to_string()));
+        }
+        delta |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            prev = prev.wrapping_add(delta);
+            values.push(prev);
+            delta = 0;
+            shift = 0;
+        } else {
+            shift += 7;
+        }
+    }
+    if shift != 0 {
+        return Err(CsvError::IndexFile("索引数据损坏: 变长整数不完整".to_string()));
+    }
+    Ok(values)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -870,18 +1938,378 @@ mod tests {
         let file = File::open(&temp_file).unwrap();
         let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
 
-        let index = RowIndex::build(&mmap, true, 1).unwrap();
+        let index = RowIndex::build(&mmap, true, LineFormat::default(), 1).unwrap();
         // 应该有3行数据（不包括表头）
         assert_eq!(index.total_rows(), 3);
         // 索引粒度是1，所以应该有3个索引点（每行一个）
         assert_eq!(index.index_count(), 3);
         
-        // 测试跳转功能
+        // 测试跳转功能：粒度为1时应精确定位到行首
         let offset = index.seek_to_row(1).unwrap();
-        assert!(offset > 0);
+        assert_eq!(&content[offset as usize..offset as usize + 9], b"row2,col2");
+        assert_eq!(index.seek_to_row_with_info(2).unwrap().1, 2);
         
         // 清理
         let _ = std::fs::remove_file(&temp_file);
     }
-}
 
+    #[test]
+    fn test_parallel_build_progress() {
+        // 约8MB，分成多个块和多个进度步长
+        let mut content = String::from("id,value\n");
+        for i in 0..400_000 {
+            content.push_str(&format!("{},value{:09}\n", i, i));
+        }
+        let content = content.into_bytes();
+
+        // 并行构建过程中共享计数器单调递增，最终等于文件大小
+        let processed = AtomicUsize::new(0);
+        let done = std::sync::atomic::AtomicBool::new(false);
+        let (index, samples) = std::thread::scope(|scope| {
+            let observer = scope.spawn(|| {
+                let mut samples = Vec::new();
+                while !done.load(Ordering::Acquire) {
+                    samples.push(processed.load(Ordering::Relaxed));
+                    std::thread::yield_now();
+                }
+                samples
+            });
+            let index = RowIndex::build_parallel_counted(&content, true, LineFormat::default(), 100, &processed).unwrap();
+            done.store(true, Ordering::Release);
+            (index, observer.join().unwrap())
+        });
+        assert!(samples.windows(2).all(|w| w[0] <= w[1]));
+        assert!(samples.iter().all(|&bytes| bytes <= content.len()));
+        assert_eq!(processed.load(Ordering::Relaxed), content.len());
+        assert_eq!(index.total_rows(), 400_000);
+
+        // 带回调的并行构建与单线程构建结果一致，进度单调递增并以100结束
+        let temp_file = std::env::temp_dir().join("test_csv_index_parallel_progress.csv");
+        std::fs::write(&temp_file, &content).unwrap();
+        let file = File::open(&temp_file).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+
+        let mut percents = Vec::new();
+        let parallel = RowIndex::build_parallel(&mmap, true, LineFormat::default(), 100, Some(|pct: f64, _: usize, _: usize| percents.push(pct))).unwrap();
+        let sequential = RowIndex::build_with_progress::<fn(f64, usize, usize)>(&mmap, true, LineFormat::default(), 100, None).unwrap();
+        assert_eq!(parallel.total_rows(), sequential.total_rows());
+        assert_eq!(parallel.seek_to_row(12_345).unwrap(), sequential.seek_to_row(12_345).unwrap());
+        assert!(percents.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(percents.last(), Some(&100.0));
+
+        drop(mmap);
+        let _ = std::fs::remove_file(&temp_file);
+    }
+
+    #[test]
+    fn test_estimate_rows_variable_width() {
+        // 前半部分每行10字节，后半部分每行100字节
+        let mut content = String::from("id\n");
+        for i in 0..100_000 {
+            content.push_str(&format!("{:09}\n", i));
+        }
+        for i in 0..10_000 {
+            content.push_str(&format!("{:099}\n", i));
+        }
+        let temp_file = std::env::temp_dir().join("test_csv_index_estimate.csv");
+        std::fs::write(&temp_file, &content).unwrap();
+
+        let file = File::open(&temp_file).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+
+        let actual = 110_000;
+        let estimate = RowIndex::estimate_rows(&mmap, true, LineFormat::default(), 64 * 1024);
+        assert!(!estimate.is_exact);
+        assert!(estimate.lower_bound <= actual && actual <= estimate.upper_bound);
+
+        // 只按开头64KB外推会高估约80%，多区域采样应在10%以内
+        let data = &mmap[3..];
+        let head_density = memchr::memchr_iter(b'\n', &data[..64 * 1024]).count() as f64 / (64 * 1024) as f64;
+        let head_error = (data.len() as f64 * head_density - actual as f64).abs() / actual as f64;
+        let error = (estimate.estimated_rows as f64 - actual as f64).abs() / actual as f64;
+        assert!(head_error > 0.5, "head_error = {}", head_error);
+        assert!(error < 0.1, "estimate = {:?}", estimate);
+
+        // 清理
+        let _ = std::fs::remove_file(&temp_file);
+    }
+
+    #[test]
+    fn test_build_validated() {
+        let content = b"a,b,c\n1,2,3\n4,\"5,6\",7\n8,9\n10,11,12,13\n";
+        let temp_file = std::env::temp_dir().join("test_csv_index_validated.csv");
+        std::fs::write(&temp_file, content).unwrap();
+
+        let file = File::open(&temp_file).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+
+        let mut validation = ColumnValidation {
+            delimiter: Delimiter::Byte(b','),
+            expected_cols: 3,
+            max_violations: 10,
+            fail_fast: false,
+        };
+        let (index, report) = RowIndex::build_validated(&mmap, true, LineFormat::default(), 2, &validation).unwrap();
+        assert_eq!(index.total_rows(), 4);
+        assert_eq!(report.violation_count, 2);
+        assert_eq!(report.violations[0], ColumnCountViolation { row: 2, field_count: 2 });
+        assert_eq!(report.violations[1], ColumnCountViolation { row: 3, field_count: 4 });
+
+        validation.fail_fast = true;
+        let err = RowIndex::build_validated(&mmap, true, LineFormat::default(), 2, &validation).unwrap_err();
+        assert!(err.to_string().contains("第 3 行有 2 个字段"));
+
+        let _ = std::fs::remove_file(&temp_file);
+    }
+
+    #[test]
+    fn test_continue_build_until() {
+        let temp_file = std::env::temp_dir().join("test_csv_index_continue_until.csv");
+        let mut content = String::from("id\n");
+        for i in 0..100 {
+            content.push_str(&format!("{}\n", i));
+        }
+        std::fs::write(&temp_file, &content).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&File::open(&temp_file).unwrap()).unwrap() };
+
+        // 只扫描到目标行为止，之后可以继续扫描到文件末尾
+        let (mut index, complete) = RowIndex::build_partial(&mmap, true, LineFormat::default(), 10, Some(5)).unwrap();
+        assert!(!complete);
+        assert!(!index.continue_build_until(&mmap, LineFormat::default(), 42, None, None).unwrap());
+        assert_eq!(index.total_rows(), 42);
+        assert_eq!(index.seek_to_row_with_info(41).unwrap(), (content.find("\n39\n").unwrap() as u64 + 1, 39));
+        assert!(index.continue_build_until(&mmap, LineFormat::default(), 1000, None, None).unwrap());
+
+        let full = RowIndex::build(&mmap, true, LineFormat::default(), 10).unwrap();
+        assert_eq!(index.total_rows(), 100);
+        assert_eq!(index.offsets, full.offsets);
+        assert_eq!(index.row_numbers, full.row_numbers);
+
+        let _ = std::fs::remove_file(&temp_file);
+    }
+
+    #[test]
+    fn test_extend_to_matches_full_build() {
+        let temp_dir = std::env::temp_dir();
+        let before_file = temp_dir.join("test_csv_index_extend_before.csv");
+        let after_file = temp_dir.join("test_csv_index_extend_after.csv");
+        // 追加前末尾是未写完的半行
+        std::fs::write(&before_file, b"a,b\n1,2\n3,4\n5,").unwrap();
+        std::fs::write(&after_file, b"a,b\n1,2\n3,4\n5,6\n7,8\n9,10").unwrap();
+
+        let before = unsafe { MmapOptions::new().map(&File::open(&before_file).unwrap()).unwrap() };
+        let after = unsafe { MmapOptions::new().map(&File::open(&after_file).unwrap()).unwrap() };
+
+        let mut index = RowIndex::build(&before, true, LineFormat::default(), 2).unwrap();
+        assert_eq!(index.total_rows(), 3);
+
+        let added = index.extend_to(&after, LineFormat::default()).unwrap();
+        let full = RowIndex::build(&after, true, LineFormat::default(), 2).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(index.total_rows(), full.total_rows());
+        assert_eq!(index.offsets, full.offsets);
+        assert_eq!(index.row_numbers, full.row_numbers);
+
+        // 文件变短时报错
+        assert!(index.extend_to(&before, LineFormat::default()).is_err());
+
+        let _ = std::fs::remove_file(&before_file);
+        let _ = std::fs::remove_file(&after_file);
+    }
+
+    #[test]
+    fn test_extend_to_cr_line_endings() {
+        let temp_dir = std::env::temp_dir();
+        let before_file = temp_dir.join("test_csv_index_extend_cr_before.csv");
+        let after_file = temp_dir.join("test_csv_index_extend_cr_after.csv");
+        // 追加前以 \r 结尾，追加的数据以 \n 开头，两者组成一个行结束符
+        std::fs::write(&before_file, b"a,b\r1,2\r3,4\r").unwrap();
+        std::fs::write(&after_file, b"a,b\r1,2\r3,4\r\n5,6\r7,8").unwrap();
+
+        let before = unsafe { MmapOptions::new().map(&File::open(&before_file).unwrap()).unwrap() };
+        let after = unsafe { MmapOptions::new().map(&File::open(&after_file).unwrap()).unwrap() };
+        let format = LineFormat::detect(&before);
+
+        let mut index = RowIndex::build(&before, true, format, 1).unwrap();
+        assert_eq!(index.total_rows(), 2);
+
+        let added = index.extend_to(&after, format).unwrap();
+        let full = RowIndex::build(&after, true, format, 1).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(full.total_rows(), 4);
+        assert_eq!(index.offsets, full.offsets);
+        assert_eq!(index.row_numbers, full.row_numbers);
+
+        let _ = std::fs::remove_file(&before_file);
+        let _ = std::fs::remove_file(&after_file);
+    }
+
+    #[test]
+    fn test_skip_empty_lines_when_building() {
+        let temp_dir = std::env::temp_dir();
+        let before_file = temp_dir.join("test_csv_index_skip_empty_before.csv");
+        let after_file = temp_dir.join("test_csv_index_skip_empty_after.csv");
+        // 追加前末尾是 \r，追加的 \n 使它成为空行
+        std::fs::write(&before_file, b"\na,b\n1,2\n\n3,4\n\r").unwrap();
+        std::fs::write(&after_file, b"\na,b\n1,2\n\n3,4\n\r\n\n5,6\r\n\r\n").unwrap();
+
+        let before = unsafe { MmapOptions::new().map(&File::open(&before_file).unwrap()).unwrap() };
+        let after = unsafe { MmapOptions::new().map(&File::open(&after_file).unwrap()).unwrap() };
+        let format = LineFormat::detect(&after).with_skip_empty(true);
+
+        let mut index = RowIndex::build(&before, true, format, 1).unwrap();
+        assert_eq!(index.total_rows(), 2);
+
+        let added = index.extend_to(&after, format).unwrap();
+        let full = RowIndex::build(&after, true, format, 1).unwrap();
+        assert_eq!(added, 1);
+        assert_eq!(full.total_rows(), 3);
+        assert_eq!(full.offsets, vec![5, 10, 17]);
+        assert_eq!(index.offsets, full.offsets);
+        assert_eq!(index.row_numbers, full.row_numbers);
+
+        // 并行构建同样跳过空行
+        let processed = AtomicUsize::new(0);
+        let parallel = RowIndex::build_parallel_counted(&after, true, format, 1, &processed).unwrap();
+        assert_eq!(parallel.total_rows(), 3);
+        assert_eq!(parallel.offsets, full.offsets);
+        assert_eq!(RowIndex::build(&after, true, LineFormat::detect(&after), 1).unwrap().total_rows(), 8);
+
+        let _ = std::fs::remove_file(&before_file);
+        let _ = std::fs::remove_file(&after_file);
+    }
+
+    #[test]
+    fn test_extend_to_skips_bom() {
+        // 没有表头、第一行是未写完的半行时，回退到的行首要跳过BOM
+        let temp_dir = std::env::temp_dir();
+        let before_file = temp_dir.join("test_csv_index_extend_bom_before.csv");
+        let after_file = temp_dir.join("test_csv_index_extend_bom_after.csv");
+        std::fs::write(&before_file, b"\xEF\xBB\xBF1,").unwrap();
+        std::fs::write(&after_file, b"\xEF\xBB\xBF1,a\n2,b\n").unwrap();
+
+        let before = unsafe { MmapOptions::new().map(&File::open(&before_file).unwrap()).unwrap() };
+        let after = unsafe { MmapOptions::new().map(&File::open(&after_file).unwrap()).unwrap() };
+
+        let mut index = RowIndex::build(&before, false, LineFormat::default(), 1).unwrap();
+        index.extend_to(&after, LineFormat::default()).unwrap();
+        let full = RowIndex::build(&after, false, LineFormat::default(), 1).unwrap();
+        assert_eq!(index.total_rows(), 2);
+        assert_eq!(index.offsets, full.offsets);
+        assert_eq!(index.row_numbers, full.row_numbers);
+
+        let _ = std::fs::remove_file(&before_file);
+        let _ = std::fs::remove_file(&after_file);
+    }
+
+    #[test]
+    fn test_dense_seek_exact() {
+        // 最后一行没有换行符
+        let content = b"id,name\n1,a\n22,bb\n333,ccc\n4444,dddd";
+        let temp_file = std::env::temp_dir().join("test_csv_index_dense.csv");
+        std::fs::write(&temp_file, content).unwrap();
+
+        let file = File::open(&temp_file).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+
+        let index = RowIndex::build_dense(&mmap, true, LineFormat::default()).unwrap();
+        assert_eq!(index.mode(), IndexMode::Dense);
+        assert_eq!(index.total_rows(), 4);
+        assert!(index.row_numbers.is_empty());
+
+        let rows: [&[u8]; 3] = [b"1,a", b"22,bb", b"333,ccc"];
+        for (n, row) in rows.iter().enumerate() {
+            let offset = index.seek_to_row(n).unwrap() as usize;
+            assert_eq!(&content[offset..offset + row.len()], *row);
+            assert_eq!(index.seek_to_row_with_info(n).unwrap().1, n);
+        }
+        // 末尾无换行的最后一行从前一行开始扫描
+        assert_eq!(index.seek_to_row_with_info(3).unwrap().1, 2);
+        assert!(index.seek_to_row(4).is_err());
+
+        // 增量扩展保持稠密模式
+        let mut partial = RowIndex::build_dense(&mmap, true, LineFormat::default()).unwrap();
+        partial.extend_to(b"id,name\n1,a\n22,bb\n333,ccc\n4444,dddd\n5,e\n", LineFormat::default()).unwrap();
+        assert_eq!(partial.total_rows(), 5);
+        assert_eq!(partial.seek_to_row_with_info(4).unwrap(), (36, 4));
+        assert!(partial.row_numbers.is_empty());
+
+        let _ = std::fs::remove_file(&temp_file);
+    }
+
+    #[test]
+    fn test_delta_encoded_index_size() {
+        // 100万行、粒度16的稀疏索引（索引点少于自动压缩阈值，只做差值编码）
+        let granularity = 16;
+        let points = 1_000_000 / granularity;
+        let index = RowIndex {
+            offsets: (0..points as u64).map(|i| 10 + i * 16 * 57 + i % 7).collect(),
+            row_numbers: (1..=points).map(|i| i * granularity).collect(),
+            granularity,
+            total_rows: 1_000_000,
+            is_complete: true,
+            indexed_bytes: 57_000_010,
+            mode: IndexMode::Sparse,
+            column_index: None,
+        };
+        assert!(index.offsets.len() < COMPRESS_MIN_POINTS);
+
+        let csv_path = std::env::temp_dir().join("test_csv_index_delta.csv");
+        let metadata = IndexMetadata::new(csv_path.clone(), 57_000_010, SystemTime::now(), granularity);
+        let index_path = index.save_to_file(&csv_path, &metadata).unwrap();
+
+        let naive_size = bincode::serialize(&index).unwrap().len() as u64;
+        let file_size = std::fs::metadata(&index_path).unwrap().len();
+        assert!(file_size * 4 <= naive_size, "{} vs {}", file_size, naive_size);
+
+        let (loaded, loaded_metadata) = RowIndex::load_from_file(&index_path).unwrap();
+        assert!(!loaded_metadata.compressed);
+        assert_eq!(loaded.offsets, index.offsets);
+        assert_eq!(loaded.row_numbers, index.row_numbers);
+        assert_eq!(loaded.total_rows, index.total_rows);
+        assert_eq!(loaded.indexed_bytes, index.indexed_bytes);
+
+        let _ = std::fs::remove_file(&index_path);
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let mut content = String::from("id,value\n");
+        for i in 0..100_000 {
+            content.push_str(&format!("{},{}\n", i, i * 7));
+        }
+        let temp_file = std::env::temp_dir().join("test_csv_index_compressed.csv");
+        std::fs::write(&temp_file, &content).unwrap();
+
+        let file = File::open(&temp_file).unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+        let index = RowIndex::build_dense(&mmap, true, LineFormat::default()).unwrap();
+
+        // 索引点超过阈值时自动压缩
+        let metadata = IndexMetadata::new(temp_file.clone(), mmap.len() as u64, SystemTime::now(), 1);
+        let index_path = index.save_to_file(&temp_file, &metadata).unwrap();
+        let raw_size = bincode::serialize(&index).unwrap().len() as u64;
+        assert!(std::fs::metadata(&index_path).unwrap().len() < raw_size / 4);
+
+        let (loaded, loaded_metadata) = RowIndex::load_from_file(&index_path).unwrap();
+        assert!(loaded_metadata.compressed);
+        assert_eq!(loaded.offsets, index.offsets);
+        assert_eq!(loaded.total_rows(), index.total_rows());
+        for row in [0, 1, 4_999, 99_999] {
+            assert_eq!(loaded.seek_to_row_with_info(row).unwrap(), index.seek_to_row_with_info(row).unwrap());
+        }
+
+        // 索引点较少时默认不压缩，但可以强制压缩
+        let small = RowIndex::build(&mmap, true, LineFormat::default(), 1000).unwrap();
+        small.save_to_file(&temp_file, &metadata).unwrap();
+        assert!(!RowIndex::load_from_file(&index_path).unwrap().1.compressed);
+        small.save_to_file(&temp_file, &metadata.clone().with_compression(true)).unwrap();
+        let (loaded, loaded_metadata) = RowIndex::load_from_file(&index_path).unwrap();
+        assert!(loaded_metadata.compressed);
+        assert_eq!(loaded.offsets, small.offsets);
+        assert_eq!(loaded.row_numbers, small.row_numbers);
+
+        let _ = std::fs::remove_file(&index_path);
+        let _ = std::fs::remove_file(&temp_file);
+    }
+}
@@ -0,0 +1,310 @@
+//! 数据区逐行遍历
+//!
+//! 索引构建、分页读取、搜索和统计都需要按行切分数据区，
+//! 统一在这里处理BOM、表头、行结束符和末尾没有换行符的最后一行，保证各处得到的行数一致
+
+use crate::csv::encoding::skip_bom;
+use crate::csv::LineEnding;
+use memchr::{memchr, memchr2, memchr2_iter, memchr_iter, memmem, memrchr, memrchr2};
+
+/// 分行方式
+///
+/// 默认只把 `\n` 作为行结束符（`\r\n` 行尾的 `\r` 留在行内，由解析时去掉）；
+/// 只用 `\r` 换行的文件中单独的 `\r` 也是行结束符。
+/// 行结束符总是一个字节，因此下一行总是从行结束符之后开始，不需要复制或改写数据。
+/// 跳过空行时空行（见 [`is_empty_line`]）不作为一行，遍历、计数和定位表头时都直接跳过
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineFormat {
+    /// 单独的 `\r` 是否也是行结束符
+    cr: bool,
+    /// 是否跳过空行
+    skip_empty: bool,
+}
+
+impl LineFormat {
+    /// 按检测到的行结束符创建（保留空行）
+    pub fn new(line_ending: Option<LineEnding>) -> Self {
+        Self { cr: line_ending == Some(LineEnding::Cr), skip_empty: false }
+    }
+
+    /// 设置是否跳过空行
+    pub fn with_skip_empty(mut self, skip: bool) -> Self {
+        self.skip_empty = skip;
+        self
+    }
+
+    /// 该行是否被跳过（`line` 不含行结束符）
+    pub fn is_skipped(&self, line: &[u8]) -> bool {
+        self.skip_empty && is_empty_line(line)
+    }
+
+    /// 根据数据开头检测行结束符（见 [`LineEnding::detect`]）
+    pub fn detect(data: &[u8]) -> Self {
+        Self::new(LineEnding::detect(data))
+    }
+
+    /// `pos` 处的字节是否为行结束符
+    pub fn is_end(&self, data: &[u8], pos: usize) -> bool {
+        match data[pos] {
+            b'\n' => true,
+            b'\r' => self.cr && data.get(pos + 1) != Some(&b'\n'),
+            _ => false,
+        }
+    }
+
+    /// 从 `from` 开始的第一个行结束符的位置
+    pub fn find_end(&self, data: &[u8], from: usize) -> Option<usize> {
+        if !self.cr {
+            return memchr(b'\n', &data[from..]).map(|p| from + p);
+        }
+        let pos = from + memchr2(b'\n', b'\r', &data[from..])?;
+        // \r\n 以其中的 \n 作为行结束符
+        if data[pos] == b'\r' && data.get(pos + 1) == Some(&b'\n') {
+            Some(pos + 1)
+        } else {
+            Some(pos)
+        }
+    }
+
+    /// `[start, end)` 内最后一个行结束符的位置
+    pub fn rfind_end(&self, data: &[u8], start: usize, end: usize) -> Option<usize> {
+        if !self.cr {
+            return memrchr(b'\n', &data[start..end]).map(|p| start + p);
+        }
+        let mut end = end;
+        loop {
+            let pos = start + memrchr2(b'\n', b'\r', &data[start..end])?;
+            if self.is_end(data, pos) {
+                return Some(pos);
+            }
+            end = pos;
+        }
+    }
+
+    /// `[start, end)` 内行结束符的个数
+    pub fn count_ends(&self, data: &[u8], start: usize, end: usize) -> usize {
+        if !self.cr {
+            return memchr_iter(b'\n', &data[start..end]).count();
+        }
+        memchr2_iter(b'\n', b'\r', &data[start..end])
+            .filter(|&p| self.is_end(data, start + p))
+            .count()
+    }
+
+    /// 依次访问 `[start, end)` 内每个行结束符的位置
+    pub fn for_each_end(&self, data: &[u8], start: usize, end: usize, mut visit: impl FnMut(usize)) {
+        if !self.cr {
+            memchr_iter(b'\n', &data[start..end]).for_each(|p| visit(start + p));
+            return;
+        }
+        for p in memchr2_iter(b'\n', b'\r', &data[start..end]) {
+            if self.is_end(data, start + p) {
+                visit(start + p);
+            }
+        }
+    }
+}
+
+/// 第一行（表头）的 `(行首, 行尾)` 字节位置：跳过BOM，跳过空行时也跳过开头的空行
+///
+/// 没有任何一行时返回None
+pub fn first_line(data: &[u8], format: LineFormat) -> Option<(usize, usize)> {
+    DataLines::new(data, skip_bom(data), format).next()
+}
+
+/// 数据区的起始位置：跳过BOM，有表头时再跳过第一行
+///
+/// 文件只有一行表头且没有换行符时返回文件末尾（没有数据行）
+pub fn data_start(data: &[u8], has_headers: bool, format: LineFormat) -> usize {
+    let mut lines = DataLines::new(data, skip_bom(data), format);
+    if has_headers {
+        lines.next();
+    }
+    lines.position()
+}
+
+/// 从 `start`（位于行首）到文件末尾的行数，末尾没有换行符的最后一行也计入
+///
+/// 跳过空行时逐行遍历，否则只统计行结束符
+pub fn count_lines(data: &[u8], start: usize, format: LineFormat) -> usize {
+    let start = start.min(data.len());
+    if format.skip_empty {
+        return DataLines::new(data, start, format).count();
+    }
+    let ends = format.count_ends(data, start, data.len());
+    if data.len() > start && !format.is_end(data, data.len() - 1) {
+        ends + 1
+    } else {
+        ends
+    }
+}
+
+/// 是否为空行：两个换行符之间没有内容（`\r\n` 行尾时只有 `\r`）
+///
+/// 只含空格的行不算空行
+pub fn is_empty_line(line: &[u8]) -> bool {
+    line.is_empty() || line == b"\r"
+}
+
+/// 数据中是否有空行（包括开头的空行和末尾多余的换行符，末尾的单个换行符不算）
+///
+/// 只查找连续的行结束符，没有空行时不需要逐行遍历；
+/// 用于判断旧版本按保留空行构建的索引能否继续使用
+pub fn has_empty_lines(data: &[u8], format: LineFormat) -> bool {
+    let body = &data[skip_bom(data)..];
+    if body.starts_with(b"\n") || body.starts_with(b"\r\n") {
+        return true;
+    }
+    if memmem::find(body, b"\n\n").is_some() || memmem::find(body, b"\n\r\n").is_some() {
+        return true;
+    }
+    // 单独的 \r 换行时，\r 之后紧跟行结束符或 \r\n 都是空行
+    format.cr
+        && (body.starts_with(b"\r")
+            || memmem::find(body, b"\r\r").is_some()
+            || memmem::find(body, b"\n\r").is_some())
+}
+
+/// 逐行遍历数据区，返回每行的 `(行首, 行尾)` 字节位置，行尾不含行结束符
+///
+/// 末尾没有换行符的最后一行同样返回，因此返回的行数总是等于 [`count_lines`]；
+/// 行尾等于数据长度说明该行没有换行符。跳过空行时不返回空行
+pub struct DataLines<'a> {
+    data: &'a [u8],
+    pos: usize,
+    format: LineFormat,
+}
+
+impl<'a> DataLines<'a> {
+    /// 从 `start`（位于行首）开始按 `format` 分行遍历
+    pub fn new(data: &'a [u8], start: usize, format: LineFormat) -> Self {
+        Self { data, pos: start, format }
+    }
+
+    /// 下一行的起始位置（遍历结束时为数据长度）
+    pub fn position(&self) -> usize {
+        self.pos.min(self.data.len())
+    }
+}
+
+impl Iterator for DataLines<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.data.len() {
+            let start = self.pos;
+            let end = self.format.find_end(self.data, start).unwrap_or(self.data.len());
+            self.pos = end + 1;
+            if !self.format.is_skipped(&self.data[start..end]) {
+                return Some((start, end));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LF: LineFormat = LineFormat { cr: false, skip_empty: false };
+    const CR: LineFormat = LineFormat { cr: true, skip_empty: false };
+
+    fn lines_with(data: &[u8], has_headers: bool, format: LineFormat) -> Vec<&[u8]> {
+        DataLines::new(data, data_start(data, has_headers, format), format)
+            .map(|(start, end)| &data[start..end])
+            .collect()
+    }
+
+    fn lines(data: &[u8], has_headers: bool) -> Vec<&[u8]> {
+        lines_with(data, has_headers, LF)
+    }
+
+    #[test]
+    fn test_trailing_newline_optional() {
+        let with_newline = b"id\n1\n2\n";
+        let without_newline = b"id\n1\n2";
+        assert_eq!(lines(with_newline, true), vec![b"1".as_slice(), b"2"]);
+        assert_eq!(lines(without_newline, true), vec![b"1".as_slice(), b"2"]);
+        assert_eq!(count_lines(with_newline, 3, LF), 2);
+        assert_eq!(count_lines(without_newline, 3, LF), 2);
+
+        // 空行同样是一行
+        assert_eq!(lines(b"1\n\n2", false), vec![b"1".as_slice(), b"", b"2"]);
+        assert_eq!(count_lines(b"1\n\n2", 0, LF), 3);
+    }
+
+    #[test]
+    fn test_header_only_and_empty() {
+        assert_eq!(data_start(b"id,name", true, LF), 7);
+        assert!(lines(b"id,name", true).is_empty());
+        assert!(lines(b"id,name\n", true).is_empty());
+        assert_eq!(count_lines(b"id,name", 7, LF), 0);
+        assert!(lines(b"", false).is_empty());
+        assert_eq!(count_lines(b"", 0, LF), 0);
+
+        // BOM不属于第一行
+        let data = b"\xEF\xBB\xBFa,b\n1,2";
+        assert_eq!(data_start(data, false, LF), 3);
+        assert_eq!(lines(data, true), vec![b"1,2".as_slice()]);
+
+        let mut iter = DataLines::new(b"a\nb", 0, LF);
+        iter.next();
+        assert_eq!(iter.position(), 2);
+        iter.next();
+        assert_eq!(iter.position(), 3);
+    }
+
+    #[test]
+    fn test_cr_line_endings() {
+        // 单独的 \r 和 \n 都是行结束符，\r\n 只算一个
+        let data = b"id\r1\r2\r\n3\n4";
+        assert_eq!(lines_with(data, true, CR), vec![b"1".as_slice(), b"2\r", b"3", b"4"]);
+        assert_eq!(count_lines(data, 3, CR), 4);
+        assert_eq!(count_lines(b"1\r2\r", 0, CR), 2);
+        assert_eq!(CR.count_ends(data, 0, data.len()), 4);
+        assert_eq!(CR.rfind_end(data, 0, 7), Some(4));
+        assert_eq!(CR.rfind_end(data, 0, 8), Some(7));
+        assert_eq!(CR.rfind_end(data, 0, 2), None);
+
+        // 默认只按 \n 分行
+        assert_eq!(lines(data, false), vec![b"id\r1\r2\r".as_slice(), b"3", b"4"]);
+        assert_eq!(LineFormat::detect(b"a\rb\rc\r\n"), CR);
+        assert_eq!(LineFormat::detect(b"a\r\nb\r\n"), LF);
+    }
+
+    #[test]
+    fn test_skip_empty_lines() {
+        let skip = LF.with_skip_empty(true);
+        let data = b"\xEF\xBB\xBF\n\r\nid\n1\n\n2\r\n\r\n  \n3\n\n";
+        assert_eq!(first_line(data, skip), Some((6, 8)));
+        assert_eq!(data_start(data, true, skip), 9);
+        assert_eq!(lines_with(data, true, skip), vec![b"1".as_slice(), b"2\r", b"  ", b"3"]);
+        assert_eq!(count_lines(data, 9, skip), 4);
+        assert_eq!(count_lines(b"a\n\nb", 0, skip), 2);
+        assert_eq!(count_lines(b"a\n\r", 0, skip), 1);
+        assert_eq!(first_line(b"\n\r\n", skip), None);
+        assert_eq!(data_start(b"\n\r\n", true, skip), 3);
+
+        // 单独的 \r 换行时连续的 \r 之间同样是空行
+        let cr = CR.with_skip_empty(true);
+        assert_eq!(lines_with(b"a\r\rb\r\r\n", false, cr), vec![b"a".as_slice(), b"b"]);
+        assert!(cr.is_skipped(b"\r"));
+        assert!(!CR.is_skipped(b""));
+    }
+
+    #[test]
+    fn test_has_empty_lines() {
+        assert!(!has_empty_lines(b"a\nb\n", LF));
+        assert!(!has_empty_lines(b"a\r\n  \r\nb", LF));
+        assert!(has_empty_lines(b"a\n\nb", LF));
+        assert!(has_empty_lines(b"a\r\n\r\nb\r\n", LF));
+        assert!(has_empty_lines(b"a\n\n", LF));
+        assert!(has_empty_lines(b"\xEF\xBB\xBF\nid\n1", LF));
+        assert!(!has_empty_lines(b"a\rb\r\nc\r", CR));
+        assert!(has_empty_lines(b"a\r\rb", CR));
+        assert!(!has_empty_lines(b"a\r\rb", LF));
+        assert!(is_empty_line(b"\r"));
+        assert!(!is_empty_line(b" "));
+    }
+}
@@ -0,0 +1,178 @@
+//! 打开文件管理模块
+//!
+//! 按路径缓存已打开的读取器，限制同时打开的文件数，超过上限时关闭最久未使用的文件
+
+use crate::csv::{CsvReader, Delimiter};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 默认最多同时打开的文件数
+pub const DEFAULT_MAX_OPEN_FILES: usize = 8;
+
+/// 已打开的文件
+struct OpenFile {
+    reader: Arc<CsvReader>,
+    /// 最近一次使用的序号，越大越新
+    last_used: u64,
+    /// 固定的文件不会被自动关闭（如有未保存的编辑）
+    pinned: bool,
+}
+
+/// 打开文件管理器
+///
+/// 每个读取器都持有文件的内存映射，长时间打开大量文件会占用地址空间，
+/// 在Windows上还会锁定文件。管理器按最近使用顺序（LRU）关闭超出上限的文件；
+/// 被关闭的读取器在其他线程仍持有 `Arc` 时会延迟到最后一个引用释放
+pub struct OpenFileManager {
+    files: HashMap<String, OpenFile>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl Default for OpenFileManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_OPEN_FILES)
+    }
+}
+
+impl OpenFileManager {
+    /// 创建管理器，`capacity` 为最多同时打开的文件数（至少为1）
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            files: HashMap::new(),
+            capacity: capacity.max(1),
+            clock: 0,
+        }
+    }
+
+    /// 最多同时打开的文件数
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 修改上限，返回因此被关闭的文件路径
+    pub fn set_capacity(&mut self, capacity: usize) -> Vec<String> {
+        self.capacity = capacity.max(1);
+        self.evict()
+    }
+
+    /// 已打开的文件数
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// 是否没有打开的文件
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 文件是否已打开
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// 获取已打开的读取器，并标记为最近使用
+    pub fn get(&mut self, path: &str) -> Option<Arc<CsvReader>> {
+        let tick = self.tick();
+        self.files.get_mut(path).map(|file| {
+            file.last_used = tick;
+            Arc::clone(&file.reader)
+        })
+    }
+
+    /// 获取读取器的可变引用（用于在没有其他引用时替换索引等），不改变使用顺序
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut Arc<CsvReader>> {
+        self.files.get_mut(path).map(|file| &mut file.reader)
+    }
+
+    /// 查找可以复用的读取器：路径、打开选项都相同，且文件大小没有变化
+    ///
+    /// 找到时标记为最近使用；选项不同或文件已变化时返回None，由调用方重新打开
+    pub fn find(
+        &mut self,
+        path: &str,
+        has_headers: bool,
+        delimiter: &Delimiter,
+        index_granularity: usize,
+    ) -> Option<Arc<CsvReader>> {
+        let file_size = std::fs::metadata(Path::new(path)).ok()?.len();
+        let reader = self.files.get(path).map(|file| &file.reader)?;
+        let matches = reader.has_headers() == has_headers
+            && reader.delimiter() == delimiter
+            && reader.index_granularity() == index_granularity
+            && reader.info().file_size == file_size;
+        if matches {
+            self.get(path)
+        } else {
+            None
+        }
+    }
+
+    /// 加入读取器（替换同一路径已有的读取器），返回因超出上限被关闭的文件路径
+    pub fn insert(&mut self, path: String, reader: Arc<CsvReader>) -> Vec<String> {
+        let tick = self.tick();
+        let pinned = self.files.get(&path).is_some_and(|file| file.pinned);
+        self.files.insert(path, OpenFile { reader, last_used: tick, pinned });
+        self.evict()
+    }
+
+    /// 关闭文件，返回其读取器
+    pub fn remove(&mut self, path: &str) -> Option<Arc<CsvReader>> {
+        self.files.remove(path).map(|file| file.reader)
+    }
+
+    /// 关闭所有文件，返回被关闭的文件路径
+    pub fn clear(&mut self) -> Vec<String> {
+        self.files.drain().map(|(path, _)| path).collect()
+    }
+
+    /// 设置文件是否固定；固定的文件不会因超出上限被关闭，但可以被显式关闭
+    ///
+    /// 文件未打开时返回false
+    pub fn set_pinned(&mut self, path: &str, pinned: bool) -> bool {
+        match self.files.get_mut(path) {
+            Some(file) => {
+                file.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 已打开文件的路径和读取器，按最近使用顺序排列（最新的在前）
+    pub fn iter_recent(&self) -> Vec<(&str, &Arc<CsvReader>)> {
+        let mut files: Vec<(&String, &OpenFile)> = self.files.iter().collect();
+        files.sort_by_key(|(_, file)| std::cmp::Reverse(file.last_used));
+        files
+            .into_iter()
+            .map(|(path, file)| (path.as_str(), &file.reader))
+            .collect()
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// 关闭最久未使用的未固定文件，直到不超过上限（最近使用的文件总是保留）
+    fn evict(&mut self) -> Vec<String> {
+        let mut evicted = Vec::new();
+        while self.files.len() > self.capacity {
+            let oldest = self.files
+                .iter()
+                .filter(|(_, file)| !file.pinned && file.last_used != self.clock)
+                .min_by_key(|(_, file)| file.last_used)
+                .map(|(path, _)| path.clone());
+            match oldest {
+                Some(path) => {
+                    self.files.remove(&path);
+                    evicted.push(path);
+                }
+                // 全部固定时允许暂时超出上限
+                None => break,
+            }
+        }
+        evicted
+    }
+}
@@ -6,13 +6,33 @@ pub mod search;
 pub mod export;
 pub mod sort;
 pub mod writer;
+pub mod encoding;
+pub mod schema;
+pub mod compression;
+pub mod column_index;
+pub mod delimiter;
+pub mod stats;
+pub mod manager;
+pub mod lines;
+pub mod advise;
+pub mod tdigest;
+pub mod sample;
 
-pub use reader::{CsvReader, CsvInfo, CsvRecord, IndexBuildHandle};
-pub use index::{RowIndex, IndexMetadata, RowEstimate};
-pub use cache::PageCache;
-pub use utils::{format_size, detect_delimiter, detect_has_headers};
-pub use search::{SearchPattern, SearchOptions, SearchResult, Searcher, highlight_matches};
-pub use export::{ExportFormat, ExportOptions, ExportStats, Exporter};
-pub use sort::{SortOrder, SortKey, SortOptions, SortedRecord, Sorter, DataType, sort_csv_data};
-pub use writer::{CsvEditor, CsvCreator, RowData, WriteOptions, LineEnding, ChangeStats, SaveStats};
-
+pub use reader::{CsvReader, CsvInfo, CsvRecord, CsvPreview, IndexBuildHandle, QuoteMode, ReaderOptions, parse_preview};
+pub use index::{RowIndex, IndexMetadata, IndexMode, IndexLocation, INDEX_DIR_ENV, CURRENT_INDEX_VERSION, RowEstimate, ColumnValidation, ColumnCountViolation, ValidationReport};
+pub use column_index::ColumnIndex;
+pub use delimiter::Delimiter;
+pub use cache::{PageCache, DEFAULT_CACHE_PAGES, DEFAULT_CACHE_BYTES};
+pub use utils::{format_size, FloatFormat, NumericCleanup, detect_delimiter, guess_delimiter, DelimiterGuess, detect_has_headers, detect_has_headers_with_delimiter, guess_has_headers, guess_has_headers_with_delimiter, HeaderGuess};
+pub use search::{SearchPattern, PatternSet, SearchOptions, SearchOutcome, SearchResult, MatchInfo, SearchCursor, SearchPage, Searcher, ProgressCallback, HighlightColor, HighlightStyle, highlight_matches};
+pub use export::{ExportFormat, ExportOptions, ExportStats, Exporter, QueryResult, RaggedPolicy};
+pub use sort::{SortOrder, SortKey, Collation, SortOptions, SortedRecord, Sorter, DataType, sort_csv_data, sort_to_file};
+pub use writer::{CsvEditor, CsvCreator, CellEdit, RowData, WriteOptions, LineEnding, ChangeStats, SaveStats};
+pub use encoding::TextEncoding;
+pub use lines::LineFormat;
+pub use schema::{ColumnType, ColumnSchema, InferredType, infer_schema};
+pub use stats::{column_stats, column_stats_with_cleanup, column_stats_with_options, histogram, value_counts, Bucket, ColumnStats, Histogram, NumericStats, StatsOptions};
+pub use tdigest::TDigest;
+pub use sample::{SampleOptions, random_seed, sample_rows, sample_to_file, shuffle_to_file};
+pub use manager::{OpenFileManager, DEFAULT_MAX_OPEN_FILES};
+pub use advise::AccessPattern;
@@ -1,11 +1,16 @@
 use crate::error::{CsvError, Result};
-use crate::csv::{RowIndex, PageCache, IndexMetadata, RowEstimate};
+use crate::csv::{RowIndex, PageCache, Delimiter, LineEnding, DEFAULT_CACHE_PAGES, IndexMetadata, IndexMode, IndexLocation, RowEstimate, ColumnIndex, ColumnValidation, ValidationReport, InferredType};
+use crate::csv::encoding::{self, skip_bom, TextEncoding};
+use crate::csv::lines::{count_lines, data_start, first_line, has_empty_lines, DataLines, LineFormat};
+use crate::csv::advise::{self, AccessHints, AccessPattern, SequentialScan};
+use crate::csv::compression::{self, DecompressedFile};
 use memmap2::{Mmap, MmapOptions};
-use memchr::memchr;  // SIMD加速的换行符查找
+use memchr::memmem;  // SIMD加速的子串查找
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::SystemTime;
 use std::thread;
@@ -57,14 +62,33 @@ impl Drop for IndexBuildHandle {
 pub struct CsvInfo {
     /// 文件路径
     pub file_path: PathBuf,
-    /// 文件大小（字节）
+    /// 文件大小（字节，压缩文件为压缩后大小）
     pub file_size: u64,
+    /// 解压后的大小（仅gzip压缩文件）
+    pub decompressed_size: Option<u64>,
     /// 总行数（不包括表头）
     pub total_rows: usize,
     /// 总列数
     pub total_cols: usize,
     /// 表头
     pub headers: Vec<String>,
+    /// 打开文件时使用的分隔符
+    pub delimiter: Delimiter,
+    /// 检测到的文件编码
+    pub encoding: TextEncoding,
+    /// 检测到的行结束符（没有换行符时为平台默认值）
+    pub line_ending: LineEnding,
+}
+
+/// 引号字段的识别方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteMode {
+    /// 宽松（默认）：忽略引号外的首尾空白，` "a,b" ` 解析为 `a,b`；
+    /// 没有被引号包围的字段保留原有空白
+    #[default]
+    Flexible,
+    /// 严格按RFC 4180：只有紧贴分隔符的引号才包围字段，` "a,b" ` 原样保留
+    Strict,
 }
 
 /// CSV记录（零拷贝）
@@ -73,6 +97,8 @@ pub struct CsvInfo {
 pub struct CsvRecord<'a> {
     /// 字段列表（引用mmap数据）
     pub fields: Vec<Cow<'a, str>>,
+    /// 每个字段在源数据中是否带引号（仅 [`CsvRecord::parse_line_quoted`] 记录，否则为None）
+    pub quoted: Option<Vec<bool>>,
 }
 
 impl<'a> CsvRecord<'a> {
@@ -82,55 +108,81 @@ impl<'a> CsvRecord<'a> {
     /// - `line`: 一行的字节数据（不包括换行符）
     /// - `delimiter`: 分隔符（默认逗号）
     pub fn parse_line(line: &'a [u8], delimiter: u8) -> Self {
-        // 去除行尾的 \r（处理 Windows 换行符 CRLF）
-        let line = if !line.is_empty() && line[line.len() - 1] == b'\r' {
-            &line[..line.len() - 1]
-        } else {
-            line
-        };
-        
+        Self::parse_line_with(line, &Delimiter::Byte(delimiter))
+    }
+
+    /// 按单字节或多字节分隔符解析一行CSV记录
+    /// 
+    /// # 参数
+    /// - `line`: 一行的字节数据（不包括换行符）
+    /// - `delimiter`: 分隔符
+    pub fn parse_line_with(line: &'a [u8], delimiter: &Delimiter) -> Self {
+        Self::parse_line_mode(line, delimiter, QuoteMode::Flexible)
+    }
+
+    /// 按指定的引号识别方式解析一行CSV记录
+    /// 
+    /// # 参数
+    /// - `line`: 一行的字节数据（不包括换行符）
+    /// - `delimiter`: 分隔符
+    /// - `mode`: 引号字段的识别方式
+    pub fn parse_line_mode(line: &'a [u8], delimiter: &Delimiter, mode: QuoteMode) -> Self {
         let mut fields = Vec::new();
-        let mut start = 0;
-        let mut in_quotes = false;
+        split_fields(line, delimiter, |field| fields.push(Self::parse_field_with(field, mode)));
+        Self { fields, quoted: None }
+    }
 
-        for (i, &byte) in line.iter().enumerate() {
-            match byte {
-                b'"' => {
-                    in_quotes = !in_quotes;
-                }
-                _ if byte == delimiter && !in_quotes => {
-                    // 找到分隔符
-                    if start < i {
-                        let field = &line[start..i];
-                        fields.push(Self::parse_field(field));
-                    } else {
-                        fields.push(Cow::Borrowed(""));
-                    }
-                    start = i + 1;
-                }
-                _ => {}
-            }
-        }
+    /// 解析一行CSV记录，并记录每个字段在源数据中是否带引号
+    /// 
+    /// 保存时据此保留未修改字段原有的引用风格
+    /// 
+    /// # 参数
+    /// - `line`: 一行的字节数据（不包括换行符）
+    /// - `delimiter`: 分隔符
+    pub fn parse_line_quoted(line: &'a [u8], delimiter: &Delimiter) -> Self {
+        Self::parse_line_quoted_mode(line, delimiter, QuoteMode::Flexible)
+    }
 
-        // 添加最后一个字段
-        if start < line.len() {
-            let field = &line[start..];
-            fields.push(Self::parse_field(field));
-        } else {
-            fields.push(Cow::Borrowed(""));
-        }
+    /// 按指定的引号识别方式解析一行CSV记录，并记录每个字段是否带引号
+    pub fn parse_line_quoted_mode(line: &'a [u8], delimiter: &Delimiter, mode: QuoteMode) -> Self {
+        let mut record = Self::parse_line_mode(line, delimiter, mode);
+        record.quoted = Some(Self::quoted_fields(line, delimiter, mode));
+        record
+    }
 
-        Self { fields }
+    /// 按与解析相同的规则切分字段，返回每个字段是否带引号
+    fn quoted_fields(line: &[u8], delimiter: &Delimiter, mode: QuoteMode) -> Vec<bool> {
+        let mut quoted = Vec::new();
+        split_fields(line, delimiter, |field| quoted.push(Self::quoted_content(field, mode).is_some()));
+        quoted
     }
 
-    /// 解析单个字段（处理引号和转义）
-    fn parse_field(field: &[u8]) -> Cow<'_, str> {
-        // 移除首尾的引号
-        let field = if field.len() >= 2 && field[0] == b'"' && field[field.len() - 1] == b'"' {
-            &field[1..field.len() - 1]
-        } else {
-            field
+    /// 字段以引号包围时返回引号内的内容
+    /// 
+    /// 宽松模式下先去掉首尾的空格和制表符再检查
+    fn quoted_content(field: &[u8], mode: QuoteMode) -> Option<&[u8]> {
+        let field = match mode {
+            QuoteMode::Flexible => trim_blanks(field),
+            QuoteMode::Strict => field,
         };
+        (field.len() >= 2 && field[0] == b'"' && field[field.len() - 1] == b'"')
+            .then(|| &field[1..field.len() - 1])
+    }
+
+    /// 第 `index` 个字段在源数据中是否带引号（未记录时返回None）
+    pub fn is_quoted(&self, index: usize) -> Option<bool> {
+        self.quoted.as_ref()?.get(index).copied()
+    }
+
+    /// 解析单个字段（处理引号和转义，宽松模式）
+    pub(crate) fn parse_field(field: &[u8]) -> Cow<'_, str> {
+        Self::parse_field_with(field, QuoteMode::Flexible)
+    }
+
+    /// 按指定的引号识别方式解析单个字段
+    pub(crate) fn parse_field_with(field: &[u8], mode: QuoteMode) -> Cow<'_, str> {
+        // 移除首尾的引号
+        let field = Self::quoted_content(field, mode).unwrap_or(field);
 
         // 处理转义的引号（"" -> "）
         if field.contains(&b'"') {
@@ -163,25 +215,309 @@ impl<'a> CsvRecord<'a> {
     pub fn to_owned(&self) -> CsvRecord<'static> {
         CsvRecord {
             fields: self.fields.iter().map(|f| Cow::Owned(f.to_string())).collect(),
+            quoted: self.quoted.clone(),
+        }
+    }
+}
+
+/// 去掉首尾的空格和制表符
+fn trim_blanks(field: &[u8]) -> &[u8] {
+    let is_blank = |b: &u8| *b == b' ' || *b == b'\t';
+    let start = field.iter().position(|b| !is_blank(b)).unwrap_or(field.len());
+    let end = field.iter().rposition(|b| !is_blank(b)).map_or(start, |pos| pos + 1);
+    &field[start..end]
+}
+
+/// 按分隔符切分一行，引号内的分隔符不切分，行尾的 `\r`（CRLF换行）被去除
+/// 
+/// 总是产生 引号外的分隔符数 + 1 个字段：空行是一个空字段，
+/// `a,` 的最后一个字段和 `a,,c` 的中间字段都为空
+fn split_fields<'l>(line: &'l [u8], delimiter: &Delimiter, mut visit: impl FnMut(&'l [u8])) {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let delimiter_len = delimiter.as_bytes().len();
+
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut i = 0;
+
+    while i < line.len() {
+        if line[i] == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && delimiter.matches_at(line, i) {
+            visit(&line[start..i]);
+            i += delimiter_len;
+            start = i;
+            continue;
+        }
+        i += 1;
+    }
+    visit(&line[start..]);
+}
+
+/// 不建立索引直接解析的文件开头几行
+#[derive(Debug, Clone)]
+pub struct CsvPreview<'a> {
+    /// 表头（无表头时为空）
+    pub headers: Vec<String>,
+    /// 预览的数据行
+    pub rows: Vec<CsvRecord<'a>>,
+    /// 数据区（表头之后）已解析的字节数
+    pub bytes_read: usize,
+    /// 数据区的总字节数
+    pub data_bytes: usize,
+}
+
+impl CsvPreview<'_> {
+    /// 是否已读到文件末尾（预览即为全部数据）
+    pub fn is_complete(&self) -> bool {
+        self.bytes_read >= self.data_bytes
+    }
+}
+
+/// 解析文件开头最多 `rows` 行，不建立索引
+/// 
+/// 跳过BOM，按与 [`CsvReader`] 相同的规则分行和解析字段，
+/// 预览的内容与之后分页读取的内容一致
+/// 
+/// # 参数
+/// - `data`: 文件内容
+/// - `rows`: 最多解析的数据行数
+/// - `delimiter`: 分隔符
+/// - `has_headers`: 第一行是否为表头
+pub fn parse_preview<'a>(data: &'a [u8], rows: usize, delimiter: &Delimiter, has_headers: bool) -> CsvPreview<'a> {
+    let format = LineFormat::detect(&data[skip_bom(data)..]).with_skip_empty(true);
+    
+    let headers = match first_line(data, format) {
+        Some((header_start, header_end)) if has_headers => {
+            CsvRecord::parse_line_with(&data[header_start..header_end], delimiter)
+                .fields
+                .iter()
+                .map(|f| f.to_string())
+                .collect()
         }
+        _ => Vec::new(),
+    };
+    let data_start = data_start(data, has_headers, format);
+    
+    let mut records = Vec::with_capacity(rows.min(1024));
+    let mut lines = DataLines::new(data, data_start, format);
+    for (line_start, line_end) in lines.by_ref().take(rows) {
+        records.push(CsvRecord::parse_line_with(&data[line_start..line_end], delimiter));
+    }
+    
+    CsvPreview {
+        headers,
+        rows: records,
+        bytes_read: lines.position() - data_start,
+        data_bytes: data.len() - data_start,
+    }
+}
+
+/// 原始字节预筛选时每次查找的字节数（扩展到行尾）
+const PREFILTER_WINDOW_BYTES: usize = 1024 * 1024;
+
+/// 读取行范围时最多提前读入的字节数
+const PREFETCH_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+/// 校验报告默认最多记录的行数
+pub const DEFAULT_MAX_VIOLATIONS: usize = 100;
+
+/// 读取器打开选项
+#[derive(Debug, Clone)]
+pub struct ReaderOptions {
+    /// 是否有表头
+    pub has_headers: bool,
+    /// 分隔符
+    pub delimiter: Delimiter,
+    /// 索引粒度（每N行记录一次）
+    pub index_granularity: usize,
+    /// 构建索引时校验每行的列数
+    pub validate_columns: bool,
+    /// 严格模式：发现列数不一致的行时立即报错（隐含 `validate_columns`）
+    pub strict: bool,
+    /// 校验报告最多记录的行数
+    pub max_violations: usize,
+    /// 索引模式
+    pub index_mode: IndexMode,
+    /// 额外构建列索引的列（从0开始，仅 `open_with_options` 支持）
+    pub column_index: Option<usize>,
+    /// 是否读写 `.idx` 索引文件（关闭时索引只保存在内存中）
+    pub persist_index: bool,
+    /// 索引文件存放位置（默认由 [`IndexLocation::from_env`] 决定）
+    pub index_location: IndexLocation,
+    /// 加载索引时是否用整个文件的哈希校验（较慢，可发现文件中间大小不变的修改），
+    /// 开启时保存的索引才会记录整个文件的哈希
+    pub verify_index: bool,
+    /// 页面缓存容量（页数，0表示不缓存）
+    pub cache_pages: usize,
+    /// 引号字段的识别方式
+    pub quote_mode: QuoteMode,
+    /// 是否向内核提示访问模式（扫描时顺序预读，翻页时预读目标页）
+    pub access_hints: bool,
+    /// 保留空行（默认去掉，空行不计入行数）
+    pub keep_empty_lines: bool,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        Self {
+            has_headers: true,
+            delimiter: Delimiter::default(),
+            index_granularity: 1000,
+            validate_columns: false,
+            strict: false,
+            max_violations: DEFAULT_MAX_VIOLATIONS,
+            index_mode: IndexMode::Sparse,
+            column_index: None,
+            persist_index: true,
+            index_location: IndexLocation::from_env(),
+            verify_index: false,
+            cache_pages: DEFAULT_CACHE_PAGES,
+            quote_mode: QuoteMode::default(),
+            access_hints: true,
+            keep_empty_lines: false,
+        }
+    }
+}
+
+impl ReaderOptions {
+    /// 创建新选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否有表头
+    pub fn with_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// 设置分隔符（单字节或 [`Delimiter`]）
+    pub fn with_delimiter(mut self, delimiter: impl Into<Delimiter>) -> Self {
+        self.delimiter = delimiter.into();
+        self
+    }
+
+    /// 设置索引粒度
+    pub fn with_granularity(mut self, granularity: usize) -> Self {
+        self.index_granularity = granularity;
+        self
+    }
+
+    /// 设置是否校验每行的列数
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate_columns = validate;
+        self
+    }
+
+    /// 设置严格模式
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// 设置校验报告最多记录的行数
+    pub fn with_max_violations(mut self, max: usize) -> Self {
+        self.max_violations = max;
+        self
+    }
+
+    /// 设置索引模式
+    pub fn with_index_mode(mut self, mode: IndexMode) -> Self {
+        self.index_mode = mode;
+        self
+    }
+
+    /// 设置额外构建列索引的列
+    pub fn with_column_index(mut self, column: Option<usize>) -> Self {
+        self.column_index = column;
+        self
+    }
+
+    /// 设置是否读写索引文件
+    /// 
+    /// 只读目录、共享目录或临时输入可以关闭，避免在CSV文件旁写入 `.idx` 文件
+    pub fn with_persist_index(mut self, persist: bool) -> Self {
+        self.persist_index = persist;
+        self
+    }
+
+    /// 设置索引文件存放位置
+    pub fn with_index_location(mut self, location: IndexLocation) -> Self {
+        self.index_location = location;
+        self
+    }
+
+    /// 设置加载索引时是否用整个文件的哈希校验
+    pub fn with_verify_index(mut self, verify: bool) -> Self {
+        self.verify_index = verify;
+        self
+    }
+
+    /// 设置页面缓存容量（页数）
+    /// 
+    /// 设为0时不缓存，读取页面时也不再复制记录；只读取一次的命令行场景可以关闭
+    pub fn with_cache_pages(mut self, pages: usize) -> Self {
+        self.cache_pages = pages;
+        self
+    }
+
+    /// 设置引号字段的识别方式
+    /// 
+    /// 默认宽松模式允许引号外有空白；需要严格按RFC 4180解析时使用 [`QuoteMode::Strict`]
+    pub fn with_quote_mode(mut self, mode: QuoteMode) -> Self {
+        self.quote_mode = mode;
+        self
+    }
+
+    /// 设置是否向内核提示访问模式
+    /// 
+    /// 默认开启：构建索引、搜索和导出期间按顺序预读，读取页面时提前读入目标数据；
+    /// 不支持的平台上没有效果
+    pub fn with_access_hints(mut self, enabled: bool) -> Self {
+        self.access_hints = enabled;
+        self
+    }
+
+    /// 设置是否保留空行
+    /// 
+    /// 默认分行时跳过所有空行（两个换行符之间没有内容的行），它们不会作为只有一个空字段的记录
+    /// 出现在分页、搜索和导出中，也不计入行数；文件内容不会被复制或改写。
+    /// 保留空行时不读写 `.idx` 索引文件，避免与跳过空行构建的索引混用
+    pub fn with_keep_empty_lines(mut self, keep: bool) -> Self {
+        self.keep_empty_lines = keep;
+        self
+    }
+
+    /// 是否读写 `.idx` 索引文件
+    fn persists_index(&self) -> bool {
+        self.persist_index && !self.keep_empty_lines
     }
 }
 
 /// 高性能CSV读取器
 /// 使用内存映射、行索引和页面缓存
+/// 
+/// 读取和搜索只需要 `&self`，可以放在 `Arc` 中供多个线程同时读取
 pub struct CsvReader {
     /// 内存映射的文件
     mmap: Arc<Mmap>,
     /// 行索引
     index: RowIndex,
-    /// 页面缓存
-    cache: PageCache,
+    /// 索引未完成时按需读取更深的行而扩展的索引（从 `index` 复制，只扫描到读取过的最深的行）
+    extended_index: Mutex<Option<RowIndex>>,
+    /// 页面缓存（多个线程读取时共享）
+    cache: Mutex<PageCache>,
+    /// 推断出的列类型缓存（采样行数、总行数、结果）
+    column_types: Mutex<Option<(usize, usize, Vec<InferredType>)>>,
     /// 文件信息
     info: CsvInfo,
     /// CSV分隔符
-    delimiter: u8,
+    delimiter: Delimiter,
     /// 数据起始偏移量（跳过表头后的位置）
     data_start_offset: u64,
+    /// 分行方式（按检测到的行结束符）
+    line_format: LineFormat,
     /// 是否有表头
     has_headers: bool,
     /// 索引粒度
@@ -192,6 +528,30 @@ pub struct CsvReader {
     build_progress: Arc<AtomicUsize>,
     /// 行数估算（如果尚未完成精确计数）
     row_estimate: Option<RowEstimate>,
+    /// gzip解压得到的临时文件（释放时自动删除）
+    decompressed: Option<DecompressedFile>,
+    /// 列数校验报告（仅在启用校验时存在）
+    validation_report: Option<ValidationReport>,
+    /// 是否读写索引文件
+    persist_index: bool,
+    /// 索引文件存放位置
+    index_location: IndexLocation,
+    /// 引号字段的识别方式
+    quote_mode: QuoteMode,
+    /// 访问模式提示（后台构建索引时共享）
+    access_hints: Arc<AccessHints>,
+}
+
+/// 构建索引时的进度回调（进度百分比, 已处理字节数, 总字节数）
+type BuildProgress<'a> = &'a mut dyn FnMut(f64, usize, usize);
+
+/// 内存映射后的文件数据
+struct MappedFile {
+    mmap: Arc<Mmap>,
+    encoding: TextEncoding,
+    line_ending: LineEnding,
+    line_format: LineFormat,
+    decompressed: Option<DecompressedFile>,
 }
 
 impl CsvReader {
@@ -205,26 +565,57 @@ impl CsvReader {
     pub fn open<P: AsRef<Path>>(
         path: P,
         has_headers: bool,
-        delimiter: u8,
+        delimiter: impl Into<Delimiter>,
         index_granularity: usize,
     ) -> Result<Self> {
-        let path = path.as_ref();
+        let options = ReaderOptions::new()
+            .with_headers(has_headers)
+            .with_delimiter(delimiter)
+            .with_granularity(index_granularity);
+        Self::open_with_options(path, &options)
+    }
+
+    /// 按选项打开CSV文件并创建读取器
+    /// 
+    /// 启用列数校验时总是重新扫描文件（不使用缓存的索引），
+    /// 严格模式下遇到第一条列数不一致的行即返回 `CsvError::Format`
+    pub fn open_with_options<P: AsRef<Path>>(path: P, options: &ReaderOptions) -> Result<Self> {
+        Self::open_with_build_progress(path.as_ref(), options, None)
+    }
+
+    /// 按选项打开CSV文件，并汇报构建索引的进度
+    /// 
+    /// `progress` 的参数为（进度百分比, 已处理字节数, 总字节数），百分比单调递增，
+    /// 结束时总会以100.0调用一次（使用缓存的索引时只有这一次）。
+    /// 大文件并行构建索引时，回调在调用线程中定期执行
+    pub fn open_with_progress<P, F>(path: P, options: &ReaderOptions, mut progress: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: FnMut(f64, usize, usize),
+    {
+        Self::open_with_build_progress(path.as_ref(), options, Some(&mut progress))
+    }
+
+    fn open_with_build_progress(
+        path: &Path,
+        options: &ReaderOptions,
+        progress: Option<BuildProgress<'_>>,
+    ) -> Result<Self> {
+        let has_headers = options.has_headers;
+        let delimiter = &options.delimiter;
+        let index_granularity = options.index_granularity;
         
         // 获取文件元数据
         let file_metadata = std::fs::metadata(path)?;
         let file_size = file_metadata.len();
-        let file_mtime = file_metadata.modified().unwrap_or_else(|_| SystemTime::now());
 
-        // 打开文件并创建内存映射
-        let file = File::open(path)?;
-        let mmap = Arc::new(
-            unsafe { MmapOptions::new().map(&file) }
-                .map_err(|e| CsvError::Mmap(e.to_string()))?
-        );
+        // 打开文件并创建内存映射（UTF-16文件会转码为UTF-8）
+        let MappedFile { mmap, encoding, line_ending, line_format, decompressed } =
+            Self::map_file(path, options.keep_empty_lines)?;
 
         // 读取表头
         let headers = if has_headers {
-            Self::read_headers(&mmap, delimiter)?
+            Self::read_headers(&mmap, line_format, delimiter, options.quote_mode)?
         } else {
             Vec::new()
         };
@@ -233,61 +624,66 @@ impl CsvReader {
             headers.len()
         } else {
             // 从第一行推断列数
-            Self::count_columns_first_line(&mmap, delimiter)?
+            Self::count_columns_first_line(&mmap, line_format, delimiter)?
         };
 
-        // 尝试加载索引，如果失败则构建新索引
-        let (index, total_rows) = Self::load_or_build_index(
+        let validation = (options.validate_columns || options.strict).then(|| ColumnValidation {
+            delimiter: delimiter.clone(),
+            expected_cols: total_cols,
+            max_violations: options.max_violations,
+            fail_fast: options.strict,
+        });
+
+        // 尝试加载索引，如果失败则构建新索引（构建时顺序扫描整个文件）
+        let access_hints = Arc::new(AccessHints::new(options.access_hints));
+        let scan = access_hints.sequential(&mmap);
+        let (index, total_rows, validation_report) = Self::load_or_build_index(
             path,
             &mmap,
-            has_headers,
-            index_granularity,
-            file_size,
-            file_mtime,
+            line_format,
+            options,
+            &file_metadata,
+            validation.as_ref(),
+            progress,
         )?;
+        drop(scan);
 
-        // 计算数据起始偏移量（跳过表头）- 使用memchr加速
-        let data_start_offset = if has_headers {
-            let start = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-                3
-            } else {
-                0
-            };
-            // 找到第一个换行符后的位置
-            let header_slice = &mmap[start..];
-            if let Some(pos) = memchr(b'\n', header_slice) {
-                (start + pos + 1) as u64
-            } else {
-                start as u64
-            }
-        } else {
-            if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-                3
-            } else {
-                0
-            }
-        };
+        // 计算数据起始偏移量（跳过BOM和表头）
+        let data_start_offset = Self::data_start_offset(&mmap, has_headers, line_format);
 
         let info = CsvInfo {
             file_path: path.to_path_buf(),
             file_size,
+            decompressed_size: decompressed.as_ref().map(|_| mmap.len() as u64),
             total_rows,
             total_cols,
             headers,
+            delimiter: delimiter.clone(),
+            encoding,
+            line_ending,
         };
 
         Ok(Self {
             mmap,
             index,
-            cache: PageCache::default(),
+            extended_index: Mutex::new(None),
+            cache: Mutex::new(PageCache::new(options.cache_pages)),
+            column_types: Mutex::new(None),
             info,
-            delimiter,
+            delimiter: delimiter.clone(),
             data_start_offset,
+            line_format,
             has_headers,
             index_granularity,
             cancel_flag: Arc::new(AtomicBool::new(false)),
             build_progress: Arc::new(AtomicUsize::new(0)),
             row_estimate: None,
+            decompressed,
+            validation_report,
+            persist_index: options.persists_index(),
+            index_location: options.index_location.clone(),
+            quote_mode: options.quote_mode,
+            access_hints,
         })
     }
 
@@ -309,25 +705,37 @@ impl CsvReader {
     pub fn open_fast<P: AsRef<Path>>(
         path: P,
         has_headers: bool,
-        delimiter: u8,
+        delimiter: impl Into<Delimiter>,
         index_granularity: usize,
     ) -> Result<Self> {
+        let options = ReaderOptions::new()
+            .with_headers(has_headers)
+            .with_delimiter(delimiter)
+            .with_granularity(index_granularity);
+        Self::open_fast_with_options(path, &options)
+    }
+
+    /// 按选项快速打开CSV文件
+    /// 
+    /// 快速打开不扫描整个文件，因此忽略列数校验相关选项
+    pub fn open_fast_with_options<P: AsRef<Path>>(path: P, options: &ReaderOptions) -> Result<Self> {
         let path = path.as_ref();
+        let has_headers = options.has_headers;
+        let delimiter = &options.delimiter;
+        let index_granularity = options.index_granularity;
+        let index_mode = options.index_mode;
         
         // 获取文件元数据
         let file_metadata = std::fs::metadata(path)?;
         let file_size = file_metadata.len();
 
-        // 打开文件并创建内存映射
-        let file = File::open(path)?;
-        let mmap = Arc::new(
-            unsafe { MmapOptions::new().map(&file) }
-                .map_err(|e| CsvError::Mmap(e.to_string()))?
-        );
+        // 打开文件并创建内存映射（UTF-16文件会转码为UTF-8）
+        let MappedFile { mmap, encoding, line_ending, line_format, decompressed } =
+            Self::map_file(path, options.keep_empty_lines)?;
 
         // 读取表头
         let headers = if has_headers {
-            Self::read_headers(&mmap, delimiter)?
+            Self::read_headers(&mmap, line_format, delimiter, options.quote_mode)?
         } else {
             Vec::new()
         };
@@ -335,61 +743,119 @@ impl CsvReader {
         let total_cols = if has_headers {
             headers.len()
         } else {
-            Self::count_columns_first_line(&mmap, delimiter)?
+            Self::count_columns_first_line(&mmap, line_format, delimiter)?
         };
 
         // 尝试加载已有索引
-        let index_path = RowIndex::index_file_path(path);
-        let (index, total_rows, row_estimate) = if index_path.exists() {
+        let index_path = options.persists_index()
+            .then(|| RowIndex::find_index_file(path, &options.index_location))
+            .flatten();
+        let (index, total_rows, row_estimate) = if let Some(index_path) = index_path {
             match RowIndex::load_from_file(&index_path) {
-                Ok((index, metadata)) => {
-                    if RowIndex::is_index_valid(path, &metadata) && metadata.granularity == index_granularity {
+                Ok((mut index, mut metadata))
+                    if metadata.matches(has_headers, index_granularity, index_mode)
+                        && Self::counts_rows_alike(&metadata, &mmap, line_format) =>
+                {
+                    if Self::is_loaded_index_valid(path, &metadata, &index, &mmap, options) {
+                        // 由旧版本升级的索引确认可用后记录下来，之后打开时不再检查空行
+                        if !std::mem::replace(&mut metadata.skips_empty_lines, true) {
+                            let _ = index.save_to_location(path, &metadata, &options.index_location);
+                        }
+                        let total_rows = index.total_rows();
+                        (index, total_rows, None)
+                    } else if !options.verify_index && index.refresh(path, &mmap, line_format, &metadata).unwrap_or(false) {
+                        // 文件只在末尾追加了数据，增量更新后保存
+                        let file_mtime = file_metadata.modified().unwrap_or_else(|_| SystemTime::now());
+                        let metadata = Self::index_metadata(path, options, file_size, file_mtime);
+                        let _ = index.save_to_location(path, &metadata, &options.index_location);
                         let total_rows = index.total_rows();
                         (index, total_rows, None)
                     } else {
                         // 索引无效，使用快速模式
-                        Self::build_fast_index(&mmap, has_headers, index_granularity)?
+                        Self::build_fast_index(&mmap, has_headers, line_format, index_granularity, index_mode)?
                     }
                 }
-                Err(_) => Self::build_fast_index(&mmap, has_headers, index_granularity)?,
+                Ok(_) => Self::build_fast_index(&mmap, has_headers, line_format, index_granularity, index_mode)?,
+                Err(_) => Self::build_fast_index(&mmap, has_headers, line_format, index_granularity, index_mode)?,
             }
         } else {
-            Self::build_fast_index(&mmap, has_headers, index_granularity)?
+            Self::build_fast_index(&mmap, has_headers, line_format, index_granularity, index_mode)?
         };
 
-        // 计算数据起始偏移量
-        let data_start_offset = if has_headers {
-            let start = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" { 3 } else { 0 };
-            let header_slice = &mmap[start..];
-            if let Some(pos) = memchr(b'\n', header_slice) {
-                (start + pos + 1) as u64
-            } else {
-                start as u64
-            }
-        } else {
-            if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" { 3 } else { 0 }
-        };
+        // 计算数据起始偏移量（跳过BOM和表头）
+        let data_start_offset = Self::data_start_offset(&mmap, has_headers, line_format);
 
         let info = CsvInfo {
             file_path: path.to_path_buf(),
             file_size,
+            decompressed_size: decompressed.as_ref().map(|_| mmap.len() as u64),
             total_rows,
             total_cols,
             headers,
+            delimiter: delimiter.clone(),
+            encoding,
+            line_ending,
         };
 
         Ok(Self {
             mmap,
             index,
-            cache: PageCache::default(),
+            extended_index: Mutex::new(None),
+            cache: Mutex::new(PageCache::new(options.cache_pages)),
+            column_types: Mutex::new(None),
             info,
-            delimiter,
+            delimiter: delimiter.clone(),
             data_start_offset,
+            line_format,
             has_headers,
             index_granularity,
             cancel_flag: Arc::new(AtomicBool::new(false)),
             build_progress: Arc::new(AtomicUsize::new(0)),
             row_estimate,
+            decompressed,
+            validation_report: None,
+            persist_index: options.persists_index(),
+            index_location: options.index_location.clone(),
+            quote_mode: options.quote_mode,
+            access_hints: Arc::new(AccessHints::new(options.access_hints)),
+        })
+    }
+
+    /// 创建文件的内存映射
+    /// 
+    /// UTF-16文件会一次性转码为UTF-8并放入匿名内存映射，
+    /// 因此索引和解析逻辑始终按UTF-8字节处理；
+    /// 行结束符只做检测，按检测结果分行；除非 `keep_empty_lines`，分行时跳过空行，同样不改写数据；
+    /// gzip文件会先解压到临时文件，再映射解压后的数据；
+    /// 索引文件仍以原始路径为准，并记录压缩文件的大小和修改时间
+    fn map_file(path: &Path, keep_empty_lines: bool) -> Result<MappedFile> {
+        let decompressed = if compression::is_gzip_file(path)? {
+            Some(DecompressedFile::create(path)?)
+        } else {
+            None
+        };
+
+        let file = File::open(decompressed.as_ref().map_or(path, |d| d.path()))?;
+        let mmap = unsafe { MmapOptions::new().map(&file) }
+            .map_err(|e| CsvError::Mmap(e.to_string()))?;
+
+        let encoding = TextEncoding::detect(&mmap);
+        let mmap = if encoding.is_utf16() {
+            encoding::transcode_to_mmap(&mmap, encoding)?
+        } else {
+            mmap
+        };
+
+        // 旧Mac风格的文件中单独的 \r 也作为行结束符；空行不作为记录
+        let detected = LineEnding::detect(&mmap);
+        let line_format = LineFormat::new(detected).with_skip_empty(!keep_empty_lines);
+
+        Ok(MappedFile {
+            mmap: Arc::new(mmap),
+            encoding,
+            line_ending: detected.unwrap_or_default(),
+            line_format,
+            decompressed,
         })
     }
 
@@ -401,9 +867,12 @@ impl CsvReader {
     fn build_fast_index(
         mmap: &Mmap,
         has_headers: bool,
+        format: LineFormat,
         granularity: usize,
+        mode: IndexMode,
     ) -> Result<(RowIndex, usize, Option<RowEstimate>)> {
         let file_size = mmap.len();
+        let granularity = mode.build_granularity(granularity);
         
         // 智能采样策略：根据文件大小调整采样大小
         // - 小文件 (<10MB): 256KB
@@ -420,12 +889,12 @@ impl CsvReader {
             64 * 1024   // 64KB for large files (>100MB)
         };
         
-        let estimate = RowIndex::estimate_rows(mmap, has_headers, sample_size);
+        let estimate = RowIndex::estimate_rows(mmap, has_headers, format, sample_size);
         
         // 对于小文件（<1MB），直接构建完整索引（通常 <100ms）
         const TINY_FILE_THRESHOLD: usize = 1 * 1024 * 1024;
         if file_size <= TINY_FILE_THRESHOLD || estimate.is_exact {
-            let index = RowIndex::build(mmap, has_headers, granularity)?;
+            let index = RowIndex::build(mmap, has_headers, format, granularity)?.into_mode(mode);
             let total_rows = index.total_rows();
             return Ok((index, total_rows, None));
         }
@@ -433,7 +902,8 @@ impl CsvReader {
         // 对于大文件，只构建前 500 行的索引（确保首页立即可用）
         // 从 2000 行降低到 500 行，进一步提升打开速度
         const INITIAL_ROWS: usize = 500;
-        let (index, _complete) = RowIndex::build_partial(mmap, has_headers, granularity, Some(INITIAL_ROWS))?;
+        let (index, _complete) = RowIndex::build_partial(mmap, has_headers, format, granularity, Some(INITIAL_ROWS))?;
+        let index = index.into_mode(mode);
         
         // 使用估算的行数（但至少是已索引的行数）
         let total_rows = estimate.estimated_rows.max(index.total_rows());
@@ -451,7 +921,11 @@ impl CsvReader {
         let cancel_flag = Arc::clone(&self.cancel_flag);
         let progress = Arc::clone(&self.build_progress);
         let granularity = self.index_granularity;
-        let _has_headers = self.has_headers; // 保留用于未来扩展
+        let has_headers = self.has_headers;
+        let line_format = self.line_format;
+        let persist_index = self.persist_index;
+        let index_location = self.index_location.clone();
+        let access_hints = Arc::clone(&self.access_hints);
         let file_path = self.info.file_path.clone();
         let file_size = self.info.file_size;
         let file_mtime = std::fs::metadata(&file_path)
@@ -460,17 +934,22 @@ impl CsvReader {
 
         let handle = thread::spawn(move || {
             // 继续构建索引
-            let result = index.continue_build(&mmap, Some(&cancel_flag), Some(&progress));
+            let scan = access_hints.sequential(&mmap);
+            let result = index.continue_build(&mmap, line_format, Some(&cancel_flag), Some(&progress));
+            drop(scan);
             
-            if let Ok(true) = result {
+            if persist_index && matches!(result, Ok(true)) {
                 // 索引构建完成，保存到文件
                 let metadata = IndexMetadata::new(
                     file_path.clone(),
                     file_size,
                     file_mtime,
                     granularity,
-                );
-                let _ = index.save_to_file(&file_path, &metadata);
+                )
+                .with_headers(has_headers)
+                .with_mode(index.mode())
+                .with_checksum(&file_path);
+                let _ = index.save_to_location(&file_path, &metadata, &index_location);
             }
 
             (index, result.is_ok())
@@ -480,7 +959,7 @@ impl CsvReader {
             handle: Some(handle),
             cancel_flag: Arc::clone(&self.cancel_flag),
             progress: Arc::clone(&self.build_progress),
-            total_bytes: self.info.file_size as usize,
+            total_bytes: self.mmap.len(),
         }
     }
 
@@ -488,8 +967,9 @@ impl CsvReader {
     pub fn update_index(&mut self, new_index: RowIndex) {
         self.info.total_rows = new_index.total_rows();
         self.index = new_index;
+        *self.extended_index.get_mut().unwrap_or_else(|e| e.into_inner()) = None;
         self.row_estimate = None; // 清除估算值，使用精确值
-        self.cache.clear(); // 清除缓存，因为行数可能变化
+        self.clear_cache(); // 清除缓存，因为行数可能变化
     }
 
     /// 检查索引是否完成
@@ -502,10 +982,15 @@ impl CsvReader {
         self.row_estimate.as_ref()
     }
 
+    /// 总行数是否为精确值（快速打开大文件时为估算值，完整索引后变为精确值）
+    pub fn is_row_count_exact(&self) -> bool {
+        self.row_estimate.is_none()
+    }
+
     /// 获取索引构建进度（0-100）
     pub fn index_build_progress(&self) -> f64 {
         let progress = self.build_progress.load(Ordering::Relaxed);
-        let total = self.info.file_size as usize;
+        let total = self.mmap.len();
         if total == 0 {
             100.0
         } else {
@@ -513,83 +998,240 @@ impl CsvReader {
         }
     }
 
-    /// 读取表头
-    fn read_headers(mmap: &Mmap, delimiter: u8) -> Result<Vec<String>> {
-        // 跳过BOM
-        let start = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3
-        } else {
-            0
-        };
+    /// 是否为gzip压缩文件
+    pub fn is_compressed(&self) -> bool {
+        self.decompressed.is_some()
+    }
+
+    /// 获取gzip解压得到的临时文件路径
+    pub fn decompressed_path(&self) -> Option<&Path> {
+        self.decompressed.as_ref().map(|d| d.path())
+    }
 
-        // 找到第一行的结束位置 - 使用memchr加速
-        let header_slice = &mmap[start..];
-        let line_end = memchr(b'\n', header_slice)
-            .or_else(|| memchr(b'\r', header_slice))
-            .map(|pos| start + pos)
-            .unwrap_or(mmap.len());
+    /// 设置释放读取器时是否保留解压的临时文件（默认删除）
+    pub fn set_keep_decompressed(&mut self, keep: bool) {
+        if let Some(ref mut decompressed) = self.decompressed {
+            decompressed.keep(keep);
+        }
+    }
+
+    /// 数据区的起始偏移量：跳过BOM，有表头时再跳过第一行
+    fn data_start_offset(mmap: &[u8], has_headers: bool, format: LineFormat) -> u64 {
+        data_start(mmap, has_headers, format) as u64
+    }
+
+    /// 读取表头
+    fn read_headers(mmap: &Mmap, format: LineFormat, delimiter: &Delimiter, quote_mode: QuoteMode) -> Result<Vec<String>> {
+        // 跳过BOM（跳过空行时还跳过开头的空行），找到第一行
+        let (start, line_end) = first_line(mmap, format).unwrap_or((0, 0));
 
         if line_end == start {
             return Err(CsvError::Format("文件为空或格式错误".to_string()));
         }
 
         let header_line = &mmap[start..line_end];
-        let record = CsvRecord::parse_line(header_line, delimiter);
+        let record = CsvRecord::parse_line_mode(header_line, delimiter, quote_mode);
         
         Ok(record.fields.iter().map(|f| f.to_string()).collect())
     }
 
     /// 从第一行推断列数
-    fn count_columns_first_line(mmap: &Mmap, delimiter: u8) -> Result<usize> {
-        let start = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" {
-            3
-        } else {
-            0
-        };
-
-        // 找到第一行的结束位置 - 使用memchr加速
-        let first_slice = &mmap[start..];
-        let line_end = memchr(b'\n', first_slice)
-            .or_else(|| memchr(b'\r', first_slice))
-            .map(|pos| start + pos)
-            .unwrap_or(mmap.len());
-
-        if line_end == start {
+    fn count_columns_first_line(mmap: &Mmap, format: LineFormat, delimiter: &Delimiter) -> Result<usize> {
+        // 保留空行时第一行可以是空行（一个空字段）
+        let Some((start, line_end)) = first_line(mmap, format) else {
             return Err(CsvError::Format("文件为空或格式错误".to_string()));
-        }
+        };
 
-        let first_line = &mmap[start..line_end];
-        let record = CsvRecord::parse_line(first_line, delimiter);
+        let record = CsvRecord::parse_line_with(&mmap[start..line_end], delimiter);
         Ok(record.fields.len())
     }
 
     /// 读取指定页的数据
     /// 
+    /// 索引尚未构建完成时同样可以读取任意页：超出已索引区域的部分先扫描到目标页并扩展索引，
+    /// 之后再读取该页之前的内容时直接按索引定位；总行数为估算值时不按估算值截断，
+    /// 超出实际行数的页返回空列表
+    /// 
     /// # 参数
     /// - `page`: 页码（从0开始）
     /// - `page_size`: 每页行数
     /// 
     /// # 返回
     /// 该页的记录列表
-    pub fn read_page(&mut self, page: usize, page_size: usize) -> Result<Vec<CsvRecord<'_>>> {
-        // 计算目标行范围
+    pub fn read_page(&self, page: usize, page_size: usize) -> Result<Vec<CsvRecord<'_>>> {
         let start_row = page * page_size;
-        let end_row = (start_row + page_size).min(self.info.total_rows);
+        let end_row = (start_row + page_size).min(self.row_limit());
+        let records = self.scan_rows(start_row, end_row, false)?;
+
+        // 存入缓存（转换为owned版本，用于后续快速访问；不缓存时跳过复制）
+        let mut cache = self.cache();
+        if cache.is_enabled() {
+            let cached_records: Vec<CsvRecord<'static>> = records.iter()
+                .map(|r| r.to_owned())
+                .collect();
+            cache.put(page, cached_records);
+        }
+
+        Ok(records)
+    }
+
+    /// 读取单行数据
+    /// 
+    /// 借助稀疏索引定位，只需从最近的索引点扫描不超过索引粒度的行，不经过页面缓存
+    /// 
+    /// # 参数
+    /// - `row`: 行号（从0开始，不含表头）
+    /// 
+    /// # 返回
+    /// 该行的记录，行号超出总行数时返回None
+    pub fn get_row(&self, row: usize) -> Result<Option<CsvRecord<'_>>> {
+        if row >= self.row_limit() {
+            return Ok(None);
+        }
+        Ok(self.read_rows(row, row + 1)?.into_iter().next())
+    }
+
+    /// 读取指定行范围的数据
+    /// 
+    /// # 参数
+    /// - `start`: 起始行号（从0开始，包含）
+    /// - `end`: 结束行号（不包含），超出总行数时截断到文件末尾
+    /// 
+    /// # 返回
+    /// 该范围内的记录列表，`start` 超出总行数时返回空列表
+    pub fn read_rows(&self, start: usize, end: usize) -> Result<Vec<CsvRecord<'_>>> {
+        self.scan_rows(start, end.min(self.row_limit()), false)
+    }
+
+    /// 读取指定行范围的数据，并记录每个字段在源文件中是否带引号
+    /// 
+    /// 与 [`CsvReader::read_rows`] 相同，但返回的记录带有 `quoted` 信息
+    pub fn read_rows_quoted(&self, start: usize, end: usize) -> Result<Vec<CsvRecord<'_>>> {
+        self.scan_rows(start, end.min(self.row_limit()), true)
+    }
+
+    /// 读取范围的上限：索引完成时为总行数，总行数为估算值时不限制，按实际扫描到的行为准
+    fn row_limit(&self) -> usize {
+        if self.index.is_complete() {
+            self.info.total_rows
+        } else {
+            usize::MAX
+        }
+    }
+
+    /// 定位到 `start_row` 所在或之前最近的索引点，返回 (字节偏移量, 索引点对应的行号)
+    /// 
+    /// 索引未完成且目标超出已索引区域时，扩展索引直到覆盖 `end_row` 之前的行（或到达文件末尾），
+    /// 重复读取同一区域时不需要重新扫描；`start_row` 超出实际行数时返回None
+    fn seek_row(&self, start_row: usize, end_row: usize) -> Result<Option<(u64, usize)>> {
+        if self.index.is_complete() || end_row <= self.index.total_rows() {
+            if start_row >= self.index.total_rows() {
+                return Ok(None);
+            }
+            return self.index.seek_to_row_with_info(start_row).map(Some);
+        }
+
+        let mut extended = self.extended_index.lock().unwrap_or_else(|e| e.into_inner());
+        let index = extended.get_or_insert_with(|| self.index.clone());
+        if index.total_rows() < end_row {
+            index.continue_build_until(&self.mmap, self.line_format, end_row, None, None)?;
+        }
+        if start_row >= index.total_rows() {
+            return Ok(None);
+        }
+        index.seek_to_row_with_info(start_row).map(Some)
+    }
+
+    /// 按顺序遍历所有数据行（不含表头）
+    ///
+    /// 直接逐行扫描文件，不依赖索引也不经过页面缓存；
+    /// 末尾没有换行符的最后一行同样返回，行数与 `info().total_rows`（精确计数时）一致
+    pub fn rows(&self) -> impl Iterator<Item = CsvRecord<'_>> + '_ {
+        let data: &[u8] = &self.mmap;
+        self.line_spans().map(move |(start, end)| self.parse_line(&data[start..end]))
+    }
 
-        if start_row >= self.info.total_rows {
+    /// 按顺序遍历所有数据行在 [`CsvReader::data`] 中的 `(行首, 行尾)` 字节位置（行尾不含行结束符）
+    pub(crate) fn line_spans(&self) -> DataLines<'_> {
+        DataLines::new(&self.mmap, self.data_start_offset as usize, self.line_format)
+    }
+
+    /// 从第 `start_row` 行开始按顺序遍历各数据行的字节位置
+    /// 
+    /// 借助行索引定位到起始行附近，只逐行扫描不超过索引粒度的行；`start_row` 超出实际行数时返回None
+    pub(crate) fn line_spans_from(&self, start_row: usize) -> Result<Option<DataLines<'_>>> {
+        let Some((index_offset, index_row)) = self.seek_row(start_row, start_row.saturating_add(1))? else {
+            return Ok(None);
+        };
+        let data_start = self.data_start_offset as usize;
+        let offset = (index_offset as usize).max(data_start);
+        let current_row = if offset == data_start { 0 } else { index_row };
+        let mut lines = DataLines::new(&self.mmap, offset, self.line_format);
+        for _ in current_row..start_row {
+            lines.next();
+        }
+        Ok(Some(lines))
+    }
+
+    /// 按给定行号的顺序返回各行的字节位置，只扫描一遍到最大的行号为止
+    /// 
+    /// 超出总行数的行号对应 `(0, 0)`
+    pub(crate) fn row_spans(&self, rows: &[usize]) -> Vec<(usize, usize)> {
+        let mut wanted: Vec<(usize, usize)> = rows.iter().enumerate().map(|(i, &row)| (row, i)).collect();
+        wanted.sort_unstable();
+        let mut spans = vec![(0, 0); rows.len()];
+        let mut wanted_iter = wanted.iter().peekable();
+        for (row, span) in self.line_spans().enumerate() {
+            while let Some(&(_, position)) = wanted_iter.next_if(|&&(wanted_row, _)| wanted_row == row) {
+                spans[position] = span;
+            }
+            if wanted_iter.peek().is_none() {
+                break;
+            }
+        }
+        spans
+    }
+
+    /// 按读取器的分隔符和引号规则解析一行
+    pub(crate) fn parse_line<'a>(&self, line: &'a [u8]) -> CsvRecord<'a> {
+        CsvRecord::parse_line_mode(line, &self.delimiter, self.quote_mode)
+    }
+
+    /// 表头各字段在源文件中是否带引号（无表头时返回None）
+    pub(crate) fn header_quoting(&self) -> Option<Vec<bool>> {
+        if !self.has_headers {
+            return None;
+        }
+        let (start, end) = first_line(&self.mmap, self.line_format)?;
+        let line = &self.mmap[start..end];
+        CsvRecord::parse_line_quoted_mode(line, &self.delimiter, self.quote_mode).quoted
+    }
+
+    /// 借助稀疏索引定位并解析 `[start_row, end_row)` 范围内的行
+    /// 
+    /// `record_quoting` 为true时同时记录每个字段是否带引号
+    fn scan_rows(&self, start_row: usize, end_row: usize, record_quoting: bool) -> Result<Vec<CsvRecord<'_>>> {
+        if start_row >= end_row {
             return Ok(Vec::new());
         }
+        let mmap: &[u8] = &self.mmap;
+        let data_start_offset = self.data_start_offset as usize;
+        let delimiter = &self.delimiter;
 
-        // 使用索引快速定位到起始行附近
-        let (index_offset, index_row) = self.index.seek_to_row_with_info(start_row)?;
+        // 使用索引快速定位到起始行附近；快速打开时索引只覆盖文件开头，
+        // 超出已索引区域的行先扩展索引再定位
+        let Some((index_offset, index_row)) = self.seek_row(start_row, end_row)? else {
+            return Ok(Vec::new());
+        };
         let index_offset = index_offset as usize;
+        self.prefetch_rows(index_offset.max(data_start_offset), end_row - index_row.min(start_row));
         
         // 从起始偏移量开始解析行
         let mut records = Vec::new();
         // 确保从数据区域开始（跳过表头）
-        let mut current_offset = index_offset.max(self.data_start_offset as usize);
+        let mut current_offset = index_offset.max(data_start_offset);
         // 设置当前行号为索引点对应的行号，如果从数据开头开始则为0
-        let mut current_row = if index_offset <= self.data_start_offset as usize {
+        let mut current_row = if index_offset <= data_start_offset {
             0
         } else {
             index_row
@@ -597,63 +1239,146 @@ impl CsvReader {
 
         // 如果使用索引定位，需要找到实际的行起始位置
         // 从索引点开始向前找到行首（最多向前查找1000字节）
-        if current_offset > 0 && current_offset > self.data_start_offset as usize {
+        if current_offset > 0 && current_offset > data_start_offset {
             let search_start = current_offset.saturating_sub(1000);
-            for i in (search_start..current_offset).rev() {
-                if self.mmap[i] == b'\n' {
-                    current_offset = i + 1;
-                    break;
-                }
+            if let Some(pos) = self.line_format.rfind_end(mmap, search_start, current_offset) {
+                current_offset = pos + 1;
             }
         }
 
-        // 从当前位置开始扫描到目标行 - 使用memchr加速
         // 由于索引是稀疏的，我们需要从索引点继续扫描到目标行
-        while current_row < start_row && current_offset < self.mmap.len() {
-            let remaining = &self.mmap[current_offset..];
-            if let Some(pos) = memchr(b'\n', remaining) {
-                current_offset += pos + 1;
-                current_row += 1;
-            } else {
-                break; // 文件结束
-            }
-            if current_row >= start_row {
-                break;
-            }
+        let mut lines = DataLines::new(mmap, current_offset, self.line_format);
+        while current_row < start_row && lines.next().is_some() {
+            current_row += 1;
         }
 
-        // 解析行直到达到目标数量或文件结束 - 使用memchr加速
-        while current_row < end_row && current_offset < self.mmap.len() {
-            // 找到当前行的结束位置
-            let remaining = &self.mmap[current_offset..];
-            let line_end = if let Some(pos) = memchr(b'\n', remaining) {
-                current_offset + pos
+        // 解析行直到达到目标数量或文件结束（包括末尾没有换行符的最后一行）
+        if current_row < start_row {
+            return Ok(records);
+        }
+        for (line_start, line_end) in lines.take(end_row - start_row) {
+            let line = &mmap[line_start..line_end];
+            let record = if record_quoting {
+                CsvRecord::parse_line_quoted_mode(line, delimiter, self.quote_mode)
             } else {
-                // 文件结束，但可能还有最后一行
-                if current_offset < self.mmap.len() {
-                    self.mmap.len()
-                } else {
-                    break; // 文件结束
-                }
+                CsvRecord::parse_line_mode(line, delimiter, self.quote_mode)
             };
-
-            // 解析当前行
-            let line = &self.mmap[current_offset..line_end];
-            let record = CsvRecord::parse_line(line, self.delimiter);
             records.push(record);
+        }
 
-            // 移动到下一行
-            current_offset = line_end + 1;
-            current_row += 1;
+        Ok(records)
+    }
+
+    /// 提示内核提前读入从 `offset` 开始约 `rows` 行的数据
+    /// 
+    /// 按平均行长估算范围，最多预读 [`PREFETCH_MAX_BYTES`]；只影响性能，估算偏小时其余部分照常按需读取
+    fn prefetch_rows(&self, offset: usize, rows: usize) {
+        if !self.access_hints.is_enabled() || self.info.total_rows == 0 {
+            return;
         }
+        let data_len = self.mmap.len().saturating_sub(self.data_start_offset as usize);
+        let avg_row_bytes = data_len / self.info.total_rows + 1;
+        let len = rows.saturating_add(1).saturating_mul(avg_row_bytes).min(PREFETCH_MAX_BYTES);
+        advise::will_need(&self.mmap, offset, offset.saturating_add(len));
+    }
 
-        // 存入缓存（转换为owned版本，用于后续快速访问）
-        let cached_records: Vec<CsvRecord<'static>> = records.iter()
-            .map(|r| r.to_owned())
-            .collect();
-        self.cache.put(page, cached_records);
+    /// 设置读取文件的访问模式提示
+    /// 
+    /// 界面中按页随机跳转时可以设为 [`AccessPattern::Random`]，避免每次翻页都预读大量用不到的数据；
+    /// 构建索引、搜索和导出期间临时切换为顺序读取，结束后恢复这里设置的模式。
+    /// 打开时关闭了访问模式提示（[`ReaderOptions::with_access_hints`]）则没有效果
+    pub fn set_access_pattern(&self, pattern: AccessPattern) {
+        self.access_hints.set_pattern(&self.mmap, pattern);
+    }
 
-        Ok(records)
+    /// 开始一次顺序扫描整个文件的操作，返回的守卫释放前按顺序预读
+    pub(crate) fn sequential_scan(&self) -> SequentialScan<'_> {
+        self.access_hints.sequential(&self.mmap)
+    }
+
+    /// 无表头时以 `col1..colN` 作为列名，便于显示和按列名引用
+    /// 
+    /// 只影响 `headers()` 和 `info().headers`，数据行的解析不变
+    pub fn synthesize_headers(&mut self) {
+        if self.info.headers.is_empty() {
+            self.info.headers = (1..=self.info.total_cols)
+                .map(|i| format!("col{}", i))
+                .collect();
+        }
+    }
+
+    /// 重复的列名及其所在的列索引（从0开始），按第一次出现的顺序排列
+    /// 
+    /// 按列名引用时总是解析为第一列，JSON导出时后面的列会覆盖前面的键；空列名不在此列出
+    pub fn duplicate_headers(&self) -> Vec<(String, Vec<usize>)> {
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        let mut columns: Vec<(String, Vec<usize>)> = Vec::new();
+        for (i, name) in self.info.headers.iter().enumerate() {
+            if name.trim().is_empty() {
+                continue;
+            }
+            match positions.get(name.as_str()) {
+                Some(&pos) => columns[pos].1.push(i),
+                None => {
+                    positions.insert(name, columns.len());
+                    columns.push((name.clone(), vec![i]));
+                }
+            }
+        }
+        columns.retain(|(_, cols)| cols.len() > 1);
+        columns
+    }
+
+    /// 列名为空的列索引（从0开始）
+    pub fn empty_headers(&self) -> Vec<usize> {
+        self.info.headers.iter()
+            .enumerate()
+            .filter(|(_, name)| name.trim().is_empty())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// 获取文件内容（已解压、转码为UTF-8）
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// 文件内容的分行方式
+    pub(crate) fn line_format(&self) -> LineFormat {
+        self.line_format
+    }
+
+    /// 获取指定列的列索引（需以 `with_column_index` 打开）
+    /// 
+    /// 列索引按宽松模式解析字段，严格引号模式下不使用
+    pub fn column_index(&self, column: usize) -> Option<&ColumnIndex> {
+        self.index.column_index().filter(|column_index| {
+            column_index.column() == column
+                && self.delimiter.as_byte() == Some(column_index.delimiter())
+                && self.quote_mode == QuoteMode::Flexible
+        })
+    }
+
+    /// 获取索引文件路径（索引尚未保存或未启用持久化时返回None）
+    pub fn index_path(&self) -> Option<PathBuf> {
+        self.persist_index
+            .then(|| RowIndex::find_index_file(&self.info.file_path, &self.index_location))
+            .flatten()
+    }
+
+    /// 获取索引文件存放位置
+    pub fn index_location(&self) -> &IndexLocation {
+        &self.index_location
+    }
+
+    /// 获取行索引
+    pub fn index(&self) -> &RowIndex {
+        &self.index
+    }
+
+    /// 获取列数校验报告（仅在启用校验打开时可用）
+    pub fn validation_report(&self) -> Option<&ValidationReport> {
+        self.validation_report.as_ref()
     }
 
     /// 获取文件信息
@@ -661,105 +1386,507 @@ impl CsvReader {
         &self.info
     }
 
-    /// 获取总页数
+    /// 获取总页数（没有数据行时为0）
     pub fn total_pages(&self, page_size: usize) -> usize {
-        (self.info.total_rows + page_size - 1) / page_size
+        self.info.total_rows.div_ceil(page_size.max(1))
     }
 
     /// 清空缓存
-    pub fn clear_cache(&mut self) {
-        self.cache.clear();
+    pub fn clear_cache(&self) {
+        self.cache().clear();
+    }
+
+    /// 设置页面缓存容量（页数，0表示不缓存），已缓存的页面会被丢弃
+    pub fn with_cache_capacity(mut self, pages: usize) -> Self {
+        self.cache = Mutex::new(PageCache::new(pages));
+        self
+    }
+
+    /// 当前缓存的页面数
+    pub fn cache_len(&self) -> usize {
+        self.cache().len()
+    }
+
+    /// 页面缓存容量（页数）
+    pub fn cache_capacity(&self) -> usize {
+        self.cache().capacity()
+    }
+
+    /// 页面缓存当前占用的内存（估算，字节）
+    pub fn cache_memory_usage(&self) -> usize {
+        self.cache().memory_usage()
+    }
+
+    /// 页面缓存的内存上限（字节）
+    pub fn cache_byte_budget(&self) -> usize {
+        self.cache().byte_budget()
+    }
+
+    /// 锁定页面缓存（缓存只是加速手段，其他线程panic导致的锁中毒不影响继续使用）
+    fn cache(&self) -> MutexGuard<'_, PageCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
     }
 
     /// 搜索CSV文件
     /// 
+    /// 只搜索一列的纯文本且该列已有列索引时，只读取该列的字段，
+    /// 并跳过布隆过滤器判定不含搜索词的行块。
+    /// 设置了 `max_results` 时找到足够的结果即停止扫描
+    /// 
+    /// 结果中保存了每个匹配行的副本，内存占用与匹配数成正比，适合结果不多的搜索；
+    /// 匹配可能很多时用 [`search_to_file`](Self::search_to_file) 直接写出，
+    /// 或用 [`for_each_match`](Self::for_each_match) 逐个处理
+    /// 
     /// # 参数
     /// - `options`: 搜索选项
     /// 
     /// # 返回
-    /// 搜索结果列表
+    /// 搜索结果列表（被取消时为已找到的部分结果）
     pub fn search(&self, options: &crate::csv::search::SearchOptions) -> Result<Vec<crate::csv::search::SearchResult>> {
-        use crate::csv::search::{Searcher, SearchResult};
+        Ok(self.search_with_status(options)?.value)
+    }
+
+    /// 搜索并把匹配的行直接写入文件，不在内存中保留结果
+    /// 
+    /// 输出格式、导出列和行范围取自 `export_options`（行范围按匹配的顺序计算），
+    /// 其中的搜索筛选被 `options` 替换。JSON、JSON Lines、CSV和TSV边扫描边写出，
+    /// 内存占用与匹配数无关
+    pub fn search_to_file<P: AsRef<Path>>(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+        output: P,
+        export_options: crate::csv::export::ExportOptions,
+    ) -> Result<crate::csv::export::ExportStats> {
+        use crate::csv::export::Exporter;
+        
+        Exporter::new(self, export_options.with_search_filter(options.clone())).export_to_file(output)
+    }
+
+    /// 搜索CSV文件，并返回是否因取消而提前结束
+    /// 
+    /// 设置了进度回调时每扫描几MB汇报一次进度；
+    /// 取消标志被置位后停止扫描，返回已找到的结果
+    pub fn search_with_status(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+    ) -> Result<crate::csv::search::SearchOutcome<Vec<crate::csv::search::SearchResult>>> {
+        use crate::csv::search::{SearchOutcome, SearchResult};
         
-        let searcher = Searcher::new(options.clone());
         let mut results = Vec::new();
-        let max_results = options.max_results.unwrap_or(usize::MAX);
+        let cancelled = self.for_each_match(options, |row_number, matches, record| {
+            results.push(SearchResult {
+                row_number,
+                matches,
+                record: record.to_owned(),
+            });
+            Ok(true)
+        })?;
+        Ok(SearchOutcome { value: results, cancelled })
+    }
+
+    /// 按顺序逐个访问匹配的行，不保留结果
+    /// 
+    /// `visit` 的参数为行号（从0开始，不含表头）、匹配信息和借用文件数据的记录，
+    /// 返回false时停止扫描，返回错误时停止扫描并返回该错误。
+    /// 与 [`search`](Self::search) 使用相同的加速路径，最多访问 `max_results` 个匹配
+    /// 
+    /// # 返回
+    /// 是否因取消而提前结束
+    pub fn for_each_match<F>(&self, options: &crate::csv::search::SearchOptions, mut visit: F) -> Result<bool>
+    where
+        F: FnMut(usize, Vec<crate::csv::search::MatchInfo>, CsvRecord<'_>) -> Result<bool>,
+    {
+        use crate::csv::search::{MatchInfo, ScanMonitor, Searcher};
         
-        // 从数据起始位置开始扫描
-        let mut current_offset = self.data_start_offset as usize;
-        let mut row_number = 0;
+        let max_results = options.max_results.unwrap_or(usize::MAX);
+        if max_results == 0 {
+            return Ok(false);
+        }
+        let mut visited = 0;
+        let mut error = None;
+        // 访问一个匹配，返回是否继续扫描
+        let mut accept = |row_number: usize, matches: Vec<MatchInfo>, record: CsvRecord<'_>| -> bool {
+            visited += 1;
+            match visit(row_number, matches, record) {
+                Ok(go_on) => go_on && visited < max_results,
+                Err(e) => {
+                    error = Some(e);
+                    false
+                }
+            }
+        };
         
-        while current_offset < self.mmap.len() && results.len() < max_results {
-            // 找到当前行的结束位置 - 使用memchr加速
-            let remaining = &self.mmap[current_offset..];
-            let line_end = if let Some(pos) = memchr(b'\n', remaining) {
-                current_offset + pos
+        let cancelled = if let Some(cancelled) = self.search_column_index(options, &mut accept) {
+            cancelled
+        } else {
+            let _scan = self.sequential_scan();
+            let searcher = Searcher::new(options.clone());
+            
+            // 从数据起始位置开始扫描
+            let data_start = self.data_start_offset as usize;
+            let mut monitor = ScanMonitor::new(options, self.mmap.len().saturating_sub(data_start) as u64);
+            let mut cancelled = false;
+            
+            if let Some(needle) = Self::prefilter_needle(options) {
+                // 纯文本搜索先在原始字节中查找，只解析包含搜索词的行
+                cancelled = self.scan_prefiltered(needle, &mut monitor, |row_number, line| {
+                    let record = CsvRecord::parse_line_mode(line, &self.delimiter, self.quote_mode);
+                    match searcher.matches_record(&record) {
+                        Some(matches) => accept(row_number, matches, record),
+                        None => true,
+                    }
+                });
             } else {
-                // 文件结束，但可能还有最后一行
-                if current_offset < self.mmap.len() {
-                    self.mmap.len()
-                } else {
-                    break;
+                for (row_number, (line_start, line_end)) in self.line_spans().enumerate() {
+                    if monitor.tick((line_start - data_start) as u64) {
+                        cancelled = true;
+                        break;
+                    }
+                    
+                    // 解析当前行并检查是否匹配
+                    let line = &self.mmap[line_start..line_end];
+                    let record = CsvRecord::parse_line_mode(line, &self.delimiter, self.quote_mode);
+                    if let Some(matches) = searcher.matches_record(&record) {
+                        if !accept(row_number, matches, record) {
+                            break;
+                        }
+                    }
                 }
-            };
+            }
             
-            // 解析当前行
-            let line = &self.mmap[current_offset..line_end];
-            let record = CsvRecord::parse_line(line, self.delimiter);
+            if !cancelled {
+                monitor.finish();
+            }
+            cancelled
+        };
+        
+        match error {
+            Some(e) => Err(e),
+            None => Ok(cancelled),
+        }
+    }
+
+    /// 分页搜索：跳过前 `offset` 个匹配，返回之后最多 `limit` 个
+    /// 
+    /// 找够一页后即停止扫描，不受 `max_results` 限制
+    /// 
+    /// # 返回
+    /// 本页结果，以及匹配总数（本页扫描到文件末尾时才有）
+    pub fn search_page(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<crate::csv::search::SearchResult>, Option<usize>)> {
+        let page = self.search_page_from(options, None, offset, limit)?;
+        Ok((page.results, page.total))
+    }
+
+    /// 从游标处继续分页搜索：跳过游标之后的 `offset` 个匹配，返回之后最多 `limit` 个
+    /// 
+    /// `cursor` 为None时从文件开头扫描。返回的游标用于获取下一页，
+    /// 不必重新扫描之前的内容；被取消时游标停在取消的位置
+    pub fn search_page_from(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+        cursor: Option<&crate::csv::search::SearchCursor>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<crate::csv::search::SearchPage> {
+        let start = match cursor {
+            Some(cursor) => {
+                if cursor.byte_offset < self.data_start_offset || cursor.byte_offset > self.mmap.len() as u64 {
+                    return Err(CsvError::Format(format!("无效的搜索游标: {}", cursor.byte_offset)));
+                }
+                *cursor
+            }
+            None => crate::csv::search::SearchCursor {
+                byte_offset: self.data_start_offset,
+                row_number: 0,
+                matched: 0,
+            },
+        };
+        Ok(self.scan_search_page(options, start, offset, limit))
+    }
+
+    /// 从第 `start_row` 行（从0开始，不含表头）开始向后查找第一个匹配
+    /// 
+    /// 借助行索引定位到起始行，不扫描之前的内容，找到第一个匹配即停止，
+    /// 用于逐个查找下一个匹配。`start_row` 超出总行数时返回None
+    pub fn search_from(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+        start_row: usize,
+    ) -> Result<Option<crate::csv::search::SearchResult>> {
+        let Some(lines) = self.line_spans_from(start_row)? else {
+            return Ok(None);
+        };
+        
+        let start = crate::csv::search::SearchCursor {
+            byte_offset: lines.position() as u64,
+            row_number: start_row,
+            matched: 0,
+        };
+        Ok(self.scan_search_page(options, start, 0, 1).results.into_iter().next())
+    }
+
+    /// 从 `start` 开始逐行扫描，跳过 `skip` 个匹配后收集最多 `limit` 个
+    fn scan_search_page(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+        start: crate::csv::search::SearchCursor,
+        skip: usize,
+        limit: usize,
+    ) -> crate::csv::search::SearchPage {
+        use crate::csv::search::{ScanMonitor, SearchCursor, SearchPage, Searcher, SearchResult};
+        
+        let _scan = self.sequential_scan();
+        let searcher = Searcher::new(options.clone());
+        let data_start = self.data_start_offset as usize;
+        let mut monitor = ScanMonitor::new(options, self.mmap.len().saturating_sub(data_start) as u64);
+        let mut lines = DataLines::new(&self.mmap, start.byte_offset as usize, self.line_format);
+        let mut row_number = start.row_number;
+        let mut matched = start.matched;
+        let mut skipped = 0;
+        let mut results = Vec::new();
+        
+        while lines.position() < self.mmap.len() {
+            let offset = lines.position();
+            if results.len() >= limit || monitor.tick((offset - data_start) as u64) {
+                let cursor = SearchCursor { byte_offset: offset as u64, row_number, matched };
+                return SearchPage { results, total: None, cursor: Some(cursor) };
+            }
             
-            // 检查是否匹配
+            // 剩下的都是跳过的空行时结束
+            let Some((line_start, line_end)) = lines.next() else {
+                break;
+            };
+            let record = CsvRecord::parse_line_mode(&self.mmap[line_start..line_end], &self.delimiter, self.quote_mode);
             if let Some(matches) = searcher.matches_record(&record) {
-                results.push(SearchResult {
-                    row_number,
-                    matches,
-                    record: record.to_owned(),
-                });
+                matched += 1;
+                if skipped < skip {
+                    skipped += 1;
+                } else {
+                    results.push(SearchResult {
+                        row_number,
+                        matches,
+                        record: record.to_owned(),
+                    });
+                }
+            }
+            
+            row_number += 1;
+        }
+        
+        monitor.finish();
+        SearchPage { results, total: Some(matched), cursor: None }
+    }
+
+    /// 可以在原始字节中预筛选的搜索词
+    /// 
+    /// 只用于区分大小写、搜索所有列且非反向匹配的纯文本搜索；
+    /// 搜索词包含换行符或回车符时不预筛选
+    fn prefilter_needle(options: &crate::csv::search::SearchOptions) -> Option<&[u8]> {
+        use crate::csv::search::SearchPattern;
+        
+        match &options.pattern {
+            SearchPattern::Text(text)
+                if options.case_sensitive
+                    && options.columns.is_none()
+                    && !options.invert_match
+                    && !text.is_empty()
+                    && !text.contains(['\n', '\r']) =>
+            {
+                Some(text.as_bytes())
+            }
+            _ => None,
+        }
+    }
+
+    /// 在原始字节中查找搜索词，只把包含搜索词的行交给 `visit`
+    /// 
+    /// `visit` 的参数为行号（从0开始，不含表头）和该行的字节，返回false时停止扫描；
+    /// 搜索词可能跨越字段边界或引号，是否真正匹配由 `visit` 解析该行后判断
+    /// 
+    /// # 返回
+    /// 是否因取消而提前结束
+    fn scan_prefiltered(
+        &self,
+        needle: &[u8],
+        monitor: &mut crate::csv::search::ScanMonitor,
+        mut visit: impl FnMut(usize, &[u8]) -> bool,
+    ) -> bool {
+        let finder = memmem::Finder::new(needle);
+        let data: &[u8] = &self.mmap;
+        let format = self.line_format;
+        let data_start = self.data_start_offset as usize;
+        // pos 总是位于行首，row_number 为该行的行号
+        let mut pos = data_start;
+        let mut row_number = 0;
+        
+        while pos < data.len() {
+            if monitor.tick((pos - data_start) as u64) {
+                return true;
+            }
+            
+            // 每次只在约 PREFILTER_WINDOW_BYTES 的完整行内查找，以便及时汇报进度和响应取消
+            let window_end = (pos + PREFILTER_WINDOW_BYTES).min(data.len());
+            let window_end = format.find_end(data, window_end).map_or(data.len(), |p| p + 1);
+            let Some(hit) = finder.find(&data[pos..window_end]) else {
+                row_number += count_lines(&data[..window_end], pos, format);
+                pos = window_end;
+                continue;
+            };
+            
+            let hit = pos + hit;
+            let line_start = format.rfind_end(data, pos, hit).map_or(pos, |p| p + 1);
+            row_number += count_lines(&data[..line_start], pos, format);
+            let line_end = format.find_end(data, hit).unwrap_or(data.len());
+            if !visit(row_number, &data[line_start..line_end]) {
+                return false;
             }
             
-            // 移动到下一行
-            current_offset = line_end + 1;
+            pos = line_end + 1;
             row_number += 1;
         }
         
-        Ok(results)
+        false
+    }
+
+    /// 借助列索引搜索单列纯文本，把匹配交给 `accept`（返回false时停止），不满足条件时返回None
+    /// 
+    /// # 返回
+    /// 是否因取消而提前结束
+    fn search_column_index<F>(&self, options: &crate::csv::search::SearchOptions, accept: &mut F) -> Option<bool>
+    where
+        F: FnMut(usize, Vec<crate::csv::search::MatchInfo>, CsvRecord<'_>) -> bool,
+    {
+        use crate::csv::search::{MatchInfo, ScanMonitor, SearchPattern};
+        
+        let (SearchPattern::Text(text), Some(&[column])) = (&options.pattern, options.columns.as_deref()) else {
+            return None;
+        };
+        // 反向匹配需要检查每一行；空文本会匹配缺少该列的行
+        if options.invert_match || text.is_empty() {
+            return None;
+        }
+        let column_index = self.column_index(column)?;
+        
+        // 只读取该列，按行号比例估算扫描进度
+        let total = self.mmap.len().saturating_sub(self.data_start_offset as usize) as u64;
+        let rows = column_index.len().max(1) as u64;
+        let mut monitor = ScanMonitor::new(options, total);
+        for row in column_index.candidate_rows(text) {
+            if monitor.tick(total * row as u64 / rows) {
+                return Some(true);
+            }
+            let Some(field) = column_index.field(&self.mmap, row) else {
+                continue;
+            };
+            let positions = options.pattern.find_matches_with(&field, options.case_sensitive, options.whole_word);
+            if positions.is_empty() {
+                continue;
+            }
+            if let Some(record) = column_index.record(&self.mmap, self.line_format, row) {
+                let matches = vec![MatchInfo { column, positions, patterns: Vec::new() }];
+                if !accept(row, matches, record) {
+                    break;
+                }
+            }
+        }
+        
+        monitor.finish();
+        Some(false)
     }
 
     /// 统计匹配数量（不返回详细结果，更高效）
+    /// 
+    /// 设置了 `max_results` 时数到该数量即停止扫描，此时返回值等于 `max_results`，
+    /// 表示“至少有这么多个匹配”，而不是准确的总数
     pub fn count_matches(&self, options: &crate::csv::search::SearchOptions) -> Result<usize> {
-        use crate::csv::search::Searcher;
+        Ok(self.count_matches_with_status(options)?.value)
+    }
+
+    /// 是否至少有 `n` 个匹配，数到 `n` 个即停止扫描
+    pub fn has_at_least_matches(&self, options: &crate::csv::search::SearchOptions, n: usize) -> Result<bool> {
+        let options = options.clone().with_max_results(n);
+        Ok(self.count_matches(&options)? >= n)
+    }
+
+    /// 统计匹配数量，并返回是否因取消而提前结束（被取消时为已扫描部分的数量）
+    /// 
+    /// 与 [`count_matches`](Self::count_matches) 一样，数量不超过 `max_results`
+    pub fn count_matches_with_status(
+        &self,
+        options: &crate::csv::search::SearchOptions,
+    ) -> Result<crate::csv::search::SearchOutcome<usize>> {
+        use crate::csv::search::{ScanMonitor, SearchOutcome, Searcher};
         
         let searcher = Searcher::new(options.clone());
         let mut count = 0;
+        let max_results = options.max_results.unwrap_or(usize::MAX);
+        if max_results == 0 {
+            return Ok(SearchOutcome { value: 0, cancelled: false });
+        }
+        let _scan = self.sequential_scan();
         
         // 从数据起始位置开始扫描
-        let mut current_offset = self.data_start_offset as usize;
+        let data_start = self.data_start_offset as usize;
+        let mut monitor = ScanMonitor::new(options, self.mmap.len().saturating_sub(data_start) as u64);
+        let mut cancelled = false;
         
-        while current_offset < self.mmap.len() {
-            // 找到当前行的结束位置 - 使用memchr加速
-            let remaining = &self.mmap[current_offset..];
-            let line_end = if let Some(pos) = memchr(b'\n', remaining) {
-                current_offset + pos
-            } else {
-                // 文件结束，但可能还有最后一行
-                if current_offset < self.mmap.len() {
-                    self.mmap.len()
-                } else {
-                    break;
+        // 纯文本搜索先在原始字节中查找，只解析包含搜索词的行
+        if let Some(needle) = Self::prefilter_needle(options) {
+            let cancelled = self.scan_prefiltered(needle, &mut monitor, |_, line| {
+                if searcher.is_match(&CsvRecord::parse_line_mode(line, &self.delimiter, self.quote_mode)) {
+                    count += 1;
                 }
-            };
+                count < max_results
+            });
+            if !cancelled {
+                monitor.finish();
+            }
+            return Ok(SearchOutcome { value: count, cancelled });
+        }
+        
+        for (line_start, line_end) in self.line_spans() {
+            if count >= max_results {
+                break;
+            }
+            if monitor.tick((line_start - data_start) as u64) {
+                cancelled = true;
+                break;
+            }
             
             // 解析并检查匹配
-            let line = &self.mmap[current_offset..line_end];
-            let record = CsvRecord::parse_line(line, self.delimiter);
-            
-            if searcher.is_match(&record) {
+            let line = &self.mmap[line_start..line_end];
+            if searcher.is_match(&CsvRecord::parse_line_mode(line, &self.delimiter, self.quote_mode)) {
                 count += 1;
             }
-            
-            current_offset = line_end + 1;
         }
         
-        Ok(count)
+        if !cancelled {
+            monitor.finish();
+        }
+        Ok(SearchOutcome { value: count, cancelled })
+    }
+
+    /// 分散采样约 `sample_rows` 行，推断每列的类型和空值比例
+    /// 
+    /// 借助行索引从文件中均匀分布的位置采样，而不只是文件开头。
+    /// 结果缓存在读取器中，采样行数和总行数不变时直接返回缓存
+    pub fn infer_column_types(&self, sample_rows: usize) -> Result<Vec<InferredType>> {
+        let total_rows = self.info.total_rows;
+        let mut cached = self.column_types.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((rows, total, types)) = cached.as_ref() {
+            if *rows == sample_rows && *total == total_rows {
+                return Ok(types.clone());
+            }
+        }
+        
+        let types = crate::csv::schema::infer_column_types(self, sample_rows)?;
+        *cached = Some((sample_rows, total_rows, types.clone()));
+        Ok(types)
     }
 
     /// 获取表头
@@ -768,37 +1895,85 @@ impl CsvReader {
     }
 
     /// 获取分隔符
-    pub fn delimiter(&self) -> u8 {
-        self.delimiter
+    pub fn delimiter(&self) -> &Delimiter {
+        &self.delimiter
+    }
+
+    /// 第一行是否作为表头
+    pub fn has_headers(&self) -> bool {
+        self.has_headers
+    }
+
+    /// 数据区的起始字节偏移量（跳过BOM和表头；表头行没有行结束符时为文件末尾）
+    pub fn data_start(&self) -> u64 {
+        self.data_start_offset
+    }
+
+    /// 获取打开时指定的索引粒度
+    pub fn index_granularity(&self) -> usize {
+        self.index_granularity
     }
 
     /// 加载或构建索引
     /// 
-    /// 优先尝试加载已保存的索引，如果索引不存在或无效，则构建新索引并保存
+    /// 优先尝试加载已保存的索引，如果索引不存在或无效，则构建新索引并保存；
+    /// 需要列索引而缓存的索引中没有时，只补建列索引
     fn load_or_build_index(
         csv_path: &Path,
         mmap: &Mmap,
-        has_headers: bool,
-        index_granularity: usize,
-        file_size: u64,
-        file_mtime: SystemTime,
-    ) -> Result<(RowIndex, usize)> {
-        let index_path = RowIndex::index_file_path(csv_path);
+        format: LineFormat,
+        options: &ReaderOptions,
+        file_metadata: &std::fs::Metadata,
+        validation: Option<&ColumnValidation>,
+        mut progress: Option<BuildProgress<'_>>,
+    ) -> Result<(RowIndex, usize, Option<ValidationReport>)> {
+        let file_size = file_metadata.len();
+        let file_mtime = file_metadata.modified().unwrap_or_else(|_| SystemTime::now());
+        let has_headers = options.has_headers;
+        let index_granularity = options.index_granularity;
+        let index_mode = options.index_mode;
+        let index_path = options.persists_index()
+            .then(|| RowIndex::find_index_file(csv_path, &options.index_location))
+            .flatten();
         
-        // 尝试加载索引
-        if index_path.exists() {
+        // 尝试加载索引（需要校验列数时必须重新扫描）
+        if let (Some(index_path), None) = (index_path, validation) {
             match RowIndex::load_from_file(&index_path) {
-                Ok((index, metadata)) => {
+                // 验证表头设置、索引粒度和模式是否匹配，以及行号是否按相同的方式计算
+                Ok((mut index, mut metadata))
+                    if metadata.matches(has_headers, index_granularity, index_mode)
+                        && Self::counts_rows_alike(&metadata, mmap, format) =>
+                {
                     // 验证索引有效性
-                    if RowIndex::is_index_valid(csv_path, &metadata) {
-                        // 验证索引粒度是否匹配
-                        if metadata.granularity == index_granularity {
-                            let total_rows = index.total_rows();
-                            return Ok((index, total_rows));
+                    if Self::is_loaded_index_valid(csv_path, &metadata, &index, mmap, options) {
+                        // 由旧版本升级的索引确认可用后记录下来，之后打开时不再检查空行
+                        let upgraded = !std::mem::replace(&mut metadata.skips_empty_lines, true);
+                        if Self::attach_column_index(&mut index, mmap, format, options) || upgraded {
+                            if let Err(e) = index.save_to_location(csv_path, &metadata, &options.index_location) {
+                                eprintln!("警告: 无法保存索引文件: {}", e);
+                            }
                         }
+                        Self::report_build_complete(progress, mmap.len());
+                        let total_rows = index.total_rows();
+                        return Ok((index, total_rows, None));
+                    }
+
+                    // 文件只在末尾追加了数据时，只扫描新增部分（完整校验时总是重建）
+                    if !options.verify_index && index.refresh(csv_path, mmap, format, &metadata).unwrap_or(false) {
+                        Self::attach_column_index(&mut index, mmap, format, options);
+                        let metadata = Self::index_metadata(csv_path, options, file_size, file_mtime);
+                        if let Err(e) = index.save_to_location(csv_path, &metadata, &options.index_location) {
+                            eprintln!("警告: 无法保存索引文件: {}", e);
+                        }
+                        Self::report_build_complete(progress, mmap.len());
+                        let total_rows = index.total_rows();
+                        return Ok((index, total_rows, None));
                     }
                     // 索引无效，继续构建新索引
                 }
+                Ok(_) => {
+                    // 索引设置不匹配，继续构建新索引
+                }
                 Err(_) => {
                     // 加载失败，继续构建新索引
                 }
@@ -806,25 +1981,174 @@ impl CsvReader {
         }
 
         // 构建新索引（这里不传递进度回调，因为调用者会处理）
-        let index = RowIndex::build(mmap, has_headers, index_granularity)?;
+        let build_granularity = index_mode.build_granularity(index_granularity);
+        let (mut index, report) = match (validation, progress.take()) {
+            (Some(validation), progress) => {
+                let (index, report) = RowIndex::build_validated(mmap, has_headers, format, build_granularity, validation)?;
+                Self::report_build_complete(progress, mmap.len());
+                (index.into_mode(index_mode), Some(report))
+            }
+            (None, Some(progress)) => {
+                let index = RowIndex::build_auto(mmap, has_headers, format, build_granularity, Some(progress))?;
+                (index.into_mode(index_mode), None)
+            }
+            (None, None) => (RowIndex::build(mmap, has_headers, format, build_granularity)?.into_mode(index_mode), None),
+        };
+        Self::attach_column_index(&mut index, mmap, format, options);
         let total_rows = index.total_rows();
 
+        if !options.persists_index() {
+            return Ok((index, total_rows, report));
+        }
+
         // 保存索引
+        let metadata = Self::index_metadata(csv_path, options, file_size, file_mtime);
+
+        if let Err(e) = index.save_to_location(csv_path, &metadata, &options.index_location) {
+            // 索引保存失败不影响使用，只记录警告
+            eprintln!("警告: 无法保存索引文件: {}", e);
+        }
+
+        Ok((index, total_rows, report))
+    }
+
+    /// 不经扫描得到索引（使用缓存或校验构建）时汇报100%进度
+    fn report_build_complete(progress: Option<BuildProgress<'_>>, total_bytes: usize) {
+        if let Some(progress) = progress {
+            progress(100.0, total_bytes, total_bytes);
+        }
+    }
+
+    /// 已加载的索引与按 `format` 分行得到的行号是否一致
+    /// 
+    /// 由旧版本升级的索引构建时空行计入行数，只有文件中没有空行时才能继续使用
+    fn counts_rows_alike(metadata: &IndexMetadata, mmap: &[u8], format: LineFormat) -> bool {
+        metadata.skips_empty_lines || !has_empty_lines(mmap, format)
+    }
+
+    /// 按选项验证已加载的索引是否有效
+    fn is_loaded_index_valid(
+        csv_path: &Path,
+        metadata: &IndexMetadata,
+        index: &RowIndex,
+        mmap: &[u8],
+        options: &ReaderOptions,
+    ) -> bool {
+        // 完整索引覆盖的字节数与映射的数据不一致时不能使用
+        let covers_data = index.indexed_bytes() <= mmap.len() as u64
+            && (!index.is_complete() || index.indexed_bytes() == mmap.len() as u64);
+        covers_data
+            && RowIndex::is_index_valid(csv_path, metadata)
+            && (!options.verify_index || RowIndex::verify_full_checksum(csv_path, metadata))
+    }
+
+    /// 按选项生成要保存的索引元数据
+    /// 
+    /// 只有开启 `verify_index` 时才计算整个文件的哈希
+    fn index_metadata(csv_path: &Path, options: &ReaderOptions, file_size: u64, file_mtime: SystemTime) -> IndexMetadata {
         let metadata = IndexMetadata::new(
             csv_path.to_path_buf(),
             file_size,
             file_mtime,
-            index_granularity,
-        );
-        
-        // 克隆index用于保存，因为save_to_file需要&self，但我们需要返回原始index
-        let index_clone = index.clone();
-        if let Err(e) = index_clone.save_to_file(csv_path, &metadata) {
-            // 索引保存失败不影响使用，只记录警告
-            eprintln!("警告: 无法保存索引文件: {}", e);
+            options.index_granularity,
+        )
+        .with_headers(options.has_headers)
+        .with_mode(options.index_mode)
+        .with_checksum(csv_path);
+        if options.verify_index {
+            metadata.with_full_checksum(csv_path)
+        } else {
+            metadata
+        }
+    }
+
+    /// 按选项为索引构建列索引（多字节分隔符不支持列索引）
+    /// 
+    /// # 返回
+    /// 是否新建了列索引（已有匹配的列索引时返回false）
+    fn attach_column_index(index: &mut RowIndex, mmap: &Mmap, format: LineFormat, options: &ReaderOptions) -> bool {
+        let (Some(column), Some(delimiter)) = (options.column_index, options.delimiter.as_byte()) else {
+            return false;
+        };
+        let up_to_date = index.column_index().is_some_and(|column_index| {
+            column_index.column() == column && column_index.delimiter() == delimiter
+        });
+        if up_to_date {
+            return false;
         }
 
-        Ok((index, total_rows))
+        index.set_column_index(Some(ColumnIndex::build(mmap, options.has_headers, format, column, delimiter)));
+        true
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(line: &str) -> Vec<String> {
+        CsvRecord::parse_line(line.as_bytes(), b',')
+            .fields
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_line_field_count() {
+        // 字段数总是引号外的分隔符数 + 1
+        assert_eq!(fields(""), vec![""]);
+        assert_eq!(fields("\"\""), vec![""]);
+        assert_eq!(fields(","), vec!["", ""]);
+        assert_eq!(fields("\",\""), vec![","]);
+        assert_eq!(fields("a"), vec!["a"]);
+        assert_eq!(fields("a,"), vec!["a", ""]);
+        assert_eq!(fields(",a"), vec!["", "a"]);
+        assert_eq!(fields("a,,c"), vec!["a", "", "c"]);
+        assert_eq!(fields(",,"), vec!["", "", ""]);
+        assert_eq!(fields("a,\"\",b"), vec!["a", "", "b"]);
+        assert_eq!(fields("\"a\","), vec!["a", ""]);
+        assert_eq!(fields("\"a,b\",\"\""), vec!["a,b", ""]);
+    }
+
+    #[test]
+    fn test_parse_line_trailing_cr() {
+        assert_eq!(fields("\r"), vec![""]);
+        assert_eq!(fields("a,b\r"), vec!["a", "b"]);
+        assert_eq!(fields("a,\r"), vec!["a", ""]);
+        assert_eq!(fields("\"a\",\r"), vec!["a", ""]);
+        // 只去除一个行尾的 \r
+        assert_eq!(fields("a\r\r"), vec!["a\r"]);
+    }
+
+    #[test]
+    fn test_parse_line_quoted_matches_fields() {
+        let delimiter = Delimiter::Byte(b',');
+        for line in ["", ",", "a,", ",a", "a,\"\",b", "\"a\",\r", "\"x,y\",z"] {
+            let record = CsvRecord::parse_line_quoted(line.as_bytes(), &delimiter);
+            assert_eq!(record.quoted.as_ref().unwrap().len(), record.fields.len(), "{:?}", line);
+        }
+        let record = CsvRecord::parse_line_quoted(b"a,\"\",\"b\"", &delimiter);
+        assert_eq!(record.quoted, Some(vec![false, true, true]));
+    }
+
+    #[test]
+    fn test_quote_mode_whitespace() {
+        let delimiter = Delimiter::Byte(b',');
+        let line = b" \"a,b\" , c ";
+
+        // 宽松模式忽略引号外的空白，未加引号的字段保留空白
+        let record = CsvRecord::parse_line_quoted_mode(line, &delimiter, QuoteMode::Flexible);
+        assert_eq!(record.fields, vec!["a,b", " c "]);
+        assert_eq!(record.quoted, Some(vec![true, false]));
+
+        // 严格模式下引号不紧贴分隔符，字段原样保留
+        let record = CsvRecord::parse_line_quoted_mode(line, &delimiter, QuoteMode::Strict);
+        assert_eq!(record.fields, vec![" \"a,b\" ", " c "]);
+        assert_eq!(record.quoted, Some(vec![false, false]));
+
+        // 制表符同样视为空白
+        let record = CsvRecord::parse_line_mode(b"\t\"x\"\t,\"y\"", &delimiter, QuoteMode::Flexible);
+        assert_eq!(record.fields, vec!["x", "y"]);
+    }
+}
@@ -0,0 +1,177 @@
+//! 随机抽样与打乱
+//!
+//! 抽样用蓄水池算法，只扫描一遍文件，内存占用与抽取的行数成正比，适合任意大小的文件；
+//! 打乱需要记下每行的位置，适合较小的文件。两者都按行的字节位置从内存映射中取出原始行写出
+
+use crate::csv::{CsvReader, SaveStats, WriteOptions};
+use crate::csv::writer::write_spans_to_file;
+use crate::error::Result;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+
+/// 抽样选项
+#[derive(Debug, Clone)]
+pub struct SampleOptions {
+    /// 随机种子，相同种子对同一文件得到相同的结果
+    pub seed: u64,
+    /// 按原文件中的顺序输出抽到的行（默认随机顺序）
+    pub keep_order: bool,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self {
+            seed: random_seed(),
+            keep_order: false,
+        }
+    }
+}
+
+impl SampleOptions {
+    /// 创建默认选项（随机种子）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置随机种子
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// 设置是否保持原有顺序
+    pub fn with_keep_order(mut self, keep_order: bool) -> Self {
+        self.keep_order = keep_order;
+        self
+    }
+}
+
+/// 生成一个随机种子（每次调用不同）
+pub fn random_seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64),
+    );
+    hasher.finish()
+}
+
+/// 伪随机数生成器（SplitMix64）
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// `[0, n)` 范围内的随机数
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// 随机打乱（Fisher-Yates）
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// 随机抽取 `n` 行，返回 `(行号, 字节位置)`，顺序由选项决定
+fn reservoir(reader: &CsvReader, n: usize, options: &SampleOptions) -> Vec<(usize, (usize, usize))> {
+    let _scan = reader.sequential_scan();
+    let mut rng = Rng::new(options.seed);
+    let mut selected = Vec::with_capacity(n.min(reader.info().total_rows));
+    for (row, span) in reader.line_spans().enumerate() {
+        if row < n {
+            selected.push((row, span));
+        } else {
+            let slot = rng.below(row + 1);
+            if slot < n {
+                selected[slot] = (row, span);
+            }
+        }
+    }
+
+    if options.keep_order {
+        selected.sort_unstable_by_key(|&(row, _)| row);
+    } else {
+        // 蓄水池中未被替换的行仍按原顺序排列，再打乱一次
+        rng.shuffle(&mut selected);
+    }
+    selected
+}
+
+/// 随机抽取 `n` 行，返回抽到的行号（从0开始）
+///
+/// 总行数不足 `n` 时返回所有行
+pub fn sample_rows(reader: &CsvReader, n: usize, options: &SampleOptions) -> Vec<usize> {
+    reservoir(reader, n, options).into_iter().map(|(row, _)| row).collect()
+}
+
+/// 随机抽取 `n` 行写入CSV文件（带表头）
+///
+/// # 参数
+/// - `reader`: 源文件读取器
+/// - `n`: 抽取的行数
+/// - `options`: 抽样选项
+/// - `output_path`: 输出文件路径
+/// - `write_options`: 写入选项
+pub fn sample_to_file<P: AsRef<Path>>(
+    reader: &CsvReader,
+    n: usize,
+    options: &SampleOptions,
+    output_path: P,
+    write_options: &WriteOptions,
+) -> Result<SaveStats> {
+    let selected = reservoir(reader, n, options);
+    write_spans_to_file(reader, selected.into_iter().map(|(_, span)| span), output_path.as_ref(), write_options)
+}
+
+/// 把所有行随机打乱后写入CSV文件（带表头）
+///
+/// 需要保存每行的位置（每行16字节），适合能放进内存的文件
+pub fn shuffle_to_file<P: AsRef<Path>>(
+    reader: &CsvReader,
+    seed: u64,
+    output_path: P,
+    write_options: &WriteOptions,
+) -> Result<SaveStats> {
+    let mut spans: Vec<(usize, usize)> = {
+        let _scan = reader.sequential_scan();
+        reader.line_spans().collect()
+    };
+    Rng::new(seed).shuffle(&mut spans);
+    write_spans_to_file(reader, spans, output_path.as_ref(), write_options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert!((0..1000).all(|_| a.below(7) < 7));
+
+        let mut items: Vec<usize> = (0..100).collect();
+        a.shuffle(&mut items);
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,284 @@
+//! 列类型推断模块
+//!
+//! 通过采样推断每列的数据类型，用于排序和导出前了解数据结构
+
+use crate::csv::{CsvReader, CsvRecord, SearchOptions, SearchPattern};
+use crate::error::Result;
+
+/// 推断出的列类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// 整数
+    Integer,
+    /// 浮点数
+    Float,
+    /// 布尔值（true/false）
+    Bool,
+    /// 日期（YYYY-MM-DD 或 YYYY/MM/DD，可带时间）
+    Date,
+    /// 字符串
+    String,
+}
+
+impl ColumnType {
+    /// 获取类型名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "float",
+            ColumnType::Bool => "bool",
+            ColumnType::Date => "date",
+            ColumnType::String => "string",
+        }
+    }
+
+    /// 推断单个非空值的类型
+    /// 
+    /// 带前导零的数字（如邮编 "007"）按字符串处理，以免丢失前导零
+    pub fn of_value(value: &str) -> Self {
+        if has_leading_zero(value) {
+            ColumnType::String
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if value.parse::<f64>().is_ok_and(|f| f.is_finite()) {
+            ColumnType::Float
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            ColumnType::Bool
+        } else if looks_like_date(value) {
+            ColumnType::Date
+        } else {
+            ColumnType::String
+        }
+    }
+
+    /// 合并两个类型（取能同时容纳两者的类型）
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ColumnType::Integer, ColumnType::Float) | (ColumnType::Float, ColumnType::Integer) => {
+                ColumnType::Float
+            }
+            _ => ColumnType::String,
+        }
+    }
+}
+
+/// 列结构信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSchema {
+    /// 列名
+    pub name: String,
+    /// 推断的类型
+    pub inferred_type: ColumnType,
+    /// 采样中是否出现空值
+    pub nullable: bool,
+}
+
+/// 读取器推断出的列类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InferredType {
+    /// 非空值的类型（全部为空时为字符串）
+    pub column_type: ColumnType,
+    /// 采样中空值所占比例（0.0 ~ 1.0）
+    pub null_fraction: f64,
+}
+
+impl InferredType {
+    /// 是否为数值列（整数或浮点数）
+    pub fn is_numeric(&self) -> bool {
+        matches!(self.column_type, ColumnType::Integer | ColumnType::Float)
+    }
+}
+
+/// 分散采样时的采样段数
+const SAMPLE_SEGMENTS: usize = 10;
+
+/// 默认采样行数
+pub const DEFAULT_SAMPLE_ROWS: usize = 1000;
+
+/// 检查值是否视为空值
+pub fn is_null_value(value: &str) -> bool {
+    value.is_empty() || value.eq_ignore_ascii_case("null")
+}
+
+/// 采样前 `sample_rows` 行推断每列的类型
+///
+/// # 参数
+/// - `reader`: CSV读取器
+/// - `sample_rows`: 采样行数
+pub fn infer_schema(reader: &CsvReader, sample_rows: usize) -> Result<Vec<ColumnSchema>> {
+    let pattern = SearchPattern::regex(".*", true)?;
+    let options = SearchOptions::new(pattern).with_max_results(sample_rows);
+
+    let records: Vec<CsvRecord<'static>> = reader.search(&options)?
+        .into_iter()
+        .map(|r| r.record)
+        .collect();
+
+    Ok(infer_schema_from_records(reader.headers(), reader.info().total_cols, &records))
+}
+
+/// 从已读取的记录推断每列的类型
+pub fn infer_schema_from_records(
+    headers: &[String],
+    total_cols: usize,
+    records: &[CsvRecord<'_>],
+) -> Vec<ColumnSchema> {
+    let col_count = records.iter()
+        .map(|r| r.fields.len())
+        .fold(headers.len().max(total_cols), usize::max);
+    let (types, nulls) = scan_column_types(col_count, records);
+
+    (0..col_count)
+        .map(|col| ColumnSchema {
+            name: headers.get(col)
+                .cloned()
+                .unwrap_or_else(|| format!("列{}", col + 1)),
+            // 全部为空的列按字符串处理
+            inferred_type: types[col].unwrap_or(ColumnType::String),
+            nullable: nulls[col] > 0 || types[col].is_none(),
+        })
+        .collect()
+}
+
+/// 在整个文件中分散采样约 `sample_rows` 行推断每列的类型
+/// 
+/// 行数不超过采样行数时读取全部行；否则借助行索引从均匀分布的若干位置各读取一段，
+/// 不只采样文件开头
+pub(crate) fn infer_column_types(reader: &CsvReader, sample_rows: usize) -> Result<Vec<InferredType>> {
+    let total_rows = reader.info().total_rows;
+    let records = if total_rows <= sample_rows {
+        reader.read_rows(0, total_rows)?
+    } else {
+        let segments = SAMPLE_SEGMENTS.min(sample_rows.max(1));
+        let stride = total_rows / segments;
+        let segment_rows = sample_rows.div_ceil(segments).min(stride);
+        let mut records = Vec::with_capacity(sample_rows);
+        for segment in 0..segments {
+            let start = segment * stride;
+            records.extend(reader.read_rows(start, start + segment_rows)?);
+        }
+        records
+    };
+
+    let col_count = records.iter()
+        .map(|r| r.fields.len())
+        .fold(reader.info().total_cols, usize::max);
+    let (types, nulls) = scan_column_types(col_count, &records);
+    let sampled = records.len().max(1) as f64;
+
+    Ok((0..col_count)
+        .map(|col| InferredType {
+            column_type: types[col].unwrap_or(ColumnType::String),
+            null_fraction: nulls[col] as f64 / sampled,
+        })
+        .collect())
+}
+
+/// 统计每列非空值合并后的类型（全部为空时为None）和空值个数
+fn scan_column_types(col_count: usize, records: &[CsvRecord<'_>]) -> (Vec<Option<ColumnType>>, Vec<usize>) {
+    let mut types: Vec<Option<ColumnType>> = vec![None; col_count];
+    let mut nulls = vec![0; col_count];
+
+    for record in records {
+        for col in 0..col_count {
+            let value = record.fields.get(col).map(|f| f.as_ref()).unwrap_or("");
+            if is_null_value(value) {
+                nulls[col] += 1;
+                continue;
+            }
+            let value_type = ColumnType::of_value(value);
+            types[col] = Some(match types[col] {
+                Some(current) => current.merge(value_type),
+                None => value_type,
+            });
+        }
+    }
+
+    (types, nulls)
+}
+
+/// 检查数字是否带前导零（"0" 和 "0.5" 不算）
+fn has_leading_zero(value: &str) -> bool {
+    let digits = value.trim_start_matches(['-', '+']).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+}
+
+/// 检查值是否像日期（YYYY-MM-DD / YYYY/MM/DD，可带 T 或空格分隔的时间）
+fn looks_like_date(value: &str) -> bool {
+    let (date_part, time_part) = match value.find(['T', ' ']) {
+        Some(pos) => (&value[..pos], Some(&value[pos + 1..])),
+        None => (value, None),
+    };
+
+    let separator = if date_part.contains('-') { '-' } else { '/' };
+    let parts: Vec<&str> = date_part.split(separator).collect();
+    if parts.len() != 3 || !parts.iter().all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit())) {
+        return false;
+    }
+    if parts[0].len() != 4 || parts[1].len() > 2 || parts[2].len() > 2 {
+        return false;
+    }
+
+    let month: u32 = parts[1].parse().unwrap_or(0);
+    let day: u32 = parts[2].parse().unwrap_or(0);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return false;
+    }
+
+    match time_part {
+        None => true,
+        Some(time) => {
+            time.starts_with(|c: char| c.is_ascii_digit())
+                && time.contains(':')
+                && time.chars().all(|c| c.is_ascii_digit() || ":.+-Z".contains(c))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_of_value() {
+        assert_eq!(ColumnType::of_value("42"), ColumnType::Integer);
+        assert_eq!(ColumnType::of_value("-3.5"), ColumnType::Float);
+        assert_eq!(ColumnType::of_value("TRUE"), ColumnType::Bool);
+        assert_eq!(ColumnType::of_value("2024-01-31"), ColumnType::Date);
+        assert_eq!(ColumnType::of_value("2024/1/5 12:30:00"), ColumnType::Date);
+        assert_eq!(ColumnType::of_value("2024-13-01"), ColumnType::String);
+        assert_eq!(ColumnType::of_value("NaN"), ColumnType::String);
+        assert_eq!(ColumnType::of_value("hello"), ColumnType::String);
+        assert_eq!(ColumnType::of_value("007"), ColumnType::String);
+        assert_eq!(ColumnType::of_value("0"), ColumnType::Integer);
+        assert_eq!(ColumnType::of_value("0.5"), ColumnType::Float);
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(ColumnType::Integer.merge(ColumnType::Float), ColumnType::Float);
+        assert_eq!(ColumnType::Integer.merge(ColumnType::Integer), ColumnType::Integer);
+        assert_eq!(ColumnType::Bool.merge(ColumnType::Integer), ColumnType::String);
+    }
+
+    #[test]
+    fn test_promotion_int_float_text() {
+        let record = |value: &'static str| CsvRecord::parse_line(value.as_bytes(), b',');
+        let rows = |values: &[&'static str]| values.iter().map(|&v| record(v)).collect::<Vec<_>>();
+
+        // 整数 -> 浮点数 -> 字符串，空值不影响类型
+        let (types, nulls) = scan_column_types(1, &rows(&["1", "2", ""]));
+        assert_eq!(types, vec![Some(ColumnType::Integer)]);
+        assert_eq!(nulls, vec![1]);
+        let (types, _) = scan_column_types(1, &rows(&["1", "2.5", "NULL"]));
+        assert_eq!(types, vec![Some(ColumnType::Float)]);
+        let (types, _) = scan_column_types(1, &rows(&["1", "2.5", "abc"]));
+        assert_eq!(types, vec![Some(ColumnType::String)]);
+
+        // 全部为空时没有类型
+        let (types, nulls) = scan_column_types(1, &rows(&["", "null"]));
+        assert_eq!(types, vec![None]);
+        assert_eq!(nulls, vec![2]);
+    }
+}
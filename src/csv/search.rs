@@ -4,8 +4,19 @@
 
 use crate::csv::CsvRecord;
 use crate::error::{CsvError, Result};
+use aho_corasick::{AhoCorasick, MatchKind};
 use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 扫描进度回调，参数为（已扫描字节数，总字节数）
+pub type ProgressCallback = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// 两次进度回调之间至少扫描的字节数
+const PROGRESS_INTERVAL_BYTES: u64 = 4 * 1024 * 1024;
 
 /// 搜索模式
 #[derive(Debug, Clone)]
@@ -14,6 +25,8 @@ pub enum SearchPattern {
     Text(String),
     /// 正则表达式搜索
     Regex(Regex),
+    /// 多个模式，任一模式匹配即可
+    AnyOf(PatternSet),
 }
 
 impl SearchPattern {
@@ -28,13 +41,45 @@ impl SearchPattern {
 
     /// 创建正则表达式搜索模式
     pub fn regex(pattern: &str, case_sensitive: bool) -> Result<Self> {
-        let regex = RegexBuilder::new(pattern)
+        Self::regex_with(pattern, case_sensitive, false)
+    }
+
+    /// 创建正则表达式搜索模式，`whole_word` 为true时在表达式两端加上单词边界 `\b`
+    pub fn regex_with(pattern: &str, case_sensitive: bool, whole_word: bool) -> Result<Self> {
+        let pattern = if whole_word {
+            Cow::Owned(format!(r"\b(?:{})\b", pattern))
+        } else {
+            Cow::Borrowed(pattern)
+        };
+        let regex = RegexBuilder::new(&pattern)
             .case_insensitive(!case_sensitive)
             .build()
             .map_err(|e| CsvError::Format(format!("无效的正则表达式: {}", e)))?;
         Ok(SearchPattern::Regex(regex))
     }
 
+    /// 创建通配符搜索模式：`*` 匹配任意多个字符，`?` 匹配一个字符，`[abc]` 匹配其中之一
+    ///
+    /// 转换为正则表达式，且必须匹配整个字段（如 `A*` 表示以A开头的字段），其余字符按原样匹配
+    pub fn glob(pattern: &str, case_sensitive: bool) -> Result<Self> {
+        let regex = RegexBuilder::new(&glob_to_regex(pattern))
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| CsvError::Format(format!("无效的通配符模式: {}", e)))?;
+        Ok(SearchPattern::Regex(regex))
+    }
+
+    /// 组合多个模式，任一模式匹配即可，只需扫描一遍文件
+    ///
+    /// 纯文本模式合并为一个Aho-Corasick自动机，模式很多时也只需查找一次；
+    /// 只有一个模式时直接返回该模式
+    pub fn any_of(patterns: Vec<SearchPattern>) -> Result<Self> {
+        if patterns.len() == 1 {
+            return Ok(patterns.into_iter().next().unwrap());
+        }
+        PatternSet::new(patterns).map(SearchPattern::AnyOf)
+    }
+
     /// 检查字符串是否匹配
     pub fn is_match(&self, text: &str, case_sensitive: bool) -> bool {
         match self {
@@ -46,6 +91,7 @@ impl SearchPattern {
                 }
             }
             SearchPattern::Regex(regex) => regex.is_match(text),
+            SearchPattern::AnyOf(set) => set.is_match(text, case_sensitive),
         }
     }
 
@@ -53,32 +99,268 @@ impl SearchPattern {
     pub fn find_matches(&self, text: &str, case_sensitive: bool) -> Vec<(usize, usize)> {
         match self {
             SearchPattern::Text(pattern) => {
-                let search_text = if case_sensitive {
-                    Cow::Borrowed(text)
-                } else {
-                    Cow::Owned(text.to_lowercase())
-                };
-                
-                let mut matches = Vec::new();
-                let mut start = 0;
-                while let Some(pos) = search_text[start..].find(pattern) {
-                    let abs_pos = start + pos;
-                    matches.push((abs_pos, abs_pos + pattern.len()));
-                    start = abs_pos + 1;
+                if case_sensitive || pattern.is_empty() {
+                    return find_text(text, pattern);
                 }
-                matches
+                // 小写化可能改变字节长度（如 'İ' 变为 "i̇"），匹配位置需要映射回原文
+                let (lowered, origins) = lowercase_with_origins(text);
+                find_text(&lowered, pattern)
+                    .into_iter()
+                    .map(|(start, end)| (origins[start].0, origins[end - 1].1))
+                    .collect()
             }
             SearchPattern::Regex(regex) => {
                 regex.find_iter(text)
                     .map(|m| (m.start(), m.end()))
                     .collect()
             }
+            SearchPattern::AnyOf(set) => set.find_matches_with(text, case_sensitive, false).0,
+        }
+    }
+
+    /// 查找所有匹配位置，`whole_word` 为true时只保留整词匹配
+    ///
+    /// 纯文本模式检查匹配两侧的字符不是单词字符；正则表达式的单词边界在编译时加入
+    /// （见 [`regex_with`](Self::regex_with)），这里不再过滤
+    pub fn find_matches_with(&self, text: &str, case_sensitive: bool, whole_word: bool) -> Vec<(usize, usize)> {
+        match self {
+            SearchPattern::AnyOf(set) => set.find_matches_with(text, case_sensitive, whole_word).0,
+            SearchPattern::Text(pattern) if whole_word && !pattern.is_empty() => {
+                find_whole_words(text, pattern, case_sensitive)
+            }
+            SearchPattern::Text(_) if whole_word => self.find_matches(text, case_sensitive)
+                .into_iter()
+                .filter(|&(start, end)| is_whole_word(text, start, end))
+                .collect(),
+            _ => self.find_matches(text, case_sensitive),
         }
     }
 }
 
-/// 搜索选项
+/// 多个搜索模式的组合（见 [`SearchPattern::any_of`]）
 #[derive(Debug, Clone)]
+pub struct PatternSet {
+    /// 全部子模式，匹配结果中按此顺序编号
+    patterns: Vec<SearchPattern>,
+    /// 非空纯文本子模式编译成的自动机
+    literals: Option<AhoCorasick>,
+    /// 自动机中的模式编号对应的子模式序号
+    literal_ids: Vec<usize>,
+    /// 需要单独匹配的子模式序号（正则表达式、空文本）
+    other_ids: Vec<usize>,
+}
+
+impl PatternSet {
+    fn new(patterns: Vec<SearchPattern>) -> Result<Self> {
+        let mut literal_ids = Vec::new();
+        let mut other_ids = Vec::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            match pattern {
+                SearchPattern::Text(text) if !text.is_empty() => literal_ids.push(i),
+                _ => other_ids.push(i),
+            }
+        }
+
+        // 需要找出每个子模式的匹配（用于记录命中的模式和整词过滤），因此使用可重叠查找
+        let literals = if literal_ids.is_empty() {
+            None
+        } else {
+            let texts = literal_ids.iter().map(|&i| match &patterns[i] {
+                SearchPattern::Text(text) => text.as_str(),
+                _ => unreachable!(),
+            });
+            let automaton = AhoCorasick::builder()
+                .match_kind(MatchKind::Standard)
+                .build(texts)
+                .map_err(|e| CsvError::Format(format!("无法编译搜索模式: {}", e)))?;
+            Some(automaton)
+        };
+
+        Ok(Self { patterns, literals, literal_ids, other_ids })
+    }
+
+    /// 子模式列表
+    pub fn patterns(&self) -> &[SearchPattern] {
+        &self.patterns
+    }
+
+    /// 检查字符串是否匹配任一子模式
+    pub fn is_match(&self, text: &str, case_sensitive: bool) -> bool {
+        let literal_hit = self.literals.as_ref().is_some_and(|literals| {
+            if case_sensitive {
+                literals.is_match(text)
+            } else {
+                literals.is_match(text.to_lowercase().as_str())
+            }
+        });
+        literal_hit || self.other_ids.iter().any(|&i| self.patterns[i].is_match(text, case_sensitive))
+    }
+
+    /// 查找所有子模式的匹配位置，同时返回匹配的子模式序号（升序）
+    ///
+    /// 不同子模式的匹配可能重叠，返回的位置已合并为互不重叠的区间，便于高亮
+    pub fn find_matches_with(
+        &self,
+        text: &str,
+        case_sensitive: bool,
+        whole_word: bool,
+    ) -> (Vec<(usize, usize)>, Vec<usize>) {
+        let mut positions = Vec::new();
+        let mut hit = vec![false; self.patterns.len()];
+
+        if let Some(literals) = &self.literals {
+            // 与单个纯文本模式相同：不区分大小写时在小写化的文本中查找，再映射回原文位置
+            let lowered = (!case_sensitive).then(|| lowercase_with_origins(text));
+            let haystack = lowered.as_ref().map_or(text, |(lowered, _)| lowered.as_str());
+            for m in literals.find_overlapping_iter(haystack) {
+                let (start, end) = match &lowered {
+                    Some((_, origins)) => (origins[m.start()].0, origins[m.end() - 1].1),
+                    None => (m.start(), m.end()),
+                };
+                if whole_word && !is_whole_word(text, start, end) {
+                    continue;
+                }
+                hit[self.literal_ids[m.pattern().as_usize()]] = true;
+                positions.push((start, end));
+            }
+        }
+
+        for &i in &self.other_ids {
+            let found = self.patterns[i].find_matches_with(text, case_sensitive, whole_word);
+            if !found.is_empty() {
+                hit[i] = true;
+                positions.extend(found);
+            }
+        }
+
+        let patterns = hit.iter().enumerate().filter_map(|(i, &hit)| hit.then_some(i)).collect();
+        (merge_ranges(positions), patterns)
+    }
+}
+
+/// 排序并合并重叠的区间（空区间保留）
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start < last.1 => last.1 = last.1.max(end),
+            Some(last) if *last == (start, end) => {}
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// 把通配符模式转换为匹配整个字段的正则表达式
+///
+/// `[...]` 原样保留（`[!...]` 转为取反的 `[^...]`），没有闭合 `]` 的 `[` 按普通字符处理
+fn glob_to_regex(pattern: &str) -> String {
+    let mut translated = String::from("^(?:");
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '*' => translated.push_str(".*"),
+            '?' => translated.push('.'),
+            '[' => {
+                let negated = rest.starts_with('!');
+                let body = &rest[usize::from(negated)..];
+                // 紧跟在 [ 之后的 ] 属于类本身（如 `[]a]`）
+                let close = body.char_indices().skip(1).find(|&(_, c)| c == ']').map(|(i, _)| i);
+                let Some(close) = close else {
+                    translated.push_str(r"\[");
+                    continue;
+                };
+                translated.push_str(if negated { "[^" } else { "[" });
+                for c in body[..close].chars() {
+                    // 除表示范围的 - 以外，类中的字符都按字面匹配
+                    if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                        translated.push('\\');
+                    }
+                    translated.push(c);
+                }
+                translated.push(']');
+                rest = &body[close + 1..];
+            }
+            _ => translated.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    translated.push_str(")$");
+    translated
+}
+
+/// 是否为单词字符（Unicode字母、数字和下划线）
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 匹配 `text[start..end]` 两侧是否都不是单词字符（或位于文本开头、结尾）
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+/// 查找文本中所有不重叠的匹配位置（空模式只在开头匹配一次）
+fn find_text(text: &str, pattern: &str) -> Vec<(usize, usize)> {
+    if pattern.is_empty() {
+        return vec![(0, 0)];
+    }
+    text.match_indices(pattern)
+        .map(|(pos, m)| (pos, pos + m.len()))
+        .collect()
+}
+
+/// 查找文本中所有不重叠的整词匹配位置（`pattern` 非空，不区分大小写时已小写化）
+///
+/// 候选位置不是整词时从它的下一个字符继续查找，不会漏掉与之重叠的整词匹配
+fn find_whole_words(text: &str, pattern: &str, case_sensitive: bool) -> Vec<(usize, usize)> {
+    let lowered;
+    let (haystack, origins) = if case_sensitive {
+        (text, None)
+    } else {
+        let (lower, origins) = lowercase_with_origins(text);
+        lowered = lower;
+        (lowered.as_str(), Some(origins))
+    };
+
+    let mut matches = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = haystack[pos..].find(pattern) {
+        let start = pos + offset;
+        let end = start + pattern.len();
+        // 小写化可能改变字节长度，整词检查使用原文中的位置
+        let range = match &origins {
+            Some(origins) => (origins[start].0, origins[end - 1].1),
+            None => (start, end),
+        };
+        if is_whole_word(text, range.0, range.1) {
+            matches.push(range);
+            pos = end;
+        } else {
+            pos = start + haystack[start..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    matches
+}
+
+/// 小写化文本，同时记录小写文本每个字节对应的原文字符范围
+fn lowercase_with_origins(text: &str) -> (String, Vec<(usize, usize)>) {
+    let mut lowered = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (pos, c) in text.char_indices() {
+        let range = (pos, pos + c.len_utf8());
+        for lower in c.to_lowercase() {
+            lowered.push(lower);
+            origins.resize(lowered.len(), range);
+        }
+    }
+    (lowered, origins)
+}
+
+/// 搜索选项
+#[derive(Clone)]
 pub struct SearchOptions {
     /// 搜索模式
     pub pattern: SearchPattern,
@@ -90,6 +372,27 @@ pub struct SearchOptions {
     pub max_results: Option<usize>,
     /// 反向匹配（显示不匹配的行）
     pub invert_match: bool,
+    /// 只匹配整个单词（纯文本模式有效，正则表达式使用 `SearchPattern::regex_with`）
+    pub whole_word: bool,
+    /// 扫描进度回调（每扫描几MB调用一次）
+    pub progress: Option<ProgressCallback>,
+    /// 取消标志，置为true后扫描提前结束并返回已找到的结果
+    pub cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl fmt::Debug for SearchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchOptions")
+            .field("pattern", &self.pattern)
+            .field("columns", &self.columns)
+            .field("case_sensitive", &self.case_sensitive)
+            .field("max_results", &self.max_results)
+            .field("invert_match", &self.invert_match)
+            .field("whole_word", &self.whole_word)
+            .field("progress", &self.progress.is_some())
+            .field("cancel_flag", &self.cancel_flag)
+            .finish()
+    }
 }
 
 impl SearchOptions {
@@ -101,6 +404,9 @@ impl SearchOptions {
             case_sensitive: true,
             max_results: None,
             invert_match: false,
+            whole_word: false,
+            progress: None,
+            cancel_flag: None,
         }
     }
 
@@ -127,6 +433,95 @@ impl SearchOptions {
         self.invert_match = invert;
         self
     }
+
+    /// 设置是否只匹配整个单词（如 `cat` 不匹配 `concatenate`）
+    pub fn with_whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// 设置扫描进度回调，参数为（已扫描字节数，总字节数）
+    pub fn with_progress(mut self, progress: ProgressCallback) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// 设置取消标志，其他线程将其置为true后扫描提前结束
+    pub fn with_cancel_flag(mut self, cancel_flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(cancel_flag);
+        self
+    }
+}
+
+/// 可取消的扫描结果
+#[derive(Debug, Clone)]
+pub struct SearchOutcome<T> {
+    /// 扫描结果（被取消时为取消前已得到的部分结果）
+    pub value: T,
+    /// 是否因取消而提前结束
+    pub cancelled: bool,
+}
+
+/// 分页搜索的游标，记录上一页停止扫描的位置
+/// 
+/// 只能用于创建它的同一个读取器和相同的搜索选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchCursor {
+    /// 继续扫描的字节偏移量（位于行首）
+    pub byte_offset: u64,
+    /// 该位置的行号（从0开始，不含表头）
+    pub row_number: usize,
+    /// 该位置之前的匹配数
+    pub matched: usize,
+}
+
+/// 一页搜索结果
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    /// 本页的搜索结果
+    pub results: Vec<SearchResult>,
+    /// 匹配总数，扫描到文件末尾时才能得到
+    pub total: Option<usize>,
+    /// 获取下一页的游标，已扫描到文件末尾时为None
+    pub cursor: Option<SearchCursor>,
+}
+
+/// 扫描过程中的进度汇报和取消检查
+pub(crate) struct ScanMonitor<'a> {
+    progress: Option<&'a ProgressCallback>,
+    cancel_flag: Option<&'a AtomicBool>,
+    total: u64,
+    next_report: u64,
+}
+
+impl<'a> ScanMonitor<'a> {
+    /// 按搜索选项创建，`total` 为需要扫描的总字节数
+    pub(crate) fn new(options: &'a SearchOptions, total: u64) -> Self {
+        Self {
+            progress: options.progress.as_ref(),
+            cancel_flag: options.cancel_flag.as_deref(),
+            total,
+            next_report: 0,
+        }
+    }
+
+    /// 记录已扫描的字节数（按间隔调用进度回调），返回是否已被取消
+    pub(crate) fn tick(&mut self, scanned: u64) -> bool {
+        if let Some(progress) = self.progress {
+            if scanned >= self.next_report {
+                progress(scanned.min(self.total), self.total);
+                self.next_report = scanned + PROGRESS_INTERVAL_BYTES;
+            }
+        }
+        self.cancel_flag.is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// 扫描结束（未取消）时汇报100%进度
+    pub(crate) fn finish(&self) {
+        if let Some(progress) = self.progress {
+            progress(self.total, self.total);
+        }
+    }
 }
 
 /// 单个匹配信息
@@ -136,6 +531,8 @@ pub struct MatchInfo {
     pub column: usize,
     /// 匹配位置（起始，结束）
     pub positions: Vec<(usize, usize)>,
+    /// 在该列匹配的子模式序号（从0开始，仅 [`SearchPattern::AnyOf`]，其余模式为空）
+    pub patterns: Vec<usize>,
 }
 
 /// 搜索结果
@@ -185,12 +582,18 @@ impl Searcher {
         for &col in &columns {
             if let Some(field) = record.fields.get(col) {
                 let text = field.as_ref();
-                let positions = self.options.pattern.find_matches(text, self.options.case_sensitive);
+                let case_sensitive = self.options.case_sensitive;
+                let whole_word = self.options.whole_word;
+                let (positions, patterns) = match &self.options.pattern {
+                    SearchPattern::AnyOf(set) => set.find_matches_with(text, case_sensitive, whole_word),
+                    pattern => (pattern.find_matches_with(text, case_sensitive, whole_word), Vec::new()),
+                };
                 
                 if !positions.is_empty() {
                     all_matches.push(MatchInfo {
                         column: col,
                         positions,
+                        patterns,
                     });
                 }
             }
@@ -223,7 +626,14 @@ impl Searcher {
 
         let has_match = columns.iter().any(|&col| {
             record.fields.get(col).map_or(false, |field| {
-                self.options.pattern.is_match(field.as_ref(), self.options.case_sensitive)
+                let text = field.as_ref();
+                if self.options.whole_word {
+                    !self.options.pattern
+                        .find_matches_with(text, self.options.case_sensitive, true)
+                        .is_empty()
+                } else {
+                    self.options.pattern.is_match(text, self.options.case_sensitive)
+                }
             })
         });
 
@@ -235,22 +645,116 @@ impl Searcher {
     }
 }
 
+/// 高亮颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HighlightColor {
+    /// 红色
+    Red,
+    /// 绿色
+    Green,
+    /// 黄色（默认）
+    #[default]
+    Yellow,
+    /// 蓝色
+    Blue,
+    /// 品红
+    Magenta,
+    /// 青色
+    Cyan,
+}
+
+impl HighlightColor {
+    /// 从字符串解析
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "red" => Some(HighlightColor::Red),
+            "green" => Some(HighlightColor::Green),
+            "yellow" => Some(HighlightColor::Yellow),
+            "blue" => Some(HighlightColor::Blue),
+            "magenta" | "purple" => Some(HighlightColor::Magenta),
+            "cyan" => Some(HighlightColor::Cyan),
+            _ => None,
+        }
+    }
+
+    /// ANSI前景色代码
+    fn ansi_code(self) -> u8 {
+        match self {
+            HighlightColor::Red => 31,
+            HighlightColor::Green => 32,
+            HighlightColor::Yellow => 33,
+            HighlightColor::Blue => 34,
+            HighlightColor::Magenta => 35,
+            HighlightColor::Cyan => 36,
+        }
+    }
+}
+
+/// 匹配文本的高亮样式（默认黄色加粗）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightStyle {
+    /// 前景色
+    pub color: HighlightColor,
+    /// 是否加粗
+    pub bold: bool,
+}
+
+impl Default for HighlightStyle {
+    fn default() -> Self {
+        Self {
+            color: HighlightColor::default(),
+            bold: true,
+        }
+    }
+}
+
+impl HighlightStyle {
+    /// 创建指定颜色的加粗样式
+    pub fn new(color: HighlightColor) -> Self {
+        Self { color, bold: true }
+    }
+
+    /// 设置是否加粗
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    /// 开始高亮的ANSI转义序列
+    pub fn prefix(&self) -> String {
+        if self.bold {
+            format!("\x1b[1;{}m", self.color.ansi_code())
+        } else {
+            format!("\x1b[{}m", self.color.ansi_code())
+        }
+    }
+}
+
 /// 用于高亮显示的辅助函数
-pub fn highlight_matches(text: &str, positions: &[(usize, usize)]) -> String {
+///
+/// 匹配位置按字节计算；越界或不在字符边界上的位置会被收缩到相邻的字符边界，
+/// 与前一个匹配重叠的部分不会重复输出
+pub fn highlight_matches(text: &str, positions: &[(usize, usize)], style: &HighlightStyle) -> String {
     if positions.is_empty() {
         return text.to_string();
     }
 
+    let prefix = style.prefix();
     let mut result = String::new();
     let mut last_end = 0;
 
     for &(start, end) in positions {
+        let start = floor_char_boundary(text, start).max(last_end);
+        let end = ceil_char_boundary(text, end);
+        if start >= end {
+            continue;
+        }
         // 添加匹配前的文本
         if start > last_end {
             result.push_str(&text[last_end..start]);
         }
         // 添加高亮的匹配文本（使用 ANSI 颜色代码）
-        result.push_str("\x1b[1;33m"); // 黄色加粗
+        result.push_str(&prefix);
         result.push_str(&text[start..end]);
         result.push_str("\x1b[0m"); // 重置
         last_end = end;
@@ -264,6 +768,24 @@ pub fn highlight_matches(text: &str, positions: &[(usize, usize)]) -> String {
     result
 }
 
+/// 不大于 `pos` 的最近字符边界
+fn floor_char_boundary(text: &str, pos: usize) -> usize {
+    let mut pos = pos.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+/// 不小于 `pos` 的最近字符边界
+fn ceil_char_boundary(text: &str, pos: usize) -> usize {
+    let mut pos = pos.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos += 1;
+    }
+    pos
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,14 +821,167 @@ mod tests {
         assert_eq!(matches[2], (12, 16));
     }
 
+    #[test]
+    fn test_find_whole_word_matches() {
+        let pattern = SearchPattern::text("cat", true);
+        assert_eq!(pattern.find_matches_with("cat concat cat", true, true), vec![(0, 3), (11, 14)]);
+        assert_eq!(pattern.find_matches_with("cat concat cat", true, false).len(), 3);
+        assert_eq!(pattern.find_matches_with("«cat»。cat_1", true, true), vec![(2, 5)]);
+
+        // 不是整词的候选之后仍能找到与之重叠的整词匹配
+        let pattern = SearchPattern::text("a a", true);
+        assert_eq!(pattern.find_matches_with("ba a a", true, true), vec![(3, 6)]);
+        let pattern = SearchPattern::text("A A", false);
+        assert_eq!(pattern.find_matches_with("bA a A", false, true), vec![(3, 6)]);
+
+        // 非ASCII字母同样是单词字符
+        let pattern = SearchPattern::text("北京", true);
+        assert!(pattern.find_matches_with("北京市", true, true).is_empty());
+        assert_eq!(pattern.find_matches_with("去 北京", true, true), vec![(4, 10)]);
+        let pattern = SearchPattern::text("cafe", true);
+        assert!(pattern.find_matches_with("écafe", true, true).is_empty());
+
+        let pattern = SearchPattern::regex_with("ca.", true, true).unwrap();
+        assert_eq!(pattern.find_matches("cat cats scar", true), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn test_any_of_patterns() {
+        let pattern = SearchPattern::any_of(vec![
+            SearchPattern::text("cat", true),
+            SearchPattern::text("dog", true),
+            SearchPattern::regex(r"\d+", true).unwrap(),
+        ])
+        .unwrap();
+        let SearchPattern::AnyOf(set) = &pattern else {
+            panic!("多个模式应组合为 AnyOf");
+        };
+        assert_eq!(set.patterns().len(), 3);
+
+        assert!(pattern.is_match("hotdog", true));
+        assert!(pattern.is_match("room 42", true));
+        assert!(!pattern.is_match("bird", true));
+
+        // 记录命中的子模式，位置按顺序排列
+        let (positions, hit) = set.find_matches_with("dog 7 cat", true, false);
+        assert_eq!(positions, vec![(0, 3), (4, 5), (6, 9)]);
+        assert_eq!(hit, vec![0, 1, 2]);
+
+        // 重叠的匹配合并为一个区间
+        let set = PatternSet::new(vec![SearchPattern::text("Ali", true), SearchPattern::text("Alice", true)]).unwrap();
+        assert_eq!(set.find_matches_with("Alice", true, false), (vec![(0, 5)], vec![0, 1]));
+
+        // 整词匹配和大小写不敏感
+        let set = PatternSet::new(vec![SearchPattern::text("CAT", false), SearchPattern::text("İ", false)]).unwrap();
+        assert_eq!(set.find_matches_with("Cat concat", false, true), (vec![(0, 3)], vec![0]));
+        assert!(set.is_match("xİx", false));
+        assert_eq!(set.find_matches_with("xİx", false, false).0, vec![(1, 3)]);
+
+        // 只有一个模式时不组合
+        let pattern = SearchPattern::any_of(vec![SearchPattern::text("cat", true)]).unwrap();
+        assert!(matches!(pattern, SearchPattern::Text(_)));
+    }
+
+    #[test]
+    fn test_glob_pattern() {
+        // 匹配整个字段
+        let pattern = SearchPattern::glob("A*", true).unwrap();
+        assert!(pattern.is_match("Alice", true));
+        assert!(!pattern.is_match("Bob Alice", true));
+        let pattern = SearchPattern::glob("?ob", true).unwrap();
+        assert!(pattern.is_match("Bob", true));
+        assert!(!pattern.is_match("Jacob", true));
+        assert_eq!(pattern.find_matches("Rob", true), vec![(0, 3)]);
+
+        // 正则表达式的特殊字符按字面匹配
+        let pattern = SearchPattern::glob("a.b(1)+", true).unwrap();
+        assert!(pattern.is_match("a.b(1)+", true));
+        assert!(!pattern.is_match("axb(1)+", true));
+
+        // 字符类和取反的字符类，未闭合的 [ 按字面匹配
+        let pattern = SearchPattern::glob("[BR]ob", true).unwrap();
+        assert!(pattern.is_match("Rob", true) && !pattern.is_match("Job", true));
+        let pattern = SearchPattern::glob("[!0-9]*", true).unwrap();
+        assert!(pattern.is_match("x1", true) && !pattern.is_match("1x", true));
+        assert!(SearchPattern::glob("[a", true).unwrap().is_match("[a", true));
+        assert!(SearchPattern::glob("[]]", true).unwrap().is_match("]", true));
+
+        // 大小写不敏感
+        let pattern = SearchPattern::glob("*@EXAMPLE.com", false).unwrap();
+        assert!(pattern.is_match("alice@example.COM", false));
+    }
+
     #[test]
     fn test_highlight_matches() {
         let text = "hello world";
         let positions = vec![(0, 5)];
-        let highlighted = highlight_matches(text, &positions);
+        let highlighted = highlight_matches(text, &positions, &HighlightStyle::default());
         assert!(highlighted.contains("\x1b[1;33m"));
         assert!(highlighted.contains("hello"));
     }
-}
 
+    #[test]
+    fn test_highlight_style() {
+        let style = HighlightStyle::new(HighlightColor::parse("Red").unwrap());
+        assert_eq!(highlight_matches("abc", &[(1, 2)], &style), "a\x1b[1;31mb\x1b[0mc");
+
+        let style = HighlightStyle::new(HighlightColor::Cyan).with_bold(false);
+        assert_eq!(highlight_matches("abc", &[(0, 1)], &style), "\x1b[36ma\x1b[0mbc");
 
+        assert_eq!(HighlightStyle::default().prefix(), "\x1b[1;33m");
+        assert!(HighlightColor::parse("orange").is_none());
+    }
+
+    #[test]
+    fn test_find_matches_case_insensitive_non_ascii() {
+        // 'İ' 小写为 "i̇"，比原文多一个字节
+        let text = "İstanbul";
+        let pattern = SearchPattern::text("STANBUL", false);
+        let matches = pattern.find_matches(text, false);
+        assert_eq!(matches, vec![(2, 9)]);
+        assert_eq!(&text[2..9], "stanbul");
+        let pattern = SearchPattern::text("i̇s", false);
+        assert_eq!(pattern.find_matches(text, false), vec![(0, 3)]);
+
+        // 'ẞ' 小写为 'ß'，比原文少一个字节
+        let text = "ẞẞ Straße";
+        let pattern = SearchPattern::text("STRAßE", false);
+        let matches = pattern.find_matches(text, false);
+        assert_eq!(matches.len(), 1);
+        let (start, end) = matches[0];
+        assert_eq!(&text[start..end], "Straße");
+        assert_eq!(
+            highlight_matches(text, &matches, &HighlightStyle::default()),
+            "ẞẞ \x1b[1;33mStraße\x1b[0m"
+        );
+        let pattern = SearchPattern::text("ß", false);
+        let matches = pattern.find_matches(text, false);
+        let found: Vec<&str> = matches.iter().map(|&(s, e)| &text[s..e]).collect();
+        assert_eq!(found, vec!["ẞ", "ẞ", "ß"]);
+
+        // CJK文本中重复出现的多字节匹配
+        let text = "北京BEIJING北京";
+        let pattern = SearchPattern::text("北京", false);
+        assert_eq!(pattern.find_matches(text, false), vec![(0, 6), (13, 19)]);
+        let pattern = SearchPattern::text("beijing", false);
+        let matches = pattern.find_matches(text, false);
+        assert_eq!(
+            highlight_matches(text, &matches, &HighlightStyle::default()),
+            "北京\x1b[1;33mBEIJING\x1b[0m北京"
+        );
+    }
+
+    #[test]
+    fn test_highlight_matches_clamps_positions() {
+        let text = "北京";
+        let style = HighlightStyle::default();
+        // 不在字符边界上、越界和重叠的位置都不会panic
+        assert_eq!(highlight_matches(text, &[(1, 4)], &style), "\x1b[1;33m北京\x1b[0m");
+        assert_eq!(highlight_matches(text, &[(3, 100)], &style), "北\x1b[1;33m京\x1b[0m");
+        assert_eq!(
+            highlight_matches(text, &[(0, 3), (1, 6)], &style),
+            "\x1b[1;33m北\x1b[0m\x1b[1;33m京\x1b[0m"
+        );
+        assert_eq!(highlight_matches(text, &[(10, 12)], &style), "北京");
+    }
+}
@@ -2,9 +2,22 @@
 //! 
 //! 支持按列排序（升序/降序），支持多种数据类型
 
-use crate::csv::{CsvReader, CsvRecord, SearchPattern, SearchOptions};
+use crate::csv::{ColumnIndex, CsvReader, CsvRecord, LineFormat, NumericCleanup, SaveStats, WriteOptions};
+use crate::csv::writer::write_spans_to_file;
 use crate::error::Result;
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::path::Path;
+
+/// 部分排序时堆最多预分配的行数
+const TOP_N_PREALLOC_ROWS: usize = 10_000;
+
+/// 自动检测数据类型时采样的行数
+const AUTO_SAMPLE_ROWS: usize = 1000;
+
+/// 自动检测时，采样的非空值中至少有此比例能解析为数字才按数字排序
+const AUTO_NUMERIC_RATIO: f64 = 0.5;
 
 /// 排序方向
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -57,6 +70,92 @@ impl DataType {
     }
 }
 
+/// 字符串排序规则
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Collation {
+    /// 按Unicode码点比较（默认）
+    #[default]
+    Binary,
+    /// 忽略重音符号比较（`é` 排在 `e` 旁边），不区分语言；只有重音不同时无重音的在前
+    AccentFolding,
+}
+
+impl Collation {
+    /// 从字符串解析
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "binary" | "codepoint" | "b" => Some(Collation::Binary),
+            "fold" | "folding" | "accent" | "accent-folding" | "f" => Some(Collation::AccentFolding),
+            _ => None,
+        }
+    }
+}
+
+/// 去掉拉丁字母的重音符号，其他字符不变（如 `é` -> `e`、`ß` -> `ss`、`Æ` -> `AE`）
+fn fold_accents(s: &str) -> String {
+    let mut folded = String::with_capacity(s.len());
+    for c in s.chars() {
+        match fold_char(c) {
+            Some(base) => folded.push_str(base),
+            None => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// 带重音的拉丁字母（Latin-1 补充和 Latin 扩展-A）对应的基本字母
+fn fold_char(c: char) -> Option<&'static str> {
+    let base = match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ď' | 'Đ' | 'Ð' => "D",
+        'ď' | 'đ' | 'ð' => "d",
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "TH",
+        'þ' => "th",
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(base)
+}
+
 /// 排序键
 #[derive(Debug, Clone)]
 pub struct SortKey {
@@ -100,6 +199,16 @@ pub struct SortOptions {
     pub nulls_last: bool,
     /// 大小写敏感
     pub case_sensitive: bool,
+    /// 字符串排序规则
+    pub collation: Collation,
+    /// 稳定排序：键相同的行按原始行号排列
+    pub stable: bool,
+    /// 比较前去掉首尾空白（None表示只对数字去掉）
+    pub trim: Option<bool>,
+    /// 数字解析前的清理规则（千位分隔符、货币符号），None时按原样解析
+    pub numeric_cleanup: Option<NumericCleanup>,
+    /// 视为空值的标记（如 `NA`、`N/A`、`-`，不区分大小写），与空字符串一样按 `nulls_last` 排列
+    pub null_values: Vec<String>,
 }
 
 impl Default for SortOptions {
@@ -108,6 +217,11 @@ impl Default for SortOptions {
             keys: Vec::new(),
             nulls_last: true,
             case_sensitive: true,
+            collation: Collation::Binary,
+            stable: true,
+            trim: None,
+            numeric_cleanup: None,
+            null_values: Vec::new(),
         }
     }
 }
@@ -135,6 +249,110 @@ impl SortOptions {
         self.case_sensitive = case_sensitive;
         self
     }
+
+    /// 设置字符串排序规则
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
+    /// 设置比较前是否去掉首尾空白
+    /// 
+    /// 默认只对数字去掉（` 42 ` 按 42 排序），字符串保持原样；
+    /// 设为true时字符串也去掉，设为false时数字也不去掉（带空白的数字按空值处理）
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = Some(trim);
+        self
+    }
+
+    /// 设置数字解析前的清理规则（如 `1,234`、`$3.50`、`1.234,56`）
+    pub fn with_numeric_cleanup(mut self, cleanup: NumericCleanup) -> Self {
+        self.numeric_cleanup = Some(cleanup);
+        self
+    }
+
+    /// 设置视为空值的标记（不区分大小写，比较时忽略首尾空白）
+    pub fn with_null_values(mut self, null_values: Vec<String>) -> Self {
+        self.null_values = null_values;
+        self
+    }
+
+    /// 值是否是空字符串或空值标记
+    fn is_null_token(&self, value: &str) -> bool {
+        value.is_empty() || self.null_values.iter().any(|token| token.trim().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// 按清理规则把值解析为数字，无法解析或为NaN时返回None
+    pub(crate) fn parse_number(&self, value: &str) -> Option<f64> {
+        let value = if self.trim.unwrap_or(true) { value.trim() } else { value };
+        match &self.numeric_cleanup {
+            Some(cleanup) => cleanup.parse(value),
+            None => value.parse::<f64>().ok().filter(|n| !n.is_nan()),
+        }
+    }
+
+    /// 按设置去掉字符串比较时的首尾空白
+    fn trim_value<'v>(&self, value: &'v str) -> &'v str {
+        if self.trim == Some(true) {
+            value.trim()
+        } else {
+            value
+        }
+    }
+
+    /// 确定自动检测类型的排序键实际按数字还是字符串排序
+    /// 
+    /// 逐对判断会使同一列有的按数字、有的按字符串比较，顺序不一致甚至成环。
+    /// 这里对每个 [`DataType::Auto`] 键只判断一次：采样的非空值中至少一半能解析为数字时
+    /// 按数字排序（其余值按空值处理），否则按字符串排序
+    pub fn resolve_auto_types<'r, 'a: 'r>(&self, sample: impl IntoIterator<Item = &'r CsvRecord<'a>>) -> Self {
+        let mut options = self.clone();
+        let auto_columns: Vec<usize> = options
+            .keys
+            .iter()
+            .filter(|key| key.data_type == DataType::Auto)
+            .map(|key| key.column)
+            .collect();
+        if auto_columns.is_empty() {
+            return options;
+        }
+
+        // 每列的 (非空值数, 可解析为数字的值数)
+        let mut counts = vec![(0usize, 0usize); auto_columns.len()];
+        for record in sample.into_iter().take(AUTO_SAMPLE_ROWS) {
+            for (count, &column) in counts.iter_mut().zip(&auto_columns) {
+                match record.fields.get(column).map(|f| self.trim_value(f.as_ref())) {
+                    Some(value) if !self.is_null_token(value) => {
+                        count.0 += 1;
+                        if self.parse_number(value).is_some() {
+                            count.1 += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for key in options.keys.iter_mut().filter(|key| key.data_type == DataType::Auto) {
+            let position = auto_columns.iter().position(|&column| column == key.column).unwrap_or(0);
+            let (non_empty, numeric) = counts[position];
+            key.data_type = if non_empty > 0 && numeric as f64 >= non_empty as f64 * AUTO_NUMERIC_RATIO {
+                DataType::Number
+            } else {
+                DataType::String
+            };
+        }
+        options
+    }
+
+    /// 设置是否稳定排序（默认开启）
+    /// 
+    /// 开启时键相同的行总是按原始行号升序排列，与排序算法无关；
+    /// 关闭后不再保证相同键的顺序，排序稍快
+    pub fn with_stable(mut self, stable: bool) -> Self {
+        self.stable = stable;
+        self
+    }
 }
 
 /// 排序后的结果
@@ -158,7 +376,16 @@ impl Sorter {
     }
 
     /// 对记录进行排序
+    /// 
+    /// 自动检测类型的排序键先按这些记录确定类型，见 [`SortOptions::resolve_auto_types`]
     pub fn sort(&self, records: Vec<(usize, CsvRecord<'static>)>) -> Vec<SortedRecord> {
+        let options = self.options.resolve_auto_types(records.iter().map(|(_, record)| record));
+        let sorter = Sorter::new(options);
+        sorter.sort_resolved(records)
+    }
+
+    /// 对记录进行排序（排序键的类型已确定）
+    fn sort_resolved(&self, records: Vec<(usize, CsvRecord<'static>)>) -> Vec<SortedRecord> {
         let mut indexed: Vec<SortedRecord> = records
             .into_iter()
             .map(|(idx, record)| SortedRecord {
@@ -167,13 +394,40 @@ impl Sorter {
             })
             .collect();
 
-        indexed.sort_by(|a, b| self.compare_records(&a.record, &b.record));
+        if self.options.stable {
+            indexed.sort_by(|a, b| self.compare_records(a, b));
+        } else {
+            indexed.sort_unstable_by(|a, b| self.compare_records(a, b));
+        }
 
         indexed
     }
 
+    /// 部分排序：只返回排在最前面的 `limit` 条记录
+    /// 
+    /// 用大小为 `limit` 的堆逐条筛选，时间复杂度 O(n log k)，内存只保留k条记录；
+    /// 结果与完整排序后截断相同
+    pub fn top_n<'r>(
+        &self,
+        records: impl IntoIterator<Item = (usize, CsvRecord<'r>)>,
+        limit: usize,
+    ) -> Vec<SortedRecord> {
+        let mut top = TopN::new(self, limit);
+        for (row, record) in records {
+            top.push(row, &record);
+        }
+        top.into_sorted()
+    }
+
     /// 比较两条记录
-    fn compare_records(&self, a: &CsvRecord, b: &CsvRecord) -> Ordering {
+    fn compare_records(&self, a: &SortedRecord, b: &SortedRecord) -> Ordering {
+        self.compare_rows(a.original_row, &a.record, b.original_row, &b.record)
+    }
+
+    /// 比较两行
+    /// 
+    /// 稳定排序时所有键都相同的行再按原始行号比较
+    fn compare_rows(&self, a_row: usize, a: &CsvRecord, b_row: usize, b: &CsvRecord) -> Ordering {
         for key in &self.options.keys {
             let field_a = a.fields.get(key.column).map(|f| f.as_ref());
             let field_b = b.fields.get(key.column).map(|f| f.as_ref());
@@ -184,7 +438,16 @@ impl Sorter {
                 return ordering;
             }
         }
-        Ordering::Equal
+        self.tie_break(a_row, b_row)
+    }
+
+    /// 键相同时的顺序
+    fn tie_break(&self, a: usize, b: usize) -> Ordering {
+        if self.options.stable {
+            a.cmp(&b)
+        } else {
+            Ordering::Equal
+        }
     }
 
     /// 比较两个字段值
@@ -207,9 +470,11 @@ impl Sorter {
                 };
             }
             (Some(a_str), Some(b_str)) => {
-                // 检查空字符串 - 也不受排序方向影响
-                let a_empty = a_str.is_empty();
-                let b_empty = b_str.is_empty();
+                let a_str = self.options.trim_value(a_str);
+                let b_str = self.options.trim_value(b_str);
+                // 检查空字符串（数字列中还包括无法解析为数字的值） - 也不受排序方向影响
+                let a_empty = self.is_null(a_str, key);
+                let b_empty = self.is_null(b_str, key);
                 
                 if a_empty && b_empty {
                     return Ordering::Equal;
@@ -237,6 +502,12 @@ impl Sorter {
         }
     }
 
+    /// 是否按空值处理：空字符串、空值标记，或数字列中无法解析为数字（包括NaN）的值
+    fn is_null(&self, value: &str, key: &SortKey) -> bool {
+        self.options.is_null_token(value)
+            || (key.data_type == DataType::Number && self.options.parse_number(value).is_none())
+    }
+
     /// 比较两个非空值
     fn compare_values(&self, a: &str, b: &str, key: &SortKey) -> Ordering {
         match key.data_type {
@@ -244,7 +515,7 @@ impl Sorter {
             DataType::Number => self.compare_numbers(a, b),
             DataType::Auto => {
                 // 尝试作为数字比较
-                if let (Ok(num_a), Ok(num_b)) = (a.parse::<f64>(), b.parse::<f64>()) {
+                if let (Some(num_a), Some(num_b)) = (self.options.parse_number(a), self.options.parse_number(b)) {
                     num_a.partial_cmp(&num_b).unwrap_or(Ordering::Equal)
                 } else {
                     self.compare_strings(a, b)
@@ -255,6 +526,17 @@ impl Sorter {
 
     /// 字符串比较
     fn compare_strings(&self, a: &str, b: &str) -> Ordering {
+        match self.options.collation {
+            Collation::Binary => self.compare_case(a, b),
+            // 先忽略重音比较，只有重音不同时再按原文比较，保证顺序确定
+            Collation::AccentFolding => self
+                .compare_case(&fold_accents(a), &fold_accents(b))
+                .then_with(|| self.compare_case(a, b)),
+        }
+    }
+
+    /// 按大小写敏感设置比较
+    fn compare_case(&self, a: &str, b: &str) -> Ordering {
         if self.options.case_sensitive {
             a.cmp(b)
         } else {
@@ -262,53 +544,130 @@ impl Sorter {
         }
     }
 
-    /// 数字比较
+    /// 数字比较（无法解析的值已在 `compare_fields` 中按空值处理）
     fn compare_numbers(&self, a: &str, b: &str) -> Ordering {
-        let num_a = a.parse::<f64>().unwrap_or(f64::NAN);
-        let num_b = b.parse::<f64>().unwrap_or(f64::NAN);
-
-        // 处理 NaN
-        match (num_a.is_nan(), num_b.is_nan()) {
-            (true, true) => Ordering::Equal,
-            (true, false) => {
-                if self.options.nulls_last {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
-            }
-            (false, true) => {
-                if self.options.nulls_last {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
+        let num_a = self.options.parse_number(a).unwrap_or(f64::NAN);
+        let num_b = self.options.parse_number(b).unwrap_or(f64::NAN);
+        num_a.partial_cmp(&num_b).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// 部分排序时堆中的记录，按排序结果比较（堆顶是当前保留的最后一条）
+struct HeapEntry<'s> {
+    sorter: &'s Sorter,
+    record: SortedRecord,
+}
+
+impl Ord for HeapEntry<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorter.compare_records(&self.record, &other.record)
+    }
+}
+
+impl PartialOrd for HeapEntry<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HeapEntry<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry<'_> {}
+
+/// 保留排在最前面的k条记录
+struct TopN<'s> {
+    sorter: &'s Sorter,
+    limit: usize,
+    heap: BinaryHeap<HeapEntry<'s>>,
+}
+
+impl<'s> TopN<'s> {
+    fn new(sorter: &'s Sorter, limit: usize) -> Self {
+        Self {
+            sorter,
+            limit,
+            heap: BinaryHeap::with_capacity(limit.saturating_add(1).min(TOP_N_PREALLOC_ROWS)),
+        }
+    }
+
+    /// 加入一行；只有能进入前k条时才复制记录
+    fn push(&mut self, row: usize, record: &CsvRecord) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.heap.len() == self.limit {
+            let Some(last) = self.heap.peek() else {
+                return;
+            };
+            let ordering = self.sorter.compare_rows(row, record, last.record.original_row, &last.record.record);
+            if ordering != Ordering::Less {
+                return;
             }
-            (false, false) => num_a.partial_cmp(&num_b).unwrap_or(Ordering::Equal),
+            self.heap.pop();
         }
+        self.heap.push(HeapEntry {
+            sorter: self.sorter,
+            record: SortedRecord {
+                original_row: row,
+                record: record.to_owned(),
+            },
+        });
+    }
+
+    /// 按排序结果返回保留的记录
+    fn into_sorted(self) -> Vec<SortedRecord> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|entry| entry.record)
+            .collect()
     }
 }
 
 /// 从 CsvReader 读取并排序数据
+/// 
+/// 按单列排序且读取器带有该列的列索引时，只读取该列的字节进行排序，
+/// 再解析排序结果中需要输出的行；
+/// 指定 `limit` 且少于总行数时逐行扫描一遍，只用堆保留前 `limit` 行，不排序整个文件
 pub fn sort_csv_data(
     reader: &CsvReader,
     options: &SortOptions,
     limit: Option<usize>,
 ) -> Result<Vec<SortedRecord>> {
-    // 读取所有数据
-    let pattern = SearchPattern::regex(".*", true)?;
-    let search_opts = SearchOptions::new(pattern);
-    
-    let results = reader.search(&search_opts)?;
-    
-    let records: Vec<(usize, CsvRecord<'static>)> = results
-        .into_iter()
-        .map(|r| (r.row_number, r.record))
+    let options = &resolve_auto_types(reader, options)?;
+    if let [key] = options.keys.as_slice() {
+        if let Some(column_index) = reader.column_index(key.column) {
+            return Ok(sort_by_column_index(reader.data(), reader.line_format(), column_index, options, limit));
+        }
+    }
+
+    let total_rows = reader.info().total_rows;
+    if let Some(limit) = limit.filter(|&n| n < total_rows) {
+        let sorter = Sorter::new(options.clone());
+        let mut top = TopN::new(&sorter, limit);
+        let _scan = reader.sequential_scan();
+        for (row, record) in reader.rows().enumerate() {
+            top.push(row, &record);
+        }
+        return Ok(top.into_sorted());
+    }
+
+    // 逐行读取所有数据直到文件末尾（快速打开时总行数只是估算值）
+    let scan = reader.sequential_scan();
+    let records: Vec<(usize, CsvRecord<'static>)> = reader
+        .rows()
+        .enumerate()
+        .map(|(row, record)| (row, record.to_owned()))
         .collect();
+    drop(scan);
 
     // 排序
     let sorter = Sorter::new(options.clone());
-    let mut sorted = sorter.sort(records);
+    let mut sorted = sorter.sort_resolved(records);
 
     // 限制结果数量
     if let Some(n) = limit {
@@ -318,6 +677,139 @@ pub fn sort_csv_data(
     Ok(sorted)
 }
 
+/// 排序并直接写入CSV文件
+/// 
+/// 结果与 [`sort_csv_data`] 相同，但不保留解析后的记录：扫描时只记下每行的字节位置和排序键
+/// （键直接引用内存映射的数据），对行号排序后按顺序从内存映射中取出每行写出。
+/// 内存占用大约是每行几十字节，与列数无关。
+/// 输出分隔符与源文件相同且不总是引用字段时原样复制每行的字节，保留原有的引号写法；
+/// 否则重新解析该行并按写入选项转义。
+/// 指定的 `limit` 少于总行数时只保留前 `limit` 行再写出
+/// 
+/// # 参数
+/// - `reader`: 源文件读取器
+/// - `options`: 排序选项
+/// - `limit`: 最多输出的行数
+/// - `output_path`: 输出文件路径
+/// - `write_options`: 写入选项（`preserve_line_ending` 时沿用源文件的行结束符）
+pub fn sort_to_file<P: AsRef<Path>>(
+    reader: &CsvReader,
+    options: &SortOptions,
+    limit: Option<usize>,
+    output_path: P,
+    write_options: &WriteOptions,
+) -> Result<SaveStats> {
+    let total_rows = reader.info().total_rows;
+
+    if limit.is_some_and(|n| n < total_rows) {
+        // 部分排序只保留少量记录，再扫描一遍找出这些行的位置
+        let sorted = sort_csv_data(reader, options, limit)?;
+        let rows: Vec<usize> = sorted.iter().map(|r| r.original_row).collect();
+        let spans = reader.row_spans(&rows);
+        write_spans_to_file(reader, spans, output_path.as_ref(), write_options)
+    } else {
+        let sorter = Sorter::new(resolve_auto_types(reader, options)?);
+        let (spans, keys) = collect_sort_keys(reader, &sorter.options)?;
+        let key_count = sorter.options.keys.len();
+        let mut order: Vec<usize> = (0..spans.len()).collect();
+        let compare = |&a: &usize, &b: &usize| {
+            let keys_a = &keys[a * key_count..(a + 1) * key_count];
+            let keys_b = &keys[b * key_count..(b + 1) * key_count];
+            sorter.options.keys.iter()
+                .zip(keys_a.iter().zip(keys_b))
+                .map(|(key, (field_a, field_b))| sorter.compare_fields(field_a.as_deref(), field_b.as_deref(), key))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| sorter.tie_break(a, b))
+        };
+        if options.stable {
+            order.sort_by(compare);
+        } else {
+            order.sort_unstable_by(compare);
+        }
+        drop(keys);
+        write_spans_to_file(reader, order.iter().map(|&row| spans[row]), output_path.as_ref(), write_options)
+    }
+}
+
+/// 按行连续存放的排序键值，缺少该列的行为None
+type SortKeys<'r> = Vec<Option<Cow<'r, str>>>;
+
+/// 扫描一遍文件，记下每行的字节位置和各排序键的值
+/// 
+/// 键按行连续存放（每行 `options.keys.len()` 个），缺少该列的行为None。
+/// 按单列排序且读取器带有该列的列索引时直接取列索引中的值，不解析整行
+fn collect_sort_keys<'r>(
+    reader: &'r CsvReader,
+    options: &SortOptions,
+) -> Result<(Vec<(usize, usize)>, SortKeys<'r>)> {
+    let _scan = reader.sequential_scan();
+    let data = reader.data();
+    let spans: Vec<(usize, usize)> = reader.line_spans().collect();
+
+    if let [key] = options.keys.as_slice() {
+        if let Some(column_index) = reader.column_index(key.column) {
+            let mut values = column_index.values(data).into_iter();
+            let keys = spans.iter().map(|_| values.next()).collect();
+            return Ok((spans, keys));
+        }
+    }
+
+    let mut keys = Vec::with_capacity(spans.len() * options.keys.len());
+    for &(start, end) in &spans {
+        let record = reader.parse_line(&data[start..end]);
+        keys.extend(options.keys.iter().map(|key| record.fields.get(key.column).cloned()));
+    }
+    Ok((spans, keys))
+}
+
+/// 按文件开头的采样行确定自动检测类型的排序键
+fn resolve_auto_types(reader: &CsvReader, options: &SortOptions) -> Result<SortOptions> {
+    if options.keys.iter().all(|key| key.data_type != DataType::Auto) {
+        return Ok(options.clone());
+    }
+    let sample = reader.read_rows(0, AUTO_SAMPLE_ROWS)?;
+    Ok(options.resolve_auto_types(&sample))
+}
+
+/// 借助列索引按单列排序
+fn sort_by_column_index(
+    data: &[u8],
+    format: LineFormat,
+    column_index: &ColumnIndex,
+    options: &SortOptions,
+    limit: Option<usize>,
+) -> Vec<SortedRecord> {
+    let sorter = Sorter::new(options.clone());
+    let key = &options.keys[0];
+    let values = column_index.values(data);
+
+    let mut rows: Vec<usize> = (0..values.len()).collect();
+    let compare = |&a: &usize, &b: &usize| {
+        sorter
+            .compare_fields(Some(values[a].as_ref()), Some(values[b].as_ref()), key)
+            .then_with(|| sorter.tie_break(a, b))
+    };
+    if options.stable {
+        rows.sort_by(compare);
+    } else {
+        rows.sort_unstable_by(compare);
+    }
+
+    if let Some(n) = limit {
+        rows.truncate(n);
+    }
+
+    rows.into_iter()
+        .filter_map(|row| {
+            column_index.record(data, format, row).map(|record| SortedRecord {
+                original_row: row,
+                record: record.to_owned(),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,5 +854,64 @@ mod tests {
         
         assert_eq!(sorter.compare_values("Apple", "apple", &key), Ordering::Equal);
     }
+
+    #[test]
+    fn test_collation() {
+        assert_eq!(Collation::parse("binary"), Some(Collation::Binary));
+        assert_eq!(Collation::parse("accent-folding"), Some(Collation::AccentFolding));
+        assert_eq!(Collation::parse("icu"), None);
+        assert_eq!(fold_accents("Crème Brûlée"), "Creme Brulee");
+        assert_eq!(fold_accents("Straße"), "Strasse");
+
+        let sorter = Sorter::new(SortOptions::new().with_collation(Collation::AccentFolding));
+        let key = SortKey::new(0, SortOrder::Ascending, DataType::String);
+        assert_eq!(sorter.compare_values("é", "f", &key), Ordering::Less);
+        assert_eq!(sorter.compare_values("e", "é", &key), Ordering::Less);
+        assert_eq!(sorter.compare_values("é", "é", &key), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_trim_and_numeric_cleanup() {
+        let number = SortKey::new(0, SortOrder::Ascending, DataType::Number);
+        let string = SortKey::new(0, SortOrder::Ascending, DataType::String);
+
+        // 默认只对数字去掉空白
+        let sorter = Sorter::new(SortOptions::new());
+        assert_eq!(sorter.compare_fields(Some("  42 "), Some("5"), &number), Ordering::Greater);
+        assert_eq!(sorter.compare_fields(Some(" b"), Some("a"), &string), Ordering::Less);
+        // 没有清理规则时千位分隔符无法解析，按空值排在最后
+        assert_eq!(sorter.compare_fields(Some("1,234"), Some("5"), &number), Ordering::Greater);
+        assert_eq!(sorter.compare_fields(Some("1,234"), Some("99999"), &number), Ordering::Greater);
+
+        let sorter = Sorter::new(SortOptions::new().with_trim(true));
+        assert_eq!(sorter.compare_fields(Some(" b"), Some("a"), &string), Ordering::Greater);
+
+        let sorter = Sorter::new(SortOptions::new().with_trim(false));
+        assert_eq!(sorter.compare_fields(Some("  42 "), Some("5"), &number), Ordering::Greater);
+        assert_eq!(sorter.compare_fields(Some("  42 "), Some("99"), &number), Ordering::Greater);
+
+        let sorter = Sorter::new(SortOptions::new().with_numeric_cleanup(NumericCleanup::new()));
+        assert_eq!(sorter.compare_fields(Some("1,234"), Some("99999"), &number), Ordering::Less);
+        assert_eq!(sorter.compare_fields(Some("$3.50"), Some("3.4"), &number), Ordering::Greater);
+
+        let sorter = Sorter::new(
+            SortOptions::new().with_numeric_cleanup(NumericCleanup::new().with_decimal_comma(true)),
+        );
+        assert_eq!(sorter.compare_fields(Some("1.234,56"), Some("1.234,5"), &number), Ordering::Greater);
+        assert_eq!(sorter.compare_fields(Some("1.234,56"), Some("2.000"), &number), Ordering::Less);
+    }
+
+    #[test]
+    fn test_null_values() {
+        let key = SortKey::new(0, SortOrder::Ascending, DataType::String);
+        let sorter = Sorter::new(SortOptions::new());
+        assert_eq!(sorter.compare_fields(Some("NA"), Some("b"), &key), Ordering::Less);
+
+        let sorter = Sorter::new(SortOptions::new().with_null_values(vec!["NA".into(), "-".into()]));
+        assert_eq!(sorter.compare_fields(Some("na"), Some("b"), &key), Ordering::Greater);
+        assert_eq!(sorter.compare_fields(Some(" - "), Some("b"), &key), Ordering::Greater);
+        assert_eq!(sorter.compare_fields(Some("NA"), Some(""), &key), Ordering::Equal);
+        assert_eq!(sorter.compare_fields(Some("NAN"), Some("b"), &key), Ordering::Less);
+    }
 }
 
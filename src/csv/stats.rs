@@ -0,0 +1,468 @@
+//! 列统计模块
+//!
+//! 按列统计值的出现次数等信息，只需顺序扫描一遍文件
+//!
+//! 精确分位数需要保存整列的数值（每个值8字节）；大文件可以改用近似计算（t-digest），
+//! 内存占用与行数无关
+
+use crate::csv::schema::is_null_value;
+use crate::csv::tdigest::TDigest;
+use crate::csv::{CsvReader, FloatFormat, NumericCleanup, SearchOutcome};
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 每扫描多少行检查一次取消标志
+const SCAN_CHUNK_ROWS: usize = 10_000;
+
+/// 统计一列中每个不同值的出现次数
+///
+/// 读取器带有该列的列索引时只读取该列的字节。缺少该列的行按空值统计。
+///
+/// # 参数
+/// - `reader`: CSV读取器
+/// - `column`: 列索引（从0开始）
+/// - `ignore_case`: 不区分大小写（结果使用该值第一次出现时的写法）
+///
+/// # 返回
+/// `(值, 次数)` 列表，按次数降序排列，次数相同时按值升序排列
+pub fn value_counts(
+    reader: &CsvReader,
+    column: usize,
+    ignore_case: bool,
+) -> Result<Vec<(String, usize)>> {
+    let mut counter = ValueCounter::new(ignore_case);
+    scan_column(reader, column, None, |value| counter.add(value))?;
+    Ok(counter.into_sorted())
+}
+
+/// 数值列的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericStats {
+    /// 最小值
+    pub min: f64,
+    /// 最大值
+    pub max: f64,
+    /// 平均值
+    pub mean: f64,
+    /// 最小值在源数据中的写法（去掉首尾空白）
+    pub min_text: String,
+    /// 最大值在源数据中的写法（去掉首尾空白）
+    pub max_text: String,
+    /// 请求的百分位数 `(百分位, 值)`，按 [`StatsOptions::percentiles`] 的顺序排列
+    pub percentiles: Vec<(f64, f64)>,
+}
+
+impl NumericStats {
+    /// 按指定格式输出最小值
+    pub fn format_min(&self, format: FloatFormat) -> String {
+        format.format_text(&self.min_text, self.min)
+    }
+
+    /// 按指定格式输出最大值
+    pub fn format_max(&self, format: FloatFormat) -> String {
+        format.format_text(&self.max_text, self.max)
+    }
+
+    /// 按指定格式输出平均值（平均值是计算结果，`AsWritten` 时使用最短写法）
+    pub fn format_mean(&self, format: FloatFormat) -> String {
+        format.format(self.mean)
+    }
+
+    /// 第 `percentile` 百分位数（未请求该百分位时返回None）
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        self.percentiles.iter().find(|(p, _)| *p == percentile).map(|&(_, value)| value)
+    }
+}
+
+/// 列统计选项
+#[derive(Debug, Clone, Default)]
+pub struct StatsOptions {
+    /// 数字解析前的清理规则，None时按原样解析
+    pub numeric_cleanup: Option<NumericCleanup>,
+    /// 要计算的百分位（0到100，如 `[50.0, 90.0, 99.0]`），为空时不计算
+    pub percentiles: Vec<f64>,
+    /// 用t-digest近似计算百分位，不保存整列数值
+    pub approx: bool,
+}
+
+impl StatsOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置数字解析前的清理规则
+    pub fn with_numeric_cleanup(mut self, cleanup: NumericCleanup) -> Self {
+        self.numeric_cleanup = Some(cleanup);
+        self
+    }
+
+    /// 设置要计算的百分位（超出0到100的值被截断）
+    pub fn with_percentiles(mut self, percentiles: Vec<f64>) -> Self {
+        self.percentiles = percentiles;
+        self
+    }
+
+    /// 设置是否近似计算百分位
+    pub fn with_approx(mut self, approx: bool) -> Self {
+        self.approx = approx;
+        self
+    }
+}
+
+/// 数值列的百分位计算：精确模式保存所有数值，近似模式使用t-digest
+enum Quantiles {
+    Exact(Vec<f64>),
+    Approx(TDigest),
+}
+
+impl Quantiles {
+    fn add(&mut self, value: f64) {
+        match self {
+            Quantiles::Exact(values) => values.push(value),
+            Quantiles::Approx(digest) => digest.add(value),
+        }
+    }
+
+    /// 计算各百分位（精确模式在相邻两个值之间线性插值）
+    fn compute(self, percentiles: &[f64]) -> Vec<(f64, f64)> {
+        match self {
+            Quantiles::Exact(mut values) => {
+                values.sort_unstable_by(f64::total_cmp);
+                percentiles.iter()
+                    .map(|&p| (p, exact_percentile(&values, p)))
+                    .collect()
+            }
+            Quantiles::Approx(mut digest) => percentiles.iter()
+                .map(|&p| (p, digest.quantile(p.clamp(0.0, 100.0) / 100.0).unwrap_or(f64::NAN)))
+                .collect(),
+        }
+    }
+}
+
+/// 已排序数值的第 `percentile` 百分位数
+fn exact_percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let rank = percentile.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// 单列的汇总统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// 列索引（从0开始）
+    pub column: usize,
+    /// 统计的行数
+    pub rows: usize,
+    /// 空值数量（空字符串或 `null`，缺少该列的行也算空值）
+    pub null_count: usize,
+    /// 不同非空值的数量
+    pub distinct_count: usize,
+    /// 所有非空值都是数字时的数值统计（没有非空值时为None）
+    pub numeric: Option<NumericStats>,
+    /// 每个非空值的出现次数，按次数降序、值升序排列
+    pub value_counts: Vec<(String, usize)>,
+}
+
+impl ColumnStats {
+    /// 是否为数值列
+    pub fn is_numeric(&self) -> bool {
+        self.numeric.is_some()
+    }
+
+    /// 出现次数最多的 `n` 个值
+    pub fn top_values(&self, n: usize) -> &[(String, usize)] {
+        &self.value_counts[..n.min(self.value_counts.len())]
+    }
+}
+
+/// 扫描一列计算汇总统计
+///
+/// 按块读取，不会一次载入整个文件；每读完一块检查一次取消标志，
+/// 被取消时返回已扫描部分的统计
+///
+/// # 参数
+/// - `reader`: CSV读取器
+/// - `column`: 列索引（从0开始）
+/// - `cancel_flag`: 取消标志，置为true后提前结束
+pub fn column_stats(
+    reader: &CsvReader,
+    column: usize,
+    cancel_flag: Option<&AtomicBool>,
+) -> Result<SearchOutcome<ColumnStats>> {
+    column_stats_with_options(reader, column, &StatsOptions::new(), cancel_flag)
+}
+
+/// 扫描一列计算汇总统计，数字按清理规则解析（与排序的 `--numeric-cleanup` 相同）
+///
+/// `1,234`、`$3.50` 这样的值计入数值统计，最小值和最大值保留原始写法
+pub fn column_stats_with_cleanup(
+    reader: &CsvReader,
+    column: usize,
+    cleanup: &NumericCleanup,
+    cancel_flag: Option<&AtomicBool>,
+) -> Result<SearchOutcome<ColumnStats>> {
+    let options = StatsOptions::new().with_numeric_cleanup(cleanup.clone());
+    column_stats_with_options(reader, column, &options, cancel_flag)
+}
+
+/// 按选项扫描一列计算汇总统计，可以同时计算百分位数
+///
+/// 精确百分位需要保存整列的数值，约占 8 字节 × 行数的内存；
+/// `approx` 时改用t-digest，内存固定，结果有少量误差。
+/// 一旦遇到非数字的值就不再收集数值，非数值列不会占用额外内存
+pub fn column_stats_with_options(
+    reader: &CsvReader,
+    column: usize,
+    options: &StatsOptions,
+    cancel_flag: Option<&AtomicBool>,
+) -> Result<SearchOutcome<ColumnStats>> {
+    let cleanup = options.numeric_cleanup.as_ref();
+    let mut quantiles = match (options.percentiles.is_empty(), options.approx) {
+        (true, _) => None,
+        (false, false) => Some(Quantiles::Exact(Vec::new())),
+        (false, true) => Some(Quantiles::Approx(TDigest::default())),
+    };
+    let mut counter = ValueCounter::new(false);
+    let mut rows = 0;
+    let mut null_count = 0;
+    // 数值列的（最小值, 最大值, 总和, 个数），遇到非数字的值后为None
+    let mut numeric = Some((f64::INFINITY, f64::NEG_INFINITY, 0.0, 0usize));
+    // 最小值和最大值的原始写法
+    let mut min_text = String::new();
+    let mut max_text = String::new();
+
+    let cancelled = scan_column(reader, column, cancel_flag, |value| {
+        rows += 1;
+        if is_null_value(value) {
+            null_count += 1;
+            return;
+        }
+        counter.add(value);
+        if let Some((min, max, sum, count)) = &mut numeric {
+            let parsed = match cleanup {
+                Some(cleanup) => cleanup.parse(value),
+                None => value.trim().parse::<f64>().ok(),
+            };
+            match parsed {
+                Some(n) if n.is_finite() => {
+                    if n < *min {
+                        *min = n;
+                        min_text = value.trim().to_string();
+                    }
+                    if n > *max {
+                        *max = n;
+                        max_text = value.trim().to_string();
+                    }
+                    *sum += n;
+                    *count += 1;
+                    if let Some(quantiles) = &mut quantiles {
+                        quantiles.add(n);
+                    }
+                }
+                _ => {
+                    numeric = None;
+                    quantiles = None;
+                }
+            }
+        }
+    })?;
+
+    let value_counts = counter.into_sorted();
+    let numeric = numeric
+        .filter(|&(_, _, _, count)| count > 0)
+        .map(|(min, max, sum, count)| NumericStats {
+            min,
+            max,
+            mean: sum / count as f64,
+            min_text,
+            max_text,
+            percentiles: quantiles.map_or_else(Vec::new, |q| q.compute(&options.percentiles)),
+        });
+    let stats = ColumnStats {
+        column,
+        rows,
+        null_count,
+        distinct_count: value_counts.len(),
+        numeric,
+        value_counts,
+    };
+    Ok(SearchOutcome { value: stats, cancelled })
+}
+
+/// 直方图的一个区间 `[start, end)`（最后一个区间包含 `end`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    /// 区间起点
+    pub start: f64,
+    /// 区间终点
+    pub end: f64,
+    /// 落在区间内的值的个数
+    pub count: usize,
+}
+
+/// 数值列的直方图
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// 等宽区间，按起点升序排列
+    pub buckets: Vec<Bucket>,
+    /// 空值数量
+    pub null_count: usize,
+    /// 无法解析为数字而跳过的值的数量
+    pub skipped: usize,
+    /// 超出指定范围的值的数量
+    pub out_of_range: usize,
+}
+
+/// 把一列的数值分成 `bins` 个等宽区间并计数
+///
+/// `range` 未指定的一端先扫描一遍该列取最小值/最大值，之后再扫描一遍计数，
+/// 两遍都按块读取，不保存整列数值。所有值相同时只有一个宽度为0的区间
+///
+/// # 参数
+/// - `reader`: CSV读取器
+/// - `column`: 列索引（从0开始）
+/// - `bins`: 区间个数（至少为1）
+/// - `range`: `(最小值, 最大值)`，超出范围的值计入 `out_of_range`
+pub fn histogram(
+    reader: &CsvReader,
+    column: usize,
+    bins: usize,
+    range: (Option<f64>, Option<f64>),
+) -> Result<Histogram> {
+    let parse = |value: &str| value.trim().parse::<f64>().ok().filter(|n| n.is_finite());
+
+    let (min, max) = match range {
+        (Some(min), Some(max)) => (min, max),
+        _ => {
+            let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+            scan_column(reader, column, None, |value| {
+                if let Some(n) = parse(value) {
+                    min = min.min(n);
+                    max = max.max(n);
+                }
+            })?;
+            (range.0.unwrap_or(min), range.1.unwrap_or(max))
+        }
+    };
+
+    let mut histogram = Histogram { buckets: Vec::new(), null_count: 0, skipped: 0, out_of_range: 0 };
+    let bins = if min < max { bins.max(1) } else { 1 };
+    let mut counts = vec![0usize; bins];
+    let width = if min < max { (max - min) / bins as f64 } else { 0.0 };
+    scan_column(reader, column, None, |value| {
+        if is_null_value(value) {
+            histogram.null_count += 1;
+            return;
+        }
+        match parse(value) {
+            None => histogram.skipped += 1,
+            Some(n) if n < min || n > max => histogram.out_of_range += 1,
+            Some(n) => {
+                let index = if width > 0.0 { ((n - min) / width) as usize } else { 0 };
+                counts[index.min(bins - 1)] += 1;
+            }
+        }
+    })?;
+
+    // 没有数值时不生成区间
+    if min <= max {
+        histogram.buckets = counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| Bucket {
+                start: min + width * i as f64,
+                end: if i + 1 == bins { max } else { min + width * (i + 1) as f64 },
+                count,
+            })
+            .collect();
+    }
+    Ok(histogram)
+}
+
+/// 逐行读取一列的值（缺少该列的行为空值），返回是否因取消而提前结束
+///
+/// 读取器带有该列的列索引时只读取该列的字节，否则逐行解析到文件末尾
+/// （快速打开时总行数只是估算值，不以它为界）
+fn scan_column(
+    reader: &CsvReader,
+    column: usize,
+    cancel_flag: Option<&AtomicBool>,
+    mut visit: impl FnMut(&str),
+) -> Result<bool> {
+    let cancelled = || cancel_flag.is_some_and(|flag| flag.load(Ordering::Relaxed));
+
+    if let Some(column_index) = reader.column_index(column) {
+        let data = reader.data();
+        for row in 0..column_index.len() {
+            if row % SCAN_CHUNK_ROWS == 0 && cancelled() {
+                return Ok(true);
+            }
+            if let Some(value) = column_index.field(data, row) {
+                visit(&value);
+            }
+        }
+    } else {
+        let _scan = reader.sequential_scan();
+        for (row, record) in reader.rows().enumerate() {
+            if row % SCAN_CHUNK_ROWS == 0 && cancelled() {
+                return Ok(true);
+            }
+            visit(record.fields.get(column).map_or("", |f| f.as_ref()));
+        }
+    }
+
+    Ok(false)
+}
+
+/// 值计数器
+struct ValueCounter {
+    ignore_case: bool,
+    /// 值（不区分大小写时为小写值）到出现次数
+    counts: HashMap<String, usize>,
+    /// 不区分大小写时，小写值到第一次出现时的写法
+    spellings: HashMap<String, String>,
+}
+
+impl ValueCounter {
+    fn new(ignore_case: bool) -> Self {
+        Self {
+            ignore_case,
+            counts: HashMap::new(),
+            spellings: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: &str) {
+        if !self.ignore_case {
+            match self.counts.get_mut(value) {
+                Some(count) => *count += 1,
+                None => {
+                    self.counts.insert(value.to_string(), 1);
+                }
+            }
+            return;
+        }
+
+        let key = value.to_lowercase();
+        self.spellings.entry(key.clone()).or_insert_with(|| value.to_string());
+        *self.counts.entry(key).or_insert(0) += 1;
+    }
+
+    fn into_sorted(mut self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = self.counts
+            .into_iter()
+            .map(|(key, count)| match self.spellings.remove(&key) {
+                Some(spelling) => (spelling, count),
+                None => (key, count),
+            })
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+}
@@ -0,0 +1,167 @@
+//! 近似分位数（t-digest）
+//!
+//! 把数值合并成有限个质心，两端的质心更小以保证尾部分位数（如p99）的精度。
+//! 内存占用只与压缩参数有关，与数据量无关，适合不便保存整列数值的大文件
+
+/// 默认压缩参数，质心数量大致不超过此值
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+/// 质心：一组相邻值的平均值和个数
+#[derive(Debug, Clone, Copy)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// 流式分位数估计
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    /// 按平均值排列的质心
+    centroids: Vec<Centroid>,
+    /// 尚未合并的新值
+    buffer: Vec<f64>,
+    count: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    /// 创建估计器，`compression` 越大越精确、占用内存越多
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression: compression.max(10.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// 加入一个值（NaN被忽略）
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.buffer.push(value);
+        self.count += 1.0;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= self.compression as usize * 5 {
+            self.compress();
+        }
+    }
+
+    /// 已加入的值的个数
+    pub fn count(&self) -> usize {
+        self.count as usize
+    }
+
+    /// 估计第 `q` 分位数（`q` 在 0 到 1 之间），没有值时返回None
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.compress();
+        let first = self.centroids.first()?;
+        let last = self.centroids.last()?;
+        if self.centroids.len() == 1 {
+            return Some(first.mean);
+        }
+        if q <= 0.0 {
+            return Some(self.min);
+        }
+        if q >= 1.0 {
+            return Some(self.max);
+        }
+
+        // 每个质心代表的值以其平均值为中心，在相邻质心的中心之间线性插值
+        let position = q * self.count;
+        let first_center = first.weight / 2.0;
+        if position <= first_center {
+            return Some(self.min + (first.mean - self.min) * position / first_center);
+        }
+        let last_center = self.count - last.weight / 2.0;
+        if position >= last_center {
+            let span = self.count - last_center;
+            return Some(last.mean + (self.max - last.mean) * (position - last_center) / span);
+        }
+
+        let mut center = first_center;
+        for pair in self.centroids.windows(2) {
+            let next_center = center + (pair[0].weight + pair[1].weight) / 2.0;
+            if position < next_center {
+                let fraction = (position - center) / (next_center - center);
+                let value = pair[0].mean + (pair[1].mean - pair[0].mean) * fraction;
+                return Some(value.clamp(self.min, self.max));
+            }
+            center = next_center;
+        }
+        Some(last.mean)
+    }
+
+    /// 把缓冲的新值合并进质心
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut items: Vec<Centroid> = self.centroids.drain(..)
+            .chain(self.buffer.drain(..).map(|mean| Centroid { mean, weight: 1.0 }))
+            .collect();
+        items.sort_unstable_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        // 相邻质心合并后在刻度函数上跨度不超过1时合并，两端刻度更密，质心更小
+        let total = self.count;
+        let scale = |q: f64| self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).asin();
+        let mut merged = Vec::with_capacity(self.compression as usize * 2);
+        let mut current = items[0];
+        let mut weight_before = 0.0;
+        for item in &items[1..] {
+            let combined = current.weight + item.weight;
+            let q_start = weight_before / total;
+            let q_end = ((weight_before + combined) / total).min(1.0);
+            if scale(q_end) - scale(q_start) <= 1.0 {
+                current.mean += (item.mean - current.mean) * item.weight / combined;
+                current.weight = combined;
+            } else {
+                weight_before += current.weight;
+                merged.push(current);
+                current = *item;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantiles() {
+        let mut digest = TDigest::default();
+        assert_eq!(digest.quantile(0.5), None);
+
+        // 打乱顺序加入 1..=10000
+        for i in 0..10_000u64 {
+            digest.add(((i * 7919) % 10_000 + 1) as f64);
+        }
+        assert_eq!(digest.count(), 10_000);
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(1.0), Some(10_000.0));
+        for (q, expected) in [(0.5, 5000.5), (0.9, 9000.1), (0.99, 9900.01)] {
+            let estimate = digest.quantile(q).unwrap();
+            assert!((estimate - expected).abs() < 50.0, "q={} estimate={}", q, estimate);
+        }
+
+        let mut single = TDigest::default();
+        single.add(3.0);
+        single.add(f64::NAN);
+        assert_eq!(single.quantile(0.9), Some(3.0));
+    }
+}
@@ -1,5 +1,7 @@
 //! CSV工具实用函数
 
+use crate::csv::{CsvRecord, Delimiter, LineEnding};
+use crate::csv::encoding::skip_bom;
 use crate::error::Result;
 use std::path::Path;
 
@@ -29,55 +31,271 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
+/// 浮点数输出格式（统计结果、按列类型导出时使用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// 能精确还原数值的最短写法（如 `3.0` 输出为 `3`）
+    #[default]
+    Shortest,
+    /// 保留源数据中的原始写法，计算得到的值使用最短写法
+    AsWritten,
+    /// 固定小数位数
+    Fixed(usize),
+    /// 有效数字位数
+    Significant(usize),
+}
+
+impl FloatFormat {
+    /// 格式化计算得到的数值
+    pub fn format(&self, value: f64) -> String {
+        match *self {
+            FloatFormat::Shortest | FloatFormat::AsWritten => value.to_string(),
+            FloatFormat::Fixed(decimals) => format!("{:.*}", decimals, value),
+            FloatFormat::Significant(digits) => format_significant(value, digits.max(1)),
+        }
+    }
+
+    /// 格式化源数据中的数值，`text` 为其原始写法（`AsWritten` 时原样返回去掉首尾空白的文本）
+    pub fn format_text(&self, text: &str, value: f64) -> String {
+        match self {
+            FloatFormat::AsWritten => text.trim().to_string(),
+            _ => self.format(value),
+        }
+    }
+}
+
+/// 按有效数字位数格式化
+fn format_significant(value: f64, digits: usize) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return value.to_string();
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let decimals = (digits as i32 - 1 - magnitude).max(0) as usize;
+    let scale = 10f64.powi(magnitude + 1 - digits as i32);
+    let rounded = (value / scale).round() * scale;
+    format!("{:.*}", decimals, rounded)
+}
+
+/// 默认去掉的货币符号
+const DEFAULT_CURRENCY_SYMBOLS: [&str; 5] = ["$", "€", "£", "¥", "￥"];
+
+/// 数字文本的清理规则：解析前去掉首尾空白、货币符号和千位分隔符
+/// 
+/// 从电子表格导出的数据常带有 ` 1,234.50 `、`$99` 这样的写法，直接按 `f64` 解析会失败。
+/// 排序和统计共用这套规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericCleanup {
+    /// 作为前缀或后缀去掉的货币符号（如 `$`、`€`、`元`）
+    pub currency: Vec<String>,
+    /// 使用逗号作为小数点（欧洲格式 `1.234,56`），此时 `.` 为千位分隔符
+    pub decimal_comma: bool,
+}
+
+impl Default for NumericCleanup {
+    fn default() -> Self {
+        Self {
+            currency: DEFAULT_CURRENCY_SYMBOLS.iter().map(|s| s.to_string()).collect(),
+            decimal_comma: false,
+        }
+    }
+}
+
+impl NumericCleanup {
+    /// 创建默认规则（去掉常见货币符号，`.` 为小数点）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置去掉的货币符号（替换默认的 `$ € £ ¥ ￥`）
+    pub fn with_currency(mut self, symbols: Vec<String>) -> Self {
+        self.currency = symbols;
+        self
+    }
+
+    /// 设置是否以逗号作为小数点
+    pub fn with_decimal_comma(mut self, decimal_comma: bool) -> Self {
+        self.decimal_comma = decimal_comma;
+        self
+    }
+
+    /// 清理后解析为数字，无法解析或为NaN时返回None
+    /// 
+    /// 负号可以写在货币符号之前（`-$3.50`）；千位分隔符不检查分组位置
+    pub fn parse(&self, value: &str) -> Option<f64> {
+        let mut text = value.trim();
+        let negative = match text.strip_prefix('-') {
+            Some(rest) => {
+                text = rest.trim_start();
+                true
+            }
+            None => false,
+        };
+        if let Some(rest) = self.currency.iter().find_map(|symbol| text.strip_prefix(symbol.as_str())) {
+            text = rest.trim_start();
+        }
+        if let Some(rest) = self.currency.iter().find_map(|symbol| text.strip_suffix(symbol.as_str())) {
+            text = rest.trim_end();
+        }
+        if negative && text.starts_with(['-', '+']) {
+            return None;
+        }
+
+        let (thousands, decimal) = if self.decimal_comma { ('.', ',') } else { (',', '.') };
+        let normalized: String = text
+            .chars()
+            .filter(|&c| c != thousands)
+            .map(|c| if c == decimal { '.' } else { c })
+            .collect();
+        let number = normalized.parse::<f64>().ok().filter(|n| !n.is_nan())?;
+        Some(if negative { -number } else { number })
+    }
+}
+
+/// 候选分隔符（按优先级排列，得分相同时靠前者优先）
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+/// 检测分隔符时采样的行数
+const DELIMITER_SAMPLE_LINES: usize = 20;
+
+/// 分隔符检测结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DelimiterGuess {
+    /// 检测到的分隔符
+    pub delimiter: u8,
+    /// 置信度（0.0-1.0）：采样行中字段数与 `field_count` 一致的比例
+    pub confidence: f64,
+    /// 按该分隔符解析得到的字段数（采样行中最常见的值）
+    pub field_count: usize,
+}
+
 /// 检测CSV文件的分隔符
 /// 
 /// # 参数
 /// - `path`: CSV文件路径
 /// 
 /// # 返回
-/// 检测到的分隔符（逗号、分号、制表符等）
+/// 检测到的分隔符（逗号、分号、制表符等），无法判断时返回逗号
 pub fn detect_delimiter<P: AsRef<Path>>(path: P) -> Result<u8> {
+    Ok(guess_delimiter(path)?.delimiter)
+}
+
+/// 检测CSV文件的分隔符并给出置信度
+/// 
+/// 对每个候选分隔符按引号规则逐行统计字段数，优先选择各行字段数最一致的候选；
+/// 一致性相同时选择字段数更多的候选。引号内的分隔符不计入
+/// 
+/// # 参数
+/// - `path`: CSV文件路径
+/// 
+/// # 返回
+/// 检测结果，没有任何候选能把行拆分为多个字段时返回逗号且置信度为0
+pub fn guess_delimiter<P: AsRef<Path>>(path: P) -> Result<DelimiterGuess> {
+    let lines = read_sample_lines(path, DELIMITER_SAMPLE_LINES)?;
+    Ok(guess_delimiter_in_lines(&lines))
+}
+
+/// 读取文件开头的若干非空行（去除BOM和行尾换行符）
+fn read_sample_lines<P: AsRef<Path>>(path: P, max_lines: usize) -> Result<Vec<Vec<u8>>> {
     use std::fs::File;
     use std::io::{BufRead, BufReader};
     
     let file = File::open(path)?;
     let mut reader = BufReader::new(file);
-    let mut first_line = String::new();
-    reader.read_line(&mut first_line)?;
+    let mut lines = Vec::new();
     
-    // 统计各种分隔符的出现次数
-    let mut comma_count = first_line.matches(',').count();
-    let mut semicolon_count = first_line.matches(';').count();
-    let mut tab_count = first_line.matches('\t').count();
-    let mut pipe_count = first_line.matches('|').count();
+    // 只用 \r 换行的文件按 \r 分行
+    let terminator = match LineEnding::detect(reader.fill_buf()?) {
+        Some(LineEnding::Cr) => b'\r',
+        _ => b'\n',
+    };
     
-    // 读取更多行以获得更准确的统计
-    for _ in 0..10 {
-        let mut line = String::new();
-        if reader.read_line(&mut line)? == 0 {
+    while lines.len() < max_lines {
+        let mut line = Vec::new();
+        if reader.read_until(terminator, &mut line)? == 0 {
             break;
         }
-        comma_count += line.matches(',').count();
-        semicolon_count += line.matches(';').count();
-        tab_count += line.matches('\t').count();
-        pipe_count += line.matches('|').count();
+        if lines.is_empty() {
+            line.drain(..skip_bom(&line));
+        }
+        while matches!(line.last(), Some(b'\n' | b'\r')) {
+            line.pop();
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
     }
     
-    // 返回出现次数最多的分隔符
-    let max_count = comma_count.max(semicolon_count).max(tab_count).max(pipe_count);
+    Ok(lines)
+}
+
+/// 在采样行中选择最合适的分隔符
+fn guess_delimiter_in_lines(lines: &[Vec<u8>]) -> DelimiterGuess {
+    let mut best = DelimiterGuess {
+        delimiter: b',',
+        confidence: 0.0,
+        field_count: 1,
+    };
     
-    if max_count == comma_count && comma_count > 0 {
-        Ok(b',')
-    } else if max_count == semicolon_count && semicolon_count > 0 {
-        Ok(b';')
-    } else if max_count == tab_count && tab_count > 0 {
-        Ok(b'\t')
-    } else if max_count == pipe_count && pipe_count > 0 {
-        Ok(b'|')
-    } else {
-        // 默认返回逗号
-        Ok(b',')
+    for delimiter in DELIMITER_CANDIDATES {
+        let counts: Vec<usize> = lines.iter()
+            .map(|line| count_fields_quoted(line, &Delimiter::Byte(delimiter)))
+            .collect();
+        
+        // 最常见的字段数（相同时取较大者）
+        let mut field_count = 1;
+        let mut occurrences = 0;
+        for &count in &counts {
+            let n = counts.iter().filter(|&&c| c == count).count();
+            if n > occurrences || (n == occurrences && count > field_count) {
+                field_count = count;
+                occurrences = n;
+            }
+        }
+        
+        // 无法把行拆分为多个字段的候选不予考虑
+        if field_count < 2 {
+            continue;
+        }
+        
+        let confidence = occurrences as f64 / counts.len() as f64;
+        if confidence > best.confidence
+            || (confidence == best.confidence && field_count > best.field_count)
+        {
+            best = DelimiterGuess { delimiter, confidence, field_count };
+        }
     }
+    
+    best
+}
+
+/// 统计一行中的字段数（引号内的分隔符不计）
+pub(crate) fn count_fields_quoted(line: &[u8], delimiter: &Delimiter) -> usize {
+    let mut in_quotes = false;
+    let mut count = 1;
+    let mut i = 0;
+    while i < line.len() {
+        if line[i] == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && delimiter.matches_at(line, i) {
+            count += 1;
+            i += delimiter.as_bytes().len();
+            continue;
+        }
+        i += 1;
+    }
+    count
+}
+
+/// 检测表头时采样的数据行数（不含第一行）
+const HEADER_SAMPLE_ROWS: usize = 20;
+
+/// 表头检测结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeaderGuess {
+    /// 第一行是否为表头
+    pub has_headers: bool,
+    /// 置信度（0.0-1.0）：支持该结论的证据占全部证据的比例，没有任何证据时为0.5
+    pub confidence: f64,
 }
 
 /// 检测CSV文件是否有表头
@@ -88,24 +306,115 @@ pub fn detect_delimiter<P: AsRef<Path>>(path: P) -> Result<u8> {
 /// # 返回
 /// 如果有表头返回true，否则返回false
 pub fn detect_has_headers<P: AsRef<Path>>(path: P) -> Result<bool> {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
+    Ok(guess_has_headers(path)?.has_headers)
+}
+
+/// 按指定分隔符检测CSV文件是否有表头
+pub fn detect_has_headers_with_delimiter<P: AsRef<Path>>(path: P, delimiter: impl Into<Delimiter>) -> Result<bool> {
+    Ok(guess_has_headers_with_delimiter(path, delimiter)?.has_headers)
+}
+
+/// 检测CSV文件是否有表头并给出置信度（自动检测分隔符）
+pub fn guess_has_headers<P: AsRef<Path>>(path: P) -> Result<HeaderGuess> {
+    let delimiter = detect_delimiter(&path)?;
+    guess_has_headers_with_delimiter(path, delimiter)
+}
+
+/// 按指定分隔符检测CSV文件是否有表头并给出置信度
+/// 
+/// 用第2行起的若干行推断每列的特征，再看第一行是否与之不符：
+/// - 下方为数值而第一行为文本，支持有表头；第一行同样为数值，支持无表头
+/// - 下方为长度固定的文本而第一行长度不同，支持有表头
+/// - 第一行的值在同列下方再次出现、第一行有重复值或空值，支持无表头
+/// 
+/// 没有任何证据时（如全为文本的表格）保持默认的“有表头”；
+/// 只有一行时仅当该行包含数值字段才判定为无表头
+/// 
+/// # 参数
+/// - `path`: CSV文件路径
+/// - `delimiter`: 分隔符
+pub fn guess_has_headers_with_delimiter<P: AsRef<Path>>(path: P, delimiter: impl Into<Delimiter>) -> Result<HeaderGuess> {
+    let delimiter = delimiter.into();
+    let lines = read_sample_lines(path, HEADER_SAMPLE_ROWS + 1)?;
+    let records: Vec<CsvRecord> = lines.iter()
+        .map(|line| CsvRecord::parse_line_with(line, &delimiter))
+        .collect();
+    Ok(guess_has_headers_in_records(&records))
+}
+
+/// 在采样行中判断第一行是否为表头
+fn guess_has_headers_in_records(records: &[CsvRecord]) -> HeaderGuess {
+    let Some((first, rows)) = records.split_first() else {
+        return HeaderGuess { has_headers: true, confidence: 0.5 };
+    };
     
-    let file = File::open(path)?;
-    let mut reader = BufReader::new(file);
+    // 只有一行时无从比较，退回到“表头不会是数值”的规则
+    if rows.is_empty() {
+        let has_headers = !first.fields.iter().any(|field| is_numeric(field));
+        return HeaderGuess { has_headers, confidence: 0.5 };
+    }
     
-    let mut first_line = String::new();
-    reader.read_line(&mut first_line)?;
+    let mut header_score = 0.0;
+    let mut data_score = 0.0;
     
-    let mut second_line = String::new();
-    reader.read_line(&mut second_line)?;
+    for (col, header) in first.fields.iter().enumerate() {
+        let header = header.trim();
+        let values: Vec<&str> = rows.iter()
+            .filter_map(|row| row.fields.get(col))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .collect();
+        
+        if header.is_empty() {
+            // 表头很少留空
+            data_score += 1.0;
+            continue;
+        }
+        if values.is_empty() {
+            continue;
+        }
+        
+        if values.iter().all(|value| is_numeric(value)) {
+            if is_numeric(header) {
+                data_score += 1.0;
+            } else {
+                header_score += 1.0;
+            }
+        } else if values.contains(&header) {
+            // 列名不会作为该列的值再次出现
+            data_score += 1.0;
+        } else if values.len() >= 2 {
+            let len = values[0].chars().count();
+            if values.iter().all(|value| value.chars().count() == len) && header.chars().count() != len {
+                header_score += 0.5;
+            }
+        }
+    }
     
-    // 简单的启发式方法：
-    // 如果第一行看起来像表头（包含字母，第二行包含数字），则可能有表头
-    let first_has_letters = first_line.chars().any(|c| c.is_alphabetic());
-    let second_has_numbers = second_line.chars().any(|c| c.is_numeric());
+    // 表头中的列名通常互不相同
+    let mut names: Vec<&str> = first.fields.iter()
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .collect();
+    let total = names.len();
+    names.sort_unstable();
+    names.dedup();
+    data_score += (total - names.len()) as f64;
     
-    Ok(first_has_letters && second_has_numbers)
+    let evidence = header_score + data_score;
+    if evidence == 0.0 {
+        return HeaderGuess { has_headers: true, confidence: 0.5 };
+    }
+    
+    let has_headers = header_score >= data_score;
+    let confidence = if has_headers { header_score } else { data_score } / evidence;
+    HeaderGuess { has_headers, confidence }
+}
+
+/// 字段是否为数值（不把 "inf"、"NaN" 这类文本当作数值）
+fn is_numeric(field: &str) -> bool {
+    let field = field.trim();
+    field.bytes().any(|b| b.is_ascii_digit()) && field.parse::<f64>().is_ok()
 }
 
 #[cfg(test)]
@@ -119,5 +428,134 @@ mod tests {
         assert_eq!(format_size(1024 * 1024), "1.00 MB");
         assert_eq!(format_size(1024 * 1024 * 1024), "1.00 GB");
     }
-}
 
+    #[test]
+    fn test_float_format() {
+        assert_eq!(FloatFormat::Shortest.format(3.0), "3");
+        assert_eq!(FloatFormat::Fixed(2).format(1.2000000001), "1.20");
+        assert_eq!(FloatFormat::Fixed(2).format(3.0), "3.00");
+        assert_eq!(FloatFormat::Significant(3).format(1234.5), "1230");
+        assert_eq!(FloatFormat::Significant(3).format(0.012345), "0.0123");
+        assert_eq!(FloatFormat::AsWritten.format_text(" 3.0 ", 3.0), "3.0");
+        assert_eq!(FloatFormat::Fixed(1).format_text("3", 3.0), "3.0");
+    }
+
+    #[test]
+    fn test_numeric_cleanup() {
+        let cleanup = NumericCleanup::new();
+        assert_eq!(cleanup.parse("1,234"), Some(1234.0));
+        assert_eq!(cleanup.parse("  42 "), Some(42.0));
+        assert_eq!(cleanup.parse("$3.50"), Some(3.5));
+        assert_eq!(cleanup.parse(" 1,234.50 "), Some(1234.5));
+        assert_eq!(cleanup.parse("-$1,000"), Some(-1000.0));
+        assert_eq!(cleanup.parse("99 €"), Some(99.0));
+        assert_eq!(cleanup.parse("--5"), None);
+        assert_eq!(cleanup.parse("abc"), None);
+        assert_eq!(cleanup.parse(""), None);
+        assert_eq!(cleanup.parse("NaN"), None);
+
+        let european = NumericCleanup::new().with_decimal_comma(true);
+        assert_eq!(european.parse("1.234,56"), Some(1234.56));
+        assert_eq!(european.parse("3,5 €"), Some(3.5));
+
+        let yuan = NumericCleanup::new().with_currency(vec!["元".to_string()]);
+        assert_eq!(yuan.parse("1,200元"), Some(1200.0));
+        assert_eq!(yuan.parse("$5"), None);
+    }
+
+    #[test]
+    fn test_detect_has_headers() {
+        let dir = std::env::temp_dir();
+        let cases: [(&str, &[u8], bool); 9] = [
+            ("with_headers", b"id,name\n1,Alice\n", true),
+            ("numeric_first_row", b"1,Alice\n2,Bob\n", false),
+            ("quoted_number", b"\"Alice\";\"3.5\"\nBob;4\n", false),
+            ("all_text", b"Alice,Beijing\nBob,Shanghai\n", true),
+            // 全文本且首行的值在下方重复出现
+            ("all_text_repeated", b"Alice,Beijing\nBob,Beijing\nCarol,Shanghai\n", false),
+            // 数值ID列，其余列为文本
+            ("numeric_id_headers", b"id,name,city\n1,Alice,Beijing\n2,Bob,Shanghai\n3,Carol,Beijing\n", true),
+            // 首行只有部分列为数值
+            ("partial_numeric", b"Alice,30,Beijing\nBob,25,Shanghai\n", false),
+            // 只有一行
+            ("single_row_text", b"id,name\n", true),
+            ("single_row_numeric", b"1,Alice", false),
+        ];
+        for (name, content, expected) in cases {
+            let path = dir.join(format!("test_detect_headers_{}.csv", name));
+            std::fs::write(&path, content).unwrap();
+            assert_eq!(detect_has_headers(&path).unwrap(), expected, "{}", name);
+            std::fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn test_guess_has_headers_confidence() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("test_guess_headers_confidence.csv");
+        
+        std::fs::write(&path, b"id,score\n1,9.5\n2,8.0\n").unwrap();
+        let guess = guess_has_headers(&path).unwrap();
+        assert!(guess.has_headers);
+        assert_eq!(guess.confidence, 1.0);
+        
+        // 首行有重复值和空值
+        std::fs::write(&path, b"id,id,\n1,Bob,3\n2,Dave,4\n").unwrap();
+        let guess = guess_has_headers(&path).unwrap();
+        assert!(!guess.has_headers);
+        assert!((guess.confidence - 2.0 / 3.0).abs() < 1e-9);
+        
+        // 没有任何证据时置信度为0.5
+        std::fs::write(&path, b"Alice,Beijing\nBob,Shanghai\n").unwrap();
+        assert_eq!(guess_has_headers(&path).unwrap().confidence, 0.5);
+        
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_guess_delimiter() {
+        let dir = std::env::temp_dir();
+        let cases: [(&str, &[u8], u8, usize); 4] = [
+            // 分号分隔，引号内含逗号
+            (
+                "quoted_commas",
+                b"name;address;note\nAlice;\"1 Main St, Springfield, IL\";\"a, b, c\"\nBob;\"2 Oak Ave, Portland, OR\";x\n",
+                b';',
+                3,
+            ),
+            // 竖线分隔，正文含逗号
+            (
+                "pipe_prose",
+                b"id|comment\n1|Well, this is fine, really\n2|No commas here\n3|One, two, three, four\n",
+                b'|',
+                2,
+            ),
+            // 逗号分隔，文本字段含大量分号
+            (
+                "comma_semicolons",
+                b"id,tags\n1,a;b;c;d\n2,e;f\n3,g\n",
+                b',',
+                2,
+            ),
+            ("tab", b"a\tb\tc\n1\t2\t3\n", b'\t', 3),
+        ];
+        for (name, content, delimiter, field_count) in cases {
+            let path = dir.join(format!("test_guess_delimiter_{}.csv", name));
+            std::fs::write(&path, content).unwrap();
+            let guess = guess_delimiter(&path).unwrap();
+            assert_eq!(guess.delimiter, delimiter, "{}", name);
+            assert_eq!(guess.field_count, field_count, "{}", name);
+            assert_eq!(guess.confidence, 1.0, "{}", name);
+            assert_eq!(detect_delimiter(&path).unwrap(), delimiter, "{}", name);
+            std::fs::remove_file(&path).ok();
+        }
+        
+        // 只有一列时无法判断，返回逗号且置信度为0
+        let path = dir.join("test_guess_delimiter_single.csv");
+        std::fs::write(&path, b"value\n1\n2\n").unwrap();
+        let guess = guess_delimiter(&path).unwrap();
+        assert_eq!(guess.delimiter, b',');
+        assert_eq!(guess.confidence, 0.0);
+        std::fs::remove_file(&path).ok();
+    }
+}
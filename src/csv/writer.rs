@@ -6,13 +6,16 @@
 //! - 列添加/删除
 //! - 流式写入（大文件支持）
 
-use crate::csv::{CsvReader, CsvRecord};
+use crate::csv::{CsvReader, CsvRecord, Delimiter};
+use crate::csv::compression::is_gzip_file;
 use crate::error::{CsvError, Result};
+use memchr::memchr2_iter;
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
+use std::sync::Mutex;
 
 /// 单元格修改记录
 #[derive(Debug, Clone)]
@@ -68,22 +71,28 @@ impl From<CsvRecord<'_>> for RowData {
 #[derive(Debug, Clone)]
 pub struct WriteOptions {
     /// 分隔符
-    pub delimiter: u8,
+    pub delimiter: Delimiter,
     /// 行结束符
     pub line_ending: LineEnding,
     /// 是否总是引用字段
     pub always_quote: bool,
     /// 是否写入表头
     pub write_headers: bool,
+    /// 编辑保存时是否保留未修改字段原有的引用风格
+    pub preserve_quoting: bool,
+    /// 编辑保存时沿用源文件的行结束符（调用 `with_line_ending` 后关闭）
+    pub preserve_line_ending: bool,
 }
 
 impl Default for WriteOptions {
     fn default() -> Self {
         Self {
-            delimiter: b',',
+            delimiter: Delimiter::default(),
             line_ending: LineEnding::default(),
             always_quote: false,
             write_headers: true,
+            preserve_quoting: false,
+            preserve_line_ending: true,
         }
     }
 }
@@ -94,15 +103,18 @@ impl WriteOptions {
         Self::default()
     }
 
-    /// 设置分隔符
-    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
-        self.delimiter = delimiter;
+    /// 设置分隔符（单字节或 [`Delimiter`]）
+    pub fn with_delimiter(mut self, delimiter: impl Into<Delimiter>) -> Self {
+        self.delimiter = delimiter.into();
         self
     }
 
     /// 设置行结束符
+    /// 
+    /// 明确指定后 [`CsvEditor::save`] 不再沿用源文件的行结束符
     pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
         self.line_ending = line_ending;
+        self.preserve_line_ending = false;
         self
     }
 
@@ -117,6 +129,15 @@ impl WriteOptions {
         self.write_headers = write_headers;
         self
     }
+
+    /// 设置是否保留原有的引用风格
+    /// 
+    /// 开启后 [`CsvEditor::save`] 中未修改的字段按源文件是否带引号原样写出，
+    /// 只有修改过的单元格和新增的行按常规规则转义，避免一次小修改产生大量差异
+    pub fn with_preserve_quoting(mut self, preserve: bool) -> Self {
+        self.preserve_quoting = preserve;
+        self
+    }
 }
 
 /// 行结束符类型
@@ -139,7 +160,55 @@ impl Default for LineEnding {
     }
 }
 
+/// 检测行结束符时采样的字节数
+const LINE_ENDING_SAMPLE_BYTES: usize = 64 * 1024;
+
 impl LineEnding {
+    /// 根据数据开头（最多64KB）检测行结束符
+    /// 
+    /// 分别统计 `\r\n`、单独的 `\n` 和单独的 `\r`：单独的 `\r` 多于其他换行符时
+    /// 判定为旧Mac风格（引号内偶尔出现的 `\r` 不会误判），否则按 `\r\n` 和 `\n` 中较多的一种；
+    /// 没有换行符时返回None
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        let sample = &data[..data.len().min(LINE_ENDING_SAMPLE_BYTES)];
+        let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+        for pos in memchr2_iter(b'\n', b'\r', sample) {
+            if sample[pos] == b'\n' {
+                if pos > 0 && sample[pos - 1] == b'\r' {
+                    crlf += 1;
+                } else {
+                    lf += 1;
+                }
+            } else if pos + 1 < sample.len() {
+                if sample[pos + 1] != b'\n' {
+                    cr += 1;
+                }
+            } else if pos + 1 == data.len() {
+                // 文件以 \r 结尾；采样末尾的 \r 无法判断是否属于 \r\n，不计入
+                cr += 1;
+            }
+        }
+
+        if lf + crlf + cr == 0 {
+            None
+        } else if cr > lf + crlf {
+            Some(LineEnding::Cr)
+        } else if crlf > lf {
+            Some(LineEnding::CrLf)
+        } else {
+            Some(LineEnding::Lf)
+        }
+    }
+
+    /// 获取名称（LF、CRLF、CR）
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
     /// 获取字节表示
     pub fn as_bytes(&self) -> &'static [u8] {
         match self {
@@ -164,11 +233,13 @@ pub struct CsvEditor {
     /// 原始行数（不含表头）
     original_row_count: usize,
     /// 分隔符
-    delimiter: u8,
+    delimiter: Delimiter,
     /// 是否有表头
     has_headers: bool,
     /// 索引粒度
     granularity: usize,
+    /// 源文件读取器，读取单元格时复用（覆盖原文件前关闭，之后按需重新打开）
+    reader: Mutex<Option<CsvReader>>,
     
     /// 单元格修改记录 (row, col) -> value
     cell_edits: HashMap<(usize, usize), String>,
@@ -182,6 +253,32 @@ pub struct CsvEditor {
     deleted_cols: HashSet<usize>,
     /// 追加的行
     appended_rows: Vec<RowData>,
+    
+    /// 已执行的修改（用于撤销）
+    undo_stack: Vec<EditOperation>,
+    /// 已撤销的修改（用于重做，执行新修改时清空）
+    redo_stack: Vec<EditOperation>,
+}
+
+/// 一次修改操作，记录撤销和重做所需的全部信息
+#[derive(Debug, Clone)]
+enum EditOperation {
+    /// 编辑单元格（`previous` 为之前的修改值，None表示之前未修改）
+    EditCell { row: usize, col: usize, previous: Option<String>, value: String },
+    /// 删除原始行，同时丢弃该行的单元格修改
+    DeleteRow { row: usize, removed_edits: Vec<((usize, usize), String)> },
+    /// 删除追加的行
+    RemoveAppendedRow { index: usize, row: RowData },
+    /// 恢复删除的行
+    UndeleteRow { row: usize },
+    /// 追加新行
+    AppendRow { row: RowData },
+    /// 在指定位置插入行
+    InsertRow { position: usize, row: RowData },
+    /// 删除列，同时丢弃该列的单元格修改
+    DeleteCol { col: usize, removed_edits: Vec<((usize, usize), String)> },
+    /// 修改表头
+    SetHeader { col: usize, previous: String, name: String },
 }
 
 impl CsvEditor {
@@ -189,29 +286,41 @@ impl CsvEditor {
     pub fn open<P: AsRef<Path>>(
         path: P,
         has_headers: bool,
-        delimiter: u8,
+        delimiter: impl Into<Delimiter>,
         granularity: usize,
     ) -> Result<Self> {
+        let delimiter = delimiter.into();
         let path_str = path.as_ref().to_string_lossy().to_string();
         
+        // 压缩文件只读，保存时无法写回gzip格式
+        if is_gzip_file(path.as_ref())? {
+            return Err(CsvError::Format(format!(
+                "不支持编辑gzip压缩文件: {}，请先解压后再编辑",
+                path_str
+            )));
+        }
+        
         // 使用CsvReader读取基本信息
-        let reader = CsvReader::open(&path_str, has_headers, delimiter, granularity)?;
-        let info = reader.info();
+        let reader = CsvReader::open(&path_str, has_headers, &delimiter, granularity)?;
+        let info = reader.info().clone();
         
         Ok(Self {
             source_path: path_str,
-            headers: info.headers.clone(),
+            headers: info.headers,
             original_col_count: info.total_cols,
             original_row_count: info.total_rows,
             delimiter,
             has_headers,
             granularity,
+            reader: Mutex::new(Some(reader)),
             cell_edits: HashMap::new(),
             inserted_rows: HashMap::new(),
             deleted_rows: HashSet::new(),
             inserted_cols: HashMap::new(),
             deleted_cols: HashSet::new(),
             appended_rows: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
         })
     }
 
@@ -269,10 +378,27 @@ impl CsvEditor {
             return Err(CsvError::Format(format!("行 {} 已被删除", row)));
         }
         
-        self.cell_edits.insert((row, col), value);
+        let previous = self.cell_edits.get(&(row, col)).cloned();
+        self.execute(EditOperation::EditCell { row, col, previous, value });
         Ok(())
     }
 
+    /// 批量编辑单元格
+    /// 
+    /// 逐个调用 [`CsvEditor::edit_cell`]，无法应用的修改（如行列超出范围）不会中断其余修改
+    /// 
+    /// # 返回
+    /// 被拒绝的修改及原因
+    pub fn apply_edits(&mut self, edits: Vec<CellEdit>) -> Vec<(CellEdit, CsvError)> {
+        let mut rejected = Vec::new();
+        for edit in edits {
+            if let Err(e) = self.edit_cell(edit.row, edit.col, edit.value.clone()) {
+                rejected.push((edit, e));
+            }
+        }
+        rejected
+    }
+
     /// 检查是否是追加的行
     fn is_appended_row(&self, row: usize) -> bool {
         row >= self.original_row_count && row < self.original_row_count + self.appended_rows.len()
@@ -300,19 +426,27 @@ impl CsvEditor {
         }
         
         // 从原始文件读取
-        let mut reader = CsvReader::open(
-            &self.source_path,
-            self.has_headers,
-            self.delimiter,
-            self.granularity,
-        )?;
-        
-        let page = reader.read_page(row, 1)?;
-        if let Some(record) = page.first() {
-            Ok(record.fields.get(col).map(|f| f.to_string()))
-        } else {
-            Ok(None)
-        }
+        self.with_reader(|reader| {
+            let record = reader.get_row(row)?;
+            Ok(record.and_then(|record| record.fields.get(col).map(|f| f.to_string())))
+        })
+    }
+
+    /// 使用源文件读取器，尚未打开时先打开
+    fn with_reader<T>(&self, f: impl FnOnce(&CsvReader) -> Result<T>) -> Result<T> {
+        let mut slot = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+        let reader = match slot.take() {
+            Some(reader) => reader,
+            None => CsvReader::open(
+                &self.source_path,
+                self.has_headers,
+                &self.delimiter,
+                self.granularity,
+            )?,
+        };
+        let result = f(&reader);
+        *slot = Some(reader);
+        result
     }
 
     /// 删除行
@@ -321,7 +455,8 @@ impl CsvEditor {
             // 删除追加的行
             let appended_idx = row - self.original_row_count;
             if appended_idx < self.appended_rows.len() {
-                self.appended_rows.remove(appended_idx);
+                let removed = self.appended_rows[appended_idx].clone();
+                self.execute(EditOperation::RemoveAppendedRow { index: appended_idx, row: removed });
                 return Ok(());
             }
             return Err(CsvError::IndexOutOfBounds {
@@ -330,19 +465,26 @@ impl CsvEditor {
             });
         }
         
-        self.deleted_rows.insert(row);
+        if self.deleted_rows.contains(&row) {
+            return Ok(());
+        }
         
-        // 清除该行的所有编辑
-        self.cell_edits.retain(|&(r, _), _| r != row);
+        // 删除时会清除该行的所有编辑，撤销时需要恢复
+        let removed_edits = self.cell_edits.iter()
+            .filter(|((r, _), _)| *r == row)
+            .map(|(&key, value)| (key, value.clone()))
+            .collect();
+        self.execute(EditOperation::DeleteRow { row, removed_edits });
         
         Ok(())
     }
 
     /// 恢复删除的行
     pub fn undelete_row(&mut self, row: usize) -> Result<()> {
-        if !self.deleted_rows.remove(&row) {
+        if !self.deleted_rows.contains(&row) {
             return Err(CsvError::Format(format!("行 {} 未被删除", row)));
         }
+        self.execute(EditOperation::UndeleteRow { row });
         Ok(())
     }
 
@@ -357,7 +499,7 @@ impl CsvEditor {
             )));
         }
         
-        self.appended_rows.push(row);
+        self.execute(EditOperation::AppendRow { row });
         Ok(())
     }
 
@@ -378,10 +520,7 @@ impl CsvEditor {
             )));
         }
         
-        self.inserted_rows
-            .entry(position)
-            .or_insert_with(Vec::new)
-            .push(row);
+        self.execute(EditOperation::InsertRow { position, row });
         
         Ok(())
     }
@@ -395,16 +534,17 @@ impl CsvEditor {
             )));
         }
         
-        self.deleted_cols.insert(col);
-        
-        // 清除该列的所有编辑
-        self.cell_edits.retain(|&(_, c), _| c != col);
-        
-        // 更新表头
-        if col < self.headers.len() {
-            // 标记为删除，实际删除在保存时处理
+        if self.deleted_cols.contains(&col) {
+            return Ok(());
         }
         
+        // 删除时会清除该列的所有编辑，撤销时需要恢复；表头在保存时才去掉
+        let removed_edits = self.cell_edits.iter()
+            .filter(|((_, c), _)| *c == col)
+            .map(|(&key, value)| (key, value.clone()))
+            .collect();
+        self.execute(EditOperation::DeleteCol { col, removed_edits });
+        
         Ok(())
     }
 
@@ -425,11 +565,12 @@ impl CsvEditor {
             )));
         }
         
-        self.headers[col] = name;
+        let previous = self.headers[col].clone();
+        self.execute(EditOperation::SetHeader { col, previous, name });
         Ok(())
     }
 
-    /// 清除所有修改
+    /// 清除所有修改（同时清空撤销和重做记录）
     pub fn discard_changes(&mut self) {
         self.cell_edits.clear();
         self.inserted_rows.clear();
@@ -437,6 +578,125 @@ impl CsvEditor {
         self.inserted_cols.clear();
         self.deleted_cols.clear();
         self.appended_rows.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// 是否有可以撤销的修改
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// 是否有可以重做的修改
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// 撤销最近一次修改
+    /// 
+    /// # 返回
+    /// 没有可以撤销的修改时返回false
+    pub fn undo(&mut self) -> bool {
+        let Some(operation) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.revert(&operation);
+        self.redo_stack.push(operation);
+        true
+    }
+
+    /// 重做最近一次撤销的修改
+    /// 
+    /// # 返回
+    /// 没有可以重做的修改时返回false
+    pub fn redo(&mut self) -> bool {
+        let Some(operation) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.apply(&operation);
+        self.undo_stack.push(operation);
+        true
+    }
+
+    /// 执行一次新的修改并记入撤销栈
+    fn execute(&mut self, operation: EditOperation) {
+        self.apply(&operation);
+        self.undo_stack.push(operation);
+        self.redo_stack.clear();
+    }
+
+    /// 将修改应用到修改记录中（调用前已完成校验）
+    fn apply(&mut self, operation: &EditOperation) {
+        match operation {
+            EditOperation::EditCell { row, col, value, .. } => {
+                self.cell_edits.insert((*row, *col), value.clone());
+            }
+            EditOperation::DeleteRow { row, .. } => {
+                self.deleted_rows.insert(*row);
+                self.cell_edits.retain(|&(r, _), _| r != *row);
+            }
+            EditOperation::RemoveAppendedRow { index, .. } => {
+                self.appended_rows.remove(*index);
+            }
+            EditOperation::UndeleteRow { row } => {
+                self.deleted_rows.remove(row);
+            }
+            EditOperation::AppendRow { row } => {
+                self.appended_rows.push(row.clone());
+            }
+            EditOperation::InsertRow { position, row } => {
+                self.inserted_rows.entry(*position).or_default().push(row.clone());
+            }
+            EditOperation::DeleteCol { col, .. } => {
+                self.deleted_cols.insert(*col);
+                self.cell_edits.retain(|&(_, c), _| c != *col);
+            }
+            EditOperation::SetHeader { col, name, .. } => {
+                self.headers[*col] = name.clone();
+            }
+        }
+    }
+
+    /// 从修改记录中撤销一次修改
+    fn revert(&mut self, operation: &EditOperation) {
+        match operation {
+            EditOperation::EditCell { row, col, previous, .. } => match previous {
+                Some(previous) => {
+                    self.cell_edits.insert((*row, *col), previous.clone());
+                }
+                None => {
+                    self.cell_edits.remove(&(*row, *col));
+                }
+            },
+            EditOperation::DeleteRow { row, removed_edits } => {
+                self.deleted_rows.remove(row);
+                self.cell_edits.extend(removed_edits.iter().cloned());
+            }
+            EditOperation::RemoveAppendedRow { index, row } => {
+                self.appended_rows.insert(*index, row.clone());
+            }
+            EditOperation::UndeleteRow { row } => {
+                self.deleted_rows.insert(*row);
+            }
+            EditOperation::AppendRow { .. } => {
+                self.appended_rows.pop();
+            }
+            EditOperation::InsertRow { position, .. } => {
+                if let Some(rows) = self.inserted_rows.get_mut(position) {
+                    rows.pop();
+                    if rows.is_empty() {
+                        self.inserted_rows.remove(position);
+                    }
+                }
+            }
+            EditOperation::DeleteCol { col, removed_edits } => {
+                self.deleted_cols.remove(col);
+                self.cell_edits.extend(removed_edits.iter().cloned());
+            }
+            EditOperation::SetHeader { col, previous, .. } => {
+                self.headers[*col] = previous.clone();
+            }
+        }
     }
 
     /// 获取修改统计
@@ -459,29 +719,42 @@ impl CsvEditor {
         let mut rows_written = 0;
         let mut bytes_written = 0;
         
+        // 打开源文件读取器
+        let reader = CsvReader::open(
+            &self.source_path,
+            self.has_headers,
+            &self.delimiter,
+            self.granularity,
+        )?;
+        
+        // 默认沿用源文件的行结束符
+        let line_ending = if options.preserve_line_ending {
+            reader.info().line_ending
+        } else {
+            options.line_ending
+        }
+        .as_bytes();
+        
         // 写入表头
         if options.write_headers && !self.headers.is_empty() {
-            let effective_headers: Vec<&str> = self.headers
+            let header_quoting = if options.preserve_quoting {
+                reader.header_quoting()
+            } else {
+                None
+            };
+            let effective_headers: Vec<(&str, Option<bool>)> = self.headers
                 .iter()
                 .enumerate()
                 .filter(|(i, _)| !self.deleted_cols.contains(i))
-                .map(|(_, h)| h.as_str())
+                .map(|(i, h)| (h.as_str(), header_quoting.as_ref().and_then(|q| q.get(i).copied())))
                 .collect();
             
-            let line = self.format_row(&effective_headers, options);
+            let line = self.format_row_quoted(&effective_headers, options);
             writer.write_all(line.as_bytes())?;
-            writer.write_all(options.line_ending.as_bytes())?;
-            bytes_written += line.len() + options.line_ending.as_bytes().len();
+            writer.write_all(line_ending)?;
+            bytes_written += line.len() + line_ending.len();
         }
         
-        // 打开源文件读取器
-        let mut reader = CsvReader::open(
-            &self.source_path,
-            self.has_headers,
-            self.delimiter,
-            self.granularity,
-        )?;
-        
         // 逐行处理
         let mut current_row = 0;
         while current_row < self.original_row_count {
@@ -497,8 +770,8 @@ impl CsvEditor {
                     
                     let line = self.format_row(&fields, options);
                     writer.write_all(line.as_bytes())?;
-                    writer.write_all(options.line_ending.as_bytes())?;
-                    bytes_written += line.len() + options.line_ending.as_bytes().len();
+                    writer.write_all(line_ending)?;
+                    bytes_written += line.len() + line_ending.len();
                     rows_written += 1;
                 }
             }
@@ -509,28 +782,32 @@ impl CsvEditor {
                 continue;
             }
             
-            // 读取并处理当前行
-            let page = reader.read_page(current_row, 1)?;
-            if let Some(record) = page.first() {
-                let fields: Vec<Cow<str>> = record.fields
+            // 读取并处理当前行（保留引用风格时需要记录源字段是否带引号）
+            let record = if options.preserve_quoting {
+                reader.read_rows_quoted(current_row, current_row + 1)?.into_iter().next()
+            } else {
+                reader.get_row(current_row)?
+            };
+            if let Some(record) = record {
+                let fields: Vec<(Cow<str>, Option<bool>)> = record.fields
                     .iter()
                     .enumerate()
                     .filter(|(i, _)| !self.deleted_cols.contains(i))
                     .map(|(i, f)| {
-                        // 检查是否有编辑
+                        // 检查是否有编辑（修改过的单元格按常规规则转义）
                         if let Some(edited) = self.cell_edits.get(&(current_row, i)) {
-                            Cow::Owned(edited.clone())
+                            (Cow::Owned(edited.clone()), None)
                         } else {
-                            Cow::Borrowed(f.as_ref())
+                            (Cow::Borrowed(f.as_ref()), record.is_quoted(i))
                         }
                     })
                     .collect();
                 
-                let field_strs: Vec<&str> = fields.iter().map(|f| f.as_ref()).collect();
-                let line = self.format_row(&field_strs, options);
+                let field_strs: Vec<(&str, Option<bool>)> = fields.iter().map(|(f, q)| (f.as_ref(), *q)).collect();
+                let line = self.format_row_quoted(&field_strs, options);
                 writer.write_all(line.as_bytes())?;
-                writer.write_all(options.line_ending.as_bytes())?;
-                bytes_written += line.len() + options.line_ending.as_bytes().len();
+                writer.write_all(line_ending)?;
+                bytes_written += line.len() + line_ending.len();
                 rows_written += 1;
             }
             
@@ -548,8 +825,8 @@ impl CsvEditor {
             
             let line = self.format_row(&fields, options);
             writer.write_all(line.as_bytes())?;
-            writer.write_all(options.line_ending.as_bytes())?;
-            bytes_written += line.len() + options.line_ending.as_bytes().len();
+            writer.write_all(line_ending)?;
+            bytes_written += line.len() + line_ending.len();
             rows_written += 1;
         }
         
@@ -568,6 +845,9 @@ impl CsvEditor {
         let temp_path = format!("{}.tmp", self.source_path);
         let stats = self.save(&temp_path, options)?;
         
+        // 关闭源文件的内存映射（Windows下无法覆盖仍被映射的文件），之后读取时重新打开
+        *self.reader.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        
         // 重命名临时文件覆盖原文件
         std::fs::rename(&temp_path, &self.source_path)?;
         
@@ -579,20 +859,31 @@ impl CsvEditor {
 
     /// 格式化一行数据
     fn format_row(&self, fields: &[&str], options: &WriteOptions) -> String {
-        let delimiter = options.delimiter as char;
-        
         fields
             .iter()
             .map(|field| self.escape_field(field, options))
             .collect::<Vec<_>>()
-            .join(&delimiter.to_string())
+            .join(&options.delimiter.to_string())
+    }
+
+    /// 格式化一行数据，`quoted` 为源字段是否带引号（None时按常规规则）
+    fn format_row_quoted(&self, fields: &[(&str, Option<bool>)], options: &WriteOptions) -> String {
+        fields
+            .iter()
+            .map(|&(field, quoted)| self.escape_field_as(field, quoted.unwrap_or(options.always_quote), options))
+            .collect::<Vec<_>>()
+            .join(&options.delimiter.to_string())
     }
 
     /// 转义字段值
     fn escape_field(&self, field: &str, options: &WriteOptions) -> String {
-        let delimiter = options.delimiter as char;
-        let needs_quote = options.always_quote
-            || field.contains(delimiter)
+        self.escape_field_as(field, options.always_quote, options)
+    }
+
+    /// 转义字段值，`quote` 为true时总是加引号，否则只在必要时加引号
+    fn escape_field_as(&self, field: &str, quote: bool, options: &WriteOptions) -> String {
+        let needs_quote = quote
+            || options.delimiter.occurs_in(field)
             || field.contains('"')
             || field.contains('\n')
             || field.contains('\r');
@@ -697,7 +988,7 @@ impl CsvCreator {
         let mut writer = BufWriter::new(file);
         
         let mut bytes_written = 0;
-        let delimiter = self.options.delimiter as char;
+        let delimiter = self.options.delimiter.to_string();
         
         // 写入表头
         if self.options.write_headers && !self.headers.is_empty() {
@@ -705,7 +996,7 @@ impl CsvCreator {
                 .iter()
                 .map(|h| escape_csv_field(h, &self.options))
                 .collect::<Vec<_>>()
-                .join(&delimiter.to_string());
+                .join(&delimiter);
             
             writer.write_all(line.as_bytes())?;
             writer.write_all(self.options.line_ending.as_bytes())?;
@@ -718,7 +1009,7 @@ impl CsvCreator {
                 .iter()
                 .map(|f| escape_csv_field(f, &self.options))
                 .collect::<Vec<_>>()
-                .join(&delimiter.to_string());
+                .join(&delimiter);
             
             writer.write_all(line.as_bytes())?;
             writer.write_all(self.options.line_ending.as_bytes())?;
@@ -735,11 +1026,74 @@ impl CsvCreator {
     }
 }
 
+/// 按给定顺序把源文件中的行写入新文件（先写表头）
+/// 
+/// `spans` 为各行在 [`CsvReader::data`] 中的字节位置。输出分隔符与源文件相同且不总是引用字段时
+/// 原样复制每行的字节，保留原有的引号写法；否则重新解析该行并按写入选项转义。
+/// `preserve_line_ending` 时沿用源文件的行结束符
+pub(crate) fn write_spans_to_file(
+    reader: &CsvReader,
+    spans: impl IntoIterator<Item = (usize, usize)>,
+    output_path: &Path,
+    options: &WriteOptions,
+) -> Result<SaveStats> {
+    let line_ending = if options.preserve_line_ending {
+        reader.info().line_ending
+    } else {
+        options.line_ending
+    }
+    .as_bytes();
+
+    let mut writer = BufWriter::new(File::create(output_path)?);
+    let mut bytes_written = 0;
+    let mut write_line = |line: &[u8]| -> Result<()> {
+        writer.write_all(line)?;
+        writer.write_all(line_ending)?;
+        bytes_written += line.len() + line_ending.len();
+        Ok(())
+    };
+
+    if options.write_headers && !reader.headers().is_empty() {
+        write_line(format_csv_line(reader.headers().iter().map(String::as_str), options).as_bytes())?;
+    }
+
+    let data = reader.data();
+    let copy_raw = options.delimiter == *reader.delimiter() && !options.always_quote;
+    let mut rows_written = 0;
+    for (start, end) in spans {
+        let line = &data[start..end];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if copy_raw {
+            write_line(line)?;
+        } else {
+            let record = reader.parse_line(line);
+            write_line(format_csv_line(record.fields.iter().map(|f| f.as_ref()), options).as_bytes())?;
+        }
+        rows_written += 1;
+    }
+
+    writer.flush()?;
+
+    Ok(SaveStats {
+        rows_written,
+        bytes_written,
+        file_path: output_path.to_string_lossy().to_string(),
+    })
+}
+
+/// 按写入选项转义字段并用分隔符连接成一行（不含行结束符）
+pub(crate) fn format_csv_line<'f>(fields: impl IntoIterator<Item = &'f str>, options: &WriteOptions) -> String {
+    fields
+        .into_iter()
+        .map(|field| escape_csv_field(field, options))
+        .collect::<Vec<_>>()
+        .join(&options.delimiter.to_string())
+}
+
 /// 转义CSV字段
-fn escape_csv_field(field: &str, options: &WriteOptions) -> String {
-    let delimiter = options.delimiter as char;
+pub(crate) fn escape_csv_field(field: &str, options: &WriteOptions) -> String {
     let needs_quote = options.always_quote
-        || field.contains(delimiter)
+        || options.delimiter.occurs_in(field)
         || field.contains('"')
         || field.contains('\n')
         || field.contains('\r');
@@ -778,6 +1132,45 @@ mod tests {
         assert_eq!(escape_csv_field("with\nnewline", &options), "\"with\nnewline\"");
     }
 
+    #[test]
+    fn test_escape_multi_byte_delimiter() {
+        let options = WriteOptions::new().with_delimiter(Delimiter::new(b"||"));
+
+        // 只有出现完整的分隔符时才需要引用
+        assert_eq!(escape_csv_field("a|b", &options), "a|b");
+        assert_eq!(escape_csv_field("a||b", &options), "\"a||b\"");
+        assert_eq!(escape_csv_field("a,b", &options), "a,b");
+    }
+
+    #[test]
+    fn test_line_ending_detect() {
+        assert_eq!(LineEnding::detect(b"a,b\nc,d\n"), Some(LineEnding::Lf));
+        assert_eq!(LineEnding::detect(b"a,b\r\nc,d\r\n"), Some(LineEnding::CrLf));
+        assert_eq!(LineEnding::detect(b"a,b\rc,d\r"), Some(LineEnding::Cr));
+        assert_eq!(LineEnding::detect(b"a,b"), None);
+        // 引号内偶尔出现的 \r 不影响判断
+        assert_eq!(LineEnding::detect(b"a,\"x\ry\"\nc,d\n"), Some(LineEnding::Lf));
+    }
+
+    #[test]
+    fn test_escape_round_trip_quote_modes() {
+        use crate::csv::QuoteMode;
+
+        let options = WriteOptions::default();
+        let fields = ["plain", " padded ", "a,b", " \"a\" ", "\"", ""];
+        let line = fields
+            .iter()
+            .map(|field| escape_csv_field(field, &options))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        // 转义后的字段在两种引号模式下都能还原
+        for mode in [QuoteMode::Flexible, QuoteMode::Strict] {
+            let record = CsvRecord::parse_line_mode(line.as_bytes(), &options.delimiter, mode);
+            assert_eq!(record.fields, fields, "{:?}", mode);
+        }
+    }
+
     #[test]
     fn test_write_options() {
         let options = WriteOptions::new()
@@ -785,11 +1178,107 @@ mod tests {
             .with_always_quote(true)
             .with_headers(false);
         
-        assert_eq!(options.delimiter, b'\t');
+        assert_eq!(options.delimiter, Delimiter::Byte(b'\t'));
         assert!(options.always_quote);
         assert!(!options.write_headers);
     }
 
+    #[test]
+    fn test_preserve_quoting_on_edit() {
+        let source = std::env::temp_dir().join("test_writer_preserve_quoting.csv");
+        let output = std::env::temp_dir().join("test_writer_preserve_quoting_out.csv");
+        std::fs::write(&source, "\"id\",\"name\",\"city\"\n\"1\",\"Alice\",\"Beijing\"\n2,\"Bob\",Shanghai\n").unwrap();
+
+        let mut editor = CsvEditor::open(&source, true, b',', 1000).unwrap();
+        editor.edit_cell(0, 1, "Carol".to_string()).unwrap();
+
+        // 只有修改过的单元格按常规规则写出，其余字段保持原样
+        let options = WriteOptions::new()
+            .with_line_ending(LineEnding::Lf)
+            .with_preserve_quoting(true);
+        editor.save(&output, &options).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            "\"id\",\"name\",\"city\"\n\"1\",Carol,\"Beijing\"\n2,\"Bob\",Shanghai\n"
+        );
+
+        // 默认按常规规则重新转义所有字段
+        editor.save(&output, &WriteOptions::new().with_line_ending(LineEnding::Lf)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            "id,name,city\n1,Carol,Beijing\n2,Bob,Shanghai\n"
+        );
+
+        std::fs::remove_file(&source).ok();
+        std::fs::remove_file(&output).ok();
+        std::fs::remove_file(crate::csv::RowIndex::index_file_path(&source)).ok();
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let source = std::env::temp_dir().join("test_writer_undo_redo.csv");
+        std::fs::write(&source, "id,name\n1,Alice\n2,Bob\n").unwrap();
+
+        let mut editor = CsvEditor::open(&source, true, b',', 1000).unwrap();
+        assert!(!editor.can_undo());
+
+        // 撤销后读回原值，重做后读回新值
+        editor.edit_cell(0, 1, "Carol".to_string()).unwrap();
+        assert!(editor.undo());
+        assert_eq!(editor.get_cell(0, 1).unwrap().as_deref(), Some("Alice"));
+        assert!(!editor.has_changes());
+        assert!(editor.can_redo());
+        assert!(editor.redo());
+        assert_eq!(editor.get_cell(0, 1).unwrap().as_deref(), Some("Carol"));
+
+        // 撤销删除时恢复该行的编辑
+        editor.delete_row(0).unwrap();
+        assert_eq!(editor.get_cell(0, 1).unwrap(), None);
+        editor.undo();
+        assert_eq!(editor.get_cell(0, 1).unwrap().as_deref(), Some("Carol"));
+
+        // 新的修改清空重做记录
+        editor.append_row(RowData::from_strs(&["3", "Dave"])).unwrap();
+        editor.undo();
+        assert_eq!(editor.effective_row_count(), 2);
+        editor.set_header(1, "full_name".to_string()).unwrap();
+        assert!(!editor.can_redo());
+        editor.undo();
+        assert_eq!(editor.headers()[1], "name");
+
+        // 撤销到最初状态后没有未保存的修改
+        while editor.undo() {}
+        assert!(!editor.has_changes());
+        assert!(!editor.undo());
+
+        std::fs::remove_file(&source).ok();
+        std::fs::remove_file(crate::csv::RowIndex::index_file_path(&source)).ok();
+    }
+
+    #[test]
+    fn test_get_cell_reuses_reader() {
+        let source = std::env::temp_dir().join("test_writer_get_cell.csv");
+        let mut content = String::from("id,name\n");
+        for i in 0..10_000 {
+            content.push_str(&format!("{},name{}\n", i, i));
+        }
+        std::fs::write(&source, content).unwrap();
+
+        let editor = CsvEditor::open(&source, true, b',', 100).unwrap();
+        let start = std::time::Instant::now();
+        for i in 0..1000 {
+            let row = (i * 7919) % 10_000;
+            assert_eq!(editor.get_cell(row, 1).unwrap(), Some(format!("name{}", row)));
+        }
+        // 每次读取都重新打开文件时需要数秒
+        assert!(start.elapsed() < std::time::Duration::from_secs(2), "{:?}", start.elapsed());
+        assert_eq!(editor.get_cell(9999, 0).unwrap().as_deref(), Some("9999"));
+        assert_eq!(editor.get_cell(10_000, 0).unwrap(), None);
+
+        std::fs::remove_file(&source).ok();
+        std::fs::remove_file(crate::csv::RowIndex::index_file_path(&source)).ok();
+    }
+
     #[test]
     fn test_change_stats() {
         let stats = ChangeStats {
@@ -0,0 +1,153 @@
+//! 终端显示辅助函数
+//!
+//! 按终端显示宽度（CJK等全角字符占2列）测量和截断文本，
+//! 文本中的ANSI转义序列（如搜索高亮）不占宽度，截断时也不会被截成两半
+
+use unicode_width::UnicodeWidthChar;
+
+/// ANSI重置序列
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// 文本片段：普通字符或一个完整的ANSI转义序列
+enum Segment<'a> {
+    Char(char),
+    Escape(&'a str),
+}
+
+/// 把文本拆分为字符和ANSI转义序列
+///
+/// 识别 `ESC [ ... 终止字节` 形式的CSI序列；不完整的序列视为普通字符
+fn segments(s: &str) -> impl Iterator<Item = Segment<'_>> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let c = rest.chars().next()?;
+        if c == '\x1b' && rest[1..].starts_with('[') {
+            let body = &rest[2..];
+            if let Some(end) = body.find(|c: char| ('\x40'..='\x7e').contains(&c)) {
+                let (escape, tail) = rest.split_at(2 + end + 1);
+                rest = tail;
+                return Some(Segment::Escape(escape));
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+        Some(Segment::Char(c))
+    })
+}
+
+/// 计算字符串的终端显示宽度，ANSI转义序列不计入
+pub fn visible_width(s: &str) -> usize {
+    segments(s)
+        .map(|segment| match segment {
+            Segment::Char(c) => UnicodeWidthChar::width(c).unwrap_or(0),
+            Segment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// 按显示宽度截断字符串，超出部分以 "…" 结尾，结果的显示宽度不超过 `max_width`
+///
+/// 全角字符放不下时整个舍去，不会把一个字符截成两半；
+/// ANSI转义序列按整体保留，截断后先重置样式再添加 "…"，避免颜色延续到表格边框
+pub fn truncate_to_width(s: &str, max_width: usize) -> String {
+    if visible_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    let target = max_width - 1;
+    let mut width = 0;
+    let mut styled = false;
+    let mut truncated = String::new();
+    for segment in segments(s) {
+        match segment {
+            Segment::Escape(escape) => {
+                truncated.push_str(escape);
+                styled = escape != ANSI_RESET;
+            }
+            Segment::Char(c) => {
+                let w = UnicodeWidthChar::width(c).unwrap_or(0);
+                if width + w > target {
+                    break;
+                }
+                width += w;
+                truncated.push(c);
+            }
+        }
+    }
+    if styled {
+        truncated.push_str(ANSI_RESET);
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// 按显示宽度在右侧补空格
+pub fn pad_right(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(visible_width(s))))
+}
+
+/// 按显示宽度在左侧补空格
+pub fn pad_left(s: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(visible_width(s))), s)
+}
+
+/// 按显示宽度居中，无法平分时多出的空格补在右侧
+pub fn pad_center(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(visible_width(s));
+    let left = padding / 2;
+    format!("{}{}{}", " ".repeat(left), s, " ".repeat(padding - left))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("hello", 10), "hello");
+        assert_eq!(truncate_to_width("hello world", 8), "hello w…");
+        assert_eq!(visible_width(&truncate_to_width("hello world", 8)), 8);
+
+        // 每个汉字占两列：宽度为6时只能放下两个汉字和截断标记
+        assert_eq!(truncate_to_width("北京上海深圳", 6), "北京…");
+        assert_eq!(truncate_to_width("北京上海深圳", 12), "北京上海深圳");
+        assert!(visible_width(&truncate_to_width("北京上海深圳", 6)) <= 6);
+
+        // 宽度过小时不会panic，也不会超出宽度
+        assert_eq!(truncate_to_width("hello", 1), "…");
+        assert_eq!(truncate_to_width("北京", 1), "…");
+        assert_eq!(truncate_to_width("hello", 0), "");
+    }
+
+    #[test]
+    fn test_ansi_aware_width() {
+        let highlighted = "ab\x1b[1;33m北京\x1b[0mcd";
+        assert_eq!(visible_width(highlighted), 8);
+        assert_eq!(visible_width("\x1b[1;33m\x1b[0m"), 0);
+
+        // 截断在高亮内部时保留完整的转义序列，并在 "…" 之前重置
+        assert_eq!(truncate_to_width(highlighted, 5), "ab\x1b[1;33m北\x1b[0m…");
+        assert_eq!(visible_width(&truncate_to_width(highlighted, 5)), 5);
+
+        // 截断在高亮之后时不需要额外重置
+        assert_eq!(truncate_to_width(highlighted, 7), "ab\x1b[1;33m北京\x1b[0m…");
+
+        // 不会在转义序列中间截断
+        for width in 0..=8 {
+            let truncated = truncate_to_width(highlighted, width);
+            assert!(visible_width(&truncated) <= width);
+            assert_eq!(truncated.matches('\x1b').count() % 2, 0, "{:?}", truncated);
+        }
+    }
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(pad_right("北京", 6), "北京  ");
+        assert_eq!(pad_left("\x1b[1m1\x1b[0m", 3), "  \x1b[1m1\x1b[0m");
+        assert_eq!(pad_right("toolong", 3), "toolong");
+        assert_eq!(pad_center("名称", 7), " 名称  ");
+        assert_eq!(pad_center("toolong", 3), "toolong");
+    }
+}
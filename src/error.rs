@@ -11,6 +11,10 @@ pub enum CsvError {
     #[error("CSV解析错误: {0}")]
     Parse(#[from] csv::Error),
 
+    /// 指定行的数据错误（行号从1开始，不含表头）
+    #[error("第 {row} 行数据错误: {message}")]
+    ParseAt { row: usize, message: String },
+
     /// 索引错误：行号超出范围
     #[error("索引错误: 行 {row} 超出范围（总行数: {total_rows}）")]
     IndexOutOfBounds { row: usize, total_rows: usize },
@@ -26,6 +30,10 @@ pub enum CsvError {
     /// 索引文件错误
     #[error("索引文件错误: {0}")]
     IndexFile(String),
+
+    /// SQLite错误
+    #[error("SQLite错误: {0}")]
+    Sqlite(#[from] rusqlite::Error),
 }
 
 /// 结果类型别名
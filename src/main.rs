@@ -1,9 +1,25 @@
-use clap::{Parser, Subcommand};
-use csv_tool::csv::{CsvReader, RowIndex, format_size, SearchPattern, SearchOptions, highlight_matches, ExportFormat, ExportOptions, Exporter, SortOrder, SortKey, SortOptions, DataType, sort_csv_data, CsvEditor, CsvCreator, RowData, WriteOptions};
+mod display;
+#[cfg_attr(not(feature = "interactive"), allow(dead_code))]
+mod pager;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use csv_tool::csv::{CsvReader, RowIndex, format_size, SearchPattern, SearchOptions, HighlightColor, HighlightStyle, highlight_matches, ExportFormat, ExportOptions, Exporter, RaggedPolicy, SortOrder, SortKey, SortOptions, DataType, Collation, sort_csv_data, sort_to_file, CsvEditor, CsvCreator, CellEdit, RowData, WriteOptions, TextEncoding, infer_schema, value_counts, column_stats_with_options, histogram, StatsOptions, FloatFormat, NumericCleanup, SampleOptions, random_seed, sample_to_file, shuffle_to_file};
+use csv_tool::csv::{CsvRecord, Delimiter, IndexLocation, LineEnding, ProgressCallback, IndexMode, QuoteMode, ReaderOptions, detect_delimiter, guess_has_headers_with_delimiter, HeaderGuess, DEFAULT_CACHE_PAGES};
+use csv_tool::csv::compression::is_gzip_file;
+use csv_tool::csv::lines::is_empty_line;
+use csv_tool::csv::schema::DEFAULT_SAMPLE_ROWS;
 use csv_tool::error::Result;
+use serde::Serialize;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::Path;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{IsTerminal, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
+use display::{truncate_to_width, visible_width};
 
 /// 高性能CSV文件查看工具
 #[derive(Parser)]
@@ -21,8 +37,12 @@ CSV Tool - 高性能CSV文件查看和处理工具
 示例:
   csv-tool data.csv              查看第1页
   csv-tool data.csv -p 5         查看第5页
+  csv-tool data.csv view --from 100 --to 120  查看第100-120行
   csv-tool data.csv info         显示文件详细信息
   csv-tool data.csv search 关键词  搜索关键词
+  csv-tool data.csv query --sql 'SELECT COUNT(*) FROM data'  SQL查询
+  csv-tool data.csv schema       推断每列的数据类型
+  csv-tool data.csv tail -f      持续输出新追加的行
   csv-tool data.csv -d ';'       使用分号作为分隔符
 ")]
 struct Args {
@@ -34,9 +54,9 @@ struct Args {
     #[arg(value_name = "PAGE", help_heading = "向后兼容")]
     page_arg: Option<usize>,
 
-    /// 分隔符字符
-    #[arg(short, long, default_value = ",", value_name = "CHAR")]
-    delimiter: char,
+    /// 分隔符（单个ASCII字符或 || 这样的多字符分隔符，制表符可写作 \t 或 tab；未指定时自动检测）
+    #[arg(short, long, value_name = "DELIM", value_parser = Delimiter::parse)]
+    delimiter: Option<Delimiter>,
 
     /// 页码（从1开始）
     #[arg(short, long, value_name = "PAGE")]
@@ -46,14 +66,34 @@ struct Args {
     #[arg(short = 's', long, default_value = "20", value_name = "SIZE")]
     page_size: usize,
 
-    /// 文件不包含表头
-    #[arg(short = 'n', long)]
+    /// 彩色输出（auto: 仅在终端中启用）
+    #[arg(long, value_enum, default_value = "auto", value_name = "WHEN")]
+    color: ColorMode,
+
+    /// 搜索结果的高亮颜色 (red, green, yellow, blue, magenta, cyan)
+    #[arg(long, default_value = "yellow", value_name = "COLOR", value_parser = parse_highlight_color)]
+    highlight_color: HighlightColor,
+
+    /// 表格单列最大显示宽度（列宽按内容自适应）
+    #[arg(long, default_value = "30", value_name = "WIDTH")]
+    max_col_width: usize,
+
+    /// 第一行是否为表头（auto: 自动检测）
+    #[arg(long, value_enum, default_value = "auto", value_name = "WHEN")]
+    headers: HeaderMode,
+
+    /// 文件不包含表头（等同于 --headers no）
+    #[arg(short = 'n', long, conflicts_with = "headers")]
     no_headers: bool,
 
     /// 索引粒度（每N行记录一次索引点）
     #[arg(short, long, default_value = "1000", value_name = "N")]
     granularity: usize,
 
+    /// 使用稠密索引（记录每一行的偏移量，随机跳转更快，但索引文件更大）
+    #[arg(long)]
+    dense_index: bool,
+
     /// 安静模式（减少输出信息）
     #[arg(short, long)]
     quiet: bool,
@@ -66,11 +106,149 @@ struct Args {
     #[arg(long)]
     rebuild_index: bool,
 
+    /// 不读写 .idx 索引文件（索引只保存在内存中）
+    #[arg(long)]
+    no_index_cache: bool,
+
+    /// 用整个文件的哈希校验缓存的索引（较慢，可发现文件中间大小不变的修改）
+    #[arg(long)]
+    verify_index: bool,
+
+    /// 页面缓存容量（页数）
+    #[arg(long, default_value_t = DEFAULT_CACHE_PAGES, value_name = "N")]
+    cache_pages: usize,
+
+    /// 不缓存读取的页面（等同于 --cache-pages 0）
+    #[arg(long, conflicts_with = "cache_pages")]
+    no_cache: bool,
+
+    /// 严格按RFC 4180识别引号字段（默认忽略引号外的空白，如 ` "a,b" ,c`）
+    #[arg(long)]
+    strict_quotes: bool,
+
+    /// 保留空行（默认去掉，空行不计入行数；保留时不读写索引文件）
+    #[arg(long)]
+    keep_empty_lines: bool,
+
+    /// 索引文件存放位置（sidecar: CSV文件同目录，无权写入时改存到缓存目录；cache: 用户缓存目录）
+    #[arg(long, value_enum, value_name = "LOCATION")]
+    index_location: Option<IndexLocationMode>,
+
+    /// 索引文件存放目录（未指定时使用环境变量 CSV_TOOL_INDEX_DIR）
+    #[arg(long, value_name = "DIR", conflicts_with = "index_location")]
+    index_dir: Option<PathBuf>,
+
+    /// 保留gzip输入解压得到的临时文件
+    #[arg(long)]
+    keep_decompressed: bool,
+
+    /// 严格模式：打开时校验每行的列数，发现不一致立即报错
+    #[arg(long)]
+    strict: bool,
+
     /// 子命令
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+/// 彩色输出模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorMode {
+    /// 标准输出为终端且 NO_COLOR 未设置或为空时启用
+    Auto,
+    /// 始终启用
+    Always,
+    /// 始终禁用
+    Never,
+}
+
+impl ColorMode {
+    /// 是否输出ANSI颜色
+    fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && std::io::stdout().is_terminal()
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// 索引文件存放位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum IndexLocationMode {
+    /// CSV文件同目录
+    Sidecar,
+    /// 用户缓存目录
+    Cache,
+}
+
+/// 表头模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HeaderMode {
+    /// 根据第一行内容自动检测
+    Auto,
+    /// 第一行是表头
+    Yes,
+    /// 没有表头（以 col1..colN 作为列名）
+    No,
+}
+
+/// 结果输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// 表格（默认）
+    Table,
+    /// JSON对象
+    Json,
+    /// 原始CSV
+    Csv,
+}
+
+/// 不同值的排列方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DistinctOrder {
+    /// 按出现次数降序（默认）
+    Count,
+    /// 按值的字母顺序
+    Alpha,
+}
+
+/// 结构化输出的数据
+#[derive(Serialize)]
+struct OutputData {
+    headers: Vec<String>,
+    rows: Vec<OutputRow>,
+}
+
+/// 结构化输出的一行
+#[derive(Serialize)]
+struct OutputRow {
+    /// 行号（从1开始）
+    row_number: usize,
+    fields: Vec<String>,
+    /// 匹配的列号（从1开始，仅搜索结果）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matched_columns: Option<Vec<usize>>,
+    /// 匹配的模式序号（从1开始，仅多个模式的搜索结果）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matched_patterns: Option<Vec<usize>>,
+}
+
+impl OutputRow {
+    /// 从记录创建（按显示列投影）
+    fn new(row_number: usize, record: &CsvRecord<'_>, columns: Option<&[usize]>) -> Self {
+        Self {
+            row_number,
+            fields: project_fields(record, columns).into_iter().map(str::to_string).collect(),
+            matched_columns: None,
+            matched_patterns: None,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// 显示文件详细信息
@@ -79,119 +257,176 @@ enum Commands {
     /// 查看CSV数据（默认行为）
     View {
         /// 指定查看的页码
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with_all = ["from", "to"])]
         page: Option<usize>,
-    },
 
-    /// 搜索CSV数据
-    Search {
-        /// 搜索模式（文本或正则表达式）
-        pattern: String,
+        /// 起始行号（从1开始，包含）
+        #[arg(long, value_name = "ROW")]
+        from: Option<usize>,
 
-        /// 使用正则表达式
-        #[arg(short = 'r', long)]
-        regex: bool,
+        /// 结束行号（包含），省略时显示一页
+        #[arg(long, value_name = "ROW")]
+        to: Option<usize>,
 
-        /// 大小写不敏感
-        #[arg(short = 'i', long)]
-        ignore_case: bool,
+        /// 只显示指定列（列名或列号，逗号分隔）
+        #[arg(long, value_name = "COLUMNS")]
+        columns: Option<String>,
 
-        /// 在指定列中搜索（列名或列号，从1开始）
-        #[arg(short = 'c', long, value_name = "COLUMN")]
-        column: Option<String>,
+        /// 输出格式 (table, json, csv)
+        #[arg(long, value_enum, default_value = "table", value_name = "FORMAT")]
+        format: OutputFormat,
 
-        /// 显示行号
-        #[arg(short = 'l', long)]
-        line_numbers: bool,
+        /// 交互式分页浏览（方向键/n/p翻页，页码+回车跳转，q退出；需要启用 interactive 特性编译）
+        #[arg(short, long, conflicts_with_all = ["from", "to", "format"])]
+        interactive: bool,
+    },
 
-        /// 只统计匹配数量
-        #[arg(long)]
-        count: bool,
+    /// 搜索CSV数据
+    Search(SearchArgs),
+
+    /// 导出CSV数据为其他格式
+    Export(ExportArgs),
+
+    /// 按列排序数据
+    Sort(SortArgs),
 
-        /// 最大结果数
-        #[arg(short = 'm', long, value_name = "N")]
-        max_results: Option<usize>,
+    /// 随机抽取若干行写入新文件（只扫描一遍，适合任意大小的文件）
+    Sample {
+        /// 抽取的行数
+        #[arg(short = 'n', long, value_name = "N")]
+        rows: usize,
+
+        /// 输出文件路径
+        #[arg(short = 'o', long, value_name = "FILE")]
+        output: String,
 
-        /// 反向匹配（显示不匹配的行）
-        #[arg(short = 'V', long)]
-        invert_match: bool,
+        /// 随机种子（相同种子得到相同的结果，默认随机）
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
 
-        /// 禁用高亮显示
+        /// 按原文件中的顺序输出抽到的行
         #[arg(long)]
-        no_highlight: bool,
+        keep_order: bool,
     },
 
-    /// 导出CSV数据为其他格式
-    Export {
+    /// 随机打乱所有行写入新文件（需要在内存中保存每行的位置）
+    Shuffle {
         /// 输出文件路径
+        #[arg(short = 'o', long, value_name = "FILE")]
         output: String,
 
-        /// 导出格式 (json, jsonl, csv, tsv)
-        #[arg(short, long, value_name = "FORMAT")]
-        format: Option<String>,
+        /// 随机种子（相同种子得到相同的结果，默认随机）
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
+    },
 
-        /// 导出指定列（列名或列号，逗号分隔）
-        #[arg(short = 'c', long, value_name = "COLUMNS")]
-        columns: Option<String>,
+    /// 预先构建并缓存列索引（之后按该列搜索纯文本、排序时只读取该列）
+    Index {
+        /// 列名或列号（从1开始）
+        #[arg(short = 'c', long, value_name = "COLUMN")]
+        column: String,
+    },
 
-        /// 起始行（从1开始）
-        #[arg(long, value_name = "ROW")]
-        from: Option<usize>,
+    /// 推断每列的数据类型
+    Schema {
+        /// 采样行数
+        #[arg(long, default_value = "1000", value_name = "N")]
+        sample: usize,
+    },
 
-        /// 结束行
-        #[arg(long, value_name = "ROW")]
-        to: Option<usize>,
+    /// 统计一列中每个不同值的出现次数
+    Distinct {
+        /// 列名或列号（从1开始）
+        #[arg(value_name = "COLUMN")]
+        column: String,
 
-        /// 只导出匹配搜索条件的行
-        #[arg(long, value_name = "PATTERN")]
-        search: Option<String>,
+        /// 排列方式 (count, alpha)
+        #[arg(long, value_enum, default_value = "count", value_name = "ORDER")]
+        sort: DistinctOrder,
 
-        /// 搜索使用正则表达式
-        #[arg(short = 'r', long)]
-        regex: bool,
+        /// 只显示出现次数最多的N个值
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
 
-        /// JSON美化输出
-        #[arg(long)]
-        pretty: bool,
+        /// 大小写不敏感
+        #[arg(short = 'i', long)]
+        ignore_case: bool,
+    },
 
-        /// 不包含表头（CSV/TSV格式）
-        #[arg(long)]
-        no_headers: bool,
+    /// 统计一列的空值数、不同值数和数值范围
+    Stats {
+        /// 列名或列号（从1开始）
+        #[arg(value_name = "COLUMN")]
+        column: String,
+
+        /// 浮点数保留N位小数（默认使用最短写法）
+        #[arg(long, value_name = "N")]
+        precision: Option<usize>,
+
+        /// 非数值列显示出现次数最多的N个值
+        #[arg(long, default_value = "10", value_name = "N")]
+        top: usize,
+
+        /// 数值列额外计算的百分位（逗号分隔，0到100，如 50,90,99）
+        #[arg(long, value_name = "P", value_delimiter = ',', value_parser = parse_percentile)]
+        percentiles: Vec<f64>,
+
+        /// 近似计算百分位（t-digest，内存占用固定，适合大文件）
+        #[arg(long, requires = "percentiles")]
+        approx: bool,
     },
 
-    /// 按列排序数据
-    Sort {
-        /// 排序列（列名或列号，从1开始）
+    /// 显示数值列的分布直方图
+    Histogram {
+        /// 列名或列号（从1开始）
         #[arg(value_name = "COLUMN")]
         column: String,
 
-        /// 排序方向 (asc/desc)
-        #[arg(long, default_value = "asc")]
-        order: String,
+        /// 区间个数
+        #[arg(long, default_value = "10", value_name = "N")]
+        bins: usize,
 
-        /// 数据类型 (auto/string/number)
-        #[arg(short = 't', long, default_value = "auto")]
-        data_type: String,
+        /// 范围下限（默认为该列的最小值）
+        #[arg(long, value_name = "VALUE", allow_negative_numbers = true)]
+        min: Option<f64>,
 
-        /// 显示结果数量限制
-        #[arg(short = 'n', long, value_name = "N")]
-        limit: Option<usize>,
+        /// 范围上限（默认为该列的最大值）
+        #[arg(long, value_name = "VALUE", allow_negative_numbers = true)]
+        max: Option<f64>,
+    },
 
-        /// 大小写不敏感（字符串排序）
-        #[arg(short = 'i', long)]
-        ignore_case: bool,
+    /// 使用SQL查询数据（载入内存SQLite，表名为 data）
+    Query {
+        /// SQL语句
+        #[arg(long, value_name = "SQL")]
+        sql: String,
 
-        /// 空值排在最前
-        #[arg(long)]
-        nulls_first: bool,
+        /// 只载入指定列（列名或列号，逗号分隔）
+        #[arg(short = 'c', long, value_name = "COLUMNS")]
+        columns: Option<String>,
 
-        /// 显示行号
-        #[arg(short = 'l', long)]
-        line_numbers: bool,
+        /// 起始行（从1开始）
+        #[arg(long, value_name = "ROW")]
+        from: Option<usize>,
 
-        /// 导出排序结果到文件
-        #[arg(short = 'o', long, value_name = "FILE")]
-        output: Option<String>,
+        /// 结束行
+        #[arg(long, value_name = "ROW")]
+        to: Option<usize>,
+    },
+
+    /// 显示文件末尾的行，可持续跟踪新追加的行
+    Tail {
+        /// 显示末尾的行数
+        #[arg(short = 'n', long, default_value = "10", value_name = "N")]
+        lines: usize,
+
+        /// 持续输出新追加的行（Ctrl-C 退出）
+        #[arg(short = 'f', long)]
+        follow: bool,
+
+        /// 检查文件变化的间隔（毫秒）
+        #[arg(long, default_value = "500", value_name = "MS")]
+        interval: u64,
     },
 
     /// 编辑CSV文件
@@ -216,6 +451,247 @@ enum Commands {
     },
 }
 
+/// search 子命令的参数
+#[derive(clap::Args)]
+struct SearchArgs {
+    /// 搜索模式（文本或正则表达式）
+    #[arg(required_unless_present_any = ["extra_patterns", "pattern_file"])]
+    pattern: Option<String>,
+
+    /// 额外的搜索模式（可重复，任一模式匹配即可，只扫描一遍文件）
+    #[arg(short = 'e', long = "pattern", value_name = "PATTERN")]
+    extra_patterns: Vec<String>,
+
+    /// 从文件读取搜索模式（每行一个，忽略空行）
+    #[arg(short = 'f', long, value_name = "FILE")]
+    pattern_file: Option<String>,
+
+    /// 使用正则表达式
+    #[arg(short = 'r', long)]
+    regex: bool,
+
+    /// 使用通配符（* 匹配任意字符，? 匹配一个字符，[abc] 匹配其中之一），须匹配整个字段
+    #[arg(short = 'g', long, conflicts_with = "regex")]
+    glob: bool,
+
+    /// 大小写不敏感
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// 只匹配整个单词（如 cat 不匹配 concatenate）
+    #[arg(short = 'w', long)]
+    word: bool,
+
+    /// 在指定列中搜索（列名或列号，从1开始）
+    #[arg(short = 'c', long, value_name = "COLUMN")]
+    column: Option<String>,
+
+    /// 显示行号
+    #[arg(short = 'l', long)]
+    line_numbers: bool,
+
+    /// 只统计匹配数量
+    #[arg(long)]
+    count: bool,
+
+    /// 最大结果数
+    #[arg(short = 'm', long, value_name = "N")]
+    max_results: Option<usize>,
+
+    /// 反向匹配（显示不匹配的行）
+    #[arg(short = 'V', long)]
+    invert_match: bool,
+
+    /// 禁用高亮显示
+    #[arg(long)]
+    no_highlight: bool,
+
+    /// 只显示指定列（列名或列号，逗号分隔），不影响搜索范围
+    #[arg(long, value_name = "COLUMNS")]
+    columns: Option<String>,
+
+    /// 每个匹配的字段输出一行：行号、列名和字段内容（表格格式）
+    #[arg(short = 'o', long, conflicts_with_all = ["count", "invert_match", "columns", "matching_columns"])]
+    only_matching: bool,
+
+    /// 只显示在搜索结果中有过匹配的列
+    #[arg(long, conflicts_with_all = ["count", "invert_match", "columns"])]
+    matching_columns: bool,
+
+    /// 输出格式 (table, json, csv)
+    #[arg(long, value_enum, default_value = "table", value_name = "FORMAT")]
+    format: OutputFormat,
+}
+
+/// export 子命令的参数
+#[derive(clap::Args)]
+struct ExportArgs {
+    /// 输出文件路径（`-` 表示标准输出）
+    output: String,
+
+    /// 导出格式 (json, jsonl, csv, tsv, sqlite)
+    #[arg(short, long, value_name = "FORMAT")]
+    format: Option<String>,
+
+    /// 导出指定列（列名或列号，逗号分隔）
+    #[arg(short = 'c', long, value_name = "COLUMNS")]
+    columns: Option<String>,
+
+    /// 起始行（从1开始）
+    #[arg(long, value_name = "ROW")]
+    from: Option<usize>,
+
+    /// 结束行
+    #[arg(long, value_name = "ROW")]
+    to: Option<usize>,
+
+    /// 只导出匹配搜索条件的行
+    #[arg(long, value_name = "PATTERN")]
+    search: Option<String>,
+
+    /// 搜索使用正则表达式
+    #[arg(short = 'r', long)]
+    regex: bool,
+
+    /// 搜索使用通配符（须匹配整个字段）
+    #[arg(short = 'g', long, conflicts_with = "regex")]
+    glob: bool,
+
+    /// JSON美化输出
+    #[arg(long)]
+    pretty: bool,
+
+    /// JSON按推断的列类型输出（每列类型一致）
+    #[arg(long)]
+    typed: bool,
+
+    /// JSON所有字段都输出为字符串
+    #[arg(long, conflicts_with = "typed")]
+    keep_strings: bool,
+
+    /// 不包含表头（CSV/TSV格式）
+    #[arg(long)]
+    no_headers: bool,
+
+    /// 为重复的列名添加 _2、_3 后缀（避免JSON键重复）
+    #[arg(long)]
+    dedupe_headers: bool,
+
+    /// JSON按列名中的 . 生成嵌套对象（如 address.city）
+    #[arg(long)]
+    nested: bool,
+
+    /// 浮点数列保留N位小数（按列类型输出，JSON/SQLite格式）
+    #[arg(long, value_name = "N", conflicts_with = "keep_strings")]
+    precision: Option<usize>,
+
+    /// 字段数与表头不一致的行 (keep: 原样导出, skip: 跳过, pad: 补齐或截断到表头宽度, error: 报错停止)
+    #[arg(long, default_value = "keep", value_name = "POLICY", value_parser = parse_ragged_policy)]
+    on_ragged: RaggedPolicy,
+}
+
+/// sort 子命令的参数
+#[derive(clap::Args)]
+struct SortArgs {
+    /// 排序列（列名或列号，从1开始）
+    #[arg(value_name = "COLUMN")]
+    column: String,
+
+    /// 排序方向 (asc/desc)
+    #[arg(long, default_value = "asc")]
+    order: String,
+
+    /// 数据类型 (auto/string/number)
+    #[arg(short = 't', long, default_value = "auto")]
+    data_type: String,
+
+    /// 显示结果数量限制
+    #[arg(short = 'n', long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// 大小写不敏感（字符串排序）
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// 字符串排序规则 (binary: 按码点, accent-folding: 忽略重音，é 排在 e 旁边)
+    #[arg(long, default_value = "binary", value_name = "COLLATION")]
+    collation: String,
+
+    /// 空值排在最前
+    #[arg(long)]
+    nulls_first: bool,
+
+    /// 显示行号
+    #[arg(short = 'l', long)]
+    line_numbers: bool,
+
+    /// 导出排序结果到文件
+    #[arg(short = 'o', long, value_name = "FILE")]
+    output: Option<String>,
+
+    /// 输出格式 (table, json, csv)
+    #[arg(long, value_enum, default_value = "table", value_name = "FORMAT")]
+    format: OutputFormat,
+
+    /// 构建并缓存排序列的列索引（重复按同一列排序时只读取该列）
+    #[arg(long)]
+    column_index: bool,
+
+    /// 解析数字前去掉千位分隔符和货币符号（如 `1,234`、`$3.50`）
+    #[arg(long)]
+    numeric_cleanup: bool,
+
+    /// 去掉的货币符号（可重复或用逗号分隔，替换默认的 $ € £ ¥），隐含 --numeric-cleanup
+    #[arg(long, value_name = "SYMBOL", value_delimiter = ',')]
+    currency: Vec<String>,
+
+    /// 逗号作为小数点、点作为千位分隔符（如 `1.234,56`），隐含 --numeric-cleanup
+    #[arg(long)]
+    decimal_comma: bool,
+
+    /// 视为空值的标记（逗号分隔，不区分大小写，如 "NA,N/A,-"），与空单元格一起排在最后
+    #[arg(long, value_name = "VALUES", value_delimiter = ',')]
+    null_values: Vec<String>,
+
+    /// 不保证键相同的行保持原有顺序（默认按原始行号排列），排序稍快
+    #[arg(long)]
+    unstable: bool,
+}
+
+impl Args {
+    /// 实际使用的分隔符（未指定且未检测到时为逗号）
+    fn delimiter(&self) -> Delimiter {
+        self.delimiter.clone().unwrap_or_default()
+    }
+
+    /// 结构化输出、导出和 tail 使用的单字节分隔符（多字符分隔符时使用逗号）
+    fn output_delimiter(&self) -> u8 {
+        self.delimiter().as_byte().unwrap_or(b',')
+    }
+
+    /// 索引文件存放位置
+    fn index_location(&self) -> IndexLocation {
+        match (&self.index_dir, self.index_location) {
+            (Some(dir), _) => IndexLocation::Custom(dir.clone()),
+            (None, Some(IndexLocationMode::Sidecar)) => IndexLocation::Sidecar,
+            (None, Some(IndexLocationMode::Cache)) => IndexLocation::CacheDir,
+            (None, None) => IndexLocation::from_env(),
+        }
+    }
+}
+
+impl Commands {
+    /// 获取子命令的结果输出格式
+    fn output_format(&self) -> OutputFormat {
+        match self {
+            Commands::View { format, .. }
+            | Commands::Search(SearchArgs { format, .. })
+            | Commands::Sort(SortArgs { format, .. }) => *format,
+            _ => OutputFormat::Table,
+        }
+    }
+}
+
 /// 编辑操作
 #[derive(Subcommand, Clone)]
 enum EditAction {
@@ -238,6 +714,17 @@ enum EditAction {
         output: Option<String>,
     },
 
+    /// 按编辑脚本批量修改单元格（只保存一次）
+    Apply {
+        /// 编辑脚本路径，每行一条 `行号,列,新值`（值包含逗号时加引号，# 开头为注释）
+        #[arg(short, long, value_name = "FILE")]
+        script: String,
+
+        /// 输出文件路径（不指定则覆盖原文件）
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
     /// 删除行
     DeleteRow {
         /// 要删除的行号（从1开始，可多个，逗号分隔）
@@ -292,7 +779,49 @@ enum EditAction {
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    
+    // 结构化输出时标准输出只保留数据
+    if args.command.as_ref().is_some_and(|c| c.output_format() != OutputFormat::Table) {
+        args.quiet = true;
+        args.verbose = false;
+    }
+    
+    // 未显式指定 -d 时自动检测分隔符
+    if args.delimiter.is_none() {
+        if let Some(detected) = detect_file_delimiter(&args.file) {
+            if detected != b',' && !args.quiet {
+                // 输出到标准错误，避免干扰 tail / 导出到标准输出等管道场景
+                eprintln!("检测到分隔符: {:?}", detected as char);
+            }
+            args.delimiter = Some(Delimiter::Byte(detected));
+        }
+    }
+    
+    // 未指定 --headers / --no-headers 时自动检测表头
+    if !args.no_headers {
+        let has_headers = match args.headers {
+            HeaderMode::Yes => true,
+            HeaderMode::No => false,
+            HeaderMode::Auto => {
+                let guess = detect_file_has_headers(&args.file, &args.delimiter());
+                let detected = guess.is_none_or(|g| g.has_headers);
+                if !args.quiet {
+                    if !detected {
+                        eprintln!("未检测到表头，使用 col1..colN 作为列名（可用 --headers yes 覆盖）");
+                    }
+                    if let Some(guess) = guess.filter(|g| g.confidence < HEADER_CONFIDENCE_WARN) {
+                        eprintln!(
+                            "警告: 表头检测不确定（置信度 {:.0}%），可用 --headers yes|no 明确指定",
+                            guess.confidence * 100.0
+                        );
+                    }
+                }
+                detected
+            }
+        };
+        args.no_headers = !has_headers;
+    }
     
     // 向后兼容：如果直接传递了页码数字（page_arg），优先使用它
     let final_page = if let Some(page_arg) = args.page_arg {
@@ -303,82 +832,53 @@ fn main() -> Result<()> {
     
     match &args.command {
         Some(Commands::Info) => cmd_info(&args),
-        Some(Commands::View { page }) => {
-            let page_num = page.or(Some(final_page)).unwrap_or(1);
-            cmd_view(&args, page_num)
-        }
-        Some(Commands::Search { 
-            pattern, 
-            regex, 
-            ignore_case, 
-            column, 
-            line_numbers, 
-            count, 
-            max_results,
-            invert_match,
-            no_highlight,
-        }) => cmd_search(
-            &args, 
-            pattern, 
-            *regex, 
-            *ignore_case, 
-            column.as_deref(), 
-            *line_numbers, 
-            *count, 
-            *max_results,
-            *invert_match,
-            *no_highlight,
+        Some(Commands::View { page, from, to, columns, format, interactive }) => {
+            if *interactive {
+                let page_num = page.or(Some(final_page)).unwrap_or(1);
+                cmd_view_interactive(&args, page_num, columns.as_deref())
+            } else if from.is_some() || to.is_some() {
+                let from = from.unwrap_or(1);
+                let to = to.unwrap_or_else(|| from.saturating_add(args.page_size.saturating_sub(1)));
+                cmd_view_range(&args, from, to, columns.as_deref(), *format)
+            } else {
+                let page_num = page.or(Some(final_page)).unwrap_or(1);
+                cmd_view(&args, page_num, columns.as_deref(), *format)
+            }
+        }
+        Some(Commands::Search(search)) => cmd_search(&args, search),
+        Some(Commands::Export(export)) => cmd_export(&args, export),
+        Some(Commands::Sort(sort)) => cmd_sort(&args, sort),
+        Some(Commands::Sample { rows, output, seed, keep_order }) => cmd_sample(&args, *rows, output, *seed, *keep_order),
+        Some(Commands::Shuffle { output, seed }) => cmd_shuffle(&args, output, *seed),
+        Some(Commands::Index { column }) => cmd_index(&args, column),
+        Some(Commands::Schema { sample }) => cmd_schema(&args, *sample),
+        Some(Commands::Distinct { column, sort, top, ignore_case }) => cmd_distinct(
+            &args,
+            column,
+            *sort,
+            *top,
+            *ignore_case,
         ),
-        Some(Commands::Export {
-            output,
-            format,
-            columns,
-            from,
-            to,
-            search,
-            regex,
-            pretty,
-            no_headers,
-        }) => cmd_export(
+        Some(Commands::Stats { column, precision, top, percentiles, approx }) => {
+            cmd_stats(&args, column, *precision, *top, percentiles, *approx)
+        }
+        Some(Commands::Histogram { column, bins, min, max }) => cmd_histogram(&args, column, *bins, *min, *max),
+        Some(Commands::Query { sql, columns, from, to }) => cmd_query(
             &args,
-            output,
-            format.as_deref(),
+            sql,
             columns.as_deref(),
             *from,
             *to,
-            search.as_deref(),
-            *regex,
-            *pretty,
-            *no_headers,
-        ),
-        Some(Commands::Sort {
-            column,
-            order,
-            data_type,
-            limit,
-            ignore_case,
-            nulls_first,
-            line_numbers,
-            output,
-        }) => cmd_sort(
-            &args,
-            column,
-            order,
-            data_type,
-            *limit,
-            *ignore_case,
-            *nulls_first,
-            *line_numbers,
-            output.as_deref(),
         ),
+        Some(Commands::Tail { lines, follow, interval }) => cmd_tail(&args, *lines, *follow, *interval),
         Some(Commands::Edit { action }) => cmd_edit(&args, action),
         Some(Commands::Create { output, headers, rows }) => cmd_create(
             output,
         headers,
             rows,
-            args.delimiter as u8,
+            args.delimiter(),
         ),
-        None => cmd_view(&args, final_page),
+        None => cmd_view(&args, final_page, None, OutputFormat::Table),
     }
 }
 
@@ -393,12 +893,7 @@ fn cmd_info(args: &Args) -> Result<()> {
     
     let pb = create_spinner("正在打开文件...");
     
-    let reader = CsvReader::open_fast(
-        &args.file,
-        !args.no_headers,
-        args.delimiter as u8,
-        args.granularity,
-    )?;
+    let reader = open_reader(args)?;
     
     pb.finish_and_clear();
     
@@ -406,31 +901,40 @@ fn cmd_info(args: &Args) -> Result<()> {
     let open_duration = start_time.elapsed();
     
     // 检查索引文件
-    let index_path = RowIndex::index_file_path(std::path::Path::new(&args.file));
-    let index_exists = index_path.exists();
-    let index_size = if index_exists {
-        std::fs::metadata(&index_path).map(|m| m.len()).unwrap_or(0)
-    } else {
-        0
-    };
+    let index_path = reader.index_path();
+    let index_size = index_path.as_ref()
+        .map_or(0, |path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0));
     
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║                    📄 CSV 文件信息                           ║");
     println!("╠══════════════════════════════════════════════════════════════╣");
     println!("║ 文件路径: {:<50} ║", truncate_path(&args.file, 50));
-    println!("║ 文件大小: {:<50} ║", format_size(info.file_size));
+    if let Some(decompressed_size) = info.decompressed_size {
+        println!("║ 压缩大小: {:<50} ║", format_size(info.file_size));
+        println!("║ 解压大小: {:<50} ║", format_size(decompressed_size));
+    } else {
+        println!("║ 文件大小: {:<50} ║", format_size(info.file_size));
+    }
     println!("╠══════════════════════════════════════════════════════════════╣");
     println!("║ 总行数:   {:<50} ║", format!("{} 行", info.total_rows));
     println!("║ 总列数:   {:<50} ║", format!("{} 列", info.total_cols));
     println!("║ 有表头:   {:<50} ║", if !args.no_headers { "是" } else { "否" });
-    println!("║ 分隔符:   {:<50} ║", format!("'{}'", args.delimiter));
+    println!("║ 分隔符:   {:<50} ║", format!("{:?}", info.delimiter.to_string()));
+    println!("║ 换行符:   {:<50} ║", info.line_ending.name());
+    println!("║ 编码:     {:<50} ║", info.encoding.name());
     println!("╠══════════════════════════════════════════════════════════════╣");
-    println!("║ 索引缓存: {:<50} ║", if index_exists { 
+    println!("║ 索引缓存: {:<50} ║", if index_path.is_some() { 
         format!("✅ 存在 ({})", format_size(index_size)) 
     } else { 
         "❌ 无".to_string() 
     });
-    println!("║ 索引粒度: {:<50} ║", format!("每 {} 行", args.granularity));
+    if let Some(path) = &index_path {
+        println!("║ 索引位置: {:<50} ║", truncate_path(&path.to_string_lossy(), 50));
+    }
+    println!("║ 索引粒度: {:<50} ║", match reader.index().mode() {
+        IndexMode::Dense => "每行（稠密索引）".to_string(),
+        IndexMode::Sparse => format!("每 {} 行", args.granularity),
+    });
     println!("║ 分析耗时: {:<50} ║", format!("{:.2} 秒", open_duration.as_secs_f64()));
     println!("╚══════════════════════════════════════════════════════════════╝");
     
@@ -444,21 +948,36 @@ fn cmd_info(args: &Args) -> Result<()> {
     
     if args.verbose {
         println!("\n📊 详细统计:");
-        println!("   索引点数量: {}", info.total_rows / args.granularity);
+        println!("   索引点数量: {}", reader.index().index_count());
         println!("   页面数量: {} (每页 {} 行)", 
-            (info.total_rows + args.page_size - 1) / args.page_size,
+            reader.total_pages(args.page_size),
             args.page_size
         );
-        if index_exists {
-            println!("   索引文件: {}", index_path.display());
+        if reader.cache_capacity() == 0 {
+            println!("   页面缓存: 已关闭");
+        } else {
+            println!("   页面缓存: {} / {} (最多 {} 页)",
+                format_size(reader.cache_memory_usage() as u64),
+                format_size(reader.cache_byte_budget() as u64),
+                reader.cache_capacity()
+            );
+        }
+        if let Some(path) = &index_path {
+            println!("   索引文件: {}", path.display());
+        }
+        if let Some(path) = reader.decompressed_path() {
+            println!("   解压文件: {}", path.display());
         }
     }
     
     Ok(())
 }
 
-/// 查看CSV数据
-fn cmd_view(args: &Args, page: usize) -> Result<()> {
+/// 打开文件并显示索引构建/加载进度
+/// 
+/// # 返回
+/// 读取器和打开耗时
+fn open_reader_with_progress(args: &Args) -> Result<(CsvReader, std::time::Duration)> {
     let start_time = Instant::now();
     
     // 显示加载提示
@@ -467,8 +986,9 @@ fn cmd_view(args: &Args, page: usize) -> Result<()> {
     }
     
     // 检查是否需要构建索引
-    let index_path = RowIndex::index_file_path(std::path::Path::new(&args.file));
-    let needs_build = !index_path.exists();
+    let needs_build = args.no_index_cache
+        || args.keep_empty_lines
+        || RowIndex::find_index_file(Path::new(&args.file), &args.index_location()).is_none();
     
     let pb = if needs_build {
         // 需要构建索引，显示进度条
@@ -480,23 +1000,40 @@ fn cmd_view(args: &Args, page: usize) -> Result<()> {
                 .progress_chars("#>-"),
         );
         pb.set_message("正在构建索引...");
-        Some(pb)
+        pb
     } else {
         // 只需要加载索引，显示spinner
-        Some(create_spinner("正在加载索引..."))
+        create_spinner("正在加载索引...")
     };
     
-    let mut reader = CsvReader::open_fast(
-        &args.file,
-        !args.no_headers,
-        args.delimiter as u8,
-        args.granularity,
-    )?;
+    // gzip文件需要先解压，UTF-16文件需要先转码，大文件可能耗时较长
+    if is_gzip_file(&args.file)? {
+        pb.set_message("正在解压gzip文件...");
+    } else if TextEncoding::detect_file(&args.file)?.is_utf16() {
+        pb.set_message("正在将UTF-16转码为UTF-8...");
+    }
     
-    if let Some(pb) = pb {
-        pb.finish_and_clear();
+    let mut reader = open_reader(args)?;
+    
+    // 快速打开只索引了文件开头，在后台构建完整索引并按实际进度更新进度条，
+    // 之后分页时行数和页数都是精确值
+    if !reader.is_index_complete() {
+        pb.set_message(match reader.row_estimate() {
+            Some(estimate) => format!("正在构建索引（约 {} 行）...", estimate.estimated_rows),
+            None => "正在构建索引...".to_string(),
+        });
+        let handle = reader.build_index_async();
+        while !handle.is_finished() {
+            pb.set_position(handle.progress() as u64);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        if let Some((index, true)) = handle.wait() {
+            reader.update_index(index);
+        }
     }
     
+    pb.finish_and_clear();
+    
     let open_duration = start_time.elapsed();
     
     // 如果是第一次构建索引，显示提示信息
@@ -504,9 +1041,19 @@ fn cmd_view(args: &Args, page: usize) -> Result<()> {
         println!("💡 提示: 索引已构建并保存，下次打开会更快！");
     }
     
+    Ok((reader, open_duration))
+}
+
+/// 查看CSV数据
+fn cmd_view(args: &Args, page: usize, columns: Option<&str>, format: OutputFormat) -> Result<()> {
+    let (reader, open_duration) = open_reader_with_progress(args)?;
+    
     // 获取文件信息
     let info = reader.info().clone();
     let total_pages = reader.total_pages(args.page_size);
+    let display_columns = columns
+        .map(|spec| parse_column_list(spec, &info.headers))
+        .transpose()?;
     
     // 调整页码（用户输入从1开始，内部从0开始）
     let page_idx = page.saturating_sub(1).min(total_pages.saturating_sub(1));
@@ -532,8 +1079,29 @@ fn cmd_view(args: &Args, page: usize) -> Result<()> {
         println!("⚡ 读取耗时: {:.2}毫秒", read_duration.as_secs_f64() * 1000.0);
     }
     
+    if format != OutputFormat::Table {
+        let start_row = page_idx * args.page_size;
+        let output_rows = rows.iter()
+            .enumerate()
+            .map(|(i, row)| OutputRow::new(start_row + i + 1, row, display_columns.as_deref()))
+            .collect();
+        let headers = project_headers(&info.headers, display_columns.as_deref());
+        return print_structured(format, &headers, output_rows, args.output_delimiter());
+    }
+    
+    // 只有表头或空文件时没有可显示的页
+    if rows.is_empty() {
+        println!("\n📭 没有数据行");
+        return Ok(());
+    }
+    
     // 打印表格
-    print_table(&info.headers, &rows, page_idx, total_pages, args.page_size);
+    print_table(&info.headers, &rows, args.max_col_width, display_columns.as_deref());
+    
+    // 分页信息
+    let start_row = page_idx * args.page_size + 1;
+    let end_row = start_row + rows.len().saturating_sub(1);
+    println!("📖 第 {}/{} 页 (行 {}-{})", page_idx + 1, total_pages, start_row, end_row);
     
     // 导航提示
     if !args.quiet && total_pages > 1 {
@@ -550,27 +1118,135 @@ fn cmd_view(args: &Args, page: usize) -> Result<()> {
     Ok(())
 }
 
-/// 搜索CSV数据
-fn cmd_search(
+/// 交互式分页浏览
+#[cfg(feature = "interactive")]
+fn cmd_view_interactive(args: &Args, page: usize, columns: Option<&str>) -> Result<()> {
+    let (reader, _) = open_reader_with_progress(args)?;
+    let display_columns = columns
+        .map(|spec| parse_column_list(spec, &reader.info().headers))
+        .transpose()?;
+    pager::run(args, &reader, page.saturating_sub(1), display_columns.as_deref())
+}
+
+/// 交互式分页浏览（未启用 interactive 特性）
+#[cfg(not(feature = "interactive"))]
+fn cmd_view_interactive(_args: &Args, _page: usize, _columns: Option<&str>) -> Result<()> {
+    Err(csv_tool::error::CsvError::Format(
+        "交互式浏览需要启用 interactive 特性重新编译: cargo install --path . --features interactive".to_string()
+    ))
+}
+
+/// 查看指定行范围的数据（行号从1开始，包含两端）
+fn cmd_view_range(
     args: &Args,
-    pattern: &str,
-    use_regex: bool,
-    ignore_case: bool,
-    column: Option<&str>,
-    show_line_numbers: bool,
-    count_only: bool,
-    max_results: Option<usize>,
-    invert_match: bool,
-    no_highlight: bool,
+    from: usize,
+    to: usize,
+    columns: Option<&str>,
+    format: OutputFormat,
 ) -> Result<()> {
+    if from == 0 {
+        return Err(csv_tool::error::CsvError::Format(
+            "行号从1开始".to_string()
+        ));
+    }
+    if to < from {
+        return Err(csv_tool::error::CsvError::Format(
+            format!("结束行 {} 小于起始行 {}", to, from)
+        ));
+    }
+    
+    let (reader, open_duration) = open_reader_with_progress(args)?;
+    let info = reader.info().clone();
+    
+    if from > info.total_rows {
+        return Err(csv_tool::error::CsvError::Format(
+            format!("起始行 {} 超出范围（共 {} 行）", from, info.total_rows)
+        ));
+    }
+    let to = to.min(info.total_rows);
+    let display_columns = columns
+        .map(|spec| parse_column_list(spec, &info.headers))
+        .transpose()?;
+    
+    if !args.quiet {
+        println!("\n📄 文件: {}", info.file_path.display());
+        println!("📊 大小: {} | 📋 {} 行 × {} 列",
+            format_size(info.file_size),
+            info.total_rows,
+            info.total_cols
+        );
+        println!("⏱️  加载耗时: {:.2}秒", open_duration.as_secs_f64());
+    }
+    
+    let read_start = Instant::now();
+    let rows = reader.read_rows(from - 1, to)?;
+    let read_duration = read_start.elapsed();
+    
+    if !args.quiet {
+        println!("⚡ 读取耗时: {:.2}毫秒", read_duration.as_secs_f64() * 1000.0);
+    }
+    
+    if format != OutputFormat::Table {
+        let output_rows = rows.iter()
+            .enumerate()
+            .map(|(i, row)| OutputRow::new(from + i, row, display_columns.as_deref()))
+            .collect();
+        let headers = project_headers(&info.headers, display_columns.as_deref());
+        return print_structured(format, &headers, output_rows, args.output_delimiter());
+    }
+    
+    print_table(&info.headers, &rows, args.max_col_width, display_columns.as_deref());
+    println!("📖 行 {}-{} (共 {} 行)", from, from + rows.len().saturating_sub(1), info.total_rows);
+    
+    Ok(())
+}
+
+/// 搜索CSV数据
+fn cmd_search(args: &Args, search: &SearchArgs) -> Result<()> {
+    let &SearchArgs {
+        ref pattern,
+        ref extra_patterns,
+        ref pattern_file,
+        regex: use_regex,
+        glob: use_glob,
+        ignore_case,
+        word: whole_word,
+        ref column,
+        line_numbers: show_line_numbers,
+        count: count_only,
+        max_results,
+        invert_match,
+        no_highlight,
+        ref columns,
+        only_matching,
+        matching_columns,
+        format,
+    } = search;
+    let patterns: &[String] = &collect_patterns(pattern.as_deref(), extra_patterns, pattern_file.as_deref())?;
+    let column = column.as_deref();
+    let columns = columns.as_deref();
     let start_time = Instant::now();
     
     if !args.quiet {
-        println!("\n🔍 搜索模式: {}", if use_regex { "正则表达式" } else { "文本" });
-        println!("📝 搜索内容: \"{}\"", pattern);
+        let mode = if use_regex {
+            "正则表达式"
+        } else if use_glob {
+            "通配符"
+        } else {
+            "文本"
+        };
+        println!("\n🔍 搜索模式: {}", mode);
+        if let [pattern] = patterns {
+            println!("📝 搜索内容: \"{}\"", pattern);
+        } else {
+            println!("📝 搜索内容: {} 个模式（任一匹配）", patterns.len());
+        }
         if ignore_case {
             println!("🔤 大小写: 不敏感");
         }
+        if whole_word {
+            println!("🔠 整词匹配");
+        }
         if invert_match {
             println!("🔄 模式: 反向匹配（显示不匹配的行）");
         }
@@ -578,18 +1254,18 @@ fn cmd_search(
     
     let pb = create_spinner("正在打开文件...");
     
-    let reader = CsvReader::open_fast(
-        &args.file,
-        !args.no_headers,
-        args.delimiter as u8,
-        args.granularity,
-    )?;
+    let reader = open_reader(args)?;
     
     pb.finish_and_clear();
     
     let info = reader.info();
     let headers = info.headers.clone();
     
+    // 解析显示列（只影响输出，不影响搜索范围）
+    let display_columns = columns
+        .map(|spec| parse_column_list(spec, &headers))
+        .transpose()?;
+    
     // 解析目标列
     let target_columns = if let Some(col_str) = column {
         let col_idx = parse_column_spec(col_str, &headers)?;
@@ -598,17 +1274,27 @@ fn cmd_search(
         None
     };
     
-    // 创建搜索模式
-    let search_pattern = if use_regex {
-        SearchPattern::regex(pattern, !ignore_case)?
-    } else {
-        SearchPattern::text(pattern, !ignore_case)
-    };
+    // 创建搜索模式（多个模式组合为一次扫描）
+    let search_pattern = SearchPattern::any_of(
+        patterns
+            .iter()
+            .map(|pattern| {
+                if use_regex {
+                    SearchPattern::regex_with(pattern, !ignore_case, whole_word)
+                } else if use_glob {
+                    SearchPattern::glob(pattern, !ignore_case)
+                } else {
+                    Ok(SearchPattern::text(pattern, !ignore_case))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?,
+    )?;
     
     // 创建搜索选项
     let mut options = SearchOptions::new(search_pattern)
         .with_case_sensitive(!ignore_case)
-        .with_invert_match(invert_match);
+        .with_invert_match(invert_match)
+        .with_whole_word(whole_word);
     
     if let Some(cols) = target_columns {
         options = options.with_columns(cols);
@@ -618,29 +1304,55 @@ fn cmd_search(
         options = options.with_max_results(max);
     }
     
+    // 按已扫描的字节数显示进度，Ctrl-C 中断扫描并保留已找到的结果
+    let pb = create_scan_progress("正在搜索...");
+    let progress: ProgressCallback = {
+        let pb = pb.clone();
+        Arc::new(move |scanned, total| {
+            pb.set_length(total);
+            pb.set_position(scanned);
+        })
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&cancel);
+    ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst))
+        .map_err(|e| csv_tool::error::CsvError::Format(format!("无法注册Ctrl-C处理: {}", e)))?;
+    options = options.with_progress(progress).with_cancel_flag(cancel);
+    
     // 执行搜索
     let search_start = Instant::now();
     
     if count_only {
         // 只统计数量
-        let pb = create_spinner("正在搜索...");
-        let count = reader.count_matches(&options)?;
+        let outcome = reader.count_matches_with_status(&options)?;
         pb.finish_and_clear();
+        let count = outcome.value;
         
         let search_duration = search_start.elapsed();
         
+        if outcome.cancelled {
+            eprintln!("⚠️  搜索已中断，统计只包含已扫描的部分");
+        }
         println!("\n📊 搜索结果统计:");
-        println!("   匹配行数: {}", count);
+        if max_results == Some(count) {
+            println!("   匹配行数: {} （已达到 --max-results 上限，实际可能更多）", count);
+        } else {
+            println!("   匹配行数: {}", count);
+        }
         println!("   总行数:   {}", info.total_rows);
         println!("   匹配率:   {:.2}%", (count as f64 / info.total_rows as f64) * 100.0);
         println!("   搜索耗时: {:.2}毫秒", search_duration.as_secs_f64() * 1000.0);
     } else {
         // 返回详细结果
-        let pb = create_spinner("正在搜索...");
-        let results = reader.search(&options)?;
+        let outcome = reader.search_with_status(&options)?;
         pb.finish_and_clear();
+        let results = outcome.value;
         
         let search_duration = search_start.elapsed();
+        
+        if outcome.cancelled {
+            eprintln!("⚠️  搜索已中断，以下为已找到的部分结果");
+        }
         let result_count = results.len();
         
         if !args.quiet {
@@ -648,19 +1360,51 @@ fn cmd_search(
             println!("⏱️  搜索耗时: {:.2}毫秒\n", search_duration.as_secs_f64() * 1000.0);
         }
         
+        // 只显示有过匹配的列
+        let display_columns = if matching_columns {
+            Some(matched_column_union(&results))
+        } else {
+            display_columns
+        };
+        
+        if format != OutputFormat::Table {
+            let output_rows = results.iter()
+                .map(|result| OutputRow {
+                    matched_columns: Some(result.matches.iter().map(|m| m.column + 1).collect()),
+                    matched_patterns: (patterns.len() > 1).then(|| matched_patterns(result)),
+                    ..OutputRow::new(result.row_number + 1, &result.record, display_columns.as_deref())
+                })
+                .collect();
+            let headers = project_headers(&headers, display_columns.as_deref());
+            return print_structured(format, &headers, output_rows, args.output_delimiter());
+        }
+        
         if result_count == 0 {
             println!("❌ 未找到匹配的结果");
             return Ok(());
         }
         
-        // 打印搜索结果
-        print_search_results(
-            &results, 
-            &headers, 
-            show_line_numbers, 
-            !no_highlight && !invert_match,
-            args.page_size.min(result_count),
-        );
+        // 打印搜索结果（反向匹配的行没有可高亮的内容）
+        let highlight = (!no_highlight && !invert_match && args.color.enabled())
+            .then(|| HighlightStyle::new(args.highlight_color));
+        if only_matching {
+            print_matching_fields(
+                &results,
+                &headers,
+                highlight.as_ref(),
+                args.page_size.min(result_count),
+            );
+        } else {
+            print_search_results(
+                &results, 
+                &headers, 
+                show_line_numbers, 
+                highlight.as_ref(),
+                args.page_size.min(result_count),
+                args.max_col_width,
+                display_columns.as_deref(),
+            );
+        }
         
         // 显示更多提示
         if result_count > args.page_size {
@@ -681,6 +1425,43 @@ fn cmd_search(
     Ok(())
 }
 
+/// 合并命令行中的搜索模式：位置参数、每个 `-e` 和 `-f` 文件中的每一行
+fn collect_patterns(pattern: Option<&str>, extra: &[String], file: Option<&str>) -> Result<Vec<String>> {
+    let mut patterns: Vec<String> = pattern.into_iter().map(str::to_string).collect();
+    patterns.extend(extra.iter().cloned());
+    if let Some(path) = file {
+        let content = std::fs::read_to_string(path)?;
+        patterns.extend(
+            content.lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+    if patterns.is_empty() {
+        return Err(csv_tool::error::CsvError::Format(
+            "没有指定搜索模式".to_string()
+        ));
+    }
+    Ok(patterns)
+}
+
+/// 搜索结果中匹配的模式序号（从1开始，去重并升序）
+fn matched_patterns(result: &csv_tool::csv::SearchResult) -> Vec<usize> {
+    let mut patterns: Vec<usize> = result.matches.iter()
+        .flat_map(|m| m.patterns.iter().map(|&i| i + 1))
+        .collect();
+    patterns.sort_unstable();
+    patterns.dedup();
+    patterns
+}
+
+/// 解析逗号分隔的列规格列表
+fn parse_column_list(spec: &str, headers: &[String]) -> Result<Vec<usize>> {
+    spec.split(',')
+        .map(|s| parse_column_spec(s.trim(), headers))
+        .collect()
+}
+
 /// 解析列规格（列名或列号）
 fn parse_column_spec(spec: &str, headers: &[String]) -> Result<usize> {
     // 首先尝试解析为数字
@@ -700,9 +1481,87 @@ fn parse_column_spec(spec: &str, headers: &[String]) -> Result<usize> {
         }
     }
     
-    Err(csv_tool::error::CsvError::Format(
-        format!("未找到列 '{}'. 可用的列: {:?}", spec, headers)
-    ).into())
+    // 忽略大小写、首尾空白，并把连续的空白和下划线视为一个空格再匹配
+    let normalized = normalize_column_name(spec);
+    if let Some(i) = headers.iter().position(|h| normalize_column_name(h) == normalized) {
+        return Ok(i);
+    }
+    
+    // 编辑距离足够小时提示最接近的列名
+    let suggestion = headers.iter()
+        .map(|h| (h, edit_distance(&normalize_column_name(h), &normalized)))
+        .min_by_key(|&(_, distance)| distance)
+        .filter(|&(_, distance)| distance <= (normalized.chars().count() / 3).max(1));
+    
+    let message = match suggestion {
+        Some((header, _)) => format!("未找到列 '{}'，您是否想输入 '{}'？可用的列: {:?}", spec, header, headers),
+        None => format!("未找到列 '{}'. 可用的列: {:?}", spec, headers),
+    };
+    Err(csv_tool::error::CsvError::Format(message))
+}
+
+/// 规范化列名：转为小写，去除首尾空白，连续的空白和下划线合并为一个空格
+fn normalize_column_name(name: &str) -> String {
+    name.split(|c: char| c.is_whitespace() || c == '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// 两个字符串之间的编辑距离（按字符计算）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    
+    prev[b.len()]
+}
+
+/// 解析编辑脚本
+/// 
+/// 每行为 `行号,列,新值`：行号从1开始，列可以是列名或列号，
+/// 新值包含逗号或引号时按CSV规则加引号；空行和 `#` 开头的行被忽略
+fn parse_edit_script(path: &str, headers: &[String]) -> Result<Vec<CellEdit>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut edits = Vec::new();
+    
+    for (line_no, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        
+        let record = CsvRecord::parse_line(line.as_bytes(), b',');
+        let invalid = || csv_tool::error::CsvError::Format(format!(
+            "编辑脚本第 {} 行格式错误，应为 行号,列,新值（值包含逗号时请加引号）: {}",
+            line_no + 1, line
+        ));
+        let [row, col, value] = record.fields.as_slice() else {
+            return Err(invalid());
+        };
+        let row: usize = row.trim().parse().map_err(|_| invalid())?;
+        if row == 0 {
+            return Err(invalid());
+        }
+        
+        edits.push(CellEdit {
+            row: row - 1,
+            col: parse_column_spec(col.trim(), headers)?,
+            value: value.to_string(),
+        });
+    }
+    
+    Ok(edits)
 }
 
 /// 打印搜索结果
@@ -710,89 +1569,189 @@ fn print_search_results(
     results: &[csv_tool::csv::SearchResult],
     headers: &[String],
     show_line_numbers: bool,
-    highlight: bool,
+    highlight: Option<&HighlightStyle>,
     max_display: usize,
+    max_col_width: usize,
+    columns: Option<&[usize]>,
 ) {
-    let col_count = headers.len().max(
-        results.first().map(|r| r.record.fields.len()).unwrap_or(0)
-    );
-    let max_width = 18;
+    let shown = &results[..max_display.min(results.len())];
+    let field_offset = if show_line_numbers { 1 } else { 0 };
     
-    let separator = "─".repeat(max_width + 2);
-    let line_num_width = if show_line_numbers { 8 } else { 0 };
-    let line_num_sep = if show_line_numbers { "─".repeat(line_num_width) } else { String::new() };
+    let mut display_headers = Vec::with_capacity(headers.len() + field_offset);
+    if show_line_numbers {
+        display_headers.push("行号".to_string());
+    }
+    display_headers.extend(project_headers(headers, columns));
+    
+    let rows: Vec<Vec<String>> = shown.iter()
+        .map(|result| {
+            let mut row = Vec::with_capacity(result.record.fields.len() + field_offset);
+            if show_line_numbers {
+                row.push((result.row_number + 1).to_string());
+            }
+            row.extend(project_fields(&result.record, columns).into_iter().map(str::to_string));
+            row
+        })
+        .collect();
+    
+    let layout = TableLayout::compute(&display_headers, &rows, max_col_width);
     
-    // 表头
     println!();
-    if show_line_numbers {
-        print!("┌{}┬", line_num_sep);
-    } else {
-        print!("┌");
+    println!("{}", layout.border("┌", "┬", "┐"));
+    println!("{}", layout.header_line(&display_headers));
+    println!("{}", layout.border("├", "┼", "┤"));
+    
+    // 数据行（在完整文本上高亮后再截断，匹配位置不受截断影响）
+    for (result, row) in shown.iter().zip(&rows) {
+        let line = layout.row_line_styled(row, |col, text| {
+            if let Some(style) = highlight.filter(|_| col >= field_offset) {
+                // 显示列映射回原始列号，匹配位置按原始列记录
+                let display_col = col - field_offset;
+                let source_col = columns.map_or(Some(display_col), |cols| cols.get(display_col).copied());
+                if let Some(match_info) = result.matches.iter().find(|m| Some(m.column) == source_col) {
+                    return highlight_matches(text, &match_info.positions, style);
+                }
+            }
+            text.to_string()
+        });
+        println!("{}", line);
     }
-    println!("{}┐", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┬"));
     
-    // 列名行
-    if show_line_numbers {
-        print!("│ {:^6} │", "行号");
-    } else {
-        print!("│");
+    println!("{}", layout.border("└", "┴", "┘"));
+}
+
+/// 每个匹配的字段输出一行（`行号:列名:字段内容`），适合列数很多的文件
+fn print_matching_fields(
+    results: &[csv_tool::csv::SearchResult],
+    headers: &[String],
+    highlight: Option<&HighlightStyle>,
+    max_display: usize,
+) {
+    println!();
+    for result in &results[..max_display.min(results.len())] {
+        for match_info in &result.matches {
+            let header = headers.get(match_info.column)
+                .cloned()
+                .unwrap_or_else(|| format!("col{}", match_info.column + 1));
+            let text = result.record.fields.get(match_info.column).map_or("", |f| f.as_ref());
+            let text = match highlight {
+                Some(style) => highlight_matches(text, &match_info.positions, style),
+                None => text.to_string(),
+            };
+            println!("{}:{}:{}", result.row_number + 1, header, text);
+        }
     }
-    for header in headers.iter().take(col_count) {
-        print!(" {:^width$} │", truncate_str(header, max_width), width = max_width);
+}
+
+/// 搜索结果中有过匹配的列（按列号排序）
+fn matched_column_union(results: &[csv_tool::csv::SearchResult]) -> Vec<usize> {
+    let mut columns: Vec<usize> = results.iter()
+        .flat_map(|result| result.matched_columns())
+        .collect();
+    columns.sort_unstable();
+    columns.dedup();
+    columns
+}
+
+/// 根据 `--numeric-cleanup`、`--currency` 和 `--decimal-comma` 参数生成数字清理规则
+fn numeric_cleanup_from_args(enabled: bool, currency: &[String], decimal_comma: bool) -> Option<NumericCleanup> {
+    if !enabled && currency.is_empty() && !decimal_comma {
+        return None;
     }
-    for _ in headers.len()..col_count {
-        print!(" {:^width$} │", "", width = max_width);
+    let cleanup = NumericCleanup::new().with_decimal_comma(decimal_comma);
+    Some(if currency.is_empty() { cleanup } else { cleanup.with_currency(currency.to_vec()) })
+}
+
+/// 解析 `--percentiles` 中的一个百分位
+fn parse_percentile(s: &str) -> std::result::Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(p) if (0.0..=100.0).contains(&p) => Ok(p),
+        _ => Err(format!("无效的百分位: {}（应为0到100之间的数字）", s)),
     }
-    println!();
-    
-    // 分隔线
-    if show_line_numbers {
-        print!("├{}┼", line_num_sep);
+}
+
+/// 解析 `--on-ragged` 参数
+fn parse_ragged_policy(value: &str) -> std::result::Result<RaggedPolicy, String> {
+    RaggedPolicy::parse(value).ok_or_else(|| {
+        format!("未知的处理方式: {}（可选 keep, skip, pad, error）", value)
+    })
+}
+
+/// 解析 `--highlight-color` 参数
+fn parse_highlight_color(value: &str) -> std::result::Result<HighlightColor, String> {
+    HighlightColor::parse(value).ok_or_else(|| {
+        format!("未知的颜色: {}（可选 red, green, yellow, blue, magenta, cyan）", value)
+    })
+}
+
+/// 检测文件的分隔符
+///
+/// 文件不存在（如 create 命令）或为 gzip/UTF-16 输入时无法直接检测，返回 None
+fn detect_file_delimiter(path: &str) -> Option<u8> {
+    if !Path::new(path).is_file() {
+        return None;
+    }
+    if is_gzip_file(path).ok()? || TextEncoding::detect_file(path).ok()?.is_utf16() {
+        return None;
+    }
+    detect_delimiter(path).ok()
+}
+
+/// 由全局参数生成读取器选项
+fn reader_options(args: &Args) -> ReaderOptions {
+    ReaderOptions::new()
+        .with_headers(!args.no_headers)
+        .with_delimiter(args.delimiter())
+        .with_granularity(args.granularity)
+        .with_index_mode(if args.dense_index { IndexMode::Dense } else { IndexMode::Sparse })
+        .with_persist_index(!args.no_index_cache)
+        .with_index_location(args.index_location())
+        .with_verify_index(args.verify_index)
+        .with_cache_pages(if args.no_cache { 0 } else { args.cache_pages })
+        .with_quote_mode(if args.strict_quotes { QuoteMode::Strict } else { QuoteMode::Flexible })
+        .with_keep_empty_lines(args.keep_empty_lines)
+}
+
+/// 表头检测置信度低于此值时提示用户明确指定
+const HEADER_CONFIDENCE_WARN: f64 = 0.6;
+
+/// 检测文件是否有表头
+///
+/// 与 [`detect_file_delimiter`] 相同，无法直接检测时返回 None
+fn detect_file_has_headers(path: &str, delimiter: &Delimiter) -> Option<HeaderGuess> {
+    if !Path::new(path).is_file() {
+        return None;
+    }
+    if is_gzip_file(path).ok()? || TextEncoding::detect_file(path).ok()?.is_utf16() {
+        return None;
+    }
+    guess_has_headers_with_delimiter(path, delimiter).ok()
+}
+
+/// 按全局参数打开CSV文件
+fn open_reader(args: &Args) -> Result<CsvReader> {
+    let mut reader = if args.strict {
+        // 校验需要完整扫描文件，无法使用快速打开
+        CsvReader::open_with_options(&args.file, &reader_options(args).with_strict(true))?
     } else {
-        print!("├");
+        CsvReader::open_fast_with_options(&args.file, &reader_options(args))?
+    };
+    reader.set_keep_decompressed(args.keep_decompressed);
+    if args.no_headers {
+        reader.synthesize_headers();
     }
-    println!("{}┤", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┼"));
-    
-    // 数据行
-    for result in results.iter().take(max_display) {
-        if show_line_numbers {
-            print!("│ {:>6} │", result.row_number + 1);
-        } else {
-            print!("│");
-        }
-        
-        for (col_idx, field) in result.record.fields.iter().enumerate().take(col_count) {
-            let text = field.as_ref();
-            
-            // 检查是否需要高亮
-            let display_text = if highlight {
-                if let Some(match_info) = result.matches.iter().find(|m| m.column == col_idx) {
-                    // 高亮匹配文本
-                    let highlighted = highlight_matches(text, &match_info.positions);
-                    truncate_str_with_ansi(&highlighted, max_width)
-                } else {
-                    truncate_str(text, max_width)
-                }
-            } else {
-                truncate_str(text, max_width)
-            };
-            
-            print!(" {:width$} │", display_text, width = max_width);
+    if !args.quiet {
+        for (name, cols) in reader.duplicate_headers() {
+            let cols: Vec<String> = cols.iter().map(|c| (c + 1).to_string()).collect();
+            eprintln!("警告: 列名 \"{}\" 重复（第 {} 列），按列名引用时使用第一列", name, cols.join("、"));
         }
-        
-        for _ in result.record.fields.len()..col_count {
-            print!(" {:width$} │", "", width = max_width);
+        let empty = reader.empty_headers();
+        if !empty.is_empty() {
+            let cols: Vec<String> = empty.iter().map(|c| (c + 1).to_string()).collect();
+            eprintln!("警告: 第 {} 列的列名为空", cols.join("、"));
         }
-        println!();
     }
-    
-    // 底部边框
-    if show_line_numbers {
-        print!("└{}┴", line_num_sep);
-    } else {
-        print!("└");
-    }
-    println!("{}┘", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┴"));
+    Ok(reader)
 }
 
 /// 创建加载动画
@@ -809,226 +1768,888 @@ fn create_spinner(message: &str) -> ProgressBar {
     pb
 }
 
+/// 创建按字节显示扫描进度的进度条（总长度由首次进度回调设置）
+fn create_scan_progress(message: &str) -> ProgressBar {
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb.set_message(message.to_string());
+    pb
+}
+
+/// 以结构化格式（JSON或CSV）输出结果到标准输出
+fn print_structured(
+    format: OutputFormat,
+    headers: &[String],
+    rows: Vec<OutputRow>,
+    delimiter: u8,
+) -> Result<()> {
+    let stdout = std::io::stdout();
+    match format {
+        OutputFormat::Json => {
+            let data = OutputData { headers: headers.to_vec(), rows };
+            serde_json::to_writer_pretty(stdout.lock(), &data)
+                .map_err(|e| csv_tool::error::CsvError::Format(format!("JSON序列化失败: {}", e)))?;
+            println!();
+        }
+        OutputFormat::Csv => {
+            let mut writer = ::csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .flexible(true)
+                .from_writer(stdout.lock());
+            if !headers.is_empty() {
+                writer.write_record(headers)?;
+            }
+            for row in &rows {
+                writer.write_record(&row.fields)?;
+            }
+            writer.flush()?;
+        }
+        OutputFormat::Table => unreachable!("表格由调用方打印"),
+    }
+    Ok(())
+}
+
 /// 打印表格
 fn print_table(
     headers: &[String], 
     rows: &[csv_tool::csv::CsvRecord], 
-    page: usize, 
-    total_pages: usize,
-    page_size: usize,
+    max_col_width: usize,
+    columns: Option<&[usize]>,
 ) {
-    // 计算列宽（根据内容自适应，最大20字符）
-    let col_count = headers.len().max(rows.first().map(|r| r.fields.len()).unwrap_or(0));
-    let max_width = 18;
+    println!();
+    for line in render_table(headers, rows, max_col_width, columns) {
+        println!("{}", line);
+    }
+}
+
+/// 生成表格的各行（含边框），供直接打印和交互式分页器使用
+fn render_table(
+    headers: &[String],
+    rows: &[csv_tool::csv::CsvRecord],
+    max_col_width: usize,
+    columns: Option<&[usize]>,
+) -> Vec<String> {
+    let headers = project_headers(headers, columns);
+    let cells: Vec<Vec<&str>> = rows.iter()
+        .map(|row| project_fields(row, columns))
+        .collect();
+    let layout = TableLayout::compute(&headers, &cells, max_col_width);
+    
+    let mut lines = Vec::with_capacity(cells.len() + 4);
+    lines.push(layout.border("┌", "┬", "┐"));
+    if !headers.is_empty() {
+        lines.push(layout.header_line(&headers));
+        lines.push(layout.border("├", "┼", "┤"));
+    }
+    
+    // 数据行
+    for row in &cells {
+        lines.push(layout.row_line(row));
+    }
+    
+    lines.push(layout.border("└", "┴", "┘"));
+    lines
+}
+
+/// 按显示列投影表头，未指定列时返回全部表头
+fn project_headers(headers: &[String], columns: Option<&[usize]>) -> Vec<String> {
+    match columns {
+        Some(cols) if !headers.is_empty() => cols.iter()
+            .map(|&c| headers.get(c).cloned().unwrap_or_default())
+            .collect(),
+        _ => headers.to_vec(),
+    }
+}
+
+/// 按显示列投影记录字段，缺失的字段显示为空
+fn project_fields<'r>(record: &'r CsvRecord<'_>, columns: Option<&[usize]>) -> Vec<&'r str> {
+    match columns {
+        Some(cols) => cols.iter()
+            .map(|&c| record.fields.get(c).map(|f| f.as_ref()).unwrap_or(""))
+            .collect(),
+        None => record.fields.iter().map(|f| f.as_ref()).collect(),
+    }
+}
+
+/// 最小列宽（截断后至少保留两列内容和截断标记 "…"）
+const MIN_COL_WIDTH: usize = 3;
+
+/// 表格列布局
+struct TableLayout {
+    /// 每列的显示宽度
+    widths: Vec<usize>,
+    /// 每列是否右对齐（全部为数字的列）
+    right_align: Vec<bool>,
+}
+
+impl TableLayout {
+    /// 根据表头和实际显示的行计算列宽
+    fn compute<S: AsRef<str>>(headers: &[String], rows: &[Vec<S>], max_col_width: usize) -> Self {
+        let col_count = rows.iter()
+            .map(|row| row.len())
+            .fold(headers.len(), usize::max);
+        let max_col_width = max_col_width.max(MIN_COL_WIDTH);
+        
+        let mut widths = vec![MIN_COL_WIDTH; col_count];
+        let mut numeric = vec![true; col_count];
+        let mut has_value = vec![false; col_count];
+        
+        for (i, header) in headers.iter().enumerate() {
+            widths[i] = widths[i].max(visible_width(header));
+        }
+        for row in rows {
+            for (i, cell) in row.iter().enumerate() {
+                let cell = cell.as_ref();
+                widths[i] = widths[i].max(visible_width(cell));
+                if !cell.is_empty() {
+                    has_value[i] = true;
+                    numeric[i] &= cell.trim().parse::<f64>().is_ok();
+                }
+            }
+        }
+        
+        for width in &mut widths {
+            *width = (*width).min(max_col_width);
+        }
+        let right_align = numeric.iter()
+            .zip(&has_value)
+            .map(|(&n, &v)| n && v)
+            .collect();
+        
+        Self { widths, right_align }
+    }
+    
+    /// 生成边框线
+    fn border(&self, left: &str, mid: &str, right: &str) -> String {
+        let segments: Vec<String> = self.widths.iter()
+            .map(|&w| "─".repeat(w + 2))
+            .collect();
+        format!("{}{}{}", left, segments.join(mid), right)
+    }
+    
+    /// 生成表头行（居中）
+    fn header_line(&self, headers: &[String]) -> String {
+        let mut line = String::from("│");
+        for (i, &width) in self.widths.iter().enumerate() {
+            let text = truncate_to_width(headers.get(i).map(|h| h.as_str()).unwrap_or(""), width);
+            line.push_str(&format!(" {} │", display::pad_center(&text, width)));
+        }
+        line
+    }
+    
+    /// 生成数据行
+    fn row_line<S: AsRef<str>>(&self, cells: &[S]) -> String {
+        self.row_line_styled(cells, |_, text| text.to_string())
+    }
+    
+    /// 生成数据行，`style` 接收（列号, 完整文本）并返回要显示的内容
+    ///
+    /// 先样式化再按显示宽度截断，`style` 添加的ANSI转义序列不计入宽度，
+    /// 也不会被截断破坏，因此被截断的单元格同样可以高亮
+    fn row_line_styled<S, F>(&self, cells: &[S], style: F) -> String
+    where
+        S: AsRef<str>,
+        F: Fn(usize, &str) -> String,
+    {
+        let mut line = String::from("│");
+        for (i, &width) in self.widths.iter().enumerate() {
+            let raw = cells.get(i).map(|c| c.as_ref()).unwrap_or("");
+            let text = truncate_to_width(&style(i, raw), width);
+            let padded = if self.right_align[i] {
+                display::pad_left(&text, width)
+            } else {
+                display::pad_right(&text, width)
+            };
+            line.push_str(&format!(" {} │", padded));
+        }
+        line
+    }
+}
+
+/// 截断路径显示
+fn truncate_path(path: &str, max_len: usize) -> String {
+    if path.len() > max_len {
+        format!("...{}", &path[path.len() - max_len + 3..])
+    } else {
+        path.to_string()
+    }
+}
+
+/// 导出CSV数据
+fn cmd_export(args: &Args, export: &ExportArgs) -> Result<()> {
+    let &ExportArgs {
+        ref output,
+        ref format,
+        ref columns,
+        from,
+        to,
+        ref search,
+        regex: use_regex,
+        glob: use_glob,
+        pretty,
+        typed,
+        keep_strings,
+        no_headers,
+        dedupe_headers,
+        nested,
+        precision,
+        on_ragged,
+    } = export;
+    let format = format.as_deref();
+    let columns = columns.as_deref();
+    let search = search.as_deref();
+    let start_time = Instant::now();
+    let output_path = Path::new(output);
+    // "-" 表示写入标准输出，此时提示信息不能混入数据
+    let to_stdout = output == "-";
+    let quiet = args.quiet || to_stdout;
+    
+    // 确定导出格式
+    let export_format = if let Some(fmt) = format {
+        match fmt.to_lowercase().as_str() {
+            "json" => ExportFormat::Json,
+            "jsonl" | "ndjson" => ExportFormat::JsonLines,
+            "csv" => ExportFormat::Csv,
+            "tsv" => ExportFormat::Tsv,
+            "db" | "sqlite" => ExportFormat::Sqlite,
+            _ => return Err(csv_tool::error::CsvError::Format(
+                format!("不支持的格式: {}. 支持的格式: json, jsonl, csv, tsv, sqlite", fmt)
+            ).into()),
+        }
+    } else {
+        // 从文件扩展名推断
+        ExportFormat::from_extension(output_path).unwrap_or(ExportFormat::Json)
+    };
+    
+    if to_stdout && export_format == ExportFormat::Sqlite {
+        return Err(csv_tool::error::CsvError::Format(
+            "SQLite格式不能输出到标准输出，请指定输出文件".to_string()
+        ));
+    }
+    
+    if !quiet {
+        println!("\n📤 导出配置:");
+        println!("   输出文件: {}", output);
+        println!("   导出格式: {}", export_format.name());
+    }
+    
+    let pb = create_spinner("正在打开文件...");
+    
+    let reader = open_reader(args)?;
+    
+    pb.finish_and_clear();
+    
+    let info = reader.info();
+    let headers = info.headers.clone();
+    
+    // 解析列选择
+    let export_columns = if let Some(cols_str) = columns {
+        Some(parse_column_list(cols_str, &headers)?)
+    } else {
+        None
+    };
+    
+    // 创建导出选项
+    let mut options = ExportOptions::new(export_format)
+        .with_pretty(pretty)
+        .with_typed(typed || precision.is_some())
+        .with_keep_strings(keep_strings)
+        .with_headers(!no_headers)
+        .with_dedupe_headers(dedupe_headers)
+        .with_nested(nested)
+        .with_on_ragged(on_ragged)
+        .with_delimiter(args.output_delimiter());
+    if let Some(decimals) = precision {
+        options = options.with_float_format(FloatFormat::Fixed(decimals));
+    }
+    
+    if let Some(cols) = export_columns {
+        if !quiet {
+            println!("   导出列:   {:?}", cols.iter().map(|&i| headers.get(i).cloned().unwrap_or_default()).collect::<Vec<_>>());
+        }
+        options = options.with_columns(cols);
+    }
+    
+    // 行范围
+    if from.is_some() || to.is_some() {
+        let start = from.map(|f| f.saturating_sub(1)).unwrap_or(0);
+        // 未指定结束行时导出到文件末尾（快速打开时总行数只是估算值）
+        let end = to.unwrap_or(usize::MAX);
+        if !quiet {
+            match to {
+                Some(to) => println!("   行范围:   {} - {}", start + 1, to),
+                None => println!("   行范围:   {} - 末尾", start + 1),
+            }
+        }
+        options = options.with_row_range(start, end);
+    }
+    
+    // 搜索筛选
+    let mut search_filter = None;
+    if let Some(pattern) = search {
+        if !quiet {
+            let mode = if use_regex {
+                "(正则)"
+            } else if use_glob {
+                "(通配符)"
+            } else {
+                ""
+            };
+            println!("   搜索筛选: \"{}\" {}", pattern, mode);
+        }
+        let search_pattern = if use_regex {
+            SearchPattern::regex(pattern, true)?
+        } else if use_glob {
+            SearchPattern::glob(pattern, true)?
+        } else {
+            SearchPattern::text(pattern, true)
+        };
+        search_filter = Some(SearchOptions::new(search_pattern));
+    }
+    
+    // 执行导出（有搜索筛选时边搜索边写出，不在内存中保留匹配的行）
+    let pb = create_spinner("正在导出...");
+    
+    if let Some(filter) = search_filter {
+        options = options.with_search_filter(filter);
+    }
+    let exporter = Exporter::new(&reader, options);
+    let stats = if to_stdout {
+        let mut writer = std::io::BufWriter::new(std::io::stdout().lock());
+        exporter.export_to_writer(&mut writer)?
+    } else {
+        exporter.export_to_file(output)?
+    };
+    
+    pb.finish_and_clear();
+    
+    let duration = start_time.elapsed();
+    
+    if to_stdout {
+        if !args.quiet {
+            eprintln!("✅ 导出完成: {} 行 × {} 列，{}，耗时 {:.2}秒",
+                stats.rows_exported,
+                stats.cols_exported,
+                format_size(stats.file_size),
+                duration.as_secs_f64()
+            );
+            if stats.rows_skipped > 0 {
+                eprintln!("   跳过了 {} 行字段数与表头不一致的行", stats.rows_skipped);
+            }
+        }
+        return Ok(());
+    }
+    
+    println!("\n✅ 导出完成!");
+    println!("   导出行数: {} 行", stats.rows_exported);
+    if stats.rows_skipped > 0 {
+        println!("   跳过行数: {} 行（字段数与表头不一致）", stats.rows_skipped);
+    }
+    println!("   导出列数: {} 列", stats.cols_exported);
+    println!("   文件大小: {}", format_size(stats.file_size));
+    println!("   输出文件: {}", output);
+    println!("   耗时:     {:.2}秒", duration.as_secs_f64());
+    
+    Ok(())
+}
+
+/// 随机抽样子命令
+fn cmd_sample(args: &Args, rows: usize, output: &str, seed: Option<u64>, keep_order: bool) -> Result<()> {
+    let start_time = Instant::now();
+    let pb = create_spinner("正在打开文件...");
+    let reader = open_reader(args)?;
+
+    pb.set_message("正在抽样...");
+    let options = SampleOptions::new()
+        .with_seed(seed.unwrap_or_else(random_seed))
+        .with_keep_order(keep_order);
+    let write_options = WriteOptions::new().with_delimiter(args.delimiter());
+    let stats = sample_to_file(&reader, rows, &options, output, &write_options)?;
+    pb.finish_and_clear();
+
+    if !args.quiet {
+        println!("\n✅ 抽样完成!");
+        println!("   抽取行数: {} / {} 行", stats.rows_written, reader.info().total_rows);
+        println!("   随机种子: {}", options.seed);
+        println!("   输出文件: {}", output);
+        println!("   耗时:     {:.2}秒", start_time.elapsed().as_secs_f64());
+    }
+    Ok(())
+}
+
+/// 随机打乱子命令
+fn cmd_shuffle(args: &Args, output: &str, seed: Option<u64>) -> Result<()> {
+    let start_time = Instant::now();
+    let pb = create_spinner("正在打开文件...");
+    let reader = open_reader(args)?;
+
+    pb.set_message("正在打乱...");
+    let seed = seed.unwrap_or_else(random_seed);
+    let write_options = WriteOptions::new().with_delimiter(args.delimiter());
+    let stats = shuffle_to_file(&reader, seed, output, &write_options)?;
+    pb.finish_and_clear();
+
+    if !args.quiet {
+        println!("\n✅ 打乱完成!");
+        println!("   写入行数: {} 行", stats.rows_written);
+        println!("   随机种子: {}", seed);
+        println!("   输出文件: {}", output);
+        println!("   耗时:     {:.2}秒", start_time.elapsed().as_secs_f64());
+    }
+    Ok(())
+}
+
+/// 列索引子命令
+fn cmd_index(args: &Args, column: &str) -> Result<()> {
+    let start_time = Instant::now();
+    
+    let pb = create_spinner("正在打开文件...");
+    
+    let reader = open_reader(args)?;
+    let col_idx = parse_column_spec(column, reader.headers())?;
+    
+    pb.set_message("正在构建列索引...");
+    let options = reader_options(args).with_column_index(Some(col_idx));
+    let reader = CsvReader::open_with_options(&args.file, &options)?;
+    pb.finish_and_clear();
+    
+    let column_index = reader.column_index(col_idx)
+        .ok_or_else(|| csv_tool::error::CsvError::IndexFile("列索引构建失败".to_string()))?;
+    
+    if !args.quiet {
+        println!("\n✅ 已为列 '{}' 构建列索引: {} 行，耗时 {:.2}秒",
+            reader.headers().get(col_idx).map_or(column, |name| name.as_str()),
+            column_index.len(),
+            start_time.elapsed().as_secs_f64()
+        );
+        if let Some(path) = reader.index_path() {
+            println!("   索引文件: {}", path.display());
+        }
+    }
+    
+    Ok(())
+}
+
+/// 列类型推断子命令
+fn cmd_schema(args: &Args, sample: usize) -> Result<()> {
+    let start_time = Instant::now();
+    
+    let pb = create_spinner("正在打开文件...");
+    
+    let reader = open_reader(args)?;
+    
+    pb.set_message("正在推断列类型...");
+    let schema = infer_schema(&reader, sample)?;
+    pb.finish_and_clear();
+    
+    let duration = start_time.elapsed();
+    
+    if !args.quiet {
+        println!("\n📐 列类型推断（采样前 {} 行，耗时 {:.2}秒）:", sample, duration.as_secs_f64());
+    }
+    
+    let name_width = schema.iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    
+    for (i, column) in schema.iter().enumerate() {
+        println!("   {:>3}. {:<width$}  {:<8} {}",
+            i + 1,
+            column.name,
+            column.inferred_type.name(),
+            if column.nullable { "可空" } else { "" },
+            width = name_width
+        );
+    }
+    
+    Ok(())
+}
+
+/// 统计不同值子命令
+fn cmd_distinct(
+    args: &Args,
+    column: &str,
+    order: DistinctOrder,
+    top: Option<usize>,
+    ignore_case: bool,
+) -> Result<()> {
+    let start_time = Instant::now();
+    
+    let pb = create_spinner("正在打开文件...");
+    
+    let reader = open_reader(args)?;
+    let headers = reader.headers().to_vec();
+    let col_idx = parse_column_spec(column, &headers)?;
+    
+    pb.set_message("正在统计不同值...");
+    let mut counts = value_counts(&reader, col_idx, ignore_case)?;
+    pb.finish_and_clear();
+    
+    let distinct = counts.len();
+    // 先按次数取前N个，再按指定方式排列
+    if let Some(n) = top {
+        counts.truncate(n);
+    }
+    if order == DistinctOrder::Alpha {
+        counts.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    
+    let duration = start_time.elapsed();
+    
+    if !args.quiet {
+        let name = headers.get(col_idx).map_or(column, |h| h.as_str());
+        println!("\n🔢 列 \"{}\" 共 {} 个不同值（耗时 {:.2}秒）:", name, distinct, duration.as_secs_f64());
+    }
+    
+    let count_width = counts.iter()
+        .map(|(_, count)| count.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    
+    for (value, count) in &counts {
+        println!("   {:>width$}  {}", count, value, width = count_width);
+    }
+    
+    Ok(())
+}
+
+/// 列统计子命令
+fn cmd_stats(
+    args: &Args,
+    column: &str,
+    precision: Option<usize>,
+    top: usize,
+    percentiles: &[f64],
+    approx: bool,
+) -> Result<()> {
+    let start_time = Instant::now();
+    
+    let pb = create_spinner("正在打开文件...");
     
-    let separator = "─".repeat(max_width + 2);
-    let full_separator = format!("├{}┤", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┼"));
+    let reader = open_reader(args)?;
+    let headers = reader.headers().to_vec();
+    let col_idx = parse_column_spec(column, &headers)?;
     
-    // 表头
-    println!();
-    println!("┌{}┐", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┬"));
+    pb.set_message("正在统计...");
+    let options = StatsOptions::new()
+        .with_percentiles(percentiles.to_vec())
+        .with_approx(approx);
+    let stats = column_stats_with_options(&reader, col_idx, &options, None)?.value;
+    pb.finish_and_clear();
     
-    if !headers.is_empty() {
-        print!("│");
-        for header in headers.iter().take(col_count) {
-            print!(" {:^width$} │", truncate_str(header, max_width), width = max_width);
-        }
-        // 填充空列
-        for _ in headers.len()..col_count {
-            print!(" {:^width$} │", "", width = max_width);
-    }
-    println!();
-        println!("{}", full_separator);
+    let duration = start_time.elapsed();
+    let float_format = precision.map_or(FloatFormat::Shortest, FloatFormat::Fixed);
+    
+    if !args.quiet {
+        let name = headers.get(col_idx).map_or(column, |h| h.as_str());
+        println!("\n📊 列 \"{}\" 的统计（耗时 {:.2}秒）:", name, duration.as_secs_f64());
     }
     
-    // 数据行
-    for row in rows {
-        print!("│");
-        for field in row.fields.iter().take(col_count) {
-            print!(" {:width$} │", truncate_str(field.as_ref(), max_width), width = max_width);
+    println!("   行数:     {}", stats.rows);
+    println!("   空值:     {}", stats.null_count);
+    println!("   不同值:   {}", stats.distinct_count);
+    
+    if let Some(numeric) = &stats.numeric {
+        println!("   最小值:   {}", numeric.format_min(float_format));
+        println!("   最大值:   {}", numeric.format_max(float_format));
+        println!("   平均值:   {}", numeric.format_mean(float_format));
+        for &(percentile, value) in &numeric.percentiles {
+            let label = format!("p{}{}:", percentile, if approx { "≈" } else { "" });
+            println!("   {:<10}{}", label, float_format.format(value));
         }
-        // 填充空列
-        for _ in row.fields.len()..col_count {
-            print!(" {:width$} │", "", width = max_width);
+    } else if top > 0 && !stats.value_counts.is_empty() {
+        println!("   最常见的值:");
+        let values = stats.top_values(top);
+        let count_width = values.iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        for (value, count) in values {
+            println!("   {:>width$}  {}", count, value, width = count_width);
         }
-        println!();
     }
     
-    println!("└{}┘", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┴"));
-    
-    // 分页信息
-    let start_row = page * page_size + 1;
-    let end_row = start_row + rows.len() - 1;
-    println!("📖 第 {}/{} 页 (行 {}-{})", page + 1, total_pages, start_row, end_row);
+    Ok(())
 }
 
-/// 截断字符串
-fn truncate_str(s: &str, max_len: usize) -> String {
-    let chars: Vec<char> = s.chars().collect();
-    if chars.len() > max_len {
-        let truncated: String = chars[..max_len - 2].iter().collect();
-        format!("{}..", truncated)
-    } else {
-        s.to_string()
-    }
-}
+/// 直方图中最长的条形宽度（字符数）
+const HISTOGRAM_BAR_WIDTH: usize = 40;
 
-/// 截断带有ANSI转义序列的字符串
-fn truncate_str_with_ansi(s: &str, max_len: usize) -> String {
-    // 简化处理：如果字符串包含ANSI代码，直接返回（已经高亮的文本）
-    if s.contains('\x1b') {
-        // 计算可见字符数
-        let visible_len = s.chars().filter(|&c| c != '\x1b').count() 
-            - s.matches("\x1b[").count() * 4; // 减去ANSI代码长度估计
-        if visible_len > max_len + 10 { // 给高亮代码留余量
-            return truncate_str(&s.replace("\x1b[1;33m", "").replace("\x1b[0m", ""), max_len);
+/// 直方图子命令
+fn cmd_histogram(args: &Args, column: &str, bins: usize, min: Option<f64>, max: Option<f64>) -> Result<()> {
+    if bins == 0 {
+        return Err(csv_tool::error::CsvError::Format("区间个数必须大于0".to_string()));
+    }
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(csv_tool::error::CsvError::Format(format!(
+                "范围下限 {} 大于上限 {}",
+                min, max
+            )));
         }
-        s.to_string()
-    } else {
-        truncate_str(s, max_len)
     }
-}
 
-/// 截断路径显示
-fn truncate_path(path: &str, max_len: usize) -> String {
-    if path.len() > max_len {
-        format!("...{}", &path[path.len() - max_len + 3..])
-    } else {
-        path.to_string()
+    let start_time = Instant::now();
+    let pb = create_spinner("正在打开文件...");
+    
+    let reader = open_reader(args)?;
+    let headers = reader.headers().to_vec();
+    let col_idx = parse_column_spec(column, &headers)?;
+    
+    pb.set_message("正在统计...");
+    let result = histogram(&reader, col_idx, bins, (min, max))?;
+    pb.finish_and_clear();
+    
+    if !args.quiet {
+        let name = headers.get(col_idx).map_or(column, |h| h.as_str());
+        println!("\n📊 列 \"{}\" 的分布（耗时 {:.2}秒）:", name, start_time.elapsed().as_secs_f64());
+    }
+    
+    if result.buckets.is_empty() {
+        println!("   没有数值");
+    }
+    let format = FloatFormat::Shortest;
+    let last = result.buckets.len().saturating_sub(1);
+    let labels: Vec<String> = result.buckets.iter()
+        .enumerate()
+        .map(|(i, bucket)| {
+            let close = if i == last { ']' } else { ')' };
+            format!("[{}, {}{}", format.format(bucket.start), format.format(bucket.end), close)
+        })
+        .collect();
+    let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    let max_count = result.buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    for (label, bucket) in labels.iter().zip(&result.buckets) {
+        let bar = (bucket.count * HISTOGRAM_BAR_WIDTH).div_ceil(max_count);
+        println!("   {:<width$}  {:<bar_width$}  {}", label, "█".repeat(bar), bucket.count,
+            width = label_width, bar_width = HISTOGRAM_BAR_WIDTH);
+    }
+    
+    if result.null_count > 0 {
+        println!("   空值:         {}", result.null_count);
+    }
+    if result.skipped > 0 {
+        println!("   跳过非数字值: {}", result.skipped);
     }
+    if result.out_of_range > 0 {
+        println!("   超出范围:     {}", result.out_of_range);
+    }
+    
+    Ok(())
 }
 
-/// 导出CSV数据
-fn cmd_export(
+/// SQL查询子命令
+fn cmd_query(
     args: &Args,
-    output: &str,
-    format: Option<&str>,
+    sql: &str,
     columns: Option<&str>,
     from: Option<usize>,
     to: Option<usize>,
-    search: Option<&str>,
-    use_regex: bool,
-    pretty: bool,
-    no_headers: bool,
 ) -> Result<()> {
     let start_time = Instant::now();
-    let output_path = Path::new(output);
-    
-    // 确定导出格式
-    let export_format = if let Some(fmt) = format {
-        match fmt.to_lowercase().as_str() {
-            "json" => ExportFormat::Json,
-            "jsonl" | "ndjson" => ExportFormat::JsonLines,
-            "csv" => ExportFormat::Csv,
-            "tsv" => ExportFormat::Tsv,
-            _ => return Err(csv_tool::error::CsvError::Format(
-                format!("不支持的格式: {}. 支持的格式: json, jsonl, csv, tsv", fmt)
-            ).into()),
-        }
-    } else {
-        // 从文件扩展名推断
-        ExportFormat::from_extension(output_path).unwrap_or(ExportFormat::Json)
-    };
     
     if !args.quiet {
-        println!("\n📤 导出配置:");
-        println!("   输出文件: {}", output);
-        println!("   导出格式: {}", export_format.name());
+        println!("\n🗄️  SQL查询: {}", sql);
     }
     
     let pb = create_spinner("正在打开文件...");
     
-    let reader = CsvReader::open_fast(
-        &args.file,
-        !args.no_headers,
-        args.delimiter as u8,
-        args.granularity,
-    )?;
-    
-    pb.finish_and_clear();
+    let reader = open_reader(args)?;
     
     let info = reader.info();
     let headers = info.headers.clone();
     
-    // 解析列选择
-    let export_columns = if let Some(cols_str) = columns {
-        let cols: Result<Vec<usize>> = cols_str.split(',')
-            .map(|s| parse_column_spec(s.trim(), &headers))
-            .collect();
-        Some(cols?)
-    } else {
-        None
-    };
-    
-    // 创建导出选项
-    let mut options = ExportOptions::new(export_format)
-        .with_pretty(pretty)
-        .with_headers(!no_headers)
-        .with_delimiter(args.delimiter as u8);
-    
-    if let Some(cols) = export_columns {
-        if !args.quiet {
-            println!("   导出列:   {:?}", cols.iter().map(|&i| headers.get(i).cloned().unwrap_or_default()).collect::<Vec<_>>());
-        }
-        options = options.with_columns(cols);
+    let mut options = ExportOptions::new(ExportFormat::Sqlite);
+    if let Some(cols_str) = columns {
+        options = options.with_columns(parse_column_list(cols_str, &headers)?);
     }
-    
-    // 行范围
     if from.is_some() || to.is_some() {
         let start = from.map(|f| f.saturating_sub(1)).unwrap_or(0);
-        let end = to.unwrap_or(info.total_rows);
-        if !args.quiet {
-            println!("   行范围:   {} - {}", start + 1, end);
-        }
+        // 未指定结束行时载入到文件末尾（快速打开时总行数只是估算值）
+        let end = to.unwrap_or(usize::MAX);
         options = options.with_row_range(start, end);
     }
     
-    // 搜索筛选
-    if let Some(pattern) = search {
-        if !args.quiet {
-            println!("   搜索筛选: \"{}\" {}", pattern, if use_regex { "(正则)" } else { "" });
-        }
-        let search_pattern = if use_regex {
-            SearchPattern::regex(pattern, true)?
-        } else {
-            SearchPattern::text(pattern, true)
-        };
-        let search_opts = SearchOptions::new(search_pattern);
-        options = options.with_search_filter(search_opts);
-    }
-    
-    // 执行导出
-    let pb = create_spinner("正在导出...");
+    pb.set_message("正在载入SQLite并执行查询...");
     
     let exporter = Exporter::new(&reader, options);
-    let stats = exporter.export_to_file(output)?;
+    let result = exporter.query_sql(sql)?;
     
     pb.finish_and_clear();
     
     let duration = start_time.elapsed();
     
-    println!("\n✅ 导出完成!");
-    println!("   导出行数: {} 行", stats.rows_exported);
-    println!("   导出列数: {} 列", stats.cols_exported);
-    println!("   文件大小: {}", format_size(stats.file_size));
-    println!("   输出文件: {}", output);
-    println!("   耗时:     {:.2}秒", duration.as_secs_f64());
+    if !args.quiet {
+        println!("📊 查询结果 ({} 行，耗时 {:.2}秒):", result.rows.len(), duration.as_secs_f64());
+    }
+    
+    let records: Vec<CsvRecord<'static>> = result.rows
+        .into_iter()
+        .map(|row| CsvRecord {
+            fields: row.into_iter().map(Cow::Owned).collect(),
+            quoted: None,
+        })
+        .collect();
+    
+    print_table(&result.columns, &records, args.max_col_width, None);
+    
+    Ok(())
+}
+
+/// 显示文件末尾的行，并可持续跟踪新追加的行
+fn cmd_tail(args: &Args, lines: usize, follow: bool, interval: u64) -> Result<()> {
+    if is_gzip_file(&args.file)? || TextEncoding::detect_file(&args.file)?.is_utf16() {
+        return Err(csv_tool::error::CsvError::Format(
+            "tail 仅支持未压缩的UTF-8文件".to_string()
+        ));
+    }
+    
+    let delimiter = args.delimiter();
+    let reader = CsvReader::open_with_options(&args.file, &reader_options(args))?;
+    let info = reader.info().clone();
+    // 只用 \r 换行的文件按 \r 分行（\r\n 行尾的 \r 在解析字段时去掉）
+    let terminator = if info.line_ending == LineEnding::Cr { b'\r' } else { b'\n' };
+    
+    // 末尾没有换行符的半行可能还在写入，暂不输出
+    let mut file = File::open(&args.file)?;
+    let file_len = file.metadata()?.len();
+    let boundary = last_line_boundary(&mut file, file_len, terminator)?;
+    let complete_rows = if boundary < file_len {
+        info.total_rows.saturating_sub(1)
+    } else {
+        info.total_rows
+    };
+    
+    let mut out = ::csv::WriterBuilder::new()
+        .delimiter(args.output_delimiter())
+        .flexible(true)
+        .from_writer(std::io::stdout());
+    if !args.quiet && !info.headers.is_empty() {
+        out.write_record(&info.headers)?;
+    }
+    for record in reader.read_rows(complete_rows.saturating_sub(lines), complete_rows)? {
+        out.write_record(record.fields.iter().map(|f| f.as_bytes()))?;
+    }
+    out.flush()?;
+    
+    if !follow {
+        return Ok(());
+    }
+    
+    let mut index = reader.index().clone();
+    let data_start = reader.data_start();
+    drop(reader);
+    
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&stop);
+    ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst))
+        .map_err(|e| csv_tool::error::CsvError::Format(format!("无法注册Ctrl-C处理: {}", e)))?;
+    
+    // 从最后一个完整行之后开始读取，半行留在缓冲区直到遇到换行符；
+    // 不重新读取表头，表头行还没有写完时丢弃读到的第一行
+    let mut skip_header = !args.no_headers && data_start > boundary;
+    let mut offset = if skip_header { boundary } else { boundary.max(data_start) };
+    let mut pending: Vec<u8> = Vec::new();
+    while !stop.load(Ordering::SeqCst) {
+        let len = file.metadata()?.len();
+        if len < offset {
+            return Err(csv_tool::error::CsvError::Format(
+                "文件已被截断，停止跟踪".to_string()
+            ));
+        }
+        if len == offset {
+            std::thread::sleep(Duration::from_millis(interval));
+            continue;
+        }
+        
+        file.seek(SeekFrom::Start(offset))?;
+        let read = (&mut file).take(len - offset).read_to_end(&mut pending)?;
+        offset += read as u64;
+        
+        if let Some(last_newline) = pending.iter().rposition(|&b| b == terminator) {
+            for line in pending[..last_newline].split(|&b| b == terminator) {
+                if !args.keep_empty_lines && is_empty_line(line) {
+                    continue;
+                }
+                if skip_header {
+                    skip_header = false;
+                    continue;
+                }
+                let record = CsvRecord::parse_line_with(line, &delimiter);
+                out.write_record(record.fields.iter().map(|f| f.as_bytes()))?;
+            }
+            out.flush()?;
+            pending.drain(..=last_newline);
+        }
+    }
+    
+    if args.no_index_cache || args.keep_empty_lines {
+        if !args.quiet {
+            eprintln!("\n✅ 已停止跟踪");
+        }
+        return Ok(());
+    }
+    
+    // 用追加的数据扩展索引，之后分页查看无需重新扫描整个文件
+    let added = index.extend_and_save(Path::new(&args.file), !args.no_headers, &args.index_location())?;
+    if !args.quiet {
+        eprintln!("\n✅ 已停止跟踪，新增 {} 行，索引已更新（共 {} 行）", added, index.total_rows());
+    }
     
     Ok(())
 }
 
+/// 查找最后一个完整行的结束位置（最后一个行结束符 `terminator` 之后的偏移量）
+fn last_line_boundary(file: &mut File, len: u64, terminator: u8) -> Result<u64> {
+    const CHUNK_SIZE: u64 = 64 * 1024;
+    let mut buf = vec![0u8; CHUNK_SIZE as usize];
+    let mut end = len;
+    
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_SIZE);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(pos) = chunk.iter().rposition(|&b| b == terminator) {
+            return Ok(start + pos as u64 + 1);
+        }
+        end = start;
+    }
+    
+    Ok(0)
+}
+
 /// 排序子命令
-fn cmd_sort(
-    args: &Args,
-    column: &str,
-    order_str: &str,
-    data_type_str: &str,
-    limit: Option<usize>,
-    ignore_case: bool,
-    nulls_first: bool,
-    show_line_numbers: bool,
-    output: Option<&str>,
-) -> Result<()> {
+fn cmd_sort(args: &Args, sort: &SortArgs) -> Result<()> {
+    let &SortArgs {
+        ref column,
+        order: ref order_str,
+        data_type: ref data_type_str,
+        limit,
+        ignore_case,
+        collation: ref collation_str,
+        nulls_first,
+        line_numbers: show_line_numbers,
+        ref output,
+        format,
+        column_index,
+        numeric_cleanup,
+        ref currency,
+        decimal_comma,
+        ref null_values,
+        unstable,
+    } = sort;
+    let output = output.as_deref();
+    let numeric_cleanup = numeric_cleanup_from_args(numeric_cleanup, currency, decimal_comma);
     let start_time = Instant::now();
     
     if !args.quiet {
@@ -1037,12 +2658,7 @@ fn cmd_sort(
     
     let pb = create_spinner("正在打开文件...");
     
-    let reader = CsvReader::open_fast(
-        &args.file,
-        !args.no_headers,
-        args.delimiter as u8,
-        args.granularity,
-    )?;
+    let reader = open_reader(args)?;
     
     pb.set_message("正在读取数据...");
     
@@ -1052,6 +2668,15 @@ fn cmd_sort(
     // 解析列
     let col_idx = parse_column_spec(column, &headers)?;
     
+    // 列索引需要完整扫描文件，确定排序列后重新打开
+    let reader = if column_index {
+        pb.set_message("正在加载列索引...");
+        let options = reader_options(args).with_column_index(Some(col_idx));
+        CsvReader::open_with_options(&args.file, &options)?
+    } else {
+        reader
+    };
+    
     // 解析排序方向
     let order = SortOrder::from_str(order_str)
         .ok_or_else(|| csv_tool::error::CsvError::Format(
@@ -1064,6 +2689,25 @@ fn cmd_sort(
             format!("无效的数据类型: {}，请使用 auto、string 或 number", data_type_str)
         ))?;
     
+    // 解析排序规则
+    let collation = Collation::parse(collation_str)
+        .ok_or_else(|| csv_tool::error::CsvError::Format(
+            format!("无效的排序规则: {}，请使用 binary 或 accent-folding", collation_str)
+        ))?;
+    
+    // 自动检测时，采样推断为数值的列直接按数字排序
+    let data_type = if data_type == DataType::Auto {
+        pb.set_message("正在推断列类型...");
+        let types = reader.infer_column_types(DEFAULT_SAMPLE_ROWS)?;
+        if types.get(col_idx).is_some_and(|t| t.is_numeric()) {
+            DataType::Number
+        } else {
+            DataType::Auto
+        }
+    } else {
+        data_type
+    };
+    
     if !args.quiet {
         let order_desc = match order {
             SortOrder::Ascending => "升序",
@@ -1090,7 +2734,30 @@ fn cmd_sort(
     let sort_options = SortOptions::new()
         .add_key(sort_key)
         .with_case_sensitive(!ignore_case)
-        .with_nulls_last(!nulls_first);
+        .with_collation(collation)
+        .with_nulls_last(!nulls_first)
+        .with_null_values(null_values.to_vec())
+        .with_stable(!unstable);
+    let sort_options = match numeric_cleanup {
+        Some(cleanup) => sort_options.with_numeric_cleanup(cleanup),
+        None => sort_options,
+    };
+    
+    // 导出到文件时逐行写出，不在内存中保留完整的排序结果
+    if let Some(output_path) = output {
+        let write_options = WriteOptions::new().with_delimiter(args.delimiter());
+        let stats = sort_to_file(&reader, &sort_options, limit, output_path, &write_options)?;
+        
+        pb.finish_and_clear();
+        
+        if !args.quiet {
+            println!("\n✅ 排序完成!");
+            println!("   排序行数: {} 行", stats.rows_written);
+            println!("   输出文件: {}", output_path);
+            println!("   耗时:     {:.2}秒", start_time.elapsed().as_secs_f64());
+        }
+        return Ok(());
+    }
     
     // 执行排序
     let sorted_records = sort_csv_data(&reader, &sort_options, limit)?;
@@ -1100,16 +2767,11 @@ fn cmd_sort(
     let duration = start_time.elapsed();
     
     // 输出结果
-    if let Some(output_path) = output {
-        // 导出到文件
-        export_sorted_to_file(&sorted_records, &headers, output_path, args.delimiter as u8)?;
-        
-        if !args.quiet {
-            println!("\n✅ 排序完成!");
-            println!("   排序行数: {} 行", sorted_records.len());
-            println!("   输出文件: {}", output_path);
-            println!("   耗时:     {:.2}秒", duration.as_secs_f64());
-        }
+    if format != OutputFormat::Table {
+        let output_rows = sorted_records.iter()
+            .map(|record| OutputRow::new(record.original_row + 1, &record.record, None))
+            .collect();
+        print_structured(format, &headers, output_rows, args.output_delimiter())?;
     } else {
         // 输出到终端
         if !args.quiet {
@@ -1123,7 +2785,7 @@ fn cmd_sort(
         }
         display_headers.extend(headers.iter().cloned());
         
-        print_sorted_table(&display_headers, &sorted_records, show_line_numbers);
+        print_sorted_table(&display_headers, &sorted_records, show_line_numbers, args.max_col_width);
         
         if !args.quiet {
             println!("\n   共 {} 行", sorted_records.len());
@@ -1138,81 +2800,33 @@ fn print_sorted_table(
     headers: &[String],
     records: &[csv_tool::csv::SortedRecord],
     show_line_numbers: bool,
+    max_col_width: usize,
 ) {
-    let col_count = headers.len();
-    let max_width = 18;
-    
-    let separator = "─".repeat(max_width + 2);
-    let full_separator = format!("├{}┤", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┼"));
+    let rows: Vec<Vec<String>> = records.iter()
+        .map(|record| {
+            let mut row = Vec::with_capacity(record.record.fields.len() + 1);
+            if show_line_numbers {
+                row.push((record.original_row + 1).to_string());
+            }
+            row.extend(record.record.fields.iter().map(|f| f.to_string()));
+            row
+        })
+        .collect();
+    let layout = TableLayout::compute(headers, &rows, max_col_width);
     
     // 表头
     println!();
-    println!("┌{}┐", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┬"));
-    
-    print!("│");
-    for header in headers.iter().take(col_count) {
-        print!(" {:^width$} │", truncate_str(header, max_width), width = max_width);
-    }
-    println!();
-    
-    println!("{}", full_separator);
+    println!("{}", layout.border("┌", "┬", "┐"));
+    println!("{}", layout.header_line(headers));
+    println!("{}", layout.border("├", "┼", "┤"));
     
     // 数据行
-    for record in records {
-        print!("│");
-        
-        if show_line_numbers {
-            print!(" {:>width$} │", record.original_row + 1, width = max_width);
-        }
-        
-        let field_start = if show_line_numbers { 1 } else { 0 };
-        for (i, _) in headers.iter().enumerate().skip(field_start) {
-            let idx = if show_line_numbers { i - 1 } else { i };
-            let value = record.record.fields.get(idx)
-                .map(|f| f.as_ref())
-                .unwrap_or("");
-            print!(" {:^width$} │", truncate_str(value, max_width), width = max_width);
-        }
-        println!();
+    for row in &rows {
+        println!("{}", layout.row_line(row));
     }
     
     // 表底
-    println!("└{}┘", (0..col_count).map(|_| separator.clone()).collect::<Vec<_>>().join("┴"));
-}
-
-/// 将排序结果导出到文件
-fn export_sorted_to_file(
-    records: &[csv_tool::csv::SortedRecord],
-    headers: &[String],
-    output_path: &str,
-    delimiter: u8,
-) -> Result<()> {
-    use std::fs::File;
-    use std::io::Write;
-    
-    let mut file = File::create(output_path)?;
-    
-    // 写入表头
-    writeln!(file, "{}", headers.join(&(delimiter as char).to_string()))?;
-    
-    // 写入数据行
-    for record in records {
-        let fields: Vec<String> = record.record.fields
-            .iter()
-            .map(|f| {
-                let s = f.to_string();
-                // 如果字段包含分隔符或引号，需要转义
-                if s.contains(delimiter as char) || s.contains('"') || s.contains('\n') {
-                    format!("\"{}\"", s.replace('"', "\"\""))
-                } else {
-                    s
-                }
-            })
-            .collect();
-        writeln!(file, "{}", fields.join(&(delimiter as char).to_string()))?;
-    }
-    
-    Ok(())
+    println!("{}", layout.border("└", "┴", "┘"));
 }
 
 /// 编辑命令
@@ -1226,7 +2840,7 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
     let mut editor = CsvEditor::open(
         &args.file,
         !args.no_headers,
-        args.delimiter as u8,
+        args.delimiter(),
         args.granularity,
     )?;
     
@@ -1246,7 +2860,7 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
             editor.edit_cell(row_idx, col_idx, value.clone())?;
             
             let output_path = output.as_deref().unwrap_or(&args.file);
-            let options = WriteOptions::new().with_delimiter(args.delimiter as u8);
+            let options = WriteOptions::new().with_delimiter(args.delimiter());
             
             let pb = create_spinner("正在保存...");
             let stats = if output.is_some() {
@@ -1264,6 +2878,42 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
             println!("   耗时:     {:.2}秒", duration.as_secs_f64());
         }
         
+        EditAction::Apply { script, output } => {
+            let edits = parse_edit_script(script, &headers)?;
+            println!("   编辑脚本: {} ({} 处修改)", script, edits.len());
+            
+            let rejected = editor.apply_edits(edits.clone());
+            for (edit, e) in &rejected {
+                eprintln!("   ⚠️  跳过 行 {}, 列 {}: {}", edit.row + 1, edit.col + 1, e);
+            }
+            let applied = edits.len() - rejected.len();
+            if applied == 0 {
+                println!("\n📭 没有可应用的修改，文件未改动");
+                return Ok(());
+            }
+            
+            let output_path = output.as_deref().unwrap_or(&args.file);
+            let options = WriteOptions::new().with_delimiter(args.delimiter());
+            
+            let pb = create_spinner("正在保存...");
+            let stats = if output.is_some() {
+                editor.save(output_path, &options)?
+            } else {
+                editor.save_in_place(&options)?
+            };
+            pb.finish_and_clear();
+            
+            let duration = start_time.elapsed();
+            println!("\n✅ 批量编辑完成!");
+            println!("   应用修改: {} 处", applied);
+            if !rejected.is_empty() {
+                println!("   跳过修改: {} 处（超出范围或行已删除）", rejected.len());
+            }
+            println!("   写入行数: {} 行", stats.rows_written);
+            println!("   输出文件: {}", stats.file_path);
+            println!("   耗时:     {:.2}秒", duration.as_secs_f64());
+        }
+        
         EditAction::DeleteRow { rows, output } => {
             let row_nums: Vec<usize> = rows
                 .split(',')
@@ -1277,7 +2927,7 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
             }
             
             let output_path = output.as_deref().unwrap_or(&args.file);
-            let options = WriteOptions::new().with_delimiter(args.delimiter as u8);
+            let options = WriteOptions::new().with_delimiter(args.delimiter());
             
             let pb = create_spinner("正在保存...");
             let stats = if output.is_some() {
@@ -1308,7 +2958,7 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
             }
             
             let output_path = output.as_deref().unwrap_or(&args.file);
-            let options = WriteOptions::new().with_delimiter(args.delimiter as u8);
+            let options = WriteOptions::new().with_delimiter(args.delimiter());
             
             let pb = create_spinner("正在保存...");
             let stats = if output.is_some() {
@@ -1343,7 +2993,7 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
             }
             
             let output_path = output.as_deref().unwrap_or(&args.file);
-            let options = WriteOptions::new().with_delimiter(args.delimiter as u8);
+            let options = WriteOptions::new().with_delimiter(args.delimiter());
             
             let pb = create_spinner("正在保存...");
             let stats = if output.is_some() {
@@ -1369,7 +3019,7 @@ fn cmd_edit(args: &Args, action: &EditAction) -> Result<()> {
             editor.set_header(col_idx, name.clone())?;
             
             let output_path = output.as_deref().unwrap_or(&args.file);
-            let options = WriteOptions::new().with_delimiter(args.delimiter as u8);
+            let options = WriteOptions::new().with_delimiter(args.delimiter());
             
             let pb = create_spinner("正在保存...");
             let stats = if output.is_some() {
@@ -1394,7 +3044,7 @@ fn cmd_create(
     output: &str,
     headers_str: &str,
     rows: &[String],
-    delimiter: u8,
+    delimiter: Delimiter,
 ) -> Result<()> {
     let start_time = Instant::now();
     
@@ -1441,3 +3091,57 @@ fn cmd_create(
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_layout_wide_chars() {
+        let headers = vec!["城市".to_string(), "人口".to_string(), "描述".to_string()];
+        let rows = vec![
+            vec!["北京", "2189", "中国的首都"],
+            vec!["Shanghai", "2487", "a very long description"],
+        ];
+        let layout = TableLayout::compute(&headers, &rows, 10);
+
+        let lines = vec![
+            layout.border("┌", "┬", "┐"),
+            layout.header_line(&headers),
+            layout.border("├", "┼", "┤"),
+            layout.row_line(&rows[0]),
+            layout.row_line(&rows[1]),
+            layout.border("└", "┴", "┘"),
+        ];
+        assert_eq!(lines, vec![
+            "┌──────────┬──────┬────────────┐",
+            "│   城市   │ 人口 │    描述    │",
+            "├──────────┼──────┼────────────┤",
+            "│ 北京     │ 2189 │ 中国的首都 │",
+            "│ Shanghai │ 2487 │ a very lo… │",
+            "└──────────┴──────┴────────────┘",
+        ]);
+    }
+
+    #[test]
+    fn test_table_layout_highlighted_cells() {
+        let headers = vec!["名称".to_string(), "备注".to_string()];
+        let rows = vec![vec!["上海浦东", "匹配的内容在很后面的位置"]];
+        let layout = TableLayout::compute(&headers, &rows, 8);
+
+        // 高亮在截断之前完成，被截断的单元格也保留完整的转义序列
+        let line = layout.row_line_styled(&rows[0], |_, text| {
+            highlight_matches(text, &[(0, text.len().min(6))], &HighlightStyle::default())
+        });
+        assert_eq!(
+            line,
+            "│ \x1b[1;33m上海\x1b[0m浦东 │ \x1b[1;33m匹配\x1b[0m的…  │"
+        );
+
+        // 各行的显示宽度一致，边框对齐
+        let plain = layout.row_line(&rows[0]);
+        assert_eq!(visible_width(&line), visible_width(&plain));
+        assert_eq!(visible_width(&line), visible_width(&layout.border("┌", "┬", "┐")));
+        assert_eq!(visible_width(&layout.header_line(&headers)), visible_width(&plain));
+    }
+}
@@ -0,0 +1,353 @@
+//! 交互式分页浏览
+//!
+//! 翻页逻辑（[`PagerState`]）与终端无关，可以单独测试；
+//! 终端的读取按键和绘制部分需要启用 `interactive` 特性
+
+/// 分页器按键（与终端库无关）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// 字符键
+    Char(char),
+    Enter,
+    Backspace,
+    Esc,
+    /// 方向键
+    Left,
+    Right,
+    Up,
+    Down,
+    /// 翻页键
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+/// 处理按键后需要执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// 重新绘制（页码或输入的跳转页码有变化）
+    Redraw,
+    /// 无需处理
+    Ignore,
+    /// 退出分页器
+    Quit,
+}
+
+/// 分页状态：当前页和正在输入的跳转页码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PagerState {
+    /// 当前页（从0开始）
+    page: usize,
+    total_pages: usize,
+    /// 输入中的跳转页码（从1开始）
+    input: String,
+}
+
+impl PagerState {
+    /// 创建分页状态，`page` 从0开始，超出范围时定位到最后一页
+    pub fn new(page: usize, total_pages: usize) -> Self {
+        Self {
+            page: page.min(total_pages.saturating_sub(1)),
+            total_pages,
+            input: String::new(),
+        }
+    }
+
+    /// 当前页（从0开始）
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// 总页数
+    pub fn total_pages(&self) -> usize {
+        self.total_pages
+    }
+
+    /// 输入中的跳转页码
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// 处理一次按键
+    ///
+    /// - `n` / 空格 / `j` / → / ↓ / PageDown：下一页
+    /// - `p` / `b` / `k` / ← / ↑ / PageUp：上一页
+    /// - `g` / Home：第一页，`G` / End：最后一页
+    /// - 数字后按回车：跳转到指定页，Backspace删除、Esc取消输入
+    /// - `q` / Esc：退出
+    pub fn handle_key(&mut self, key: Key) -> Step {
+        match key {
+            Key::Char(c) if c.is_ascii_digit() => {
+                // 页码不会超过总页数的位数，多余的输入直接忽略
+                if self.input.len() >= self.total_pages.to_string().len() {
+                    return Step::Ignore;
+                }
+                self.input.push(c);
+                Step::Redraw
+            }
+            Key::Backspace => {
+                if self.input.pop().is_some() {
+                    Step::Redraw
+                } else {
+                    Step::Ignore
+                }
+            }
+            Key::Enter if !self.input.is_empty() => {
+                let target = std::mem::take(&mut self.input).parse::<usize>().unwrap_or(1);
+                self.goto(target.saturating_sub(1));
+                Step::Redraw
+            }
+            Key::Esc if !self.input.is_empty() => {
+                self.input.clear();
+                Step::Redraw
+            }
+            Key::Char('q') | Key::Esc => Step::Quit,
+            Key::Char('n' | ' ' | 'j') | Key::Enter | Key::Right | Key::Down | Key::PageDown => {
+                self.goto(self.page.saturating_add(1))
+            }
+            Key::Char('p' | 'b' | 'k') | Key::Left | Key::Up | Key::PageUp => {
+                self.goto(self.page.saturating_sub(1))
+            }
+            Key::Char('g') | Key::Home => self.goto(0),
+            Key::Char('G') | Key::End => self.goto(usize::MAX),
+            _ => Step::Ignore,
+        }
+    }
+
+    /// 跳转到指定页（超出范围时停在首页或末页）
+    fn goto(&mut self, page: usize) -> Step {
+        let page = page.min(self.total_pages.saturating_sub(1));
+        if page == self.page {
+            Step::Ignore
+        } else {
+            self.page = page;
+            Step::Redraw
+        }
+    }
+}
+
+/// 按终端宽度计算单列最大显示宽度，每列额外占用两侧空格和一条边框
+pub fn fit_col_width(term_width: usize, col_count: usize, max_col_width: usize) -> usize {
+    if col_count == 0 {
+        return max_col_width;
+    }
+    let available = term_width.saturating_sub(3 * col_count + 1) / col_count;
+    available.min(max_col_width)
+}
+
+#[cfg(feature = "interactive")]
+pub use terminal::run;
+
+#[cfg(feature = "interactive")]
+mod terminal {
+    use super::{fit_col_width, Key, PagerState, Step};
+    use crate::{render_table, Args};
+    use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+    use crossterm::{cursor, execute, queue, terminal};
+    use csv_tool::csv::CsvReader;
+    use csv_tool::error::Result;
+    use std::io::{self, Write};
+
+    /// 恢复终端状态（离开备用屏幕、关闭原始模式），出错或panic时同样执行
+    struct TerminalGuard;
+
+    impl TerminalGuard {
+        fn enter() -> io::Result<Self> {
+            terminal::enable_raw_mode()?;
+            if let Err(e) = execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide) {
+                terminal::disable_raw_mode().ok();
+                return Err(e);
+            }
+            Ok(Self)
+        }
+    }
+
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen).ok();
+            terminal::disable_raw_mode().ok();
+        }
+    }
+
+    /// 把终端按键转换为分页器按键，Ctrl+C 视为退出
+    fn convert_key(event: KeyEvent) -> Option<Key> {
+        if event.kind == KeyEventKind::Release {
+            return None;
+        }
+        if event.modifiers.contains(KeyModifiers::CONTROL) && event.code == KeyCode::Char('c') {
+            return Some(Key::Char('q'));
+        }
+        Some(match event.code {
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            _ => return None,
+        })
+    }
+
+    /// 进入交互式分页浏览，`page` 为起始页（从0开始）
+    ///
+    /// 按需读取当前页，终端大小变化时按新的宽度重新计算列宽
+    pub fn run(args: &Args, reader: &CsvReader, page: usize, columns: Option<&[usize]>) -> Result<()> {
+        let total_pages = reader.total_pages(args.page_size);
+        let headers = &reader.info().headers;
+        let col_count = match columns {
+            Some(cols) => cols.len(),
+            None => reader.info().total_cols,
+        };
+
+        let mut state = PagerState::new(page, total_pages);
+        let mut rows = reader.read_page(state.page(), args.page_size)?;
+        let mut loaded_page = state.page();
+
+        let _guard = TerminalGuard::enter()?;
+        let mut stdout = io::stdout();
+        loop {
+            if state.page() != loaded_page {
+                rows = reader.read_page(state.page(), args.page_size)?;
+                loaded_page = state.page();
+            }
+
+            let (term_width, _) = terminal::size()?;
+            let max_col_width = fit_col_width(term_width as usize, col_count, args.max_col_width);
+            let lines = if rows.is_empty() {
+                vec!["📭 没有数据行".to_string()]
+            } else {
+                render_table(headers, &rows, max_col_width, columns)
+            };
+
+            queue!(stdout, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+            for line in &lines {
+                write!(stdout, "{}\r\n", line)?;
+            }
+            let start_row = state.page() * args.page_size + 1;
+            let end_row = start_row + rows.len().saturating_sub(1);
+            write!(
+                stdout,
+                "📖 第 {}/{} 页 (行 {}-{})\r\n",
+                state.page() + 1,
+                state.total_pages().max(1),
+                start_row,
+                end_row
+            )?;
+            if state.input().is_empty() {
+                write!(stdout, "n/→ 下一页  p/← 上一页  g/G 首页/末页  页码+回车 跳转  q 退出")?;
+            } else {
+                write!(stdout, "跳转到第 {} 页（回车确认，Esc取消）", state.input())?;
+            }
+            stdout.flush()?;
+
+            // 等待下一个需要重绘的事件
+            loop {
+                let step = match event::read()? {
+                    Event::Key(key) => convert_key(key).map_or(Step::Ignore, |key| state.handle_key(key)),
+                    Event::Resize(..) => Step::Redraw,
+                    _ => Step::Ignore,
+                };
+                match step {
+                    Step::Redraw => break,
+                    Step::Ignore => continue,
+                    Step::Quit => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_navigation() {
+        let mut state = PagerState::new(0, 5);
+
+        // 第一页时不能再向前翻
+        assert_eq!(state.handle_key(Key::Left), Step::Ignore);
+        assert_eq!(state.handle_key(Key::Char('n')), Step::Redraw);
+        assert_eq!(state.handle_key(Key::PageDown), Step::Redraw);
+        assert_eq!(state.page(), 2);
+        assert_eq!(state.handle_key(Key::Char('p')), Step::Redraw);
+        assert_eq!(state.page(), 1);
+
+        // 首页、末页，最后一页时不能再向后翻
+        assert_eq!(state.handle_key(Key::Char('G')), Step::Redraw);
+        assert_eq!(state.page(), 4);
+        assert_eq!(state.handle_key(Key::Right), Step::Ignore);
+        assert_eq!(state.handle_key(Key::Home), Step::Redraw);
+        assert_eq!(state.page(), 0);
+
+        assert_eq!(state.handle_key(Key::Char('x')), Step::Ignore);
+        assert_eq!(state.handle_key(Key::Char('q')), Step::Quit);
+    }
+
+    #[test]
+    fn test_jump_to_page() {
+        let mut state = PagerState::new(0, 120);
+
+        assert_eq!(state.handle_key(Key::Char('4')), Step::Redraw);
+        assert_eq!(state.handle_key(Key::Char('2')), Step::Redraw);
+        assert_eq!(state.input(), "42");
+        assert_eq!(state.handle_key(Key::Enter), Step::Redraw);
+        assert_eq!(state.page(), 41);
+        assert_eq!(state.input(), "");
+
+        // 超出范围时跳到最后一页，0跳到第一页
+        for c in "999".chars() {
+            state.handle_key(Key::Char(c));
+        }
+        // 页码位数不超过总页数的位数
+        assert_eq!(state.handle_key(Key::Char('9')), Step::Ignore);
+        state.handle_key(Key::Enter);
+        assert_eq!(state.page(), 119);
+        state.handle_key(Key::Char('0'));
+        state.handle_key(Key::Enter);
+        assert_eq!(state.page(), 0);
+
+        // Backspace删除输入，Esc取消输入而不是退出
+        state.handle_key(Key::Char('1'));
+        state.handle_key(Key::Char('5'));
+        assert_eq!(state.handle_key(Key::Backspace), Step::Redraw);
+        assert_eq!(state.input(), "1");
+        assert_eq!(state.handle_key(Key::Esc), Step::Redraw);
+        assert_eq!(state.input(), "");
+        assert_eq!(state.page(), 0);
+        assert_eq!(state.handle_key(Key::Backspace), Step::Ignore);
+
+        // 没有输入时回车翻到下一页，Esc退出
+        assert_eq!(state.handle_key(Key::Enter), Step::Redraw);
+        assert_eq!(state.page(), 1);
+        assert_eq!(state.handle_key(Key::Esc), Step::Quit);
+    }
+
+    #[test]
+    fn test_empty_and_out_of_range() {
+        let mut state = PagerState::new(10, 3);
+        assert_eq!(state.page(), 2);
+
+        let mut empty = PagerState::new(0, 0);
+        assert_eq!(empty.page(), 0);
+        assert_eq!(empty.handle_key(Key::Char('n')), Step::Ignore);
+        assert_eq!(empty.handle_key(Key::Char('G')), Step::Ignore);
+        assert_eq!(state.handle_key(Key::Char('g')), Step::Redraw);
+    }
+
+    #[test]
+    fn test_fit_col_width() {
+        // 80列宽的终端放4列：(80 - 13) / 4 = 16
+        assert_eq!(fit_col_width(80, 4, 30), 16);
+        assert_eq!(fit_col_width(200, 4, 30), 30);
+        assert_eq!(fit_col_width(10, 4, 30), 0);
+        assert_eq!(fit_col_width(80, 0, 30), 30);
+    }
+}
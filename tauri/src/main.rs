@@ -1,14 +1,21 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use csv_tool::csv::CsvReader;
-use memchr::memchr;
+use csv_tool::csv::{
+    parse_preview, sort_csv_data, AccessPattern, ColumnStats, CsvEditor, CsvReader, DataType, Delimiter, ExportFormat, ExportOptions,
+    Exporter, IndexBuildHandle, OpenFileManager, RowData, SearchCursor, SearchOptions, SearchPattern, SortKey,
+    SortOptions, SortOrder, WriteOptions,
+};
 use memmap2::MmapOptions;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::sync::{LazyLock, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
 
+/// Large files open before their row index is complete, so `total_rows` starts out as an
+/// estimate: while `is_exact` is false the frontend should treat the last page as approximate
+/// and poll `refresh_file_info` until the background index build finishes
 #[derive(Debug, Serialize, Deserialize)]
 struct CsvFileInfo {
     file_path: String,
@@ -16,6 +23,19 @@ struct CsvFileInfo {
     total_rows: usize,
     total_cols: usize,
     headers: Vec<String>,
+    /// Delimiter the file was opened with, e.g. "," or "\t"
+    delimiter: String,
+    /// Detected line ending: "LF", "CRLF" or "CR"
+    line_ending: String,
+    /// Detected text encoding, e.g. "UTF-8"
+    encoding: String,
+    /// True once `total_rows` comes from a complete index
+    is_exact: bool,
+    /// Likely range of the real row count; both equal `total_rows` when exact
+    rows_lower_bound: usize,
+    rows_upper_bound: usize,
+    /// Background index build progress (0-100)
+    index_progress: f64,
 }
 
 /// Quick preview result - returns immediately without building index
@@ -36,13 +56,241 @@ struct CsvRow {
 #[derive(Debug, Serialize, Deserialize)]
 struct PageData {
     rows: Vec<CsvRow>,
+    /// The page actually read, clamped to the last page
     page: usize,
     total_pages: usize,
     page_size: usize,
+    /// False while `total_pages` is based on an estimated row count
+    is_exact: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct MatchData {
+    column: usize,
+    /// Byte ranges (start, end) of the matches within the field
+    positions: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchHit {
+    row_number: usize,
+    fields: Vec<String>,
+    matches: Vec<MatchData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchResponse {
+    results: Vec<SearchHit>,
+    /// True when the result cap was reached and more matches may exist
+    truncated: bool,
+}
+
+/// Upper bound on results returned by one search, to keep the IPC payload bounded
+const MAX_SEARCH_RESULTS: usize = 10_000;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchRow {
+    row_number: usize,
+    fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchPageData {
+    rows: Vec<SearchRow>,
+    /// Total match count, only known once the scan reached the end of the file
+    total: Option<usize>,
+    /// Pass back to fetch the next page without rescanning; None at the end of the file
+    cursor: Option<SearchCursor>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportStatsData {
+    rows_exported: usize,
+    cols_exported: usize,
+    file_size: u64,
+}
+
+/// Payload of the `export-progress` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportProgress {
+    export_id: String,
+    percent: f64,
+    rows_written: u64,
+    total_rows: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SortInfo {
+    total_rows: usize,
+    /// Memory held by the cached permutation (one usize per row)
+    permutation_bytes: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SortedPageData {
+    rows: Vec<SearchRow>,
+    page: usize,
+    total_pages: usize,
+    page_size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChangeStatsData {
+    cells_edited: usize,
+    rows_deleted: usize,
+    rows_inserted: usize,
+    rows_appended: usize,
+    effective_rows: usize,
+    has_changes: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveResult {
+    rows_written: usize,
+    bytes_written: usize,
+    file_path: String,
+    /// Info of the reopened file when it was saved in place
+    file_info: Option<CsvFileInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NumericSummary {
+    min: f64,
+    max: f64,
+    mean: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ValueCount {
+    value: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ColumnStatsData {
+    column: usize,
+    rows: usize,
+    null_count: usize,
+    distinct_count: usize,
+    /// Present when every non-empty value is a number
+    numeric: Option<NumericSummary>,
+    /// Most frequent values, only for non-numeric columns
+    top_values: Vec<ValueCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UniqueValues {
+    values: Vec<ValueCount>,
+    total_distinct: usize,
+    /// True when `limit` cut off some values
+    truncated: bool,
+}
+
+/// Number of most frequent values reported by `column_stats`
+const TOP_VALUES: usize = 10;
+
 // Global storage for open CSV readers - using LazyLock for Rust 1.80+
-static READERS: LazyLock<Mutex<HashMap<String, CsvReader>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+// Readers are shared via Arc: the map lock is only held while looking a reader up,
+// so reads of the same or different files run concurrently
+// At most `DEFAULT_MAX_OPEN_FILES` files stay open; the least recently used one is closed
+// when another is opened, except files with pending edits
+static READERS: LazyLock<Mutex<OpenFileManager>> = LazyLock::new(|| Mutex::new(OpenFileManager::default()));
+
+// Background builds of the full row index for files opened before their index was complete
+static INDEX_BUILDS: LazyLock<Mutex<HashMap<String, IndexBuildHandle>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Cancellation flags of running exports, keyed by the export id chosen by the frontend
+static EXPORTS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Sorted views: permutation of original row numbers in display order, keyed by file path
+static SORTS: LazyLock<Mutex<HashMap<String, Arc<Vec<usize>>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Pending edits of files opened for editing, keyed by file path
+static EDITORS: LazyLock<Mutex<HashMap<String, CsvEditor>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Column statistics keyed by (file path, column), kept until the file is reopened or closed
+static COLUMN_STATS: LazyLock<Mutex<HashMap<(String, usize), Arc<ColumnStats>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Cancellation flags of running column scans, keyed by (file path, column)
+static COLUMN_SCANS: LazyLock<Mutex<HashMap<(String, usize), Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Drop everything cached for a file, e.g. when it is reopened or closed
+fn clear_file_caches(file_path: &str) {
+    SORTS.lock().unwrap().remove(file_path);
+    COLUMN_STATS.lock().unwrap().retain(|(path, _), _| path != file_path);
+}
+
+/// Drop all state kept for a file that is no longer open
+fn release_file(file_path: &str) {
+    stop_index_build(file_path);
+    clear_file_caches(file_path);
+    EDITORS.lock().unwrap().remove(file_path);
+}
+
+/// Store an opened reader, continuing its row index in the background when it is incomplete
+/// Files closed to stay within the open file limit are released
+fn store_reader(file_path: String, mut reader: CsvReader) {
+    if reader.is_index_complete() {
+        INDEX_BUILDS.lock().unwrap().remove(&file_path);
+    } else {
+        INDEX_BUILDS.lock().unwrap().insert(file_path.clone(), reader.build_index_async());
+    }
+    let editing = EDITORS.lock().unwrap().contains_key(&file_path);
+    let evicted = {
+        let mut readers = READERS.lock().unwrap();
+        let evicted = readers.insert(file_path.clone(), Arc::new(reader));
+        readers.set_pinned(&file_path, editing);
+        evicted
+    };
+    for path in evicted {
+        release_file(&path);
+    }
+}
+
+/// Cancel a running background index build and wait for it to release the file
+fn stop_index_build(file_path: &str) {
+    let handle = INDEX_BUILDS.lock().unwrap().remove(file_path);
+    if let Some(handle) = handle {
+        handle.cancel();
+        handle.wait();
+    }
+}
+
+/// Swap in the complete index once the background build has finished
+/// Skipped while another command still holds the reader; the next call picks it up
+fn apply_finished_index(file_path: &str) {
+    let mut builds = INDEX_BUILDS.lock().unwrap();
+    if !builds.get(file_path).is_some_and(|handle| handle.is_finished()) {
+        return;
+    }
+    let mut readers = READERS.lock().unwrap();
+    let Some(reader) = readers.get_mut(file_path).and_then(Arc::get_mut) else {
+        return;
+    };
+    if let Some((index, true)) = builds.remove(file_path).and_then(|handle| handle.wait()) {
+        reader.update_index(index);
+    }
+}
+
+/// Parse the delimiter sent by the frontend the same way the CLI `-d` option does
+/// Missing or empty input means a comma; non-ASCII input is an error rather than being cut to its first byte
+fn parse_delimiter(delimiter: Option<&str>) -> std::result::Result<Delimiter, String> {
+    match delimiter.filter(|d| !d.is_empty()) {
+        Some(d) => Delimiter::parse(d).map_err(|e| format!("Invalid delimiter: {}", e)),
+        None => Ok(Delimiter::default()),
+    }
+}
+
+/// Look up an open reader without keeping the map locked
+fn get_reader(file_path: &str) -> std::result::Result<Arc<CsvReader>, String> {
+    READERS
+        .lock()
+        .unwrap()
+        .get(file_path)
+        .ok_or_else(|| "File not opened".to_string())
+}
 
 #[tauri::command]
 fn open_csv_file(
@@ -51,10 +299,7 @@ fn open_csv_file(
     delimiter: Option<String>,
     index_granularity: Option<usize>,
 ) -> std::result::Result<CsvFileInfo, String> {
-    let delimiter_byte = delimiter
-        .as_ref()
-        .and_then(|d| d.as_bytes().first().copied())
-        .unwrap_or(b',');
+    let delimiter = parse_delimiter(delimiter.as_deref())?;
     
     // Dynamic index granularity based on file size
     // Larger files use coarser index to speed up initial loading
@@ -74,24 +319,30 @@ fn open_csv_file(
         }
     });
 
+    // Opening a file again with the same options keeps its reader and everything cached for it
+    let reused = READERS
+        .lock()
+        .unwrap()
+        .find(&file_path, has_headers, &delimiter, granularity)
+        .is_some();
+    if reused {
+        apply_finished_index(&file_path);
+        return Ok(file_info(&get_reader(&file_path)?));
+    }
+
     // 使用 open_fast 实现毫秒级响应
-    let reader = CsvReader::open_fast(&file_path, has_headers, delimiter_byte, granularity)
+    let reader = CsvReader::open_fast(&file_path, has_headers, delimiter, granularity)
         .map_err(|e| format!("Failed to open file: {}", e))?;
+    // The viewer jumps between pages, so skip kernel readahead between reads
+    reader.set_access_pattern(AccessPattern::Random);
 
-    let info = reader.info();
-    let file_info = CsvFileInfo {
-        file_path: info.file_path.to_string_lossy().to_string(),
-        file_size: info.file_size,
-        total_rows: info.total_rows,
-        total_cols: info.total_cols,
-        headers: info.headers.clone(),
-    };
+    let info = file_info(&reader);
 
-    // Store the reader
-    let mut readers = READERS.lock().unwrap();
-    readers.insert(file_path.clone(), reader);
+    // Store the reader; a sort order cached for a previous open of the file no longer applies
+    clear_file_caches(&file_path);
+    store_reader(file_path, reader);
 
-    Ok(file_info)
+    Ok(info)
 }
 
 #[tauri::command]
@@ -100,12 +351,11 @@ fn read_page(
     page: usize,
     page_size: usize,
 ) -> std::result::Result<PageData, String> {
-    let mut readers = READERS.lock().unwrap();
-    let reader = readers
-        .get_mut(&file_path)
-        .ok_or_else(|| "File not opened".to_string())?;
+    apply_finished_index(&file_path);
+    let reader = get_reader(&file_path)?;
 
     let total_pages = reader.total_pages(page_size);
+    let page = page.min(total_pages.saturating_sub(1));
     let rows = reader
         .read_page(page, page_size)
         .map_err(|e| format!("Failed to read page: {}", e))?;
@@ -122,9 +372,163 @@ fn read_page(
         page,
         total_pages,
         page_size,
+        is_exact: reader.is_row_count_exact(),
     })
 }
 
+/// Build search options the same way the CLI search command does
+fn build_search_options(
+    pattern: &str,
+    is_regex: bool,
+    ignore_case: bool,
+    column: Option<usize>,
+    invert: bool,
+) -> std::result::Result<SearchOptions, String> {
+    let search_pattern = if is_regex {
+        SearchPattern::regex(pattern, !ignore_case).map_err(|e| format!("Invalid regex: {}", e))?
+    } else {
+        SearchPattern::text(pattern, !ignore_case)
+    };
+
+    let mut options = SearchOptions::new(search_pattern)
+        .with_case_sensitive(!ignore_case)
+        .with_invert_match(invert);
+    if let Some(column) = column {
+        options = options.with_columns(vec![column]);
+    }
+    Ok(options)
+}
+
+/// Search an open file, returning at most `max_results` (capped at MAX_SEARCH_RESULTS) matches
+/// `column` is a 0-based column index; the whole row is searched when omitted
+#[tauri::command]
+fn search_csv(
+    file_path: String,
+    pattern: String,
+    is_regex: bool,
+    ignore_case: bool,
+    column: Option<usize>,
+    max_results: Option<usize>,
+    invert: bool,
+) -> std::result::Result<SearchResponse, String> {
+    let reader = get_reader(&file_path)?;
+    let limit = max_results.unwrap_or(MAX_SEARCH_RESULTS).min(MAX_SEARCH_RESULTS);
+    let options = build_search_options(&pattern, is_regex, ignore_case, column, invert)?
+        .with_max_results(limit);
+
+    let results = reader
+        .search(&options)
+        .map_err(|e| format!("Failed to search: {}", e))?;
+
+    let truncated = results.len() >= limit;
+    let results = results
+        .into_iter()
+        .map(|result| SearchHit {
+            row_number: result.row_number,
+            fields: result.record.fields.iter().map(|f| f.to_string()).collect(),
+            matches: result
+                .matches
+                .into_iter()
+                .map(|m| MatchData {
+                    column: m.column,
+                    positions: m.positions,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(SearchResponse { results, truncated })
+}
+
+/// Count matching rows for the "N matches" badge
+/// With `limit` the scan stops after that many matches and returns `limit`, i.e. "at least N"
+#[tauri::command]
+fn count_matches(
+    file_path: String,
+    pattern: String,
+    is_regex: bool,
+    ignore_case: bool,
+    column: Option<usize>,
+    invert: bool,
+    limit: Option<usize>,
+) -> std::result::Result<usize, String> {
+    let reader = get_reader(&file_path)?;
+    let mut options = build_search_options(&pattern, is_regex, ignore_case, column, invert)?;
+    if let Some(limit) = limit {
+        options = options.with_max_results(limit);
+    }
+
+    reader
+        .count_matches(&options)
+        .map_err(|e| format!("Failed to count matches: {}", e))
+}
+
+/// Page through search results
+/// `offset` matches after the cursor (or the start of the file) are skipped, then up to `limit` are returned
+#[tauri::command]
+fn search_page(
+    file_path: String,
+    pattern: String,
+    is_regex: bool,
+    ignore_case: bool,
+    offset: usize,
+    limit: usize,
+    cursor: Option<SearchCursor>,
+) -> std::result::Result<SearchPageData, String> {
+    let reader = get_reader(&file_path)?;
+    let options = build_search_options(&pattern, is_regex, ignore_case, None, false)?;
+
+    let page = reader
+        .search_page_from(&options, cursor.as_ref(), offset, limit)
+        .map_err(|e| format!("Failed to search: {}", e))?;
+
+    let rows = page
+        .results
+        .into_iter()
+        .map(|result| SearchRow {
+            row_number: result.row_number,
+            fields: result.record.fields.iter().map(|f| f.to_string()).collect(),
+        })
+        .collect();
+
+    Ok(SearchPageData {
+        rows,
+        total: page.total,
+        cursor: page.cursor,
+    })
+}
+
+/// Find the first match at or after `start_row` without scanning earlier rows ("find next")
+#[tauri::command]
+fn find_next(
+    file_path: String,
+    pattern: String,
+    is_regex: bool,
+    ignore_case: bool,
+    column: Option<usize>,
+    start_row: usize,
+) -> std::result::Result<Option<SearchHit>, String> {
+    let reader = get_reader(&file_path)?;
+    let options = build_search_options(&pattern, is_regex, ignore_case, column, false)?;
+
+    let result = reader
+        .search_from(&options, start_row)
+        .map_err(|e| format!("Failed to search: {}", e))?;
+
+    Ok(result.map(|result| SearchHit {
+        row_number: result.row_number,
+        fields: result.record.fields.iter().map(|f| f.to_string()).collect(),
+        matches: result
+            .matches
+            .into_iter()
+            .map(|m| MatchData {
+                column: m.column,
+                positions: m.positions,
+            })
+            .collect(),
+    }))
+}
+
 /// Quick preview - read first N rows without building index
 /// This allows instant display of large files while index builds in background
 #[tauri::command]
@@ -133,10 +537,7 @@ fn quick_preview(
     preview_rows: usize,
     delimiter: Option<String>,
 ) -> std::result::Result<QuickPreview, String> {
-    let delimiter_byte = delimiter
-        .as_ref()
-        .and_then(|d| d.as_bytes().first().copied())
-        .unwrap_or(b',');
+    let delimiter = parse_delimiter(delimiter.as_deref())?;
 
     let file = File::open(&file_path)
         .map_err(|e| format!("Failed to open file: {}", e))?;
@@ -148,51 +549,29 @@ fn quick_preview(
     let mmap = unsafe { MmapOptions::new().map(&file) }
         .map_err(|e| format!("Failed to mmap file: {}", e))?;
 
-    // Skip BOM if present
-    let start = if mmap.len() >= 3 && &mmap[0..3] == b"\xEF\xBB\xBF" { 3 } else { 0 };
+    // Parsed exactly like the paged view so the preview shows the same data
+    let preview = parse_preview(&mmap, preview_rows, &delimiter, true);
+    let line_count = preview.rows.len();
+    let rows = preview
+        .rows
+        .iter()
+        .map(|record| CsvRow {
+            fields: record.fields.iter().map(|f| f.to_string()).collect(),
+        })
+        .collect();
 
-    // Read headers
-    let header_end = memchr(b'\n', &mmap[start..])
-        .map(|p| start + p)
-        .unwrap_or(mmap.len());
-    
-    let header_line = &mmap[start..header_end];
-    let headers = parse_csv_line(header_line, delimiter_byte);
-    
-    // Read preview rows
-    let mut rows = Vec::with_capacity(preview_rows);
-    let mut current_pos = header_end + 1;
-    let mut line_count = 0;
-    
-    while current_pos < mmap.len() && line_count < preview_rows {
-        let remaining = &mmap[current_pos..];
-        let line_end = memchr(b'\n', remaining)
-            .map(|p| current_pos + p)
-            .unwrap_or(mmap.len());
-        
-        if line_end > current_pos {
-            let line = &mmap[current_pos..line_end];
-            let fields = parse_csv_line(line, delimiter_byte);
-            rows.push(CsvRow { fields });
-            line_count += 1;
-        }
-        current_pos = line_end + 1;
-    }
-    
     // Estimate total rows for large files
-    let (estimated_rows, is_complete) = if current_pos >= mmap.len() {
+    let (estimated_rows, is_complete) = if preview.is_complete() {
         // We read the entire file
         (line_count, true)
     } else {
         // Estimate based on average row size
-        let bytes_read = current_pos - start;
-        let avg_row_size = bytes_read as f64 / (line_count + 1) as f64;  // +1 for header
-        let estimated = ((mmap.len() - start) as f64 / avg_row_size) as usize;
-        (estimated.saturating_sub(1), false)  // -1 to exclude header
+        let avg_row_size = preview.bytes_read as f64 / line_count.max(1) as f64;
+        ((preview.data_bytes as f64 / avg_row_size) as usize, false)
     };
-    
+
     Ok(QuickPreview {
-        headers,
+        headers: preview.headers,
         rows,
         file_size,
         estimated_rows,
@@ -200,64 +579,538 @@ fn quick_preview(
     })
 }
 
-/// Parse a single CSV line into fields
-fn parse_csv_line(line: &[u8], delimiter: u8) -> Vec<String> {
-    // Strip trailing \r for Windows CRLF
-    let line = if !line.is_empty() && line[line.len() - 1] == b'\r' {
-        &line[..line.len() - 1]
-    } else {
-        line
-    };
-    
-    let mut fields = Vec::new();
-    let mut start = 0;
-    let mut in_quotes = false;
-    
-    for (i, &byte) in line.iter().enumerate() {
-        match byte {
-            b'"' => in_quotes = !in_quotes,
-            _ if byte == delimiter && !in_quotes => {
-                let field = String::from_utf8_lossy(&line[start..i]).to_string();
-                fields.push(field.trim_matches('"').to_string());
-                start = i + 1;
+/// Parse an export format name the same way the CLI `--format` option does
+fn parse_export_format(format: &str) -> std::result::Result<ExportFormat, String> {
+    match format.to_lowercase().as_str() {
+        "json" => Ok(ExportFormat::Json),
+        "jsonl" | "ndjson" => Ok(ExportFormat::JsonLines),
+        "csv" => Ok(ExportFormat::Csv),
+        "tsv" => Ok(ExportFormat::Tsv),
+        "db" | "sqlite" => Ok(ExportFormat::Sqlite),
+        _ => Err(format!("Invalid export format: {}", format)),
+    }
+}
+
+/// Export an open file on a blocking task
+/// Emits `export-progress` events while writing; `cancel_export(export_id)` stops it
+/// and removes the partially written output
+#[tauri::command]
+async fn export_csv(
+    window: tauri::Window,
+    export_id: String,
+    file_path: String,
+    output_path: String,
+    format: String,
+    columns: Option<Vec<usize>>,
+    from_row: Option<usize>,
+    to_row: Option<usize>,
+    pretty: bool,
+) -> std::result::Result<ExportStatsData, String> {
+    let reader = get_reader(&file_path)?;
+    let format = parse_export_format(&format)?;
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    EXPORTS
+        .lock()
+        .unwrap()
+        .insert(export_id.clone(), Arc::clone(&cancel_flag));
+
+    let progress_id = export_id.clone();
+    let mut options = ExportOptions::new(format)
+        .with_pretty(pretty)
+        .with_cancel_flag(Arc::clone(&cancel_flag))
+        .with_progress(Arc::new(move |rows_written, total_rows| {
+            let percent = if total_rows == 0 {
+                100.0
+            } else {
+                rows_written as f64 / total_rows as f64 * 100.0
+            };
+            let _ = window.emit(
+                "export-progress",
+                ExportProgress {
+                    export_id: progress_id.clone(),
+                    percent,
+                    rows_written,
+                    total_rows,
+                },
+            );
+        }));
+    if let Some(columns) = columns {
+        options = options.with_columns(columns);
+    }
+    if from_row.is_some() || to_row.is_some() {
+        let total_rows = reader.info().total_rows;
+        options = options.with_row_range(from_row.unwrap_or(0), to_row.unwrap_or(total_rows));
+    }
+
+    let task_output = output_path.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        Exporter::new(&reader, options).export_to_file(&task_output)
+    })
+    .await;
+    EXPORTS.lock().unwrap().remove(&export_id);
+
+    match result {
+        Ok(Ok(stats)) => Ok(ExportStatsData {
+            rows_exported: stats.rows_exported,
+            cols_exported: stats.cols_exported,
+            file_size: stats.file_size,
+        }),
+        Ok(Err(e)) => {
+            let _ = std::fs::remove_file(&output_path);
+            if cancel_flag.load(Ordering::Relaxed) {
+                Err("Export cancelled".to_string())
+            } else {
+                Err(format!("Failed to export: {}", e))
             }
-            _ => {}
         }
+        Err(e) => Err(format!("Export task failed: {}", e)),
     }
-    
-    // Add last field
-    if start < line.len() {
-        let field = String::from_utf8_lossy(&line[start..]).to_string();
-        fields.push(field.trim_matches('"').to_string());
-    } else {
-        fields.push(String::new());
-    }
-    
-    fields
 }
 
+/// Request cancellation of a running export; unknown ids are ignored
 #[tauri::command]
-fn close_file(file_path: String) -> std::result::Result<(), String> {
-    let mut readers = READERS.lock().unwrap();
-    readers.remove(&file_path);
+fn cancel_export(export_id: String) -> std::result::Result<(), String> {
+    if let Some(flag) = EXPORTS.lock().unwrap().get(&export_id) {
+        flag.store(true, Ordering::Relaxed);
+    }
     Ok(())
 }
 
+/// Sort the whole file by one column once and cache the resulting row order
+/// `order` is "asc"/"desc", `data_type` is "string"/"number"/"auto" (default auto)
 #[tauri::command]
-fn get_file_info(file_path: String) -> std::result::Result<CsvFileInfo, String> {
-    let readers = READERS.lock().unwrap();
-    let reader = readers
+async fn sort_and_cache(
+    file_path: String,
+    column: usize,
+    order: String,
+    data_type: Option<String>,
+) -> std::result::Result<SortInfo, String> {
+    let reader = get_reader(&file_path)?;
+    if column >= reader.info().total_cols {
+        return Err(format!("Column {} out of range", column));
+    }
+    let order = SortOrder::from_str(&order).ok_or_else(|| format!("Invalid sort order: {}", order))?;
+    let data_type = match data_type {
+        Some(name) => DataType::from_str(&name).ok_or_else(|| format!("Invalid data type: {}", name))?,
+        None => DataType::Auto,
+    };
+    let options = SortOptions::new().add_key(SortKey::new(column, order, data_type));
+
+    let permutation = tauri::async_runtime::spawn_blocking(move || {
+        sort_csv_data(&reader, &options, None)
+            .map(|sorted| sorted.into_iter().map(|record| record.original_row).collect::<Vec<usize>>())
+    })
+    .await
+    .map_err(|e| format!("Sort task failed: {}", e))?
+    .map_err(|e| format!("Failed to sort: {}", e))?;
+
+    let info = SortInfo {
+        total_rows: permutation.len(),
+        permutation_bytes: permutation.capacity() * std::mem::size_of::<usize>(),
+    };
+    SORTS.lock().unwrap().insert(file_path, Arc::new(permutation));
+    Ok(info)
+}
+
+/// Read a page of the cached sorted view; each row carries its original row number
+#[tauri::command]
+fn read_sorted_page(
+    file_path: String,
+    page: usize,
+    page_size: usize,
+) -> std::result::Result<SortedPageData, String> {
+    let reader = get_reader(&file_path)?;
+    let permutation = SORTS
+        .lock()
+        .unwrap()
         .get(&file_path)
-        .ok_or_else(|| "File not opened".to_string())?;
+        .cloned()
+        .ok_or_else(|| "File not sorted".to_string())?;
+
+    let page_size = page_size.max(1);
+    let total_pages = permutation.len().div_ceil(page_size);
+    let start = page.saturating_mul(page_size).min(permutation.len());
+    let end = start.saturating_add(page_size).min(permutation.len());
+
+    let mut rows = Vec::with_capacity(end - start);
+    for &row_number in &permutation[start..end] {
+        let record = reader
+            .get_row(row_number)
+            .map_err(|e| format!("Failed to read row: {}", e))?
+            .ok_or_else(|| format!("Row {} out of range", row_number))?;
+        rows.push(SearchRow {
+            row_number,
+            fields: record.fields.iter().map(|f| f.to_string()).collect(),
+        });
+    }
 
+    Ok(SortedPageData {
+        rows,
+        page,
+        total_pages,
+        page_size,
+    })
+}
+
+/// Statistics of one column, scanning the file on a blocking task the first time
+async fn load_column_stats(file_path: String, column: usize) -> std::result::Result<Arc<ColumnStats>, String> {
+    let key = (file_path, column);
+    if let Some(stats) = COLUMN_STATS.lock().unwrap().get(&key) {
+        return Ok(Arc::clone(stats));
+    }
+
+    let reader = get_reader(&key.0)?;
+    if column >= reader.info().total_cols {
+        return Err(format!("Column {} out of range", column));
+    }
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    COLUMN_SCANS
+        .lock()
+        .unwrap()
+        .insert(key.clone(), Arc::clone(&cancel_flag));
+
+    let scan_flag = Arc::clone(&cancel_flag);
+    let result = tauri::async_runtime::spawn_blocking(move || csv_tool::csv::column_stats(&reader, column, Some(&scan_flag))).await;
+    {
+        // Only remove our own flag; a newer scan of the same column may have replaced it
+        let mut scans = COLUMN_SCANS.lock().unwrap();
+        if scans.get(&key).is_some_and(|flag| Arc::ptr_eq(flag, &cancel_flag)) {
+            scans.remove(&key);
+        }
+    }
+
+    let outcome = result
+        .map_err(|e| format!("Column scan failed: {}", e))?
+        .map_err(|e| format!("Failed to scan column {}: {}", column, e))?;
+    if outcome.cancelled {
+        return Err("Column scan cancelled".to_string());
+    }
+    let stats = Arc::new(outcome.value);
+    COLUMN_STATS.lock().unwrap().insert(key, Arc::clone(&stats));
+    Ok(stats)
+}
+
+/// Summary of a column: numeric range and mean, or the most frequent values
+#[tauri::command]
+async fn column_stats(file_path: String, column: usize) -> std::result::Result<ColumnStatsData, String> {
+    let stats = load_column_stats(file_path, column).await?;
+    let top_values = if stats.is_numeric() {
+        Vec::new()
+    } else {
+        stats
+            .top_values(TOP_VALUES)
+            .iter()
+            .map(|(value, count)| ValueCount {
+                value: value.clone(),
+                count: *count,
+            })
+            .collect()
+    };
+
+    Ok(ColumnStatsData {
+        column: stats.column,
+        rows: stats.rows,
+        null_count: stats.null_count,
+        distinct_count: stats.distinct_count,
+        numeric: stats.numeric.as_ref().map(|n| NumericSummary {
+            min: n.min,
+            max: n.max,
+            mean: n.mean,
+        }),
+        top_values,
+    })
+}
+
+/// Up to `limit` distinct non-empty values with their counts, most frequent first, for filter dropdowns
+#[tauri::command]
+async fn column_unique_values(
+    file_path: String,
+    column: usize,
+    limit: usize,
+) -> std::result::Result<UniqueValues, String> {
+    let stats = load_column_stats(file_path, column).await?;
+    let values = stats
+        .top_values(limit)
+        .iter()
+        .map(|(value, count)| ValueCount {
+            value: value.clone(),
+            count: *count,
+        })
+        .collect();
+
+    Ok(UniqueValues {
+        values,
+        total_distinct: stats.distinct_count,
+        truncated: limit < stats.distinct_count,
+    })
+}
+
+/// Stop a running `column_stats`/`column_unique_values` scan of a column
+#[tauri::command]
+fn cancel_column_scan(file_path: String, column: usize) -> std::result::Result<(), String> {
+    if let Some(flag) = COLUMN_SCANS.lock().unwrap().get(&(file_path, column)) {
+        flag.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Drop the cached sort order, returning to file order
+#[tauri::command]
+fn clear_sort(file_path: String) -> std::result::Result<(), String> {
+    SORTS.lock().unwrap().remove(&file_path);
+    Ok(())
+}
+
+/// Run `f` on the editor of a file opened with `begin_edit`
+fn with_editor<T>(
+    file_path: &str,
+    f: impl FnOnce(&mut CsvEditor) -> std::result::Result<T, String>,
+) -> std::result::Result<T, String> {
+    let mut editors = EDITORS.lock().unwrap();
+    let editor = editors
+        .get_mut(file_path)
+        .ok_or_else(|| "File not opened for editing".to_string())?;
+    f(editor)
+}
+
+fn change_stats_data(editor: &CsvEditor) -> ChangeStatsData {
+    let stats = editor.change_stats();
+    ChangeStatsData {
+        cells_edited: stats.cells_edited,
+        rows_deleted: stats.rows_deleted,
+        rows_inserted: stats.rows_inserted,
+        rows_appended: stats.rows_appended,
+        effective_rows: editor.effective_row_count(),
+        has_changes: editor.has_changes(),
+    }
+}
+
+fn file_info(reader: &CsvReader) -> CsvFileInfo {
     let info = reader.info();
-    Ok(CsvFileInfo {
+    let (rows_lower_bound, rows_upper_bound) = match reader.row_estimate() {
+        Some(estimate) => (estimate.lower_bound, estimate.upper_bound.max(info.total_rows)),
+        None => (info.total_rows, info.total_rows),
+    };
+    CsvFileInfo {
         file_path: info.file_path.to_string_lossy().to_string(),
         file_size: info.file_size,
         total_rows: info.total_rows,
         total_cols: info.total_cols,
         headers: info.headers.clone(),
+        delimiter: info.delimiter.to_string(),
+        line_ending: info.line_ending.name().to_string(),
+        encoding: info.encoding.name().to_string(),
+        is_exact: reader.is_row_count_exact(),
+        rows_lower_bound,
+        rows_upper_bound,
+        index_progress: if reader.is_index_complete() { 100.0 } else { reader.index_build_progress() },
+    }
+}
+
+/// Start editing an open file with the settings it was opened with; a no-op if already editing
+#[tauri::command]
+fn begin_edit(file_path: String) -> std::result::Result<ChangeStatsData, String> {
+    let reader = get_reader(&file_path)?;
+    let mut editors = EDITORS.lock().unwrap();
+    if let Some(editor) = editors.get(&file_path) {
+        return Ok(change_stats_data(editor));
+    }
+
+    let editor = CsvEditor::open(
+        &file_path,
+        reader.has_headers(),
+        reader.delimiter(),
+        reader.index_granularity(),
+    )
+    .map_err(|e| format!("Failed to open file for editing: {}", e))?;
+    let stats = change_stats_data(&editor);
+    editors.insert(file_path.clone(), editor);
+    drop(editors);
+    // Keep the file open until the edits are saved or discarded
+    READERS.lock().unwrap().set_pinned(&file_path, true);
+    Ok(stats)
+}
+
+#[tauri::command]
+fn edit_cell(file_path: String, row: usize, col: usize, value: String) -> std::result::Result<ChangeStatsData, String> {
+    with_editor(&file_path, |editor| {
+        editor
+            .edit_cell(row, col, value)
+            .map_err(|e| format!("Failed to edit cell (row {}, col {}): {}", row, col, e))?;
+        Ok(change_stats_data(editor))
+    })
+}
+
+/// Delete rows by their current row numbers
+#[tauri::command]
+fn delete_rows(file_path: String, mut rows: Vec<usize>) -> std::result::Result<ChangeStatsData, String> {
+    with_editor(&file_path, |editor| {
+        // Highest first: deleting an appended row shifts the appended rows after it
+        rows.sort_unstable_by(|a, b| b.cmp(a));
+        rows.dedup();
+        for row in rows {
+            editor
+                .delete_row(row)
+                .map_err(|e| format!("Failed to delete row {}: {}", row, e))?;
+        }
+        Ok(change_stats_data(editor))
+    })
+}
+
+/// Insert a row before original row `position`, or append it when `position` is omitted
+#[tauri::command]
+fn add_row(file_path: String, fields: Vec<String>, position: Option<usize>) -> std::result::Result<ChangeStatsData, String> {
+    with_editor(&file_path, |editor| {
+        let row = RowData::new(fields);
+        match position {
+            Some(position) => editor
+                .insert_row(position, row)
+                .map_err(|e| format!("Failed to insert row at {}: {}", position, e))?,
+            None => editor
+                .append_row(row)
+                .map_err(|e| format!("Failed to append row: {}", e))?,
+        }
+        Ok(change_stats_data(editor))
+    })
+}
+
+#[tauri::command]
+fn get_change_stats(file_path: String) -> std::result::Result<ChangeStatsData, String> {
+    with_editor(&file_path, |editor| Ok(change_stats_data(editor)))
+}
+
+/// Write the edits to `output_path`, or over the original file when it is omitted
+/// Editing ends on success; saving in place reopens the file so pages show the saved data
+#[tauri::command]
+async fn save_edits(file_path: String, output_path: Option<String>) -> std::result::Result<SaveResult, String> {
+    let reader = get_reader(&file_path)?;
+    let editor = EDITORS
+        .lock()
+        .unwrap()
+        .remove(&file_path)
+        .ok_or_else(|| "File not opened for editing".to_string())?;
+
+    let has_headers = reader.has_headers();
+    let delimiter = reader.delimiter().clone();
+    let granularity = reader.index_granularity();
+    let in_place = output_path.is_none();
+    if in_place {
+        // The stored reader maps the file, which blocks overwriting it on Windows
+        stop_index_build(&file_path);
+        READERS.lock().unwrap().remove(&file_path);
+        clear_file_caches(&file_path);
+    }
+    drop(reader);
+
+    let task_path = file_path.clone();
+    let (editor, saved, reopened) = tauri::async_runtime::spawn_blocking(move || {
+        let options = WriteOptions::new().with_delimiter(delimiter.clone());
+        let saved = match &output_path {
+            Some(path) => editor.save(path, &options),
+            None => editor.save_in_place(&options),
+        };
+        // The stored reader was dropped before writing, so reopen the file whether or not the save succeeded
+        let reopened = in_place.then(|| -> csv_tool::error::Result<CsvReader> {
+            let reader = CsvReader::open_fast(&task_path, has_headers, delimiter, granularity)?;
+            reader.set_access_pattern(AccessPattern::Random);
+            Ok(reader)
+        });
+        // Editing ends once the edits are written; keeping them would re-apply row changes on a retry
+        let editor = saved.is_err().then_some(editor);
+        (editor, saved, reopened)
     })
+    .await
+    .map_err(|e| format!("Save task failed: {}", e))?;
+
+    if let Some(editor) = editor {
+        // Keep the edits so the user can retry or save elsewhere
+        EDITORS.lock().unwrap().insert(file_path.clone(), editor);
+    }
+    let file_info = match reopened {
+        Some(Ok(reader)) => {
+            let info = file_info(&reader);
+            store_reader(file_path.clone(), reader);
+            Some(info)
+        }
+        Some(Err(e)) => {
+            return Err(match saved {
+                Ok(_) => format!("Saved, but failed to reopen the file: {}", e),
+                Err(save_error) => format!("Failed to save: {}; failed to reopen the file: {}", save_error, e),
+            });
+        }
+        None => None,
+    };
+    let stats = saved.map_err(|e| format!("Failed to save: {}", e))?;
+    if !in_place {
+        READERS.lock().unwrap().set_pinned(&file_path, false);
+    }
+    Ok(SaveResult {
+        rows_written: stats.rows_written,
+        bytes_written: stats.bytes_written,
+        file_path: stats.file_path,
+        file_info,
+    })
+}
+
+#[tauri::command]
+fn discard_edits(file_path: String) -> std::result::Result<(), String> {
+    EDITORS.lock().unwrap().remove(&file_path);
+    READERS.lock().unwrap().set_pinned(&file_path, false);
+    Ok(())
+}
+
+#[tauri::command]
+fn close_file(file_path: String) -> std::result::Result<(), String> {
+    READERS.lock().unwrap().remove(&file_path);
+    release_file(&file_path);
+    Ok(())
+}
+
+/// Close every open file, discarding pending edits
+#[tauri::command]
+fn close_all_files() -> std::result::Result<(), String> {
+    let closed = READERS.lock().unwrap().clear();
+    for path in closed {
+        release_file(&path);
+    }
+    Ok(())
+}
+
+/// Open files, most recently used first
+#[tauri::command]
+fn list_open_files() -> std::result::Result<Vec<CsvFileInfo>, String> {
+    let readers = READERS.lock().unwrap();
+    Ok(readers
+        .iter_recent()
+        .into_iter()
+        .map(|(_, reader)| file_info(reader))
+        .collect())
+}
+
+/// Change how many files may stay open at once, returning the paths of files closed as a result
+#[tauri::command]
+fn set_max_open_files(max_open_files: usize) -> std::result::Result<Vec<String>, String> {
+    let closed = READERS.lock().unwrap().set_capacity(max_open_files);
+    for path in &closed {
+        release_file(path);
+    }
+    Ok(closed)
+}
+
+/// Current row count of an open file, switching to the exact count once the background
+/// index build has finished
+#[tauri::command]
+fn refresh_file_info(file_path: String) -> std::result::Result<CsvFileInfo, String> {
+    apply_finished_index(&file_path);
+    let reader = get_reader(&file_path)?;
+    Ok(file_info(&reader))
+}
+
+#[tauri::command]
+fn get_file_info(file_path: String) -> std::result::Result<CsvFileInfo, String> {
+    let reader = get_reader(&file_path)?;
+    Ok(file_info(&reader))
 }
 
 fn main() {
@@ -265,8 +1118,31 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             open_csv_file,
             read_page,
+            search_csv,
+            count_matches,
+            search_page,
+            find_next,
+            export_csv,
+            cancel_export,
+            sort_and_cache,
+            read_sorted_page,
+            clear_sort,
+            begin_edit,
+            edit_cell,
+            delete_rows,
+            add_row,
+            get_change_stats,
+            save_edits,
+            discard_edits,
+            column_stats,
+            column_unique_values,
+            cancel_column_scan,
             close_file,
+            close_all_files,
+            list_open_files,
+            set_max_open_files,
             get_file_info,
+            refresh_file_info,
             quick_preview
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,645 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+fn create_test_csv(path: &Path) {
+    let mut file = File::create(path).unwrap();
+    writeln!(file, "id,name,city").unwrap();
+    writeln!(file, "1,Alice,Beijing").unwrap();
+    writeln!(file, "2,Bob,Shanghai").unwrap();
+    writeln!(file, "3,Alice Smith,Shenzhen").unwrap();
+}
+
+/// 运行CLI并返回标准输出
+fn run_cli(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_csv-tool"))
+        .args(args)
+        .output()
+        .expect("failed to run csv-tool");
+    assert!(
+        output.status.success(),
+        "csv-tool failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_search_color_never_has_no_ansi() {
+    let test_file = std::env::temp_dir().join("test_cli_color.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    let plain = run_cli(&[file, "--color", "never", "search", "Alice"]);
+    assert!(plain.contains("Alice"));
+    assert!(!plain.contains('\x1b'));
+
+    let colored = run_cli(&[file, "--color", "always", "search", "Alice"]);
+    assert!(colored.contains("\x1b[1;33mAlice\x1b[0m"));
+
+    // 输出被重定向时 auto 不输出颜色
+    let auto = run_cli(&[file, "search", "Alice"]);
+    assert!(!auto.contains('\x1b'));
+
+    let colored = run_cli(&[file, "--color", "always", "--highlight-color", "green", "search", "Alice"]);
+    assert!(colored.contains("\x1b[1;32mAlice\x1b[0m"));
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_search_columns_projection_snapshot() {
+    let test_file = std::env::temp_dir().join("test_cli_columns.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    // 按 name 搜索，但只显示 name 和 city 两列
+    let output = run_cli(&[
+        file, "-q", "--color", "never",
+        "search", "Alice", "-l", "--columns", "name,city",
+    ]);
+    let expected = "
+┌──────┬─────────────┬──────────┐
+│ 行号 │    name     │   city   │
+├──────┼─────────────┼──────────┤
+│    1 │ Alice       │ Beijing  │
+│    3 │ Alice Smith │ Shenzhen │
+└──────┴─────────────┴──────────┘
+";
+    assert_eq!(output, expected);
+
+    // 搜索不受显示列限制：匹配 id 列，但 id 列不显示
+    let output = run_cli(&[
+        file, "-q", "--color", "never",
+        "search", "2", "--columns", "city",
+    ]);
+    assert!(output.contains("Shanghai"));
+    assert!(!output.contains("Bob"));
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_search_only_matching_snapshot() {
+    let test_file = std::env::temp_dir().join("test_cli_only_matching.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    // 每个匹配的字段一行：行号、列名和字段内容
+    let output = run_cli(&[
+        file, "-q", "--color", "never",
+        "search", "-e", "Alice", "-e", "Shenzhen", "--only-matching",
+    ]);
+    assert_eq!(output, "\n1:name:Alice\n3:name:Alice Smith\n3:city:Shenzhen\n");
+
+    // 高亮只作用于匹配的部分
+    let output = run_cli(&[file, "-q", "--color", "always", "search", "Smith", "-o"]);
+    assert_eq!(output, "\n3:name:Alice \x1b[1;33mSmith\x1b[0m\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_search_matching_columns_snapshot() {
+    let test_file = std::env::temp_dir().join("test_cli_matching_columns.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    // 只有 name 列有匹配，id 和 city 列不显示
+    let output = run_cli(&[
+        file, "-q", "--color", "never",
+        "search", "Alice", "-l", "--matching-columns",
+    ]);
+    let expected = "
+┌──────┬─────────────┐
+│ 行号 │    name     │
+├──────┼─────────────┤
+│    1 │ Alice       │
+│    3 │ Alice Smith │
+└──────┴─────────────┘
+";
+    assert_eq!(output, expected);
+
+    // 任一结果中匹配过的列都保留，即使该行在这一列没有匹配
+    let output = run_cli(&[
+        file, "-q", "--color", "never",
+        "search", "-e", "Alice", "-e", "Shenzhen", "--matching-columns",
+    ]);
+    let expected = "
+┌─────────────┬──────────┐
+│    name     │   city   │
+├─────────────┼──────────┤
+│ Alice       │ Beijing  │
+│ Alice Smith │ Shenzhen │
+└─────────────┴──────────┘
+";
+    assert_eq!(output, expected);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_search_format_json() {
+    let test_file = std::env::temp_dir().join("test_cli_json.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    let output = run_cli(&[file, "search", "Alice", "--format", "json"]);
+    let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(data["headers"], serde_json::json!(["id", "name", "city"]));
+    let rows = data["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["row_number"], 1);
+    assert_eq!(rows[0]["fields"], serde_json::json!(["1", "Alice", "Beijing"]));
+    assert_eq!(rows[0]["matched_columns"], serde_json::json!([2]));
+    assert_eq!(rows[1]["row_number"], 3);
+
+    // CSV格式输出原始数据
+    let output = run_cli(&[file, "search", "Bob", "--format", "csv", "--columns", "name,city"]);
+    assert_eq!(output, "name,city\nBob,Shanghai\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_search_multiple_patterns() {
+    let test_file = std::env::temp_dir().join("test_cli_multi_pattern.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+    let pattern_file = std::env::temp_dir().join("test_cli_multi_pattern.txt");
+    std::fs::write(&pattern_file, "Shenzhen\r\n\nnobody\n").unwrap();
+
+    // 位置参数、-e 和 -f 中的模式任一匹配即可
+    let output = run_cli(&[
+        file, "search", "Bob", "-e", "Beijing", "-f", pattern_file.to_str().unwrap(), "--format", "json",
+    ]);
+    let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let rows = data["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["matched_patterns"], serde_json::json!([2]));
+    assert_eq!(rows[1]["matched_patterns"], serde_json::json!([1]));
+    assert_eq!(rows[2]["matched_patterns"], serde_json::json!([3]));
+
+    // 只用 -e 时不需要位置参数，统计与反向匹配同样适用
+    let output = run_cli(&[file, "search", "-e", "Bob", "-e", "Smith", "--count"]);
+    assert!(output.contains("匹配行数: 2"));
+    let output = run_cli(&[file, "search", "-e", "Bob", "-e", "Smith", "-V", "--format", "csv"]);
+    assert_eq!(output, "id,name,city\n1,Alice,Beijing\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&pattern_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_tail_last_rows() {
+    let test_file = std::env::temp_dir().join("test_cli_tail.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    let output = run_cli(&[file, "tail", "-n", "2"]);
+    assert_eq!(output, "id,name,city\n2,Bob,Shanghai\n3,Alice Smith,Shenzhen\n");
+
+    // 末尾未写完的半行不输出
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&test_file)
+        .unwrap()
+        .write_all(b"4,Dav")
+        .unwrap();
+    let output = run_cli(&[file, "-q", "tail", "-n", "1"]);
+    assert_eq!(output, "3,Alice Smith,Shenzhen\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_tail_follow_header_only_file() {
+    use std::process::Stdio;
+
+    let test_file = std::env::temp_dir().join("test_cli_tail_follow_header.csv");
+    // 只有表头：表头行完整、表头行还没有写完
+    for (initial, appended) in [("id,name\n", "1,a\n2,b\n"), ("id,na", "me\n1,a\n2,b\n")] {
+        std::fs::write(&test_file, initial).unwrap();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_csv-tool"))
+            .args([test_file.to_str().unwrap(), "-q", "--no-index-cache", "tail", "-f", "--interval", "20"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to run csv-tool");
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&test_file)
+            .unwrap()
+            .write_all(appended.as_bytes())
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        child.kill().unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        // 跟踪时不把表头当作数据行输出
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "1,a\n2,b\n", "{:?}", initial);
+    }
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_tail_cr_only_line_endings() {
+    let test_file = std::env::temp_dir().join("test_cli_tail_cr.csv");
+    std::fs::write(&test_file, "id,name\r1,a\r2,b\r3,c\r4,d").unwrap();
+    let file = test_file.to_str().unwrap();
+
+    // 按 \r 分行，末尾没有行结束符的半行不输出
+    let output = run_cli(&[file, "-q", "tail", "-n", "2"]);
+    assert_eq!(output, "2,b\n3,c\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_delimiter_auto_detect() {
+    for (name, delimiter) in [("semicolon", ';'), ("tab", '\t'), ("pipe", '|')] {
+        let test_file = std::env::temp_dir().join(format!("test_cli_detect_{}.csv", name));
+        let content = "id,name,city\n1,Alice,Beijing\n2,Bob,Shanghai\n3,Alice Smith,Shenzhen\n"
+            .replace(',', &delimiter.to_string());
+        std::fs::write(&test_file, content).unwrap();
+        let file = test_file.to_str().unwrap();
+
+        let output = run_cli(&[file, "view", "--format", "json"]);
+        let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(data["headers"], serde_json::json!(["id", "name", "city"]), "{}", name);
+        assert_eq!(data["rows"].as_array().unwrap().len(), 3);
+
+        let output = run_cli(&[file, "search", "Alice", "--format", "csv", "--columns", "city"]);
+        assert_eq!(output, "city\nBeijing\nShenzhen\n", "{}", name);
+
+        // 显式指定 -d 时以指定值为准
+        let output = run_cli(&[file, "-d", ",", "view", "--format", "json"]);
+        let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(data["headers"].as_array().unwrap().len(), 1, "{}", name);
+
+        // 清理
+        std::fs::remove_file(&test_file).ok();
+        std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    }
+}
+
+#[test]
+fn test_headers_auto_detect() {
+    let test_file = std::env::temp_dir().join("test_cli_no_headers.csv");
+    std::fs::write(&test_file, "1,Alice,Beijing\n2,Bob,Shanghai\n3,Alice Smith,Shenzhen\n").unwrap();
+    let file = test_file.to_str().unwrap();
+
+    // 第一行含数值，判定为无表头，以 col1..colN 作为列名
+    let output = run_cli(&[file, "view", "--format", "json"]);
+    let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(data["headers"], serde_json::json!(["col1", "col2", "col3"]));
+    assert_eq!(data["rows"].as_array().unwrap().len(), 3);
+
+    // 合成的列名可用于按列搜索
+    let output = run_cli(&[file, "search", "Sh", "-c", "col3", "--format", "csv"]);
+    assert_eq!(output, "col1,col2,col3\n2,Bob,Shanghai\n3,Alice Smith,Shenzhen\n");
+
+    // 显式指定时不复用按其他表头设置构建的索引
+    let output = run_cli(&[file, "--headers", "yes", "view", "--format", "json"]);
+    let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(data["headers"], serde_json::json!(["1", "Alice", "Beijing"]));
+    assert_eq!(data["rows"].as_array().unwrap().len(), 2);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_view_header_only_file() {
+    let test_file = std::env::temp_dir().join("test_cli_header_only.csv");
+    std::fs::write(&test_file, "id,name,city\n").unwrap();
+    let file = test_file.to_str().unwrap();
+
+    // 没有数据行时提示而不是panic
+    let output = run_cli(&[file, "--headers", "yes", "view", "-p", "1"]);
+    assert!(output.contains("没有数据行"));
+    let output = run_cli(&[file, "--headers", "yes", "-p", "3"]);
+    assert!(output.contains("没有数据行"));
+
+    let output = run_cli(&[file, "--headers", "yes", "view", "-p", "1", "--format", "json"]);
+    let data: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(data["headers"], serde_json::json!(["id", "name", "city"]));
+    assert!(data["rows"].as_array().unwrap().is_empty());
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_non_ascii_delimiter_rejected() {
+    let test_file = std::env::temp_dir().join("test_cli_delimiter.csv");
+    create_test_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    // 全角逗号不会被截断成错误的字节，而是给出明确的错误
+    let output = Command::new(env!("CARGO_BIN_EXE_csv-tool"))
+        .args([file, "-d", "，", "view"])
+        .output()
+        .expect("failed to run csv-tool");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ASCII字符"), "{}", stderr);
+
+    // 制表符可以用转义写法指定
+    let tsv_file = std::env::temp_dir().join("test_cli_delimiter.tsv");
+    std::fs::write(&tsv_file, "id\tname\n1\tAlice\n").unwrap();
+    let output = run_cli(&[tsv_file.to_str().unwrap(), "-d", "\\t", "view", "--format", "csv"]);
+    assert_eq!(output, "id\tname\n1\tAlice\n");
+
+    // 多字符分隔符
+    let pipe_file = std::env::temp_dir().join("test_cli_delimiter_pipes.txt");
+    std::fs::write(&pipe_file, "id||name\n1||\"A||B\"\n").unwrap();
+    let output = run_cli(&[pipe_file.to_str().unwrap(), "-d", "||", "view", "--format", "csv"]);
+    assert_eq!(output, "id,name\n1,A||B\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&tsv_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&tsv_file)).ok();
+    std::fs::remove_file(&pipe_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&pipe_file)).ok();
+}
+
+#[test]
+fn test_edit_apply_script() {
+    let test_file = std::env::temp_dir().join("test_cli_edit_apply.csv");
+    let script = std::env::temp_dir().join("test_cli_edit_apply.txt");
+    let output_file = std::env::temp_dir().join("test_cli_edit_apply_out.csv");
+    create_test_csv(&test_file);
+    std::fs::write(
+        &script,
+        "# 行号,列,新值\n1,name,Alicia\n2,3,Hangzhou\n3,city,\"Shen, zhen\"\n\n1,1,10\n2,name,\"Bob \"\"B\"\"\"\n9,1,x\n",
+    )
+    .unwrap();
+
+    // 5处修改一次写出，超出范围的修改被跳过
+    let stdout = run_cli(&[
+        test_file.to_str().unwrap(),
+        "edit",
+        "apply",
+        "--script",
+        script.to_str().unwrap(),
+        "-o",
+        output_file.to_str().unwrap(),
+    ]);
+    assert!(stdout.contains("应用修改: 5 处"), "{}", stdout);
+    assert!(stdout.contains("跳过修改: 1 处"), "{}", stdout);
+
+    let content = std::fs::read_to_string(&output_file).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "id,name,city",
+            "10,Alicia,Beijing",
+            "2,\"Bob \"\"B\"\"\",Hangzhou",
+            "3,Alice Smith,\"Shen, zhen\"",
+        ]
+    );
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&script).ok();
+    std::fs::remove_file(&output_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_distinct_top_and_alpha() {
+    let test_file = std::env::temp_dir().join("test_cli_distinct.csv");
+    let mut file = File::create(&test_file).unwrap();
+    writeln!(file, "id,city").unwrap();
+    for (i, city) in ["Beijing", "Shanghai", "Beijing", "Shenzhen", "beijing", "Shanghai"].iter().enumerate() {
+        writeln!(file, "{},{}", i + 1, city).unwrap();
+    }
+    drop(file);
+    let file = test_file.to_str().unwrap();
+
+    let output = run_cli(&[file, "-q", "distinct", "city"]);
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    assert_eq!(lines, vec!["2  Beijing", "2  Shanghai", "1  Shenzhen", "1  beijing"]);
+
+    // 不区分大小写，取前2个后按字母排列
+    let output = run_cli(&[file, "-q", "distinct", "city", "-i", "--top", "2", "--sort", "alpha"]);
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    assert_eq!(lines, vec!["3  Beijing", "2  Shanghai"]);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_fuzzy_column_name() {
+    let test_file = std::env::temp_dir().join("test_cli_fuzzy_column.csv");
+    std::fs::write(&test_file, "id,first name,city\n1,Ada,Paris\n").unwrap();
+    let file = test_file.to_str().unwrap();
+
+    // 大小写、下划线和空白的差异不影响匹配
+    let output = run_cli(&[file, "view", "--columns", "First_Name", "--format", "csv"]);
+    assert_eq!(output, "first name\nAda\n");
+
+    // 拼写错误时提示最接近的列名
+    let output = Command::new(env!("CARGO_BIN_EXE_csv-tool"))
+        .args([file, "distinct", "fisrt_name"])
+        .output()
+        .expect("failed to run csv-tool");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("您是否想输入 'first name'"), "{}", stderr);
+
+    // 列号仍然可用
+    let output = run_cli(&[file, "view", "--columns", "3", "--format", "csv"]);
+    assert_eq!(output, "city\nParis\n");
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_sort_decimal_comma() {
+    let test_file = std::env::temp_dir().join("test_cli_sort_decimal_comma.csv");
+    let output_file = std::env::temp_dir().join("test_cli_sort_decimal_comma_out.csv");
+    std::fs::write(&test_file, "name;amount\nA;1.234,56 €\nB; 999,9\nC;1.234,5\n").unwrap();
+    let file = test_file.to_str().unwrap();
+    let output = output_file.to_str().unwrap();
+
+    run_cli(&[file, "-q", "-d", ";", "sort", "amount", "-t", "number", "--decimal-comma", "-o", output]);
+    let sorted = std::fs::read_to_string(&output_file).unwrap();
+    let names: Vec<&str> = sorted.lines().skip(1).map(|line| &line[..1]).collect();
+    assert_eq!(names, vec!["B", "C", "A"]);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&output_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_sample_with_seed() {
+    let test_file = std::env::temp_dir().join("test_cli_sample.csv");
+    let first_output = std::env::temp_dir().join("test_cli_sample_out1.csv");
+    let second_output = std::env::temp_dir().join("test_cli_sample_out2.csv");
+    let mut file = File::create(&test_file).unwrap();
+    writeln!(file, "id,value").unwrap();
+    for i in 0..100 {
+        writeln!(file, "{},{}", i, i * 2).unwrap();
+    }
+    drop(file);
+    let file = test_file.to_str().unwrap();
+
+    for output in [&first_output, &second_output] {
+        run_cli(&[file, "-q", "sample", "-n", "10", "--seed", "1", "-o", output.to_str().unwrap()]);
+    }
+    let sampled = std::fs::read_to_string(&first_output).unwrap();
+    assert_eq!(sampled, std::fs::read_to_string(&second_output).unwrap());
+    assert!(sampled.starts_with("id,value\n"));
+    assert_eq!(sampled.lines().count(), 11);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&first_output).ok();
+    std::fs::remove_file(&second_output).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_sort_stable_and_unstable() {
+    let test_file = std::env::temp_dir().join("test_cli_sort_stable.csv");
+    let output_file = std::env::temp_dir().join("test_cli_sort_stable_out.csv");
+    std::fs::write(&test_file, "key,id\nb,1\na,2\nb,3\na,4\nb,5\n").unwrap();
+    let file = test_file.to_str().unwrap();
+    let output = output_file.to_str().unwrap();
+
+    // 默认键相同的行保持原有顺序
+    run_cli(&[file, "-q", "sort", "key", "-o", output]);
+    let sorted = std::fs::read_to_string(&output_file).unwrap();
+    assert_eq!(sorted, "key,id\na,2\na,4\nb,1\nb,3\nb,5\n");
+
+    // --unstable 只保证按键排序
+    run_cli(&[file, "-q", "sort", "key", "--unstable", "-o", output]);
+    let sorted = std::fs::read_to_string(&output_file).unwrap();
+    let keys: Vec<&str> = sorted.lines().skip(1).map(|line| &line[..1]).collect();
+    assert_eq!(keys, vec!["a", "a", "b", "b", "b"]);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&output_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_export_from_reads_to_end_of_file() {
+    let test_file = std::env::temp_dir().join("test_cli_export_from.csv");
+    let output_file = std::env::temp_dir().join("test_cli_export_from_out.csv");
+    common::create_underestimated_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    // 只指定起始行时导出到文件末尾，不受估算的总行数限制
+    run_cli(&[file, "-q", "export", output_file.to_str().unwrap(), "--from", "30001"]);
+    let exported = std::fs::read_to_string(&output_file).unwrap();
+    let lines: Vec<&str> = exported.lines().collect();
+    assert_eq!(lines.len(), 10_001);
+    assert!(lines[1].starts_with("30000,wide"));
+    assert!(lines[10_000].starts_with("39999,wide"));
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&output_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_query_from_loads_to_end_of_file() {
+    let test_file = std::env::temp_dir().join("test_cli_query_from.csv");
+    common::create_underestimated_csv(&test_file);
+    let file = test_file.to_str().unwrap();
+
+    // 只指定起始行时载入到文件末尾，不受估算的总行数限制
+    let sql = "SELECT COUNT(*) AS n, MAX(CAST(id AS INTEGER)) AS last FROM data";
+    let output = run_cli(&[file, "-q", "query", "--sql", sql, "--from", "30001"]);
+    assert!(output.contains("10000"), "{}", output);
+    assert!(output.contains("39999"), "{}", output);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_query_compares_numeric_columns_as_numbers() {
+    let test_file = std::env::temp_dir().join("test_cli_query_numeric.csv");
+    let mut file = File::create(&test_file).unwrap();
+    writeln!(file, "name,price").unwrap();
+    writeln!(file, "apple,12").unwrap();
+    writeln!(file, "pear,3").unwrap();
+    writeln!(file, "fig,100").unwrap();
+    writeln!(file, "kiwi,").unwrap();
+    drop(file);
+    let file = test_file.to_str().unwrap();
+
+    // 整数列按数值比较和排序，空值不参与比较
+    let sql = "SELECT name FROM data WHERE price > 5 ORDER BY price";
+    let output = run_cli(&[file, "-q", "query", "--sql", sql]);
+    let names: Vec<&str> = output.lines()
+        .filter_map(|line| ["apple", "pear", "fig", "kiwi"].into_iter().find(|name| line.contains(name)))
+        .collect();
+    assert_eq!(names, vec!["apple", "fig"], "{}", output);
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
+
+#[test]
+fn test_query_and_export_sqlite_with_case_duplicate_headers() {
+    let test_file = std::env::temp_dir().join("test_cli_sqlite_dup_headers.csv");
+    let db_file = std::env::temp_dir().join("test_cli_sqlite_dup_headers.db");
+    let mut file = File::create(&test_file).unwrap();
+    writeln!(file, "id,name,Name").unwrap();
+    writeln!(file, "1,alice,Alice").unwrap();
+    drop(file);
+    let file = test_file.to_str().unwrap();
+
+    // 只有大小写不同的列名去重后可以建表
+    let output = run_cli(&[file, "-q", "query", "--sql", "SELECT Name_2 FROM data"]);
+    assert!(output.contains("Alice"), "{}", output);
+    run_cli(&[file, "-q", "export", db_file.to_str().unwrap()]);
+    assert!(db_file.exists());
+
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&db_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+}
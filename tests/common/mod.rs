@@ -0,0 +1,21 @@
+//! 集成测试共用的测试数据
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 写入40000行、超过1MB且快速打开时估算的行数少于实际行数的文件
+/// 
+/// 快速打开只索引开头，总行数按分散的采样区域估算；较窄的行（第1000～20999行，
+/// `kind` 为 `narrow`）集中在采样区域之间，使估算少于实际行数。表头为 `id,kind,note`
+pub fn create_underestimated_csv(path: &Path) {
+    let mut file = BufWriter::new(File::create(path).unwrap());
+    writeln!(file, "id,kind,note").unwrap();
+    for i in 0..40_000 {
+        if (1_000..21_000).contains(&i) {
+            writeln!(file, "{},narrow,", i).unwrap();
+        } else {
+            writeln!(file, "{},wide,{}", i, "x".repeat(60)).unwrap();
+        }
+    }
+}
@@ -1,11 +1,13 @@
 //! 导出功能集成测试
 
-use csv_tool::csv::{CsvReader, ExportFormat, ExportOptions, Exporter};
-use csv_tool::error::Result;
+use csv_tool::csv::{CsvReader, ExportFormat, ExportOptions, Exporter, RaggedPolicy, ReaderOptions, SearchOptions, SearchPattern};
+use csv_tool::error::{CsvError, Result};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
 
+mod common;
+
 fn create_test_csv(path: &PathBuf) -> Result<()> {
     let mut file = File::create(path)?;
     
@@ -166,6 +168,107 @@ fn test_export_with_row_range() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_export_sqlite() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_sqlite.csv");
+    let output_file = std::env::temp_dir().join("test_export_sqlite.db");
+    create_test_csv(&test_file)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = ExportOptions::new(ExportFormat::Sqlite)
+        .with_row_range(0, 2);
+    let exporter = Exporter::new(&reader, options);
+    
+    let stats = exporter.export_to_file(&output_file)?;
+    
+    assert_eq!(stats.rows_exported, 2);
+    assert_eq!(stats.cols_exported, 4);
+    
+    // 从数据库中查询行数
+    let conn = rusqlite::Connection::open(&output_file)?;
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM data", [], |row| row.get(0))?;
+    assert_eq!(count, 2);
+    let name: String = conn.query_row("SELECT name FROM data WHERE id = '2'", [], |row| row.get(0))?;
+    assert_eq!(name, "Bob");
+    drop(conn);
+    
+    // 内存查询
+    let exporter = Exporter::new(&reader, ExportOptions::new(ExportFormat::Sqlite));
+    let result = exporter.query_sql("SELECT city FROM data ORDER BY id DESC")?;
+    assert_eq!(result.columns, vec!["city"]);
+    assert_eq!(result.rows.len(), 3);
+    assert_eq!(result.rows[0][0], "Guangzhou");
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    fs::remove_file(&output_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_export_sqlite_without_columns() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_sqlite_empty.csv");
+    let output_file = std::env::temp_dir().join("test_export_sqlite_empty.db");
+    
+    // 空文件在打开时报错，不会生成无列的表
+    File::create(&test_file)?;
+    assert!(matches!(CsvReader::open(&test_file, false, b',', 10), Err(CsvError::Format(_))));
+    
+    // 没有可导出的列时返回格式错误，而不是SQLite的语法错误
+    create_test_csv(&test_file)?;
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = ExportOptions::new(ExportFormat::Sqlite).with_columns(vec![]);
+    let exporter = Exporter::new(&reader, options);
+    assert!(matches!(exporter.export_to_file(&output_file), Err(CsvError::Format(_))));
+    assert!(matches!(exporter.query_sql("SELECT 1"), Err(CsvError::Format(_))));
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    fs::remove_file(&output_file).ok();
+    fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_export_typed_json_keeps_zero_padded_codes() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_typed.csv");
+    let output_file = std::env::temp_dir().join("test_export_typed.jsonl");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "zip,count")?;
+    writeln!(file, "90210,1")?;
+    writeln!(file, "00501,2")?;
+    writeln!(file, "02134,3")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    
+    // 按列统一类型：邮编列始终为字符串，计数列为数字
+    let options = ExportOptions::new(ExportFormat::JsonLines).with_typed(true);
+    Exporter::new(&reader, options).export_to_file(&output_file)?;
+    let content = fs::read_to_string(&output_file)?;
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "{\"zip\":\"90210\",\"count\":1}");
+    assert_eq!(lines[1], "{\"zip\":\"00501\",\"count\":2}");
+    assert_eq!(lines[2], "{\"zip\":\"02134\",\"count\":3}");
+    
+    // 所有字段都输出为字符串
+    let options = ExportOptions::new(ExportFormat::JsonLines).with_keep_strings(true);
+    Exporter::new(&reader, options).export_to_file(&output_file)?;
+    let content = fs::read_to_string(&output_file)?;
+    assert!(content.starts_with("{\"zip\":\"90210\",\"count\":\"1\"}"));
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    fs::remove_file(&output_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
 #[test]
 fn test_export_format_detection() {
     assert_eq!(
@@ -187,3 +290,404 @@ fn test_export_format_detection() {
 }
 
 
+
+#[test]
+fn test_export_to_writer() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_writer.csv");
+    create_test_csv(&test_file)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = ExportOptions::new(ExportFormat::Json).with_columns(vec![1, 3]);
+    let exporter = Exporter::new(&reader, options);
+    
+    let mut buffer: Vec<u8> = Vec::new();
+    let stats = exporter.export_to_writer(&mut buffer)?;
+    
+    assert_eq!(stats.rows_exported, 3);
+    assert_eq!(stats.cols_exported, 2);
+    assert_eq!(stats.file_size, buffer.len() as u64);
+    
+    let value: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    let rows = value.as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["name"], "Alice");
+    assert_eq!(rows[2]["city"], "Guangzhou");
+    assert!(rows[0].get("age").is_none());
+    
+    // SQLite不支持写入流
+    let exporter = Exporter::new(&reader, ExportOptions::new(ExportFormat::Sqlite));
+    assert!(exporter.export_to_writer(&mut Vec::<u8>::new()).is_err());
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_export_middle_row_range() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_middle_range.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,name")?;
+    for i in 0..100_000 {
+        writeln!(file, "{},Name {}", i, i)?;
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    let options = ExportOptions::new(ExportFormat::JsonLines)
+        .with_row_range(50_000, 50_100);
+    let exporter = Exporter::new(&reader, options);
+    
+    let mut buffer: Vec<u8> = Vec::new();
+    let stats = exporter.export_to_writer(&mut buffer)?;
+    assert_eq!(stats.rows_exported, 100);
+    
+    let content = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 100);
+    assert!(lines[0].contains("\"name\":\"Name 50000\""));
+    assert!(lines[99].contains("\"name\":\"Name 50099\""));
+    
+    // 结束行超出总行数时截断
+    let options = ExportOptions::new(ExportFormat::JsonLines)
+        .with_row_range(99_990, 200_000);
+    let stats = Exporter::new(&reader, options).export_to_writer(&mut Vec::<u8>::new())?;
+    assert_eq!(stats.rows_exported, 10);
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_headers() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_duplicate_headers.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,name,name,")?;
+    writeln!(file, "1,Alice,Smith,x")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    assert_eq!(reader.duplicate_headers(), vec![("name".to_string(), vec![1, 2])]);
+    assert_eq!(reader.empty_headers(), vec![3]);
+    
+    // 添加后缀后JSON键不再重复
+    let options = ExportOptions::new(ExportFormat::JsonLines)
+        .with_columns(vec![0, 1, 2])
+        .with_dedupe_headers(true);
+    let mut buffer: Vec<u8> = Vec::new();
+    Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+    let value: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    assert_eq!(value["name"], "Alice");
+    assert_eq!(value["name_2"], "Smith");
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_export_progress_and_cancel() -> Result<()> {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+
+    let test_file = std::env::temp_dir().join("test_export_progress.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,name")?;
+    for i in 0..25_000 {
+        writeln!(file, "{},Name {}", i, i)?;
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    
+    // 进度按写入行数汇报，最后一次为100%
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&reports);
+    let options = ExportOptions::new(ExportFormat::Csv)
+        .with_progress(Arc::new(move |done, total| sink.lock().unwrap().push((done, total))));
+    let stats = Exporter::new(&reader, options).export_to_writer(&mut Vec::<u8>::new())?;
+    assert_eq!(stats.rows_exported, 25_000);
+    let reports = reports.lock().unwrap().clone();
+    assert!(reports.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert_eq!(reports.last(), Some(&(25_000, 25_000)));
+    
+    // 设置取消标志后导出返回错误
+    let options = ExportOptions::new(ExportFormat::Json)
+        .with_cancel_flag(Arc::new(AtomicBool::new(true)));
+    assert!(Exporter::new(&reader, options).export_to_writer(&mut Vec::<u8>::new()).is_err());
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_export_selected_columns_with_short_row() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_short_row.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,name,city,email")?;
+    writeln!(file, "1,Alice,Beijing,alice@example.com")?;
+    writeln!(file, "2,Bob")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = ExportOptions::new(ExportFormat::JsonLines)
+        .with_columns(vec![3, 1]);
+    let mut buffer: Vec<u8> = Vec::new();
+    Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+    
+    // 键按所选列的顺序排列，短行缺少的字段为null，其余字段不错位
+    let content = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], r#"{"email":"alice@example.com","name":"Alice"}"#);
+    assert_eq!(lines[1], r#"{"email":null,"name":"Bob"}"#);
+    
+    // CSV导出同样按列对齐，缺少的字段为空
+    let options = ExportOptions::new(ExportFormat::Csv)
+        .with_columns(vec![3, 1]);
+    let mut buffer: Vec<u8> = Vec::new();
+    Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+    let content = String::from_utf8(buffer).unwrap();
+    assert_eq!(content.lines().collect::<Vec<_>>(), vec!["email,name", "alice@example.com,Alice", ",Bob"]);
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_export_ragged_rows_policy() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_ragged.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,name,city")?;
+    writeln!(file, "1,Alice,Beijing")?;
+    writeln!(file, "2,Bob")?;
+    writeln!(file, "3,Carol,Shanghai,extra")?;
+    writeln!(file, "4,Dave,Shenzhen")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let export = |policy: RaggedPolicy| -> Result<(String, usize)> {
+        let options = ExportOptions::new(ExportFormat::Csv).with_on_ragged(policy);
+        let mut buffer: Vec<u8> = Vec::new();
+        let stats = Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+        Ok((String::from_utf8(buffer).unwrap(), stats.rows_skipped))
+    };
+    
+    // 默认原样导出
+    let (content, skipped) = export(RaggedPolicy::Keep)?;
+    assert_eq!(content.lines().count(), 5);
+    assert_eq!(skipped, 0);
+    
+    // 跳过字段数不一致的行并计数
+    let (content, skipped) = export(RaggedPolicy::Skip)?;
+    assert_eq!(content.lines().collect::<Vec<_>>(), vec!["id,name,city", "1,Alice,Beijing", "4,Dave,Shenzhen"]);
+    assert_eq!(skipped, 2);
+    
+    // 补齐短行、截断长行
+    let (content, skipped) = export(RaggedPolicy::Pad)?;
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec!["id,name,city", "1,Alice,Beijing", "2,Bob,", "3,Carol,Shanghai", "4,Dave,Shenzhen"]
+    );
+    assert_eq!(skipped, 0);
+    
+    // 报错时给出第一个不一致行的行号（从1开始）
+    match export(RaggedPolicy::Error) {
+        Err(CsvError::ParseAt { row, .. }) => assert_eq!(row, 2),
+        other => panic!("expected ParseAt error, got {:?}", other.map(|(c, _)| c)),
+    }
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_export_nested_json() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_nested.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "a.b,a.c,d")?;
+    writeln!(file, "1,x,true")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = ExportOptions::new(ExportFormat::Json).with_nested(true);
+    let mut buffer: Vec<u8> = Vec::new();
+    Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+    
+    let value: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    assert_eq!(value, serde_json::json!([{"a": {"b": 1, "c": "x"}, "d": true}]));
+    
+    // 默认仍为平铺的键
+    let mut buffer: Vec<u8> = Vec::new();
+    Exporter::new(&reader, ExportOptions::new(ExportFormat::Json)).export_to_writer(&mut buffer)?;
+    let value: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    assert_eq!(value[0]["a.b"], 1);
+    
+    // 清理
+    fs::remove_file(&test_file).ok();
+    let idx = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    fs::remove_file(&idx).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_parallel_csv_export_matches_sequential() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_parallel.csv");
+    let mut file = std::io::BufWriter::new(File::create(&test_file)?);
+    writeln!(file, "id,name,note")?;
+    for i in 0..65_000 {
+        // 包含需要加引号的字段和缺少字段的行
+        match i % 7 {
+            0 => writeln!(file, "{},\"Name, {}\",\"say \"\"hi\"\"\"", i, i)?,
+            3 => writeln!(file, "{},Short", i)?,
+            _ => writeln!(file, "{},Name {},note\t{}", i, i, i % 10)?,
+        }
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    let export = |options: ExportOptions| -> Result<(Vec<u8>, usize)> {
+        let mut buffer = Vec::new();
+        let stats = Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+        Ok((buffer, stats.rows_exported))
+    };
+    let filter = || SearchOptions::new(SearchPattern::text("Name 1", true));
+    
+    let cases = [
+        ExportOptions::new(ExportFormat::Csv),
+        ExportOptions::new(ExportFormat::Tsv).with_columns(vec![2, 0]),
+        ExportOptions::new(ExportFormat::Csv).with_row_range(19_990, 41_234).with_headers(false),
+        ExportOptions::new(ExportFormat::Csv).with_search_filter(filter()),
+        ExportOptions::new(ExportFormat::Csv)
+            .with_search_filter(filter().with_max_results(30_000))
+            .with_row_range(100, 25_000),
+    ];
+    for options in cases {
+        let (sequential, sequential_rows) = export(options.clone().with_parallel_min_bytes(u64::MAX))?;
+        let (parallel, parallel_rows) = export(options.clone().with_parallel_min_bytes(0))?;
+        assert_eq!(parallel_rows, sequential_rows, "{:?}", options);
+        assert!(parallel == sequential, "并行导出与顺序导出不一致: {:?}", options);
+        assert!(sequential_rows > 0);
+    }
+    
+    fs::remove_file(&test_file).ok();
+    fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_search_to_file_streams_matches() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_to_file.csv");
+    let output = std::env::temp_dir().join("test_search_to_file.jsonl");
+    let mut file = std::io::BufWriter::new(File::create(&test_file)?);
+    writeln!(file, "id,name,status")?;
+    for i in 0..200_000 {
+        writeln!(file, "{},user{},{}", i, i, if i % 4 == 0 { "inactive" } else { "active" })?;
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    let filter = SearchOptions::new(SearchPattern::text("active", true)).with_columns(vec![2]);
+    
+    // 每一行都匹配：逐行写出，结果与逐个访问匹配的行一致
+    let stats = reader.search_to_file(&filter, &output, ExportOptions::new(ExportFormat::JsonLines))?;
+    assert_eq!(stats.rows_exported, 200_000);
+    let content = fs::read_to_string(&output)?;
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 200_000);
+    assert_eq!(lines[0], r#"{"id":0,"name":"user0","status":"inactive"}"#);
+    assert_eq!(lines[199_999], r#"{"id":199999,"name":"user199999","status":"active"}"#);
+    
+    // 行范围按匹配的顺序计算
+    let inactive = SearchOptions::new(SearchPattern::regex("^inactive$", true)?).with_columns(vec![2]);
+    let export_options = ExportOptions::new(ExportFormat::Csv).with_row_range(10, 13).with_headers(false);
+    reader.search_to_file(&inactive, &output, export_options)?;
+    assert_eq!(fs::read_to_string(&output)?, "40,user40,inactive\n44,user44,inactive\n48,user48,inactive\n");
+    
+    // 逐个访问匹配的行，不保留结果；返回false时停止扫描
+    let mut visited = 0;
+    let cancelled = reader.for_each_match(&filter, |row, matches, record| {
+        assert_eq!(record.fields[0], row.to_string());
+        assert_eq!(matches[0].column, 2);
+        visited += 1;
+        Ok(visited < 1_000)
+    })?;
+    assert!(!cancelled);
+    assert_eq!(visited, 1_000);
+    
+    // 访问时的错误会中止扫描并返回
+    let result = reader.for_each_match(&filter, |row, _, _| {
+        if row == 5 {
+            return Err(CsvError::Format("stop".to_string()));
+        }
+        Ok(true)
+    });
+    assert!(matches!(result, Err(CsvError::Format(_))));
+    
+    fs::remove_file(&test_file).ok();
+    fs::remove_file(&output).ok();
+    fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_export_fast_opened_reader_reads_all_rows() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_export_fast_opened.csv");
+    common::create_underestimated_csv(&test_file);
+    
+    let options = ReaderOptions::new().with_granularity(100).with_persist_index(false);
+    let reader = CsvReader::open_fast_with_options(&test_file, &options)?;
+    assert!(!reader.is_index_complete());
+    assert!(reader.info().total_rows < 40_000);
+    
+    let export = |options: ExportOptions| -> Result<(String, usize)> {
+        let mut buffer = Vec::new();
+        let stats = Exporter::new(&reader, options).export_to_writer(&mut buffer)?;
+        Ok((String::from_utf8(buffer).unwrap(), stats.rows_exported))
+    };
+    
+    // 顺序导出和并行导出都读取到文件末尾
+    for parallel_min_bytes in [u64::MAX, 0] {
+        let options = ExportOptions::new(ExportFormat::Csv)
+            .with_headers(false)
+            .with_parallel_min_bytes(parallel_min_bytes);
+        let (content, rows) = export(options.clone())?;
+        assert_eq!(rows, 40_000);
+        assert!(content.lines().last().unwrap().starts_with("39999,wide"));
+        
+        // 行范围超出估算的总行数时仍按实际行读取
+        let (content, rows) = export(options.with_row_range(39_990, 50_000))?;
+        assert_eq!(rows, 10);
+        assert!(content.lines().next().unwrap().starts_with("39990,wide"));
+    }
+    
+    // 导入SQLite时同样逐行读取到文件末尾
+    let result = Exporter::new(&reader, ExportOptions::new(ExportFormat::Sqlite))
+        .query_sql("SELECT COUNT(*), MAX(CAST(id AS INTEGER)) FROM data")?;
+    assert_eq!(result.rows, vec![vec!["40000".to_string(), "39999".to_string()]]);
+    
+    fs::remove_file(&test_file).ok();
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! 索引无法写到CSV文件旁边时改存到缓存目录
+//!
+//! 测试修改 `XDG_CACHE_HOME`，单独放在一个测试程序中，避免与其他读取缓存目录的测试并发
+
+use csv_tool::csv::{CsvReader, IndexLocation, RowIndex};
+use csv_tool::error::Result;
+use std::fs::File;
+use std::io::Write;
+
+#[cfg(unix)]
+#[test]
+fn test_index_falls_back_to_cache_dir() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    
+    let base = std::env::temp_dir().join(format!("test_index_fallback_{}", std::process::id()));
+    let data_dir = base.join("readonly");
+    let cache_dir = base.join("cache");
+    std::fs::create_dir_all(&data_dir)?;
+    let test_file = data_dir.join("data.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,name")?;
+    for i in 1..=50 {
+        writeln!(file, "{},Name {}", i, i)?;
+    }
+    drop(file);
+    std::fs::set_permissions(&data_dir, std::fs::Permissions::from_mode(0o555))?;
+    
+    // 以 root 运行时目录权限不生效，无法模拟
+    let probe = data_dir.join("probe");
+    if File::create(&probe).is_ok() {
+        std::fs::remove_file(&probe).ok();
+        std::fs::set_permissions(&data_dir, std::fs::Permissions::from_mode(0o755))?;
+        std::fs::remove_dir_all(&base).ok();
+        return Ok(());
+    }
+    
+    std::env::set_var("XDG_CACHE_HOME", &cache_dir);
+    let cached = RowIndex::index_file_path_in(&test_file, &IndexLocation::CacheDir);
+    assert!(cached.starts_with(&cache_dir));
+    
+    // 同目录无权写入，默认的 Sidecar 位置改存到缓存目录
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    assert_eq!(reader.info().total_rows, 50);
+    assert!(!RowIndex::index_file_path(&test_file).exists());
+    assert!(cached.exists());
+    assert_eq!(reader.index_path(), Some(cached.clone()));
+    
+    // 再次打开时从缓存目录加载
+    let (_, metadata) = RowIndex::load_from_file(&cached)?;
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    assert_eq!(reader.read_rows(0, 1)?[0].fields[0], "1");
+    let (_, reloaded) = RowIndex::load_from_file(&cached)?;
+    assert_eq!(reloaded.build_time, metadata.build_time);
+    
+    // 清理
+    std::fs::set_permissions(&data_dir, std::fs::Permissions::from_mode(0o755))?;
+    std::fs::remove_dir_all(&base).ok();
+    Ok(())
+}
@@ -1,7 +1,7 @@
-use csv_tool::csv::{CsvReader, RowIndex, IndexMetadata};
-use csv_tool::error::Result;
+use csv_tool::csv::{CsvReader, ReaderOptions, RowIndex, IndexLocation, IndexMetadata, LineFormat, CURRENT_INDEX_VERSION};
+use csv_tool::error::{CsvError, Result};
 use std::fs::File;
-use std::io::Write;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -85,6 +85,215 @@ fn test_index_invalid_after_file_modification() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_index_invalid_after_in_place_edit() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_index_checksum.csv");
+    create_test_csv(&test_file, 50)?;
+    
+    // 打开文件并构建索引
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    assert_eq!(reader.read_rows(0, 1)?[0].fields[1], "Name 1");
+    drop(reader);
+    
+    let index_path = RowIndex::index_file_path(&test_file);
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert!(metadata.checksum.is_some());
+    assert!(RowIndex::is_index_valid(&test_file, &metadata));
+    
+    // 原地修改一个字节（"Name 1" -> "Mame 1"），大小不变并还原修改时间
+    let mtime = std::fs::metadata(&test_file)?.modified()?;
+    let mut file = std::fs::OpenOptions::new().write(true).open(&test_file)?;
+    file.seek(SeekFrom::Start("id,name,age\n1,".len() as u64))?;
+    file.write_all(b"M")?;
+    file.set_modified(mtime)?;
+    drop(file);
+    assert_eq!(std::fs::metadata(&test_file)?.modified()?, mtime);
+    
+    // 大小和修改时间一致，但校验和不匹配
+    assert!(!RowIndex::is_index_valid(&test_file, &metadata));
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    assert_eq!(reader.read_rows(0, 1)?[0].fields[1], "Mame 1");
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_verify_index_detects_middle_edit() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_index_verify.csv");
+    create_test_csv(&test_file, 20_000)?;
+    let options = ReaderOptions::new().with_granularity(100);
+    
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.read_rows(9_999, 10_000)?[0].fields[1], "Name 10000");
+    drop(reader);
+    
+    // 不要求完整校验时不计算整个文件的哈希
+    let index_path = RowIndex::index_file_path(&test_file);
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert!(metadata.full_checksum.is_none());
+    assert!(!RowIndex::verify_full_checksum(&test_file, &metadata));
+    
+    // 完整校验时没有哈希的索引被重建，并记录整个文件的哈希
+    let options = options.with_verify_index(true);
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.read_rows(9_999, 10_000)?[0].fields[1], "Name 10000");
+    drop(reader);
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert!(metadata.full_checksum.is_some());
+    assert!(RowIndex::verify_full_checksum(&test_file, &metadata));
+    
+    // 修改文件中间的一个字节（"Name 10000" -> "Mame 10000"），大小不变并还原修改时间
+    let content = std::fs::read_to_string(&test_file)?;
+    let offset = content.find("\n10000,Name").unwrap() + "\n10000,".len();
+    assert!(offset > 64 * 1024 && offset < content.len() - 64 * 1024);
+    let mtime = std::fs::metadata(&test_file)?.modified()?;
+    let mut file = std::fs::OpenOptions::new().write(true).open(&test_file)?;
+    file.seek(SeekFrom::Start(offset as u64))?;
+    file.write_all(b"M")?;
+    file.set_modified(mtime)?;
+    drop(file);
+    
+    // 只比较开头和末尾时发现不了，完整校验可以
+    assert!(RowIndex::is_index_valid(&test_file, &metadata));
+    assert!(!RowIndex::verify_full_checksum(&test_file, &metadata));
+    
+    // 完整校验失败后重建索引
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.read_rows(9_999, 10_000)?[0].fields[1], "Mame 10000");
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert!(RowIndex::verify_full_checksum(&test_file, &metadata));
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_index_refresh_after_append() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_index_refresh.csv");
+    create_test_csv(&test_file, 1_000_000)?;
+    
+    // 首次打开，完整构建索引
+    let start = std::time::Instant::now();
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    let build_duration = start.elapsed();
+    assert_eq!(reader.info().total_rows, 1_000_000);
+    drop(reader);
+    
+    let index_path = RowIndex::index_file_path(&test_file);
+    let (_, old_metadata) = RowIndex::load_from_file(&index_path)?;
+    
+    // 在末尾追加10000行
+    let mut file = std::fs::OpenOptions::new().append(true).open(&test_file)?;
+    for i in 1_000_001..=1_010_000 {
+        writeln!(file, "{},Name {},{}", i, i, 20 + i % 50)?;
+    }
+    drop(file);
+    std::thread::sleep(Duration::from_millis(100));
+    
+    // 原有内容未变，可以增量更新
+    let (mut index, _) = RowIndex::load_from_file(&index_path)?;
+    assert!(!RowIndex::is_index_valid(&test_file, &old_metadata));
+    let data = std::fs::read(&test_file)?;
+    assert!(index.refresh(&test_file, &data, LineFormat::detect(&data).with_skip_empty(true), &old_metadata)?);
+    assert_eq!(index.total_rows(), 1_010_000);
+    
+    // 重新打开时只扫描新增部分
+    let start = std::time::Instant::now();
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    let refresh_duration = start.elapsed();
+    println!("完整构建耗时: {:?}，增量更新耗时: {:?}", build_duration, refresh_duration);
+    assert!(refresh_duration < build_duration, "增量更新应该比完整构建快");
+    assert_eq!(reader.info().total_rows, 1_010_000);
+    
+    // 最后一页的数据正确
+    let page = reader.read_page(1_010_000 / 100 - 1, 100)?;
+    assert_eq!(page.len(), 100);
+    assert_eq!(page[0].fields[0], "1009901");
+    assert_eq!(page[99].fields[..], ["1010000", "Name 1010000", "20"]);
+    
+    // 更新后的索引已保存，再次打开直接加载
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert!(RowIndex::is_index_valid(&test_file, &metadata));
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_no_index_file_when_persistence_disabled() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_index_no_persist.csv");
+    create_test_csv(&test_file, 100)?;
+    let index_path = RowIndex::index_file_path(&test_file);
+    std::fs::remove_file(&index_path).ok();
+    
+    let options = ReaderOptions::new()
+        .with_granularity(10)
+        .with_column_index(Some(1))
+        .with_persist_index(false);
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.info().total_rows, 100);
+    assert!(reader.column_index(1).is_some());
+    assert_eq!(reader.read_rows(41, 42)?[0].fields[0], "42");
+    
+    let mut reader = CsvReader::open_fast_with_options(&test_file, &options)?;
+    reader.build_index_async().wait();
+    
+    assert!(!index_path.exists(), "关闭持久化时不应创建索引文件");
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_index_in_custom_dir() -> Result<()> {
+    let base = std::env::temp_dir().join("test_index_custom_dir");
+    let index_dir = base.join("indexes");
+    std::fs::create_dir_all(base.join("a"))?;
+    std::fs::create_dir_all(base.join("b"))?;
+    let file_a = base.join("a").join("data.csv");
+    let file_b = base.join("b").join("data.csv");
+    create_test_csv(&file_a, 20)?;
+    create_test_csv(&file_b, 30)?;
+    
+    // 不同目录下的同名文件使用不同的索引文件
+    let location = IndexLocation::Custom(index_dir.clone());
+    let path_a = RowIndex::index_file_path_in(&file_a, &location);
+    let path_b = RowIndex::index_file_path_in(&file_b, &location);
+    assert_ne!(path_a, path_b);
+    assert!(path_a.starts_with(&index_dir));
+    
+    let options = ReaderOptions::new()
+        .with_granularity(10)
+        .with_index_location(location);
+    let reader = CsvReader::open_with_options(&file_a, &options)?;
+    assert_eq!(reader.index_path(), Some(path_a.clone()));
+    assert!(!RowIndex::index_file_path(&file_a).exists());
+    
+    // 元数据记录原始CSV路径，用于校验
+    let (_, metadata) = RowIndex::load_from_file(&path_a)?;
+    assert!(RowIndex::is_index_valid(&file_a, &metadata));
+    assert!(!RowIndex::is_index_valid(&file_b, &metadata));
+    
+    // 再次打开时复用该目录下的索引（构建时间不变）
+    let reader = CsvReader::open_with_options(&file_a, &options)?;
+    assert_eq!(reader.read_rows(14, 15)?[0].fields[0], "15");
+    let (_, reloaded) = RowIndex::load_from_file(&path_a)?;
+    assert_eq!(reloaded.build_time, metadata.build_time);
+    
+    // 清理
+    std::fs::remove_dir_all(&base).ok();
+    Ok(())
+}
+
 #[test]
 fn test_index_metadata() -> Result<()> {
     let test_file = std::env::temp_dir().join("test_metadata.csv");
@@ -103,7 +312,7 @@ fn test_index_metadata() -> Result<()> {
     
     assert_eq!(index_metadata.csv_size, file_size);
     assert_eq!(index_metadata.granularity, 10);
-    assert_eq!(index_metadata.index_version, 1);
+    assert_eq!(index_metadata.index_version, CURRENT_INDEX_VERSION);
     
     // 清理
     std::fs::remove_file(&test_file).ok();
@@ -121,3 +330,112 @@ fn test_index_file_path() {
     assert_eq!(index_path2, PathBuf::from("data/test.csv.idx"));
 }
 
+#[test]
+fn test_migrate_v1_index() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_index_migrate_v1.csv");
+    create_test_csv(&test_file, 100)?;
+    let index_path = RowIndex::index_file_path(&test_file);
+    
+    // 按 v1 格式手工写入索引：粒度10，offsets[i] 为第 10*(i+1)-1 行的起始位置
+    let content = std::fs::read(&test_file)?;
+    let mut line_starts = Vec::new();
+    let mut pos = content.iter().position(|&b| b == b'\n').unwrap() + 1;
+    while pos < content.len() {
+        line_starts.push(pos as u64);
+        pos += content[pos..].iter().position(|&b| b == b'\n').unwrap() + 1;
+    }
+    let row_numbers: Vec<usize> = (1..=10).map(|i| i * 10).collect();
+    let offsets: Vec<u64> = row_numbers.iter().map(|&n| line_starts[n - 1]).collect();
+    
+    let file_metadata = std::fs::metadata(&test_file)?;
+    let build_time = std::time::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    // v1 元数据: (csv_path, csv_size, csv_mtime, index_version, build_time, granularity)
+    let metadata_v1 = bincode::serialize(&(
+        &test_file,
+        file_metadata.len(),
+        file_metadata.modified()?,
+        1u32,
+        build_time,
+        10usize,
+    )).unwrap();
+    // v1 索引: (offsets, row_numbers, granularity, total_rows, is_complete, indexed_bytes)
+    let index_v1 = bincode::serialize(&(
+        &offsets,
+        &row_numbers,
+        10usize,
+        100usize,
+        true,
+        file_metadata.len(),
+    )).unwrap();
+    let mut file = File::create(&index_path)?;
+    file.write_all(&(metadata_v1.len() as u64).to_le_bytes())?;
+    file.write_all(&metadata_v1)?;
+    file.write_all(&index_v1)?;
+    drop(file);
+    
+    // 加载时升级到当前版本
+    let (index, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert_eq!(metadata.index_version, CURRENT_INDEX_VERSION);
+    assert_eq!(metadata.build_time, build_time);
+    assert!(metadata.has_headers);
+    assert!(!metadata.skips_empty_lines);
+    assert!(RowIndex::is_index_valid(&test_file, &metadata));
+    assert_eq!(index.total_rows(), 100);
+    assert_eq!(index.index_count(), 10);
+    
+    // 索引文件已按当前版本写回
+    let bytes = std::fs::read(&index_path)?;
+    let metadata_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+    let rewritten: IndexMetadata = bincode::deserialize(&bytes[8..8 + metadata_len]).unwrap();
+    assert_eq!(rewritten.index_version, CURRENT_INDEX_VERSION);
+    
+    // 文件中没有空行，升级后的索引可以直接使用，不会重建（构建时间不变）
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let rows = reader.read_rows(55, 57)?;
+    assert_eq!(rows[0].fields[0], "56");
+    assert_eq!(rows[1].fields[0], "57");
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert_eq!(metadata.build_time, build_time);
+    assert!(metadata.skips_empty_lines);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_reject_future_index_version() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_index_future_version.csv");
+    create_test_csv(&test_file, 100)?;
+    let index_path = RowIndex::index_file_path(&test_file);
+    
+    // 写入版本号比当前更新的索引
+    let file_metadata = std::fs::metadata(&test_file)?;
+    let metadata = bincode::serialize(&(
+        &test_file,
+        file_metadata.len(),
+        file_metadata.modified()?,
+        CURRENT_INDEX_VERSION + 1,
+    )).unwrap();
+    let mut file = File::create(&index_path)?;
+    file.write_all(&(metadata.len() as u64).to_le_bytes())?;
+    file.write_all(&metadata)?;
+    drop(file);
+    
+    match RowIndex::load_from_file(&index_path) {
+        Err(CsvError::IndexFile(msg)) => assert!(msg.contains("更新版本"), "{}", msg),
+        other => panic!("应该拒绝未知的新版本索引: {:?}", other.map(|(_, m)| m.index_version)),
+    }
+    
+    // 打开时重建为当前版本
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    assert_eq!(reader.info().total_rows, 100);
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert_eq!(metadata.index_version, CURRENT_INDEX_VERSION);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
@@ -1,8 +1,9 @@
-use csv_tool::csv::CsvReader;
+use csv_tool::csv::{parse_preview, AccessPattern, sort_csv_data, CsvEditor, CsvReader, DataType, Delimiter, IndexMode, LineEnding, ReaderOptions, RowIndex, SearchOptions, SearchPattern, SortKey, SortOptions, SortOrder, TextEncoding, WriteOptions};
 use csv_tool::error::Result;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 fn create_test_csv(path: &PathBuf, rows: usize) -> Result<()> {
     let mut file = File::create(path)?;
@@ -23,7 +24,7 @@ fn test_basic_read() -> Result<()> {
     let test_file = std::env::temp_dir().join("test_basic.csv");
     create_test_csv(&test_file, 100)?;
     
-    let mut reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
     let info = reader.info();
     
     assert_eq!(info.total_rows, 100);
@@ -44,7 +45,7 @@ fn test_page_access() -> Result<()> {
     let test_file = std::env::temp_dir().join("test_pages.csv");
     create_test_csv(&test_file, 100)?;
     
-    let mut reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
     
     // 读取第0页
     let page0 = reader.read_page(0, 20)?;
@@ -63,12 +64,75 @@ fn test_page_access() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_concurrent_page_reads() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_concurrent_reads.csv");
+    create_test_csv(&test_file, 1000)?;
+    
+    // 读取只需要 &self，同一个读取器可以在多个线程间共享
+    let reader = Arc::new(CsvReader::open(&test_file, true, b',', 10)?.with_cache_capacity(4));
+    let handles: Vec<_> = (0..8)
+        .map(|page| {
+            let reader = Arc::clone(&reader);
+            std::thread::spawn(move || {
+                let rows = reader.read_page(page, 100).unwrap();
+                assert_eq!(rows.len(), 100);
+                for (i, row) in rows.iter().enumerate() {
+                    assert_eq!(row.fields[0], (page * 100 + i + 1).to_string());
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    
+    // 各线程写入的是同一个缓存
+    assert_eq!(reader.cache_len(), 4);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_page_cache_capacity() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_page_cache.csv");
+    create_test_csv(&test_file, 100)?;
+    
+    // 容量为3时最多缓存3页
+    let options = ReaderOptions::new().with_granularity(10).with_persist_index(false);
+    let reader = CsvReader::open_with_options(&test_file, &options.clone().with_cache_pages(3))?;
+    assert_eq!(reader.cache_capacity(), 3);
+    for page in 0..5 {
+        reader.read_page(page, 20)?;
+    }
+    assert_eq!(reader.cache_len(), 3);
+    
+    // 关闭缓存时不缓存任何页面
+    let reader = CsvReader::open_with_options(&test_file, &options.with_cache_pages(0))?;
+    assert_eq!(reader.read_page(4, 20)?.len(), 20);
+    assert_eq!(reader.cache_len(), 0);
+    
+    // 打开后调整容量
+    let reader = reader.with_cache_capacity(20);
+    for page in 0..5 {
+        reader.read_page(page, 20)?;
+    }
+    assert_eq!(reader.cache_len(), 5);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
 #[test]
 fn test_index_seek() -> Result<()> {
     let test_file = std::env::temp_dir().join("test_index.csv");
     create_test_csv(&test_file, 1000)?;
     
-    let mut reader = CsvReader::open(&test_file, true, b',', 100)?;
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
     
     // 测试跳转到中间页面
     let page = reader.read_page(25, 20)?;
@@ -79,6 +143,87 @@ fn test_index_seek() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_read_rows_range() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_read_rows.csv");
+    create_test_csv(&test_file, 1000)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
+    
+    // 跨越索引点的任意范围
+    let rows = reader.read_rows(452, 461)?;
+    assert_eq!(rows.len(), 9);
+    assert_eq!(rows[0].fields[0], "453");
+    assert_eq!(rows[8].fields[0], "461");
+    
+    // 结束行超出总行数时截断
+    let tail = reader.read_rows(995, 2000)?;
+    assert_eq!(tail.len(), 5);
+    assert_eq!(tail[4].fields[0], "1000");
+    
+    // 起始行超出总行数时返回空
+    assert!(reader.read_rows(1000, 1010)?.is_empty());
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_get_row() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_get_row.csv");
+    create_test_csv(&test_file, 1000)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
+    
+    let first = reader.get_row(0)?.unwrap();
+    assert_eq!(first.fields, vec!["1", "Name 1", "21", "City 1"]);
+    
+    // 不在索引点上的中间行
+    let middle = reader.get_row(537)?.unwrap();
+    assert_eq!(middle.fields[0], "538");
+    assert_eq!(middle.fields[1], "Name 538");
+    
+    let last = reader.get_row(999)?.unwrap();
+    assert_eq!(last.fields[0], "1000");
+    
+    // 超出总行数时返回None
+    assert!(reader.get_row(1000)?.is_none());
+    
+    // 单行读取不经过页面缓存
+    assert_eq!(reader.cache_len(), 0);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_dense_index() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_dense_index.csv");
+    create_test_csv(&test_file, 1000)?;
+    
+    let options = ReaderOptions::new().with_index_mode(IndexMode::Dense);
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.index().mode(), IndexMode::Dense);
+    assert_eq!(reader.index().index_count(), 1000);
+    
+    let rows = reader.read_rows(777, 779)?;
+    assert_eq!(rows[0].fields[0], "778");
+    assert_eq!(rows[1].fields[0], "779");
+    
+    // 缓存的稠密索引不会被稀疏模式复用
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
+    assert_eq!(reader.index().mode(), IndexMode::Sparse);
+    assert_eq!(reader.index().index_count(), 10);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
 #[test]
 fn test_quoted_fields() -> Result<()> {
     let test_file = std::env::temp_dir().join("test_quoted.csv");
@@ -87,7 +232,7 @@ fn test_quoted_fields() -> Result<()> {
     writeln!(file, "col1,col2,col3")?;
     writeln!(file, "\"quoted,field\",normal,\"another\"\"quote\"\"\"")?;
     
-    let mut reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
     let rows = reader.read_page(0, 10)?;
     
     assert_eq!(rows.len(), 1);
@@ -110,3 +255,597 @@ fn test_quoted_fields() -> Result<()> {
     Ok(())
 }
 
+
+#[test]
+fn test_multi_byte_delimiter() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_multi_byte_delimiter.csv");
+    std::fs::write(&test_file, "id::name::city\n1::Alice::Beijing\n2::\"Bob::Jr\"::Shanghai\n3::a:b::\n")?;
+    
+    let options = ReaderOptions::new()
+        .with_delimiter(Delimiter::new(b"::"))
+        .with_persist_index(false);
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.headers(), ["id", "name", "city"]);
+    assert_eq!(reader.info().total_rows, 3);
+    
+    let rows = reader.read_rows(0, 3)?;
+    assert_eq!(rows[0].fields, vec!["1", "Alice", "Beijing"]);
+    assert_eq!(rows[1].fields, vec!["2", "Bob::Jr", "Shanghai"]);
+    assert_eq!(rows[2].fields, vec!["3", "a:b", ""]);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_utf16le_input() -> Result<()> {
+    use csv_tool::csv::{SearchOptions, SearchPattern, TextEncoding};
+
+    let test_file = std::env::temp_dir().join("test_utf16le.csv");
+    let mut content = String::from("id,名称,city\r\n");
+    for i in 1..=30 {
+        content.push_str(&format!("{},名字{},City {}\r\n", i, i, i % 3));
+    }
+    
+    // 写入 UTF-16LE（带BOM）
+    let mut bytes = vec![0xFF, 0xFE];
+    for unit in content.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+    std::fs::write(&test_file, &bytes)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let info = reader.info().clone();
+    
+    assert_eq!(info.encoding, TextEncoding::Utf16Le);
+    assert_eq!(info.headers, vec!["id", "名称", "city"]);
+    assert_eq!(info.total_rows, 30);
+    
+    // 分页
+    let page = reader.read_page(2, 10)?;
+    assert_eq!(page.len(), 10);
+    assert_eq!(page[0].fields[0].as_ref(), "21");
+    assert_eq!(page[0].fields[1].as_ref(), "名字21");
+    
+    // 搜索
+    let options = SearchOptions::new(SearchPattern::text("名字7", true));
+    let results = reader.search(&options)?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].row_number, 6);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    let index_path = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_gzip_input() -> Result<()> {
+    use csv_tool::csv::{CsvEditor, RowIndex, SearchOptions, SearchPattern};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let test_file = std::env::temp_dir().join("test_gzip_input.csv.gz");
+    let mut encoder = GzEncoder::new(File::create(&test_file)?, Compression::default());
+    writeln!(encoder, "id,name,age,city")?;
+    for i in 1..=50 {
+        writeln!(encoder, "{},Name {},{},City {}", i, i, 20 + i % 50, i % 10)?;
+    }
+    encoder.finish()?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let info = reader.info().clone();
+    
+    assert!(reader.is_compressed());
+    assert_eq!(info.total_rows, 50);
+    assert_eq!(info.headers, vec!["id", "name", "age", "city"]);
+    assert_eq!(info.file_size, std::fs::metadata(&test_file)?.len());
+    assert!(info.decompressed_size.unwrap() > info.file_size);
+    
+    let page = reader.read_page(1, 20)?;
+    assert_eq!(page[0].fields[1].as_ref(), "Name 21");
+    
+    let options = SearchOptions::new(SearchPattern::text("Name 42", true));
+    assert_eq!(reader.search(&options)?.len(), 1);
+    
+    // 索引文件以原始 .gz 路径为准，并且仍然有效
+    let index_path = RowIndex::index_file_path(&test_file);
+    assert!(index_path.exists());
+    let (_, metadata) = RowIndex::load_from_file(&index_path)?;
+    assert!(RowIndex::is_index_valid(&test_file, &metadata));
+    
+    // 释放读取器后临时文件被删除
+    let temp_path = reader.decompressed_path().unwrap().to_path_buf();
+    drop(reader);
+    assert!(!temp_path.exists());
+    
+    // 不允许编辑压缩文件
+    assert!(CsvEditor::open(&test_file, true, b',', 10).is_err());
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_column_count_validation() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_validation.csv");
+    std::fs::write(&test_file, "id,name,age\n1,Alice,30\n2,Bob\n3,\"Carol, Jr.\",40\n4,Dave,50,extra\n")?;
+    
+    // 仅校验：可以打开，并返回报告
+    let options = ReaderOptions::new().with_validation(true);
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    let report = reader.validation_report().unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(report.violation_count, 2);
+    assert_eq!(report.violations[0].row, 1);
+    assert_eq!(report.violations[0].field_count, 2);
+    assert_eq!(report.violations[1].row, 3);
+    drop(reader);
+    
+    // 严格模式：指出第一条不一致的行
+    let options = ReaderOptions::new().with_strict(true);
+    let err = CsvReader::open_with_options(&test_file, &options).err().unwrap();
+    assert!(err.to_string().contains("第 2 行有 2 个字段，应为 3 个"));
+    
+    // 默认不校验
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    assert!(reader.validation_report().is_none());
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_background_index_progress() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_background_progress.csv");
+    // 约8MB，超过快速打开时直接完整索引的阈值
+    create_test_csv(&test_file, 200_000)?;
+    let index_path = RowIndex::index_file_path(&test_file);
+    std::fs::remove_file(&index_path).ok();
+    
+    let options = ReaderOptions::new().with_granularity(1000).with_persist_index(false);
+    let mut reader = CsvReader::open_fast_with_options(&test_file, &options)?;
+    assert!(!reader.is_index_complete());
+    
+    let handle = reader.build_index_async();
+    let mut samples = vec![handle.progress()];
+    while !handle.is_finished() {
+        samples.push(handle.progress());
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    samples.push(handle.progress());
+    
+    // 进度单调递增，结束时为100%
+    assert!(samples.windows(2).all(|w| w[0] <= w[1]), "{:?}", samples);
+    assert_eq!(*samples.last().unwrap(), 100.0);
+    
+    let (index, ok) = handle.wait().unwrap();
+    assert!(ok);
+    reader.update_index(index);
+    assert!(reader.is_index_complete());
+    assert_eq!(reader.info().total_rows, 200_000);
+    assert_eq!(reader.read_rows(199_999, 200_000)?[0].fields[0], "200000");
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_open_with_progress() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_open_progress.csv");
+    // 约8MB，每1MB汇报一次进度
+    create_test_csv(&test_file, 200_000)?;
+    let index_path = RowIndex::index_file_path(&test_file);
+    std::fs::remove_file(&index_path).ok();
+    
+    let options = ReaderOptions::new().with_granularity(1000);
+    let mut percentages = Vec::new();
+    let reader = CsvReader::open_with_progress(&test_file, &options, |percent, processed, total| {
+        assert!(processed <= total);
+        percentages.push(percent);
+    })?;
+    assert_eq!(reader.info().total_rows, 200_000);
+    
+    assert!(percentages.len() > 2, "应多次汇报进度: {:?}", percentages);
+    assert!(percentages.windows(2).all(|w| w[0] <= w[1]), "进度应单调递增: {:?}", percentages);
+    assert_eq!(percentages.last(), Some(&100.0));
+    drop(reader);
+    
+    // 使用缓存的索引时只汇报一次完成
+    let mut percentages = Vec::new();
+    CsvReader::open_with_progress(&test_file, &options, |percent, _, _| percentages.push(percent))?;
+    assert_eq!(percentages, vec![100.0]);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&index_path).ok();
+    Ok(())
+}
+
+#[test]
+fn test_preview_matches_paged_read() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_preview_matches_page.csv");
+    let content = "\u{FEFF}id,\"full name\",note\r\n\
+        1,\"Smith, John\",\"said \"\"hi\"\"\"\r\n\
+        2,\"\",plain\r\n\
+        3,\"\"\"quoted\"\"\",\"a,b,c\"\r\n\
+        4,last,\r\n";
+    std::fs::write(&test_file, content)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 2)?;
+    let data = std::fs::read(&test_file)?;
+    let preview = parse_preview(&data, 10, &Delimiter::Byte(b','), true);
+    
+    // 表头和各行字段与分页读取的结果一致（引号、引号内的逗号、CRLF）
+    assert_eq!(preview.headers, reader.headers());
+    let page = reader.read_page(0, 10)?;
+    assert_eq!(preview.rows.len(), page.len());
+    for (previewed, paged) in preview.rows.iter().zip(&page) {
+        assert_eq!(previewed.fields, paged.fields);
+    }
+    assert_eq!(preview.rows[0].fields, vec!["1", "Smith, John", "said \"hi\""]);
+    assert_eq!(preview.rows[2].fields, vec!["3", "\"quoted\"", "a,b,c"]);
+    assert!(preview.is_complete());
+    
+    // 只预览部分行时没有读完
+    let partial = parse_preview(&data, 2, &Delimiter::Byte(b','), true);
+    assert_eq!(partial.rows.len(), 2);
+    assert!(!partial.is_complete());
+    
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_row_count_becomes_exact_after_index() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_row_count_exact.csv");
+    // 约8MB，快速打开时只能估算行数
+    create_test_csv(&test_file, 200_000)?;
+    
+    let options = ReaderOptions::new().with_granularity(1000).with_persist_index(false);
+    let mut reader = CsvReader::open_fast_with_options(&test_file, &options)?;
+    assert!(!reader.is_row_count_exact());
+    let estimate = reader.row_estimate().unwrap().clone();
+    assert!(!estimate.is_exact);
+    assert!(estimate.lower_bound <= estimate.estimated_rows && estimate.estimated_rows <= estimate.upper_bound);
+    
+    let (index, ok) = reader.build_index_async().wait().unwrap();
+    assert!(ok);
+    reader.update_index(index);
+    
+    assert!(reader.is_row_count_exact());
+    assert!(reader.row_estimate().is_none());
+    assert_eq!(reader.info().total_rows, 200_000);
+    assert_eq!(reader.total_pages(1000), 200);
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_read_beyond_partial_index() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_read_beyond_partial_index.csv");
+    // 约4MB，快速打开时只索引开头的几百行
+    create_test_csv(&test_file, 100_000)?;
+    
+    let options = ReaderOptions::new().with_granularity(100).with_persist_index(false);
+    let reader = CsvReader::open_fast_with_options(&test_file, &options)?;
+    assert!(!reader.is_index_complete());
+    let indexed_rows = reader.index().total_rows();
+    assert!(indexed_rows < 1000);
+    
+    // 远超已索引区域的页按实际位置读取，而不是报错或读到错误的偏移量
+    let page = reader.read_page(1234, 50)?;
+    assert_eq!(page.len(), 50);
+    assert_eq!(page[0].fields[0], "61701");
+    assert_eq!(page[49].fields[0], "61750");
+    
+    // 跨越已索引区域边界的范围
+    let rows = reader.read_rows(indexed_rows - 2, indexed_rows + 2)?;
+    let ids: Vec<String> = rows.iter().map(|r| r.fields[0].to_string()).collect();
+    let expected: Vec<String> = (indexed_rows - 1..indexed_rows + 3).map(|i| i.to_string()).collect();
+    assert_eq!(ids, expected);
+    
+    // 不受估算的总行数限制，最后一行和文件末尾之后的行号都按实际行数处理
+    assert_eq!(reader.get_row(99_999)?.unwrap().fields[1], "Name 100000");
+    assert_eq!(reader.get_row(99_998)?.unwrap().fields[1], "Name 99999");
+    assert!(reader.get_row(100_000)?.is_none());
+    assert!(reader.read_page(2000, 50)?.is_empty());
+    assert_eq!(reader.read_rows(99_990, usize::MAX)?.len(), 10);
+    
+    // 索引构建完成后结果一致
+    let mut reader = reader;
+    let (index, ok) = reader.build_index_async().wait().unwrap();
+    assert!(ok);
+    reader.update_index(index);
+    assert_eq!(reader.read_page(1234, 50)?[0].fields[0], "61701");
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_cr_only_line_endings() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_cr_only.csv");
+    let mut content = String::from("id,name\r");
+    for i in 0..50 {
+        content.push_str(&format!("{},\"name {}\"\r", i, i));
+    }
+    std::fs::write(&test_file, &content)?;
+    
+    // 完整打开和快速打开都按 \r 分行
+    let options = ReaderOptions::new().with_granularity(10).with_persist_index(false);
+    for reader in [
+        CsvReader::open_with_options(&test_file, &options)?,
+        CsvReader::open_fast_with_options(&test_file, &options)?,
+    ] {
+        let info = reader.info();
+        assert_eq!(info.line_ending, LineEnding::Cr);
+        assert_eq!(info.headers, vec!["id", "name"]);
+        assert_eq!(info.total_rows, 50);
+        
+        let rows = reader.read_page(2, 10)?;
+        assert_eq!(rows.len(), 10);
+        assert_eq!(rows[0].fields, vec!["20", "name 20"]);
+        
+        let results = reader.search(&SearchOptions::new(SearchPattern::text("name 42", true)))?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].row_number, 42);
+    }
+    
+    // 预览与分页读取一致
+    let preview = parse_preview(content.as_bytes(), 3, &Delimiter::Byte(b','), true);
+    assert_eq!(preview.headers, vec!["id", "name"]);
+    assert_eq!(preview.rows[2].fields, vec!["2", "name 2"]);
+    
+    // 编辑保存时沿用 \r
+    let output = std::env::temp_dir().join("test_cr_only_saved.csv");
+    let mut editor = CsvEditor::open(&test_file, true, b',', 10)?;
+    editor.edit_cell(0, 1, "changed".to_string())?;
+    editor.save(&output, &WriteOptions::new())?;
+    let saved = std::fs::read_to_string(&output)?;
+    assert!(!saved.contains('\n'));
+    assert!(saved.starts_with("id,name\r0,changed\r1,name 1\r"));
+    
+    drop(editor);
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&output).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&output)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_info_reports_format() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_info_format.csv");
+    let options = ReaderOptions::new().with_persist_index(false);
+    
+    for (content, delimiter, line_ending) in [
+        ("id;name\r\n1;a\r\n", Delimiter::from(b';'), LineEnding::CrLf),
+        ("id\tname\n1\ta\n", Delimiter::from(b'\t'), LineEnding::Lf),
+        ("id||name\r1||a\r", Delimiter::new(b"||"), LineEnding::Cr),
+    ] {
+        std::fs::write(&test_file, content)?;
+        let options = options.clone().with_delimiter(&delimiter);
+        for reader in [
+            CsvReader::open_with_options(&test_file, &options)?,
+            CsvReader::open_fast_with_options(&test_file, &options)?,
+        ] {
+            let info = reader.info();
+            assert_eq!(info.delimiter, delimiter);
+            assert_eq!(info.line_ending, line_ending);
+            assert_eq!(info.encoding, TextEncoding::Utf8);
+            assert_eq!(info.headers, vec!["id", "name"]);
+        }
+    }
+    
+    // UTF-16文件报告原始编码
+    let mut utf16 = vec![0xFF, 0xFE];
+    utf16.extend("id,name\n1,a\n".encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    std::fs::write(&test_file, utf16)?;
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert_eq!(reader.info().encoding, TextEncoding::Utf16Le);
+    assert_eq!(reader.info().delimiter.as_byte(), Some(b','));
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_mixed_crlf_and_lf_line_endings() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_mixed_line_endings.csv");
+    std::fs::write(&test_file, "id,name\r\n1,a\n2,b\r\n3,c\r\n4,d\n")?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let info = reader.info();
+    assert_eq!(info.line_ending, LineEnding::CrLf);
+    assert_eq!(info.total_rows, 4);
+    
+    // 行尾的 \r 不会留在字段中
+    let rows = reader.read_rows(0, 4)?;
+    let names: Vec<&str> = rows.iter().map(|row| row.fields[1].as_ref()).collect();
+    assert_eq!(names, vec!["a", "b", "c", "d"]);
+    
+    // 明确指定行结束符时不再沿用源文件的行结束符
+    let output = std::env::temp_dir().join("test_mixed_line_endings_saved.csv");
+    let editor = CsvEditor::open(&test_file, true, b',', 10)?;
+    editor.save(&output, &WriteOptions::new().with_line_ending(LineEnding::Lf))?;
+    assert_eq!(std::fs::read_to_string(&output)?, "id,name\n1,a\n2,b\n3,c\n4,d\n");
+    
+    drop(editor);
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(&output).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_bom_never_leaks_into_first_field() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_bom_first_field.csv");
+    let first_fields = |records: &[csv_tool::csv::CsvRecord]| -> Vec<String> {
+        records.iter().map(|record| record.fields[0].to_string()).collect()
+    };
+    
+    for has_headers in [true, false] {
+        let content = if has_headers { "\u{FEFF}id,name\n1,a\n2,b\n" } else { "\u{FEFF}1,a\n2,b\n" };
+        std::fs::write(&test_file, content)?;
+        let expected = vec!["1", "2"];
+        
+        let options = ReaderOptions::new()
+            .with_headers(has_headers)
+            .with_granularity(1)
+            .with_column_index(Some(0))
+            .with_persist_index(false);
+        let reader = CsvReader::open_with_options(&test_file, &options)?;
+        if has_headers {
+            assert_eq!(reader.info().headers[0], "id");
+        }
+        assert_eq!(first_fields(&reader.read_page(0, 10)?), expected);
+        assert_eq!(first_fields(&reader.read_rows_quoted(0, 2)?), expected);
+        assert_eq!(reader.get_row(0)?.unwrap().fields[0], "1");
+        
+        let results = reader.search(&SearchOptions::new(SearchPattern::text("a", true)))?;
+        assert_eq!(results[0].record.fields[0], "1");
+        
+        // 借助列索引排序时按列索引中的偏移量解析行
+        assert!(reader.column_index(0).is_some());
+        let key = SortKey::new(0, SortOrder::Ascending, DataType::String);
+        let sorted = sort_csv_data(&reader, &SortOptions::new().add_key(key), None)?;
+        assert_eq!(sorted[0].record.fields[0], "1");
+        
+        let fast = CsvReader::open_fast_with_options(&test_file, &options)?;
+        assert_eq!(first_fields(&fast.read_page(0, 10)?), expected);
+        
+        let preview = parse_preview(content.as_bytes(), 10, &Delimiter::Byte(b','), has_headers);
+        assert_eq!(first_fields(&preview.rows), expected);
+        if has_headers {
+            assert_eq!(preview.headers[0], "id");
+        }
+
+    }
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
+
+#[test]
+fn test_last_row_without_trailing_newline() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_no_trailing_newline.csv");
+    let all = SearchOptions::new(SearchPattern::regex("", true)?);
+    
+    for (content, has_headers, expected) in [
+        ("id,name\n1,a\n2,b\n3,c", true, 3),
+        ("1,a\n2,b\n3,c", false, 3),
+        // 空行默认去掉，不计入行数
+        ("id,name\n1,a\n\n3,c", true, 2),
+        ("id,name", true, 0),
+    ] {
+        std::fs::write(&test_file, content)?;
+        let options = ReaderOptions::new()
+            .with_headers(has_headers)
+            .with_granularity(1)
+            .with_column_index(Some(1))
+            .with_persist_index(false);
+        
+        // 完整打开和快速打开对最后一行的处理一致
+        for reader in [
+            CsvReader::open_with_options(&test_file, &options)?,
+            CsvReader::open_fast_with_options(&test_file, &options)?,
+        ] {
+            let info = reader.info();
+            assert_eq!(info.total_rows, expected, "{:?}", content);
+            assert_eq!(reader.rows().count(), expected, "{:?}", content);
+            assert_eq!(reader.search(&all)?.len(), expected, "{:?}", content);
+            assert_eq!(reader.count_matches(&all)?, expected, "{:?}", content);
+            assert_eq!(reader.read_page(0, 10)?.len(), expected, "{:?}", content);
+        }
+    }
+    
+    // 最后一行可以被读取和搜索到
+    std::fs::write(&test_file, "id,name\n1,a\n2,b\n3,c")?;
+    let reader = CsvReader::open(&test_file, true, b',', 1)?;
+    assert_eq!(reader.get_row(2)?.unwrap().fields, vec!["3", "c"]);
+    let results = reader.search(&SearchOptions::new(SearchPattern::text("c", true)).with_columns(vec![1]))?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].row_number, 2);
+    
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_blank_lines_between_rows() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_blank_lines.csv");
+    std::fs::write(&test_file, "\nid,name\r\n1,a\r\n\r\n2,b\r\n  \r\n\n3,c\r\n\r\n")?;
+    let all = SearchOptions::new(SearchPattern::regex("", true)?);
+    let options = ReaderOptions::new().with_granularity(1).with_persist_index(false);
+    
+    // 默认去掉空行（包括表头前和末尾多余的空行），只有空格的行保留为数据行
+    for reader in [
+        CsvReader::open_with_options(&test_file, &options)?,
+        CsvReader::open_fast_with_options(&test_file, &options)?,
+    ] {
+        assert_eq!(reader.headers(), &["id", "name"]);
+        assert_eq!(reader.info().total_rows, 4);
+        let rows: Vec<Vec<String>> = reader.read_page(0, 10)?
+            .iter()
+            .map(|r| r.fields.iter().map(|f| f.to_string()).collect())
+            .collect();
+        assert_eq!(rows, vec![vec!["1", "a"], vec!["2", "b"], vec!["  "], vec!["3", "c"]]);
+        assert_eq!(reader.search(&all)?.len(), 4);
+        assert_eq!(reader.get_row(3)?.unwrap().fields, vec!["3", "c"]);
+    }
+    
+    // 保留空行时按物理行计数，不写索引文件
+    let keep = ReaderOptions::new()
+        .with_granularity(1)
+        .with_keep_empty_lines(true)
+        .with_persist_index(true);
+    let reader = CsvReader::open_with_options(&test_file, &keep.clone().with_headers(false))?;
+    assert_eq!(reader.info().total_rows, 9);
+    assert_eq!(reader.read_page(0, 10)?[2].fields, vec!["1", "a"]);
+    assert!(reader.index_path().is_none());
+    
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_access_hints_do_not_change_results() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_access_hints.csv");
+    create_test_csv(&test_file, 500)?;
+    let search_options = SearchOptions::new(SearchPattern::text("City 3", true));
+    
+    let plain = CsvReader::open_with_options(
+        &test_file,
+        &ReaderOptions::new().with_granularity(10).with_persist_index(false).with_access_hints(false),
+    )?;
+    let advised = CsvReader::open_with_options(
+        &test_file,
+        &ReaderOptions::new().with_granularity(10).with_persist_index(false),
+    )?;
+    advised.set_access_pattern(AccessPattern::Random);
+    
+    // 提示只影响预读，翻页、搜索和计数的结果不变
+    for page in [0, 7, 24] {
+        let expected: Vec<Vec<String>> = plain.read_page(page, 20)?.iter().map(|r| r.fields.iter().map(|f| f.to_string()).collect()).collect();
+        let actual: Vec<Vec<String>> = advised.read_page(page, 20)?.iter().map(|r| r.fields.iter().map(|f| f.to_string()).collect()).collect();
+        assert_eq!(actual, expected);
+    }
+    let expected: Vec<usize> = plain.search(&search_options)?.iter().map(|r| r.row_number).collect();
+    let actual: Vec<usize> = advised.search(&search_options)?.iter().map(|r| r.row_number).collect();
+    assert_eq!(actual, expected);
+    assert_eq!(advised.count_matches(&search_options)?, 50);
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! 打开文件管理集成测试
+
+use csv_tool::csv::{CsvReader, Delimiter, OpenFileManager, RowIndex};
+use csv_tool::error::Result;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 创建测试文件并打开，返回路径和读取器
+fn open_test_file(name: &str) -> Result<(String, Arc<CsvReader>)> {
+    let path = std::env::temp_dir().join(format!("test_manager_{}.csv", name));
+    std::fs::write(&path, "id,name\n1,a\n2,b\n")?;
+    let reader = CsvReader::open(&path, true, b',', 100)?;
+    Ok((path.to_string_lossy().to_string(), Arc::new(reader)))
+}
+
+fn cleanup(paths: &[String]) {
+    for path in paths {
+        let path = PathBuf::from(path);
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(RowIndex::index_file_path(&path)).ok();
+    }
+}
+
+#[test]
+fn test_evicts_least_recently_used() -> Result<()> {
+    let (a, reader_a) = open_test_file("lru_a")?;
+    let (b, reader_b) = open_test_file("lru_b")?;
+    let (c, reader_c) = open_test_file("lru_c")?;
+
+    let mut manager = OpenFileManager::new(2);
+    assert!(manager.insert(a.clone(), reader_a).is_empty());
+    assert!(manager.insert(b.clone(), reader_b).is_empty());
+
+    // 使用a之后，b成为最久未使用的文件
+    assert!(manager.get(&a).is_some());
+    assert_eq!(manager.insert(c.clone(), reader_c), vec![b.clone()]);
+    assert_eq!(manager.len(), 2);
+    assert!(!manager.contains(&b));
+
+    let recent: Vec<&str> = manager.iter_recent().into_iter().map(|(path, _)| path).collect();
+    assert_eq!(recent, vec![c.as_str(), a.as_str()]);
+
+    // 缩小上限时同样按使用顺序关闭
+    assert_eq!(manager.set_capacity(1), vec![a.clone()]);
+
+    cleanup(&[a, b, c]);
+    Ok(())
+}
+
+#[test]
+fn test_pinned_files_are_kept() -> Result<()> {
+    let (a, reader_a) = open_test_file("pin_a")?;
+    let (b, reader_b) = open_test_file("pin_b")?;
+    let (c, reader_c) = open_test_file("pin_c")?;
+
+    let mut manager = OpenFileManager::new(1);
+    manager.insert(a.clone(), reader_a);
+    assert!(manager.set_pinned(&a, true));
+
+    // 固定的文件不会被关闭，刚加入的文件也保留
+    assert!(manager.insert(b.clone(), reader_b).is_empty());
+    assert_eq!(manager.len(), 2);
+    assert_eq!(manager.insert(c.clone(), reader_c), vec![b.clone()]);
+    assert!(manager.contains(&a) && manager.contains(&c));
+
+    // 可以显式关闭
+    assert!(manager.remove(&a).is_some());
+    let mut closed = manager.clear();
+    closed.sort();
+    assert_eq!(closed, vec![c.clone()]);
+    assert!(manager.is_empty());
+
+    cleanup(&[a, b, c]);
+    Ok(())
+}
+
+#[test]
+fn test_find_reuses_matching_reader() -> Result<()> {
+    let (a, reader_a) = open_test_file("find")?;
+
+    let mut manager = OpenFileManager::default();
+    manager.insert(a.clone(), Arc::clone(&reader_a));
+
+    // 选项相同时复用同一个读取器
+    let found = manager.find(&a, true, &Delimiter::Byte(b','), 100).unwrap();
+    assert!(Arc::ptr_eq(&found, &reader_a));
+
+    // 选项不同时需要重新打开
+    assert!(manager.find(&a, false, &Delimiter::Byte(b','), 100).is_none());
+    assert!(manager.find(&a, true, &Delimiter::Byte(b';'), 100).is_none());
+    assert!(manager.find(&a, true, &Delimiter::Byte(b','), 1000).is_none());
+
+    // 文件被修改后不再复用
+    std::fs::write(&a, "id,name\n1,a\n2,b\n3,c\n")?;
+    assert!(manager.find(&a, true, &Delimiter::Byte(b','), 100).is_none());
+
+    drop(found);
+    drop(reader_a);
+    manager.clear();
+    cleanup(&[a]);
+    Ok(())
+}
+
+#[test]
+fn test_find_compares_multi_byte_delimiters() -> Result<()> {
+    let path = std::env::temp_dir().join("test_manager_find_multi_byte.csv");
+    std::fs::write(&path, "id||name\n1||a\n2||b\n")?;
+    let a = path.to_string_lossy().to_string();
+    let pipes = Delimiter::parse("||")?;
+    let reader = Arc::new(CsvReader::open(&path, true, &pipes, 100)?);
+    assert_eq!(reader.headers(), &["id", "name"]);
+
+    let mut manager = OpenFileManager::default();
+    manager.insert(a.clone(), Arc::clone(&reader));
+
+    // 按完整的分隔符比较，首字节相同的分隔符不会复用
+    let found = manager.find(&a, true, &Delimiter::parse("||")?, 100).unwrap();
+    assert!(Arc::ptr_eq(&found, &reader));
+    assert!(manager.find(&a, true, &Delimiter::parse("|")?, 100).is_none());
+    assert!(manager.find(&a, true, &Delimiter::parse("|:")?, 100).is_none());
+
+    drop(found);
+    drop(reader);
+    manager.clear();
+    cleanup(&[a]);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! 随机抽样与打乱集成测试
+
+use csv_tool::csv::{sample_rows, sample_to_file, shuffle_to_file, CsvReader, RowIndex, SampleOptions, WriteOptions};
+use csv_tool::error::Result;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+fn create_numbered_csv(path: &Path, rows: usize) -> Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "id,name")?;
+    for i in 0..rows {
+        writeln!(file, "{},\"name, {}\"", i, i)?;
+    }
+    Ok(())
+}
+
+/// 读取输出文件的表头和第一列
+fn read_ids(path: &Path) -> (String, Vec<usize>) {
+    let content = std::fs::read_to_string(path).unwrap();
+    let mut lines = content.lines();
+    let header = lines.next().unwrap_or_default().to_string();
+    let ids = lines.map(|line| line.split(',').next().unwrap().parse().unwrap()).collect();
+    (header, ids)
+}
+
+#[test]
+fn test_sample_rows() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_sample_rows.csv");
+    create_numbered_csv(&test_file, 1000)?;
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
+
+    // 相同种子结果相同，不同种子结果不同
+    let options = SampleOptions::new().with_seed(7);
+    let rows = sample_rows(&reader, 50, &options);
+    assert_eq!(rows.len(), 50);
+    assert_eq!(rows, sample_rows(&reader, 50, &options));
+    assert_ne!(rows, sample_rows(&reader, 50, &options.clone().with_seed(8)));
+
+    // 行号不重复且都在范围内
+    let mut sorted = rows.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(sorted.len(), 50);
+    assert!(sorted.iter().all(|&row| row < 1000));
+
+    // 保持顺序时抽到的行相同，只是按行号排列
+    let ordered = sample_rows(&reader, 50, &options.clone().with_keep_order(true));
+    assert_eq!(ordered, sorted);
+
+    // 行数不足时返回所有行
+    let all = sample_rows(&reader, 5000, &options);
+    assert_eq!(all.len(), 1000);
+
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_sample_and_shuffle_to_file() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_sample_to_file.csv");
+    let sample_file = std::env::temp_dir().join("test_sample_to_file_sample.csv");
+    let shuffle_file = std::env::temp_dir().join("test_sample_to_file_shuffle.csv");
+    create_numbered_csv(&test_file, 200)?;
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
+    let write_options = WriteOptions::new();
+
+    let options = SampleOptions::new().with_seed(42).with_keep_order(true);
+    let stats = sample_to_file(&reader, 20, &options, &sample_file, &write_options)?;
+    assert_eq!(stats.rows_written, 20);
+    let (header, ids) = read_ids(&sample_file);
+    assert_eq!(header, "id,name");
+    assert_eq!(ids, sample_rows(&reader, 20, &options));
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // 原样复制每行，保留引号
+    let content = std::fs::read_to_string(&sample_file)?;
+    let first_id = ids[0];
+    assert!(content.contains(&format!("{},\"name, {}\"", first_id, first_id)));
+
+    // 打乱后包含所有行，且相同种子结果相同
+    let stats = shuffle_to_file(&reader, 42, &shuffle_file, &write_options)?;
+    assert_eq!(stats.rows_written, 200);
+    let (header, shuffled) = read_ids(&shuffle_file);
+    assert_eq!(header, "id,name");
+    assert_ne!(shuffled, (0..200).collect::<Vec<_>>());
+    let mut sorted = shuffled.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..200).collect::<Vec<_>>());
+
+    shuffle_to_file(&reader, 42, &shuffle_file, &write_options)?;
+    assert_eq!(read_ids(&shuffle_file).1, shuffled);
+
+    for path in [&test_file, &sample_file, &shuffle_file] {
+        std::fs::remove_file(path).ok();
+    }
+    std::fs::remove_file(RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
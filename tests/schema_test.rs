@@ -0,0 +1,85 @@
+//! 列类型推断集成测试
+
+use csv_tool::csv::{infer_schema, ColumnType, CsvReader};
+use csv_tool::error::Result;
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn test_infer_schema() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_schema.csv");
+    let mut file = File::create(&test_file)?;
+    
+    writeln!(file, "id,price,name,active")?;
+    writeln!(file, "1,9.99,Apple,true")?;
+    writeln!(file, "2,15,Banana,false")?;
+    writeln!(file, "3,,Cherry,true")?;
+    writeln!(file, "4,0.5,Durian,false")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let schema = infer_schema(&reader, 100)?;
+    
+    assert_eq!(schema.len(), 4);
+    
+    assert_eq!(schema[0].name, "id");
+    assert_eq!(schema[0].inferred_type, ColumnType::Integer);
+    assert!(!schema[0].nullable);
+    
+    // 整数和浮点数混合 -> 浮点数，且有空值
+    assert_eq!(schema[1].inferred_type, ColumnType::Float);
+    assert!(schema[1].nullable);
+    
+    assert_eq!(schema[2].inferred_type, ColumnType::String);
+    assert_eq!(schema[3].inferred_type, ColumnType::Bool);
+    
+    // 只采样第一行时价格列为浮点数
+    let schema = infer_schema(&reader, 1)?;
+    assert_eq!(schema[1].inferred_type, ColumnType::Float);
+    assert!(!schema[1].nullable);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    let index_path = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    std::fs::remove_file(&index_path).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_infer_column_types_samples_whole_file() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_infer_column_types.csv");
+    let mut file = File::create(&test_file)?;
+    
+    writeln!(file, "id,amount,note")?;
+    // 前半部分金额都是整数，后半部分出现小数；备注每4行有1行为空
+    for i in 0..10_000 {
+        let amount = if i < 5_000 { format!("{}", i) } else { format!("{}.5", i) };
+        let note = if i % 4 == 0 { "" } else { "ok" };
+        writeln!(file, "{},{},{}", i, amount, note)?;
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 100)?;
+    
+    // 只采样开头时看不到小数
+    assert_eq!(infer_schema(&reader, 100)?[1].inferred_type, ColumnType::Integer);
+    
+    let types = reader.infer_column_types(100)?;
+    assert_eq!(types.len(), 3);
+    assert_eq!(types[0].column_type, ColumnType::Integer);
+    assert_eq!(types[1].column_type, ColumnType::Float);
+    assert!(types[1].is_numeric());
+    assert_eq!(types[2].column_type, ColumnType::String);
+    assert!(!types[2].is_numeric());
+    assert!((types[2].null_fraction - 0.25).abs() < 0.1);
+    assert_eq!(types[0].null_fraction, 0.0);
+    
+    // 第二次调用返回缓存的结果
+    assert_eq!(reader.infer_column_types(100)?, types);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
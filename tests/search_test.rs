@@ -1,10 +1,12 @@
 //! 搜索功能集成测试
 
-use csv_tool::csv::{CsvReader, SearchPattern, SearchOptions};
+use csv_tool::csv::{CsvReader, ProgressCallback, ReaderOptions, SearchPattern, SearchOptions, SearchCursor};
 use csv_tool::error::Result;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 
 fn create_test_csv(path: &PathBuf) -> Result<()> {
     let mut file = File::create(path)?;
@@ -180,4 +182,429 @@ fn test_search_max_results() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_max_results_stops_scan() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_max_early_exit.csv");
+    let total_rows = 300_000;
+    {
+        // 约7MB，完整扫描时进度回调会在中途触发
+        let mut file = BufWriter::new(File::create(&test_file)?);
+        writeln!(file, "id,name,city")?;
+        for i in 0..total_rows {
+            writeln!(file, "{},user{},Beijing", i, i)?;
+        }
+    }
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    
+    // 用进度回调的调用次数观察扫描了多少：找到3个匹配后立即停止，
+    // 只有开始时和结束时的两次汇报
+    let patterns = [SearchPattern::text("user", true), SearchPattern::regex("user\\d+", true)?];
+    for pattern in patterns {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress: ProgressCallback = {
+            let reports = Arc::clone(&reports);
+            Arc::new(move |scanned, total| reports.lock().unwrap().push((scanned, total)))
+        };
+        let options = SearchOptions::new(pattern)
+            .with_max_results(3)
+            .with_progress(progress);
+        
+        let results = reader.search(&options)?;
+        assert_eq!(results.iter().map(|r| r.row_number).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(reports.lock().unwrap().len(), 2);
+        
+        // 计数同样在达到上限时停止，返回值等于上限
+        reports.lock().unwrap().clear();
+        assert_eq!(reader.count_matches(&options)?, 3);
+        assert_eq!(reports.lock().unwrap().len(), 2);
+        
+        assert!(reader.has_at_least_matches(&options, 10)?);
+        assert!(!reader.has_at_least_matches(&options, total_rows + 1)?);
+    }
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_search_with_column_index() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_column_index.csv");
+    create_test_csv(&test_file)?;
+    
+    let searches = [
+        SearchOptions::new(SearchPattern::text("example", true)).with_columns(vec![2]),
+        SearchOptions::new(SearchPattern::text("TEST.ORG", false)).with_columns(vec![2]),
+        SearchOptions::new(SearchPattern::text("nowhere", true)).with_columns(vec![2]),
+        SearchOptions::new(SearchPattern::text("example", true)).with_columns(vec![2]).with_max_results(1),
+    ];
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let expected: Vec<_> = searches.iter()
+        .map(|options| reader.search(options))
+        .collect::<Result<_>>()?;
+    drop(reader);
+    
+    // 构建 email 列的列索引后结果一致
+    let options = ReaderOptions::new()
+        .with_granularity(10)
+        .with_column_index(Some(2));
+    let reader = CsvReader::open_with_options(&test_file, &options)?;
+    assert!(reader.column_index(2).is_some());
+    
+    for (options, expected) in searches.iter().zip(&expected) {
+        let results = reader.search(options)?;
+        assert_eq!(results.len(), expected.len());
+        for (a, b) in results.iter().zip(expected) {
+            assert_eq!(a.row_number, b.row_number);
+            assert_eq!(a.record.fields, b.record.fields);
+            assert_eq!(a.matches[0].column, 2);
+            assert_eq!(a.matches[0].positions, b.matches[0].positions);
+        }
+    }
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    let index_path = csv_tool::csv::RowIndex::index_file_path(&test_file);
+    std::fs::remove_file(&index_path).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_search_progress_and_cancel() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_cancel.csv");
+    let total_rows = 300_000;
+    {
+        // 约7MB，进度回调在扫描中途至少触发一次
+        let mut file = BufWriter::new(File::create(&test_file)?);
+        writeln!(file, "id,name,city")?;
+        for i in 0..total_rows {
+            writeln!(file, "{},user{},Beijing", i, i)?;
+        }
+    }
+    let reader = CsvReader::open(&test_file, true, b',', 1000)?;
+    
+    // 未取消时扫描完整个文件，最后一次进度为100%
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let progress: ProgressCallback = {
+        let reports = Arc::clone(&reports);
+        Arc::new(move |scanned, total| reports.lock().unwrap().push((scanned, total)))
+    };
+    let options = SearchOptions::new(SearchPattern::text("user", true)).with_progress(progress);
+    let outcome = reader.count_matches_with_status(&options)?;
+    assert!(!outcome.cancelled);
+    assert_eq!(outcome.value, total_rows);
+    let reports = reports.lock().unwrap();
+    assert!(reports.len() > 2);
+    let &(scanned, total) = reports.last().unwrap();
+    assert_eq!(scanned, total);
+    
+    // 扫描到中途时由另一个线程置位取消标志
+    let cancel = Arc::new(AtomicBool::new(false));
+    let (reached_tx, reached_rx) = mpsc::channel::<u64>();
+    let (resume_tx, resume_rx) = mpsc::channel::<()>();
+    let (reached_tx, resume_rx) = (Mutex::new(reached_tx), Mutex::new(resume_rx));
+    let progress: ProgressCallback = Arc::new(move |scanned, total| {
+        if scanned > 0 && scanned < total {
+            reached_tx.lock().unwrap().send(scanned).ok();
+            resume_rx.lock().unwrap().recv().ok();
+        }
+    });
+    let canceller = {
+        let cancel = Arc::clone(&cancel);
+        std::thread::spawn(move || {
+            reached_rx.recv().unwrap();
+            cancel.store(true, Ordering::Relaxed);
+            resume_tx.send(()).unwrap();
+        })
+    };
+    
+    let options = SearchOptions::new(SearchPattern::text("user", true))
+        .with_progress(progress)
+        .with_cancel_flag(Arc::clone(&cancel));
+    let outcome = reader.search_with_status(&options)?;
+    canceller.join().unwrap();
+    
+    // 提前返回已找到的结果（文件开头的连续若干行）
+    assert!(outcome.cancelled);
+    assert!(!outcome.value.is_empty());
+    assert!(outcome.value.len() < total_rows);
+    assert_eq!(outcome.value.last().unwrap().row_number + 1, outcome.value.len());
+    
+    // 已置位的取消标志使计数立即结束
+    let outcome = reader.count_matches_with_status(&options)?;
+    assert!(outcome.cancelled);
+    assert_eq!(outcome.value, 0);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_prefilter_matches_line_scan() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_prefilter.csv");
+    create_test_csv(&test_file)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let all_columns: Vec<usize> = (0..4).collect();
+    
+    // 不限制列时走原始字节预筛选，指定全部列时逐行解析，两者结果一致
+    for text in ["example", "Beijing", "@", "m,B", "org,Sh", "5,Eve", "nowhere"] {
+        let fast = SearchOptions::new(SearchPattern::text(text, true));
+        let scan = fast.clone().with_columns(all_columns.clone());
+        
+        let fast_results = reader.search(&fast)?;
+        let scan_results = reader.search(&scan)?;
+        assert_eq!(fast_results.len(), scan_results.len(), "{}", text);
+        for (a, b) in fast_results.iter().zip(&scan_results) {
+            assert_eq!(a.row_number, b.row_number);
+            assert_eq!(a.record.fields, b.record.fields);
+            assert_eq!(a.matched_columns(), b.matched_columns());
+        }
+        assert_eq!(reader.count_matches(&fast)?, reader.count_matches(&scan)?, "{}", text);
+    }
+    
+    // 跨越字段边界的命中不算匹配
+    let options = SearchOptions::new(SearchPattern::text("m,B", true));
+    assert!(reader.search(&options)?.is_empty());
+    
+    // 最大结果数同样生效
+    let options = SearchOptions::new(SearchPattern::text("example", true)).with_max_results(2);
+    let results = reader.search(&options)?;
+    assert_eq!(results.iter().map(|r| r.row_number).collect::<Vec<_>>(), vec![0, 2]);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    
+    Ok(())
+}
+
+#[test]
+fn test_search_pagination() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_pagination.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,tag")?;
+    // 每4行中有1行匹配，共25个匹配
+    for i in 0..100 {
+        writeln!(file, "{},{}", i, if i % 4 == 0 { "hit" } else { "miss" })?;
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = SearchOptions::new(SearchPattern::text("hit", true)).with_max_results(5);
+    let expected_rows = |range: std::ops::Range<usize>| range.map(|i| i * 4).collect::<Vec<_>>();
+    let rows = |results: &[csv_tool::csv::SearchResult]| results.iter().map(|r| r.row_number).collect::<Vec<_>>();
+    
+    // 按偏移量分页，不受 max_results 限制
+    let (page, total) = reader.search_page(&options, 10, 10)?;
+    assert_eq!(rows(&page), expected_rows(10..20));
+    assert_eq!(total, None, "没有扫描到文件末尾时不知道总数");
+    
+    let (page, total) = reader.search_page(&options, 20, 10)?;
+    assert_eq!(rows(&page), expected_rows(20..25));
+    assert_eq!(total, Some(25));
+    
+    // 按游标逐页获取，每页从上一页停止的位置继续
+    let mut cursor: Option<SearchCursor> = None;
+    let mut pages = Vec::new();
+    loop {
+        let page = reader.search_page_from(&options, cursor.as_ref(), 0, 10)?;
+        pages.push(rows(&page.results));
+        match page.cursor {
+            Some(next) => {
+                assert!(page.total.is_none());
+                cursor = Some(next);
+            }
+            None => {
+                assert_eq!(page.total, Some(25));
+                break;
+            }
+        }
+    }
+    assert_eq!(pages, vec![expected_rows(0..10), expected_rows(10..20), expected_rows(20..25)]);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_search_from_row() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_from_row.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,tag")?;
+    // 第50、150、151行匹配
+    for i in 0..300 {
+        writeln!(file, "{},{}", i, if matches!(i, 50 | 150 | 151) { "hit" } else { "miss" })?;
+    }
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 16)?;
+    let options = SearchOptions::new(SearchPattern::text("hit", true));
+    let find = |start_row| -> Result<Option<usize>> {
+        Ok(reader.search_from(&options, start_row)?.map(|r| r.row_number))
+    };
+    
+    // 从第100行开始跳过之前的匹配，逐个查找下一个
+    let first = reader.search_from(&options, 100)?.unwrap();
+    assert_eq!(first.row_number, 150);
+    assert_eq!(first.record.fields, vec!["150", "hit"]);
+    assert_eq!(find(151)?, Some(151));
+    assert_eq!(find(152)?, None);
+    assert_eq!(find(0)?, Some(50));
+    assert_eq!(find(50)?, Some(50));
+    assert_eq!(find(300)?, None);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_search_whole_word() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_whole_word.csv");
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "id,text")?;
+    writeln!(file, "1,cat")?;
+    writeln!(file, "2,concatenate")?;
+    writeln!(file, "3,\"the cat, sat\"")?;
+    writeln!(file, "4,Cat-food")?;
+    writeln!(file, "5,cats")?;
+    writeln!(file, "6,(cat)")?;
+    writeln!(file, "7,cat_tail")?;
+    writeln!(file, "8,bobcat")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let rows = |options: &SearchOptions| -> Result<Vec<usize>> {
+        let results = reader.search(options)?;
+        // 统计数量与搜索结果一致
+        assert_eq!(reader.count_matches(options)?, results.len());
+        Ok(results.iter().map(|r| r.row_number).collect())
+    };
+    
+    // 字段开头结尾、标点两侧都算单词边界，下划线是单词字符
+    let options = SearchOptions::new(SearchPattern::text("cat", true)).with_whole_word(true);
+    assert_eq!(rows(&options)?, vec![0, 2, 5]);
+    let results = reader.search(&options)?;
+    assert_eq!(results[1].matches[0].positions, vec![(4, 7)]);
+    
+    // 不开启时匹配所有包含cat的行
+    let options = SearchOptions::new(SearchPattern::text("cat", true));
+    assert_eq!(rows(&options)?, vec![0, 1, 2, 4, 5, 6, 7]);
+    
+    // 大小写不敏感
+    let options = SearchOptions::new(SearchPattern::text("CAT", false))
+        .with_case_sensitive(false)
+        .with_whole_word(true);
+    assert_eq!(rows(&options)?, vec![0, 2, 3, 5]);
+    
+    // 正则表达式在编译时加入单词边界
+    let options = SearchOptions::new(SearchPattern::regex_with("c.t", true, true)?).with_whole_word(true);
+    assert_eq!(rows(&options)?, vec![0, 2, 5]);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
 
+#[test]
+fn test_glob_search() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_glob.csv");
+    create_test_csv(&test_file)?;
+    let mut file = std::fs::OpenOptions::new().append(true).open(&test_file)?;
+    writeln!(file, "6,Rob,rob@test.org,Beijing")?;
+    writeln!(file, "7,Jacob,jacob@example.com.cn,Shanghai")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let rows = |options: &SearchOptions| -> Result<Vec<usize>> {
+        let results = reader.search(options)?;
+        assert_eq!(reader.count_matches(options)?, results.len());
+        Ok(results.iter().map(|r| r.row_number).collect())
+    };
+    
+    // 必须匹配整个字段：example.com.cn 不匹配
+    let options = SearchOptions::new(SearchPattern::glob("*@example.com", true)?).with_columns(vec![2]);
+    assert_eq!(rows(&options)?, vec![0, 2, 4]);
+    let results = reader.search(&options)?;
+    assert_eq!(results[0].matches[0].positions, vec![(0, 17)]);
+    
+    // ? 恰好匹配一个字符
+    let options = SearchOptions::new(SearchPattern::glob("?ob", true)?).with_columns(vec![1]);
+    assert_eq!(rows(&options)?, vec![1, 5]);
+    
+    // 不限定列时任一字段匹配即可
+    let options = SearchOptions::new(SearchPattern::glob("sh*", false)?).with_case_sensitive(false);
+    assert_eq!(rows(&options)?, vec![1, 3, 6]);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_search_any_of_patterns() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_search_any_of.csv");
+    create_test_csv(&test_file)?;
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let any_of = || -> Result<SearchPattern> {
+        SearchPattern::any_of(vec![
+            SearchPattern::text("bob", false),
+            SearchPattern::text("eve", false),
+            SearchPattern::regex("^guang", false)?,
+        ])
+    };
+    
+    // 一次扫描找到任一模式匹配的行，并记录命中的模式
+    let options = SearchOptions::new(any_of()?).with_case_sensitive(false);
+    let results = reader.search(&options)?;
+    let rows: Vec<usize> = results.iter().map(|r| r.row_number).collect();
+    assert_eq!(rows, vec![1, 2, 4]);
+    assert_eq!(reader.count_matches(&options)?, 3);
+    assert_eq!(results[0].matches[0].column, 1);
+    assert_eq!(results[0].matches[0].patterns, vec![0]);
+    assert_eq!(results[1].matches[0].column, 3);
+    assert_eq!(results[1].matches[0].patterns, vec![2]);
+    // Eve 同时出现在 name 和 email 列
+    assert_eq!(results[2].matches.len(), 2);
+    assert!(results[2].matches.iter().all(|m| m.patterns == vec![1]));
+    
+    // 反向匹配返回所有模式都不匹配的行
+    let options = SearchOptions::new(any_of()?)
+        .with_case_sensitive(false)
+        .with_invert_match(true);
+    let rows: Vec<usize> = reader.search(&options)?.iter().map(|r| r.row_number).collect();
+    assert_eq!(rows, vec![0, 3]);
+    assert_eq!(reader.count_matches(&options)?, 2);
+    
+    // 与逐个模式搜索的结果并集一致
+    let patterns = ["Alice", "David", "test.org", "nobody"];
+    let combined = SearchPattern::any_of(patterns.iter().map(|p| SearchPattern::text(p, true)).collect())?;
+    let mut expected: Vec<usize> = Vec::new();
+    for p in patterns {
+        let results = reader.search(&SearchOptions::new(SearchPattern::text(p, true)))?;
+        expected.extend(results.iter().map(|r| r.row_number));
+    }
+    expected.sort_unstable();
+    expected.dedup();
+    let rows: Vec<usize> = reader.search(&SearchOptions::new(combined))?.iter().map(|r| r.row_number).collect();
+    assert_eq!(rows, expected);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
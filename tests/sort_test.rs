@@ -4,9 +4,12 @@ use std::fs::{self, File};
 use std::io::Write;
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use csv_tool::csv::{
-    CsvReader, SortOrder, SortKey, SortOptions, DataType, sort_csv_data
+    CsvReader, ReaderOptions, SortOrder, SortKey, SortOptions, DataType, Collation, LineEnding, WriteOptions,
+    NumericCleanup, sort_csv_data, sort_to_file
 };
 
+mod common;
+
 static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 /// 创建临时测试文件（使用唯一计数器避免冲突）
@@ -217,3 +220,372 @@ fn test_sort_empty_values() {
     cleanup(&path);
 }
 
+#[test]
+fn test_sort_with_column_index() {
+    let content = "name,score,city\nAlice,95,\"Beijing, CN\"\nBob,\nCharlie,90,Shanghai\nDave,90,Shenzhen\n";
+    let path = create_test_csv(content);
+    
+    let key = SortKey::new(1, SortOrder::Descending, DataType::Number);
+    let options = SortOptions::new().add_key(key);
+    
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    assert!(reader.column_index(1).is_none());
+    let expected = sort_csv_data(&reader, &options, None).unwrap();
+    
+    let reader_options = ReaderOptions::new()
+        .with_granularity(10)
+        .with_column_index(Some(1));
+    let reader = CsvReader::open_with_options(&path, &reader_options).unwrap();
+    assert!(reader.column_index(1).is_some());
+    let sorted = sort_csv_data(&reader, &options, None).unwrap();
+    
+    // 与完整扫描的结果一致（包括值相同的行的顺序和空值位置）
+    assert_eq!(sorted.len(), expected.len());
+    for (a, b) in sorted.iter().zip(&expected) {
+        assert_eq!(a.original_row, b.original_row);
+        assert_eq!(a.record.fields, b.record.fields);
+    }
+    assert_eq!(sorted[0].record.fields[2].as_ref(), "Beijing, CN");
+    
+    // 列索引随索引文件缓存，再次打开时直接可用
+    let reader = CsvReader::open_with_options(&path, &reader_options).unwrap();
+    assert!(reader.column_index(1).is_some());
+    assert_eq!(sort_csv_data(&reader, &options, Some(1)).unwrap()[0].original_row, 0);
+    
+    cleanup(&path);
+    let _ = fs::remove_file(format!("{}.idx", path));
+}
+
+#[test]
+fn test_sort_accent_folding_collation() {
+    let content = "word\nz\nf\né\ne\n";
+    let path = create_test_csv(content);
+    
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    let key = SortKey::new(0, SortOrder::Ascending, DataType::String);
+    let words = |options: &SortOptions| -> Vec<String> {
+        sort_csv_data(&reader, options, None)
+            .unwrap()
+            .iter()
+            .map(|r| r.record.fields[0].to_string())
+            .collect()
+    };
+    
+    // 默认按码点排序，é 排在 z 之后
+    let binary = SortOptions::new().add_key(key.clone());
+    assert_eq!(words(&binary), vec!["e", "f", "z", "é"]);
+    
+    // 忽略重音时 é 紧跟在 e 之后
+    let folded = SortOptions::new().add_key(key).with_collation(Collation::AccentFolding);
+    assert_eq!(words(&folded), vec!["e", "é", "f", "z"]);
+    
+    cleanup(&path);
+}
+
+#[test]
+fn test_sort_stable_ties_keep_original_order() {
+    // 只有三种键值，每种键值有大量重复
+    let mut content = String::from("key,id\n");
+    for row in 0..300 {
+        content.push_str(&format!("{},{}\n", ["b", "a", "c"][row % 3], row));
+    }
+    let path = create_test_csv(&content);
+    
+    let key = SortKey::new(0, SortOrder::Descending, DataType::String);
+    let options = SortOptions::new().add_key(key);
+    assert!(options.stable);
+    
+    let reader_options = ReaderOptions::new()
+        .with_granularity(10)
+        .with_column_index(Some(0));
+    for reader in [
+        CsvReader::open(&path, true, b',', 10).unwrap(),
+        CsvReader::open_with_options(&path, &reader_options).unwrap(),
+    ] {
+        let sorted = sort_csv_data(&reader, &options, None).unwrap();
+        assert_eq!(sorted.len(), 300);
+        
+        // 键相同的行按原始行号升序排列
+        for pair in sorted.windows(2) {
+            if pair[0].record.fields[0] == pair[1].record.fields[0] {
+                assert!(pair[0].original_row < pair[1].original_row);
+            } else {
+                assert!(pair[0].record.fields[0] > pair[1].record.fields[0]);
+            }
+        }
+        
+        // 部分排序（堆）和写入文件时相同键的行也按原始顺序排列
+        let top = sort_csv_data(&reader, &options, Some(120)).unwrap();
+        let rows: Vec<usize> = top.iter().map(|r| r.original_row).collect();
+        assert_eq!(rows, sorted[..120].iter().map(|r| r.original_row).collect::<Vec<_>>());
+        
+        let output = format!("{}.sorted.csv", path);
+        sort_to_file(&reader, &options, None, &output, &WriteOptions::new()).unwrap();
+        let ids: Vec<usize> = fs::read_to_string(&output).unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(1).unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(ids, sorted.iter().map(|r| r.original_row).collect::<Vec<_>>());
+        cleanup(&output);
+    }
+    
+    // 关闭稳定排序后仍然按键排序
+    let unstable = options.clone().with_stable(false);
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    let sorted = sort_csv_data(&reader, &unstable, None).unwrap();
+    assert!(sorted.windows(2).all(|pair| pair[0].record.fields[0] >= pair[1].record.fields[0]));
+    
+    cleanup(&path);
+    let _ = fs::remove_file(format!("{}.idx", path));
+}
+
+#[test]
+fn test_sort_top_n_matches_full_sort() {
+    // 生成有重复值和空值的数据
+    let mut content = String::from("name,salary\n");
+    for row in 0..5000usize {
+        let salary = if row % 97 == 0 { String::new() } else { ((row * 7919) % 1000).to_string() };
+        content.push_str(&format!("p{},{}\n", row, salary));
+    }
+    let path = create_test_csv(&content);
+    let reader = CsvReader::open(&path, true, b',', 100).unwrap();
+    
+    for order in [SortOrder::Descending, SortOrder::Ascending] {
+        let key = SortKey::new(1, order, DataType::Number);
+        let options = SortOptions::new().add_key(key);
+        
+        let mut expected = sort_csv_data(&reader, &options, None).unwrap();
+        expected.truncate(3);
+        let top = sort_csv_data(&reader, &options, Some(3)).unwrap();
+        
+        assert_eq!(top.len(), 3);
+        for (a, b) in top.iter().zip(&expected) {
+            assert_eq!(a.original_row, b.original_row);
+            assert_eq!(a.record.fields, b.record.fields);
+        }
+    }
+    
+    // limit不少于总行数时返回全部行
+    let options = SortOptions::new().add_key(SortKey::new(0, SortOrder::Ascending, DataType::String));
+    assert_eq!(sort_csv_data(&reader, &options, Some(10_000)).unwrap().len(), 5000);
+    assert!(sort_csv_data(&reader, &options, Some(0)).unwrap().is_empty());
+    
+    cleanup(&path);
+}
+
+#[test]
+fn test_sort_to_file() {
+    let content = "name,city\nCharlie,\"Paris, FR\"\nAlice,\"Say \"\"hi\"\"\"\nBob,Rome\n";
+    let path = create_test_csv(content);
+    let output = format!("{}.sorted.csv", path);
+    
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    let key = SortKey::new(0, SortOrder::Ascending, DataType::String);
+    let options = SortOptions::new().add_key(key);
+    let write_options = WriteOptions::new().with_line_ending(LineEnding::Lf);
+    
+    let stats = sort_to_file(&reader, &options, None, &output, &write_options).unwrap();
+    assert_eq!(stats.rows_written, 3);
+    
+    // 按顺序写出，包含逗号和引号的字段保持正确转义
+    let expected = "name,city\nAlice,\"Say \"\"hi\"\"\"\nBob,Rome\nCharlie,\"Paris, FR\"\n";
+    let written = fs::read_to_string(&output).unwrap();
+    assert_eq!(written, expected);
+    assert_eq!(stats.bytes_written, expected.len());
+    
+    // 写出的文件可以按原样读回
+    let sorted = CsvReader::open(&output, true, b',', 10).unwrap();
+    assert_eq!(sorted.get_row(2).unwrap().unwrap().fields[1].as_ref(), "Paris, FR");
+    drop(sorted);
+    
+    // 部分排序同样写出表头和前几行
+    let stats = sort_to_file(&reader, &options, Some(1), &output, &write_options).unwrap();
+    assert_eq!(stats.rows_written, 1);
+    assert_eq!(fs::read_to_string(&output).unwrap(), "name,city\nAlice,\"Say \"\"hi\"\"\"\n");
+    
+    cleanup(&path);
+    cleanup(&output);
+    let _ = fs::remove_file(format!("{}.idx", output));
+}
+
+#[test]
+fn test_sort_auto_mixed_values() {
+    // 大部分值是数字的列按数字排序，无法解析的值与空值一样处理
+    let content = "name,value\nA,10\nB,2\nC,N/A\nD,100\nE,\nF,NaN\n";
+    let path = create_test_csv(content);
+    
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    let names = |order: SortOrder, nulls_last: bool| -> Vec<String> {
+        let key = SortKey::new(1, order, DataType::Auto);
+        let options = SortOptions::new().add_key(key).with_nulls_last(nulls_last);
+        sort_csv_data(&reader, &options, None)
+            .unwrap()
+            .iter()
+            .map(|r| r.record.fields[0].to_string())
+            .collect()
+    };
+    
+    // 空值的位置不受排序方向影响，多个空值保持原有顺序
+    assert_eq!(names(SortOrder::Ascending, true), vec!["B", "A", "D", "C", "E", "F"]);
+    assert_eq!(names(SortOrder::Descending, true), vec!["D", "A", "B", "C", "E", "F"]);
+    assert_eq!(names(SortOrder::Ascending, false), vec!["C", "E", "F", "B", "A", "D"]);
+    
+    // 部分排序和完整排序一致
+    let key = SortKey::new(1, SortOrder::Descending, DataType::Auto);
+    let options = SortOptions::new().add_key(key);
+    let top: Vec<String> = sort_csv_data(&reader, &options, Some(4))
+        .unwrap()
+        .iter()
+        .map(|r| r.record.fields[0].to_string())
+        .collect();
+    assert_eq!(top, vec!["D", "A", "B", "C"]);
+    
+    // 数字占少数时按字符串排序
+    let options = SortOptions::new().add_key(SortKey::new(0, SortOrder::Ascending, DataType::Auto));
+    let resolved = options.resolve_auto_types(&reader.read_rows(0, 6).unwrap());
+    assert_eq!(resolved.keys[0].data_type, DataType::String);
+    
+    cleanup(&path);
+}
+
+#[test]
+fn test_sort_numeric_cleanup() {
+    let content = "name,value\nA,\"1,234\"\nB,  42 \nC,$3.50\nD,7\n";
+    let path = create_test_csv(content);
+    
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    let names = |options: &SortOptions| -> Vec<String> {
+        sort_csv_data(&reader, options, None)
+            .unwrap()
+            .iter()
+            .map(|r| r.record.fields[0].to_string())
+            .collect()
+    };
+    let key = SortKey::new(1, SortOrder::Ascending, DataType::Number);
+    
+    // 默认只去掉空白：`1,234` 和 `$3.50` 无法解析，按空值排在最后
+    let options = SortOptions::new().add_key(key.clone());
+    assert_eq!(names(&options), vec!["D", "B", "A", "C"]);
+    
+    let options = SortOptions::new().add_key(key.clone()).with_numeric_cleanup(NumericCleanup::new());
+    assert_eq!(names(&options), vec!["C", "D", "B", "A"]);
+    
+    // 自动检测类型时同样使用清理规则
+    let options = SortOptions::new()
+        .add_key(SortKey::new(1, SortOrder::Descending, DataType::Auto))
+        .with_numeric_cleanup(NumericCleanup::new());
+    assert_eq!(names(&options), vec!["A", "B", "D", "C"]);
+    cleanup(&path);
+    
+    // 欧洲格式：`.` 为千位分隔符，`,` 为小数点
+    let content = "name;value\nA;1.234,56\nB;999,9\nC;1.234,5\n";
+    let path = create_test_csv(content);
+    let reader = CsvReader::open(&path, true, b';', 10).unwrap();
+    let options = SortOptions::new()
+        .add_key(key)
+        .with_numeric_cleanup(NumericCleanup::new().with_decimal_comma(true));
+    let names: Vec<String> = sort_csv_data(&reader, &options, None)
+        .unwrap()
+        .iter()
+        .map(|r| r.record.fields[0].to_string())
+        .collect();
+    assert_eq!(names, vec!["B", "C", "A"]);
+    
+    cleanup(&path);
+}
+
+#[test]
+fn test_sort_null_values() {
+    let content = "name,score\nA,80\nB,NA\nC,\nD,n/a\nE,95\nF,-\nG,70\n";
+    let path = create_test_csv(content);
+    
+    let reader = CsvReader::open(&path, true, b',', 10).unwrap();
+    let names = |data_type: DataType, order: SortOrder, nulls_last: bool| -> Vec<String> {
+        let options = SortOptions::new()
+            .add_key(SortKey::new(1, order, data_type))
+            .with_nulls_last(nulls_last)
+            .with_null_values(vec!["NA".to_string(), "N/A".to_string(), "-".to_string()]);
+        sort_csv_data(&reader, &options, None)
+            .unwrap()
+            .iter()
+            .map(|r| r.record.fields[0].to_string())
+            .collect()
+    };
+    
+    // 空值标记与空字符串一样排在一端，保持原有顺序
+    assert_eq!(names(DataType::String, SortOrder::Ascending, true), vec!["G", "A", "E", "B", "C", "D", "F"]);
+    assert_eq!(names(DataType::String, SortOrder::Descending, true), vec!["E", "A", "G", "B", "C", "D", "F"]);
+    assert_eq!(names(DataType::String, SortOrder::Ascending, false), vec!["B", "C", "D", "F", "G", "A", "E"]);
+    
+    // 自动检测类型时空值标记不参与判断，整列按数字排序
+    assert_eq!(names(DataType::Auto, SortOrder::Descending, false), vec!["B", "C", "D", "F", "E", "A", "G"]);
+    
+    // 默认没有空值标记：`-`、`NA` 按普通字符串排序
+    let options = SortOptions::new().add_key(SortKey::new(1, SortOrder::Ascending, DataType::String));
+    let names: Vec<String> = sort_csv_data(&reader, &options, None)
+        .unwrap()
+        .iter()
+        .map(|r| r.record.fields[0].to_string())
+        .collect();
+    assert_eq!(names, vec!["F", "G", "A", "E", "B", "D", "C"]);
+    
+    cleanup(&path);
+}
+
+#[test]
+fn test_sort_to_file_preserves_row_bytes() {
+    // 多余的引号、CRLF行尾和缺少末尾换行都不影响原样复制
+    let content = "id;name\r\n3;\"Carol\"\r\n1;Alice\r\n2;\"B;ob\"";
+    let path = create_test_csv(content);
+    let output = format!("{}.sorted.csv", path);
+    
+    let reader = CsvReader::open(&path, true, b';', 10).unwrap();
+    let options = SortOptions::new().add_key(SortKey::new(0, SortOrder::Ascending, DataType::Number));
+    
+    // 分隔符相同：每行原样复制，沿用源文件的行结束符
+    let write_options = WriteOptions::new().with_delimiter(b';');
+    let stats = sort_to_file(&reader, &options, None, &output, &write_options).unwrap();
+    let expected = "id;name\r\n1;Alice\r\n2;\"B;ob\"\r\n3;\"Carol\"\r\n";
+    assert_eq!(fs::read_to_string(&output).unwrap(), expected);
+    assert_eq!(stats.bytes_written, expected.len());
+    
+    // 部分排序同样原样复制
+    let stats = sort_to_file(&reader, &options, Some(2), &output, &write_options).unwrap();
+    assert_eq!(stats.rows_written, 2);
+    assert_eq!(fs::read_to_string(&output).unwrap(), "id;name\r\n1;Alice\r\n2;\"B;ob\"\r\n");
+    
+    // 分隔符不同：重新解析并按写入选项转义
+    let write_options = WriteOptions::new().with_line_ending(LineEnding::Lf);
+    sort_to_file(&reader, &options, None, &output, &write_options).unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), "id,name\n1,Alice\n2,B;ob\n3,Carol\n");
+    
+    cleanup(&path);
+    cleanup(&output);
+}
+
+#[test]
+fn test_sort_fast_opened_reader_reads_all_rows() {
+    let path = format!("target/test_sort_fast_opened_{}.csv", std::process::id());
+    common::create_underestimated_csv(std::path::Path::new(&path));
+    
+    let options = ReaderOptions::new().with_granularity(100).with_persist_index(false);
+    let reader = CsvReader::open_fast_with_options(&path, &options).unwrap();
+    assert!(!reader.is_index_complete());
+    assert!(reader.info().total_rows < 40_000);
+    
+    let sort = SortOptions::new().add_key(SortKey::new(0, SortOrder::Descending, DataType::Number));
+    let sorted = sort_csv_data(&reader, &sort, None).unwrap();
+    assert_eq!(sorted.len(), 40_000);
+    assert_eq!(sorted[0].record.fields[0].as_ref(), "39999");
+    assert_eq!(sorted[0].original_row, 39_999);
+    
+    // 部分排序同样扫描到文件末尾
+    let top: Vec<usize> = sort_csv_data(&reader, &sort, Some(3)).unwrap()
+        .iter()
+        .map(|r| r.original_row)
+        .collect();
+    assert_eq!(top, vec![39_999, 39_998, 39_997]);
+    
+    cleanup(&path);
+}
@@ -0,0 +1,251 @@
+//! 列统计集成测试
+
+use csv_tool::csv::{
+    column_stats, column_stats_with_options, histogram, value_counts, CsvReader, FloatFormat, ReaderOptions, StatsOptions,
+};
+use std::sync::atomic::AtomicBool;
+use csv_tool::error::Result;
+use std::fs::File;
+use std::io::Write;
+
+mod common;
+
+#[test]
+fn test_value_counts() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_value_counts.csv");
+    let mut file = File::create(&test_file)?;
+    
+    writeln!(file, "id,city")?;
+    let cities = ["Beijing", "Shanghai", "beijing", "Beijing", "Shenzhen", "Shanghai", "BEIJING"];
+    for (i, city) in cities.iter().enumerate() {
+        writeln!(file, "{},{}", i + 1, city)?;
+    }
+    writeln!(file, "8")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    
+    // 按次数降序，次数相同时按值升序；缺少该列的行按空值统计
+    let counts = value_counts(&reader, 1, false)?;
+    assert_eq!(counts, vec![
+        ("Beijing".to_string(), 2),
+        ("Shanghai".to_string(), 2),
+        ("".to_string(), 1),
+        ("BEIJING".to_string(), 1),
+        ("Shenzhen".to_string(), 1),
+        ("beijing".to_string(), 1),
+    ]);
+    
+    // 不区分大小写时合并，使用第一次出现的写法
+    let counts = value_counts(&reader, 1, true)?;
+    assert_eq!(counts[0], ("Beijing".to_string(), 4));
+    assert_eq!(counts[1], ("Shanghai".to_string(), 2));
+    assert_eq!(counts.len(), 4);
+    
+    // 使用列索引时结果相同
+    let options = ReaderOptions::new().with_column_index(Some(1));
+    let indexed = CsvReader::open_with_options(&test_file, &options)?;
+    assert!(indexed.column_index(1).is_some());
+    assert_eq!(value_counts(&indexed, 1, true)?, counts);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_column_stats() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_column_stats.csv");
+    let mut file = File::create(&test_file)?;
+    
+    writeln!(file, "id,price,city")?;
+    writeln!(file, "1,10.5,Beijing")?;
+    writeln!(file, "2,,Shanghai")?;
+    writeln!(file, "3,-2,Beijing")?;
+    writeln!(file, "4,NULL,")?;
+    writeln!(file, "5,7.5")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    
+    // 数值列：空值不参与计算
+    let outcome = column_stats(&reader, 1, None)?;
+    assert!(!outcome.cancelled);
+    let price = outcome.value;
+    assert_eq!(price.rows, 5);
+    assert_eq!(price.null_count, 2);
+    assert_eq!(price.distinct_count, 3);
+    let numeric = price.numeric.as_ref().unwrap();
+    assert_eq!((numeric.min, numeric.max), (-2.0, 10.5));
+    assert!((numeric.mean - 16.0 / 3.0).abs() < 1e-9);
+    
+    // 文本列：没有数值统计，按次数给出最常见的值；缺少该列的行算作空值
+    let city = column_stats(&reader, 2, None)?.value;
+    assert!(!city.is_numeric());
+    assert_eq!(city.null_count, 2);
+    assert_eq!(city.top_values(1), &[("Beijing".to_string(), 2)]);
+    assert_eq!(city.top_values(10).len(), 2);
+    
+    // 已置位的取消标志使扫描立即结束
+    let cancel = AtomicBool::new(true);
+    let outcome = column_stats(&reader, 1, Some(&cancel))?;
+    assert!(outcome.cancelled);
+    assert_eq!(outcome.value.rows, 0);
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_column_stats_float_format() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_column_stats_format.csv");
+    let mut file = File::create(&test_file)?;
+    
+    writeln!(file, "id,amount")?;
+    writeln!(file, "1,3.0")?;
+    writeln!(file, "2, 1.2000000001 ")?;
+    writeln!(file, "3,4")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let stats = column_stats(&reader, 1, None)?.value;
+    let numeric = stats.numeric.as_ref().unwrap();
+    
+    // 平均值保留两位小数
+    assert_eq!(numeric.format_mean(FloatFormat::Fixed(2)), "2.73");
+    assert_eq!(numeric.format_max(FloatFormat::Fixed(2)), "4.00");
+    
+    // 最短写法与原始写法
+    assert_eq!(numeric.format_min(FloatFormat::Shortest), "1.2000000001");
+    assert_eq!(numeric.format_min(FloatFormat::AsWritten), "1.2000000001");
+    assert_eq!(numeric.format_max(FloatFormat::AsWritten), "4");
+    assert_eq!(numeric.format_mean(FloatFormat::Significant(3)), "2.73");
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_column_stats_percentiles() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_column_stats_percentiles.csv");
+    let mut file = File::create(&test_file)?;
+    
+    writeln!(file, "id,score,name")?;
+    for (i, score) in [7, 1, 9, 3, 5, 2].iter().enumerate() {
+        writeln!(file, "{},{},n{}", i + 1, score, i)?;
+    }
+    writeln!(file, "7,,n6")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    
+    // 精确模式：偶数个值的中位数取中间两个值的平均，空值不参与
+    let options = StatsOptions::new().with_percentiles(vec![50.0, 0.0, 100.0, 90.0]);
+    let stats = column_stats_with_options(&reader, 1, &options, None)?.value;
+    let numeric = stats.numeric.as_ref().unwrap();
+    assert_eq!(numeric.percentile(50.0), Some(4.0));
+    assert_eq!(numeric.percentile(0.0), Some(1.0));
+    assert_eq!(numeric.percentile(100.0), Some(9.0));
+    assert_eq!(numeric.percentile(90.0), Some(8.0));
+    assert_eq!(numeric.percentile(75.0), None);
+    let order: Vec<f64> = numeric.percentiles.iter().map(|&(p, _)| p).collect();
+    assert_eq!(order, vec![50.0, 0.0, 100.0, 90.0]);
+    
+    // 非数值列没有百分位；不请求时也不计算
+    let text = column_stats_with_options(&reader, 2, &options, None)?.value;
+    assert!(text.numeric.is_none());
+    let plain = column_stats(&reader, 1, None)?.value;
+    assert!(plain.numeric.unwrap().percentiles.is_empty());
+    
+    // 近似模式：1..=5000 打乱顺序，p90 与精确值 4500.1 相差很小
+    let mut file = File::create(&test_file)?;
+    writeln!(file, "value")?;
+    for i in 0..5000u64 {
+        writeln!(file, "{}", (i * 2003) % 5000 + 1)?;
+    }
+    drop(file);
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    let options = StatsOptions::new().with_percentiles(vec![90.0]).with_approx(true);
+    let approx = column_stats_with_options(&reader, 0, &options, None)?.value;
+    let p90 = approx.numeric.unwrap().percentile(90.0).unwrap();
+    assert!((p90 - 4500.1).abs() < 25.0, "p90 = {}", p90);
+    
+    let options = StatsOptions::new().with_percentiles(vec![90.0]);
+    let exact = column_stats_with_options(&reader, 0, &options, None)?.value;
+    assert_eq!(exact.numeric.unwrap().percentile(90.0), Some(4500.1));
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_histogram() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_histogram.csv");
+    let mut file = File::create(&test_file)?;
+    
+    // 0..100 均匀分布，另有空值和非数字的值
+    writeln!(file, "id,value")?;
+    for i in 0..1000 {
+        writeln!(file, "{},{}", i, i as f64 / 10.0)?;
+    }
+    writeln!(file, "1000,")?;
+    writeln!(file, "1001,n/a")?;
+    writeln!(file, "1002,abc")?;
+    drop(file);
+    
+    let reader = CsvReader::open(&test_file, true, b',', 10)?;
+    
+    // 自动取范围：每个区间大约100个值
+    let result = histogram(&reader, 1, 10, (None, None))?;
+    assert_eq!(result.buckets.len(), 10);
+    assert_eq!(result.buckets[0].start, 0.0);
+    assert_eq!(result.buckets[9].end, 99.9);
+    assert!(result.buckets.iter().all(|b| (98..=102).contains(&b.count)), "{:?}", result.buckets);
+    assert_eq!(result.buckets.iter().map(|b| b.count).sum::<usize>(), 1000);
+    assert_eq!((result.null_count, result.skipped, result.out_of_range), (1, 2, 0));
+    
+    // 指定范围：区间边界整齐，超出范围的值单独计数
+    let result = histogram(&reader, 1, 5, (Some(0.0), Some(50.0)))?;
+    let counts: Vec<usize> = result.buckets.iter().map(|b| b.count).collect();
+    assert_eq!(counts, vec![100, 100, 100, 100, 101]);
+    assert_eq!((result.buckets[1].start, result.buckets[1].end), (10.0, 20.0));
+    assert_eq!(result.out_of_range, 499);
+    
+    // 范围只有一个值时只有一个区间；下限大于上限时没有区间
+    let single = histogram(&reader, 1, 10, (Some(3.0), Some(3.0)))?;
+    assert_eq!(single.buckets.len(), 1);
+    assert_eq!(single.buckets[0].count, 1);
+    let empty = histogram(&reader, 1, 10, (Some(1.0), Some(0.0)))?;
+    assert!(empty.buckets.is_empty());
+    
+    // 清理
+    std::fs::remove_file(&test_file).ok();
+    std::fs::remove_file(csv_tool::csv::RowIndex::index_file_path(&test_file)).ok();
+    Ok(())
+}
+
+#[test]
+fn test_value_counts_fast_opened_reader_reads_all_rows() -> Result<()> {
+    let test_file = std::env::temp_dir().join("test_value_counts_fast_opened.csv");
+    common::create_underestimated_csv(&test_file);
+    
+    let options = ReaderOptions::new().with_granularity(100).with_persist_index(false);
+    let reader = CsvReader::open_fast_with_options(&test_file, &options)?;
+    assert!(!reader.is_index_complete());
+    assert!(reader.info().total_rows < 40_000);
+    
+    let counts = value_counts(&reader, 1, false)?;
+    assert_eq!(counts, vec![("narrow".to_string(), 20_000), ("wide".to_string(), 20_000)]);
+    
+    std::fs::remove_file(&test_file).ok();
+    Ok(())
+}
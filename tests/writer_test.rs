@@ -230,3 +230,53 @@ fn test_write_options() {
 }
 
 
+
+#[test]
+fn test_edit_session_sequence() {
+    // 与GUI的编辑流程相同：编辑、删除、插入、追加、查看统计、保存、重新打开
+    let content = "name,age\nAlice,25\nBob,30\nCharlie,35\n";
+    let path = create_test_csv(content);
+    
+    let mut editor = CsvEditor::open(&path, true, b',', 10).unwrap();
+    editor.edit_cell(0, 1, "26".to_string()).unwrap();
+    
+    // 错误信息包含出错的行列
+    let err = editor.edit_cell(0, 5, "x".to_string()).unwrap_err().to_string();
+    assert!(err.contains('5'), "{}", err);
+    assert!(editor.edit_cell(10, 0, "x".to_string()).is_err());
+    
+    editor.delete_row(1).unwrap();
+    editor.insert_row(0, RowData::from_strs(&["Zoe", "20"])).unwrap();
+    editor.append_row(RowData::from_strs(&["Dave", "40"])).unwrap();
+    assert!(editor.append_row(RowData::from_strs(&["TooShort"])).is_err());
+    
+    // 已删除的行不能再编辑
+    assert!(editor.edit_cell(1, 0, "x".to_string()).is_err());
+    
+    let stats = editor.change_stats();
+    assert_eq!(stats.cells_edited, 1);
+    assert_eq!(stats.rows_deleted, 1);
+    assert_eq!(stats.rows_inserted, 1);
+    assert_eq!(stats.rows_appended, 1);
+    assert_eq!(editor.effective_row_count(), 4);
+    
+    let stats = editor.save_in_place(&WriteOptions::default()).unwrap();
+    assert_eq!(stats.rows_written, 4);
+    drop(editor);
+    
+    let saved = fs::read_to_string(&path).unwrap();
+    assert_eq!(saved.lines().collect::<Vec<_>>(), vec!["name,age", "Zoe,20", "Alice,26", "Charlie,35", "Dave,40"]);
+    
+    // 重新打开后没有未保存的修改；放弃修改后恢复原状
+    let mut editor = CsvEditor::open(&path, true, b',', 10).unwrap();
+    assert!(!editor.has_changes());
+    assert_eq!(editor.row_count(), 4);
+    editor.delete_row(0).unwrap();
+    editor.discard_changes();
+    assert!(!editor.has_changes());
+    assert_eq!(editor.get_cell(0, 0).unwrap().as_deref(), Some("Zoe"));
+    drop(editor);
+    
+    cleanup(&path);
+    cleanup(&format!("{}.idx", path));
+}